@@ -0,0 +1,469 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    future::Future,
+    panic::{panic_any, Location},
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinSet,
+};
+
+use crate::{
+    error::{ChannelOperation, CommError, EvaluationError, ProtocolError, ValidationError},
+    evaluation::{compare_outputs, derive_seed, party_failure},
+    statistics::{AggregatedStats, Timings},
+    ExpectedOutputs,
+};
+
+/// A party that takes part in an `AsyncProtocol`. Like a `Party`, but it runs as a lightweight task on a tokio runtime
+/// instead of on a thread of its own, so that protocols with hundreds or thousands of parties can be simulated.
+///
+/// Waiting for a message with `AsyncChannels::receive` yields to the other parties, but blocking calls (e.g.
+/// `Timings::simulate_compute` or long computations) occupy one of the runtime's worker threads.
+pub trait AsyncParty {
+    /// The input type of this party. It must be the same for all parties in a given protocol.
+    type Input: Send + 'static;
+    /// The output type of this party. It must be the same for all parties in a given protocol.
+    type Output: Debug + Send + 'static;
+
+    /// Gets the name of this party. By default, this is 'Party {id}'.
+    fn get_name(&self, id: usize) -> String {
+        format!("Party {}", id)
+    }
+
+    /// Runs the code for this party in the given protocol, with the party's id, channels and timings in the
+    /// `context`.
+    fn run(
+        &mut self,
+        input: &Self::Input,
+        context: &mut AsyncPartyContext,
+    ) -> impl Future<Output = Self::Output> + Send;
+}
+
+/// The context in which an `AsyncParty` runs, like the `PartyContext` of a `Party`: its `id` (starting from 0), the
+/// number of parties, and its `channels` and `timings`.
+pub struct AsyncPartyContext<'a> {
+    id: usize,
+    n_parties: usize,
+    /// The communication channels to and from all the other parties.
+    pub channels: &'a mut AsyncChannels,
+    /// The statistics that this party keeps track of.
+    pub timings: &'a mut Timings,
+}
+
+impl<'a> AsyncPartyContext<'a> {
+    fn new(
+        id: usize,
+        n_parties: usize,
+        channels: &'a mut AsyncChannels,
+        timings: &'a mut Timings,
+    ) -> Self {
+        AsyncPartyContext {
+            id,
+            n_parties,
+            channels,
+            timings,
+        }
+    }
+
+    /// Returns the id of this party, which starts from 0.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the number of parties in the protocol.
+    pub fn n_parties(&self) -> usize {
+        self.n_parties
+    }
+
+    /// Returns an iterator over the ids of all other parties, in increasing order.
+    pub fn others(&self) -> impl Iterator<Item = usize> {
+        let id = self.id;
+        (0..self.n_parties).filter(move |&other| other != id)
+    }
+}
+
+/// An MPC protocol whose parties run as tasks on a tokio runtime (see `AsyncParty`). Like a `Protocol`, an
+/// implementation should hold the protocol-specific parameters.
+pub trait AsyncProtocol
+where
+    Self: Debug,
+{
+    /// The type of the parties participating in the AsyncProtocol.
+    type Party: AsyncParty + Send + 'static;
+
+    /// Sets up `n_parties` according to this parameterization of the AsyncProtocol.
+    fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party>;
+
+    /// Generates each party's potentially random input for this parameterization of the AsyncProtocol. All randomness
+    /// should be drawn from `rng`, which is seeded for each repetition, so that a repetition can be replayed from its
+    /// seed (see `replay_async`).
+    fn generate_inputs(
+        &self,
+        n_parties: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<<Self::Party as AsyncParty>::Input>;
+
+    /// Validates the outputs of one run of the AsyncProtocol. If false, `evaluate_async` returns a
+    /// `ValidationError`.
+    fn validate_outputs(
+        &self,
+        _inputs: &[<Self::Party as AsyncParty>::Input],
+        _outputs: &[<Self::Party as AsyncParty>::Output],
+    ) -> bool {
+        true
+    }
+
+    /// Computes the output that each party should produce for the `inputs` in the clear, like
+    /// `Protocol::expected_outputs`. If `Some`, the outputs of each run are compared against these expected outputs
+    /// after `validate_outputs`. Returns `None` by default.
+    fn expected_outputs(
+        &self,
+        _inputs: &[<Self::Party as AsyncParty>::Input],
+    ) -> Option<ExpectedOutputs<<Self::Party as AsyncParty>::Output>> {
+        None
+    }
+
+    /// Evaluates multiple `repetitions` of the protocol with this parameterization of the AsyncProtocol, running the
+    /// parties as tasks on a multi-threaded tokio runtime. The seed of each repetition's inputs is recorded (see
+    /// `AggregatedStats::seeds`).
+    fn evaluate_async(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &AsyncNetwork,
+        repetitions: usize,
+    ) -> Result<AggregatedStats, EvaluationError>
+    where
+        Self: Sized,
+    {
+        let master_seed = rand::random();
+        let seeds = (0..repetitions).map(|repetition| derive_seed(master_seed, repetition));
+        let (stats, _) =
+            evaluate_seeds(self, experiment_name, n_parties, network_description, seeds)?;
+
+        Ok(stats)
+    }
+
+    /// Replays the repetition whose inputs were generated from the `seed` (see `AggregatedStats::seeds`) with
+    /// `n_parties` on the network, like `Protocol::replay`. Returns the statistics of the replayed repetition together
+    /// with the parties' outputs.
+    fn replay_async(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &AsyncNetwork,
+        seed: u64,
+    ) -> Result<(AggregatedStats, AsyncOutputs<Self>), EvaluationError>
+    where
+        Self: Sized,
+    {
+        evaluate_seeds(
+            self,
+            experiment_name,
+            n_parties,
+            network_description,
+            [seed],
+        )
+    }
+}
+
+/// The outputs of the parties of an `AsyncProtocol`, indexed by party id.
+pub type AsyncOutputs<P> = Vec<<<P as AsyncProtocol>::Party as AsyncParty>::Output>;
+
+/// Evaluates one repetition of the `protocol` for each of the `seeds`, from which the repetition's inputs are
+/// generated, and returns the statistics together with the outputs of the last repetition.
+fn evaluate_seeds<P: AsyncProtocol>(
+    protocol: &P,
+    experiment_name: String,
+    n_parties: usize,
+    network_description: &AsyncNetwork,
+    seeds: impl IntoIterator<Item = u64>,
+) -> Result<(AggregatedStats, AsyncOutputs<P>), EvaluationError> {
+    let runtime = Runtime::new()?;
+    let mut parties = protocol.setup_parties(n_parties);
+    debug_assert_eq!(parties.len(), n_parties);
+
+    let party_names: Vec<String> = parties
+        .iter()
+        .enumerate()
+        .map(|(id, party)| party.get_name(id))
+        .collect();
+    let mut stats = AggregatedStats::new(experiment_name, party_names.clone());
+    stats.set_parameters(format!("{:?}", protocol));
+    stats.set_network(format!("{:?}", network_description));
+
+    let mut last_outputs = vec![];
+    for (repetition, seed) in seeds.into_iter().enumerate() {
+        let inputs = protocol.generate_inputs(n_parties, &mut StdRng::seed_from_u64(seed));
+        let channels = network_description.instantiate(n_parties);
+        let start_time = Instant::now();
+        let (returned, inputs, outputs, timings) =
+            runtime.block_on(run_tasks(parties, inputs, channels, &party_names))?;
+        let makespan = start_time.elapsed();
+        parties = returned;
+
+        if !protocol.validate_outputs(&inputs, &outputs) {
+            return Err(ValidationError {
+                repetition,
+                reason: "the outputs were rejected by `validate_outputs`".to_string(),
+                mismatching_parties: vec![],
+            }
+            .into());
+        }
+        if let Some(expected_outputs) = protocol.expected_outputs(&inputs) {
+            compare_outputs(protocol, &outputs, &expected_outputs, repetition)?;
+        }
+
+        stats.incorporate_party_stats(timings);
+        stats.incorporate_makespan(makespan);
+        stats.incorporate_seed(seed);
+        last_outputs = outputs;
+    }
+
+    Ok((stats, last_outputs))
+}
+
+/// The parties, inputs, outputs and timings of one run of an `AsyncProtocol`, indexed by party id.
+type TaskResults<P> = (
+    Vec<P>,
+    Vec<<P as AsyncParty>::Input>,
+    Vec<<P as AsyncParty>::Output>,
+    Vec<Timings>,
+);
+
+/// Runs every party as a task and waits until all of them finished. When a party fails, the other tasks are aborted
+/// so that they do not wait for it forever.
+async fn run_tasks<P: AsyncParty + Send + 'static>(
+    parties: Vec<P>,
+    inputs: Vec<P::Input>,
+    channels: Vec<AsyncChannels>,
+    party_names: &[String],
+) -> Result<TaskResults<P>, EvaluationError> {
+    let n_parties = parties.len();
+
+    // Each party runs in a task of its own, which is awaited by a monitoring task that reports which party it was
+    let mut abort_handles = vec![];
+    let mut monitors = JoinSet::new();
+    for (id, ((mut party, input), mut channels)) in
+        parties.into_iter().zip(inputs).zip(channels).enumerate()
+    {
+        let task = tokio::spawn(async move {
+            let mut s = Timings::new();
+            let total_timer = s.create_timer("Total");
+            let output = party
+                .run(
+                    &input,
+                    &mut AsyncPartyContext::new(id, n_parties, &mut channels, &mut s),
+                )
+                .await;
+            s.stop_unscaled_timer(total_timer);
+            s.write_bytes(channels.sent_bytes(), channels.received_bytes());
+            (party, input, output, s)
+        });
+        abort_handles.push(task.abort_handle());
+        monitors.spawn(async move { (id, task.await) });
+    }
+
+    let mut results: Vec<_> = (0..n_parties).map(|_| None).collect();
+    let mut failure = None;
+    while let Some(monitored) = monitors.join_next().await {
+        let (id, result) = monitored.expect("the monitoring task cannot fail");
+        match result {
+            Ok(result) => results[id] = Some(result),
+            Err(error) => {
+                abort_handles.iter().for_each(|handle| handle.abort());
+                if error.is_panic() && failure.is_none() {
+                    failure = Some(party_failure(
+                        id,
+                        party_names[id].clone(),
+                        error.into_panic(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+
+    let mut parties = Vec::with_capacity(n_parties);
+    let mut inputs = Vec::with_capacity(n_parties);
+    let mut outputs = Vec::with_capacity(n_parties);
+    let mut timings = Vec::with_capacity(n_parties);
+    for (party, input, output, s) in results.into_iter().map(Option::unwrap) {
+        parties.push(party);
+        inputs.push(input);
+        outputs.push(output);
+        timings.push(s);
+    }
+
+    Ok((parties, inputs, outputs, timings))
+}
+
+/// A network in which every party can send messages to every other party, like a `FullMesh`, for parties that run as
+/// tasks (see `AsyncProtocol`). The latency and throughput are simulated by the receiving task sleeping on the runtime,
+/// so waiting parties do not occupy a thread.
+#[derive(Debug, Default)]
+pub struct AsyncNetwork {
+    latency: Duration,
+    seconds_per_byte: Duration,
+}
+
+impl AsyncNetwork {
+    /// Constructs an AsyncNetwork without communication overhead.
+    pub fn new() -> Self {
+        AsyncNetwork::default()
+    }
+
+    /// Constructs an AsyncNetwork with the specified `latency` and throughput (maximum `bytes_per_second`).
+    pub fn new_with_overhead(latency: Duration, bytes_per_second: f64) -> Self {
+        AsyncNetwork {
+            latency,
+            seconds_per_byte: Duration::from_secs_f64(1. / bytes_per_second),
+        }
+    }
+
+    /// Instantiates the AsyncChannels for each party.
+    pub fn instantiate(&self, n_parties: usize) -> Vec<AsyncChannels> {
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..n_parties).map(|_| unbounded_channel()).unzip();
+
+        receivers
+            .into_iter()
+            .enumerate()
+            .map(|(id, receiver)| AsyncChannels {
+                id,
+                senders: senders.clone(),
+                receiver,
+                buffers: (0..n_parties).map(|_| VecDeque::new()).collect(),
+                sent_bytes: vec![0; n_parties],
+                received_bytes: vec![0; n_parties],
+                latency: self.latency,
+                seconds_per_byte: self.seconds_per_byte,
+                next_vacancy: Instant::now(),
+            })
+            .collect()
+    }
+}
+
+/// A message between two parties of an `AsyncProtocol`.
+#[derive(Debug)]
+struct AsyncMessage {
+    arrival_time: Instant,
+    from_id: usize,
+    contents: Vec<u8>,
+}
+
+/// The channels of one party in an `AsyncNetwork`. Sending never waits, while receiving is `await`-ed until the
+/// message arrived according to the simulated latency and throughput.
+#[derive(Debug)]
+pub struct AsyncChannels {
+    id: usize,
+    senders: Vec<UnboundedSender<AsyncMessage>>,
+    receiver: UnboundedReceiver<AsyncMessage>,
+    buffers: Vec<VecDeque<AsyncMessage>>,
+    sent_bytes: Vec<usize>,
+    received_bytes: Vec<usize>,
+    latency: Duration,
+    seconds_per_byte: Duration,
+    next_vacancy: Instant,
+}
+
+impl AsyncChannels {
+    /// Sends `contents` to the party with `to_id`. Returns a `CommError` if the party with `to_id` disconnected, like
+    /// `Channels::send`. Raises a `ProtocolError` if `to_id` is this party's own id or not the id of any party.
+    #[track_caller]
+    pub fn send(&mut self, contents: Vec<u8>, to_id: &usize) -> Result<(), CommError> {
+        self.check_peer(*to_id, ChannelOperation::Send);
+
+        self.sent_bytes[*to_id] += contents.len();
+        let message = AsyncMessage {
+            arrival_time: Instant::now() + self.latency,
+            from_id: self.id,
+            contents,
+        };
+        self.senders[*to_id]
+            .send(message)
+            .map_err(|_| CommError::Disconnected {
+                from_id: self.id,
+                to_id: *to_id,
+            })
+    }
+
+    /// Sends `contents` to every other party, returning the `CommError` of the first party that disconnected.
+    pub fn broadcast(&mut self, contents: Vec<u8>) -> Result<(), CommError> {
+        for to_id in 0..self.senders.len() {
+            if to_id != self.id {
+                self.send(contents.clone(), &to_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receives the next message from the party with `from_id`, once it arrived. Returns a `CommError` if the channel
+    /// was closed, like `Channels::receive`. Raises a `ProtocolError` if `from_id` is this party's own id or not the
+    /// id of any party.
+    #[track_caller]
+    pub fn receive(
+        &mut self,
+        from_id: &usize,
+    ) -> impl Future<Output = Result<Vec<u8>, CommError>> + Send + '_ {
+        // The peer is checked before the future is created, because `track_caller` does not apply to async code
+        self.check_peer(*from_id, ChannelOperation::Receive);
+        let from_id = *from_id;
+
+        async move {
+            let message = loop {
+                if let Some(message) = self.buffers[from_id].pop_front() {
+                    break message;
+                }
+
+                match self.receiver.recv().await {
+                    Some(message) if message.from_id == from_id => break message,
+                    Some(message) => self.buffers[message.from_id].push_back(message),
+                    None => return Err(CommError::Closed { id: self.id }),
+                }
+            };
+
+            // The messages are received one after another, at the throughput of this party's link
+            let start = self.next_vacancy.max(message.arrival_time);
+            self.next_vacancy = start + self.seconds_per_byte * message.contents.len() as u32;
+            tokio::time::sleep_until(self.next_vacancy.into()).await;
+
+            self.received_bytes[from_id] += message.contents.len();
+            Ok(message.contents)
+        }
+    }
+
+    /// Returns the number of bytes that this party sent to each party, indexed by party id.
+    pub fn sent_bytes(&self) -> &[usize] {
+        &self.sent_bytes
+    }
+
+    /// Returns the number of bytes that this party received from each party, indexed by party id.
+    pub fn received_bytes(&self) -> &[usize] {
+        &self.received_bytes
+    }
+
+    /// Raises a `ProtocolError` that blames the caller if `peer` is not the id of another party.
+    #[track_caller]
+    fn check_peer(&self, peer: usize, operation: ChannelOperation) {
+        if peer == self.id || peer >= self.senders.len() {
+            panic_any(ProtocolError {
+                party: self.id,
+                party_name: String::new(),
+                operation,
+                peer,
+                n_parties: self.senders.len(),
+                location: Location::caller().to_string(),
+            });
+        }
+    }
+}
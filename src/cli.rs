@@ -0,0 +1,256 @@
+use std::{
+    ffi::OsString,
+    fs::{create_dir_all, File},
+    io::BufReader,
+    path::Path,
+    process,
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+
+use crate::{
+    error::{CliError, EvaluationError},
+    experiment::{file_name, Experiment, ExperimentResults, NetworkCondition},
+    statistics::AggregatedStats,
+    Protocol,
+};
+
+/// Runs an experiment with a registered protocol as described by a `RunConfig`.
+type RunProtocol = Box<dyn Fn(&RunConfig) -> Result<ExperimentResults, EvaluationError>>;
+
+/// The protocols that the command-line interface can run, by name. A binary registers its protocols and passes the
+/// registry to `main`.
+#[derive(Default)]
+pub struct Registry {
+    protocols: Vec<(String, RunProtocol)>,
+}
+
+impl Registry {
+    /// Constructs a registry without protocols.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Registers the `protocol` under the `name` that configurations refer to it by. Panics if a protocol with this
+    /// name was already registered.
+    pub fn with_protocol<P: Protocol + Clone + 'static>(mut self, name: &str, protocol: P) -> Self {
+        assert!(
+            self.protocols.iter().all(|(existing, _)| existing != name),
+            "the protocol '{}' was already registered",
+            name
+        );
+
+        self.protocols.push((
+            name.to_string(),
+            Box::new(move |config| config.experiment(protocol.clone()).run()),
+        ));
+        self
+    }
+
+    /// Returns the names of the registered protocols, in the order in which they were registered.
+    pub fn protocol_names(&self) -> Vec<&str> {
+        self.protocols
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Runs the experiment that the `config` describes with the registered protocol that it names.
+    pub fn run(&self, config: &RunConfig) -> Result<ExperimentResults, CliError> {
+        let (_, run) = self
+            .protocols
+            .iter()
+            .find(|(name, _)| *name == config.protocol)
+            .ok_or_else(|| CliError::UnknownProtocol {
+                protocol: config.protocol.clone(),
+                registered: self
+                    .protocol_names()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect(),
+            })?;
+        Ok(run(config)?)
+    }
+}
+
+/// The experiment that the `run` command runs, as described by a JSON configuration file, e.g.
+/// `{"name": "psi", "protocol": "psi", "parties": [2, 4, 8], "repetitions": 10, "latencies_ms": [1, 50]}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunConfig {
+    /// The name of the experiment.
+    pub name: String,
+    /// The name under which the protocol is registered.
+    pub protocol: String,
+    /// The numbers of parties to evaluate the protocol with (by default, two).
+    #[serde(default = "default_parties")]
+    pub parties: Vec<usize>,
+    /// The number of measured repetitions for each number of parties and network condition (by default, one).
+    #[serde(default = "default_repetitions")]
+    pub repetitions: usize,
+    /// The number of unmeasured repetitions before the measured ones.
+    #[serde(default)]
+    pub warmup: usize,
+    /// The latencies in milliseconds to sweep (see `NetworkCondition::grid`).
+    #[serde(default)]
+    pub latencies_ms: Vec<f64>,
+    /// The throughputs in bytes per second to sweep; unlimited if there are none.
+    #[serde(default)]
+    pub bytes_per_second: Vec<f64>,
+    /// The directory to save the results to: a csv with the metrics of the experiment, and the statistics of each
+    /// evaluation as JSON (which the other commands read).
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+fn default_parties() -> Vec<usize> {
+    vec![2]
+}
+
+fn default_repetitions() -> usize {
+    1
+}
+
+impl RunConfig {
+    /// Loads a configuration from the JSON file named `filename`.
+    pub fn load(filename: &str) -> Result<Self, CliError> {
+        let reader = BufReader::new(File::open(filename)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Returns the network conditions to sweep, which are every combination of a latency and a throughput. Without
+    /// latencies, the latency is zero, and without either, the network is used as is.
+    pub fn conditions(&self) -> Vec<NetworkCondition> {
+        if self.latencies_ms.is_empty() && self.bytes_per_second.is_empty() {
+            return vec![];
+        }
+
+        let latencies: Vec<Duration> = if self.latencies_ms.is_empty() {
+            vec![Duration::ZERO]
+        } else {
+            self.latencies_ms
+                .iter()
+                .map(|&ms| Duration::from_secs_f64(ms / 1000.))
+                .collect()
+        };
+        let bytes_per_second: Vec<Option<f64>> = if self.bytes_per_second.is_empty() {
+            vec![None]
+        } else {
+            self.bytes_per_second.iter().copied().map(Some).collect()
+        };
+        NetworkCondition::grid(&latencies, &bytes_per_second)
+    }
+
+    /// Returns the experiment that this configuration describes for the `protocol`.
+    fn experiment<P: Protocol>(&self, protocol: P) -> Experiment<P> {
+        Experiment::new(&self.name)
+            .protocol(protocol)
+            .parties(self.parties.clone())
+            .repetitions(self.repetitions)
+            .warmup(self.warmup)
+            .conditions(self.conditions())
+    }
+}
+
+/// Runs and reports multi-party computation experiments.
+#[derive(Debug, Parser)]
+#[command(name = "mpc-bench")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Runs the experiment that a JSON configuration file describes.
+    Run {
+        /// The configuration file.
+        config: String,
+    },
+    /// Summarizes the timings of statistics that were saved as JSON.
+    Report {
+        /// The statistics file.
+        results: String,
+    },
+    /// Compares two saved statistics side by side, relative to the first.
+    Compare {
+        /// The statistics file of the baseline.
+        a: String,
+        /// The statistics file to compare with the baseline.
+        b: String,
+    },
+    /// Renders a chart of the phases of each party in saved statistics.
+    Plot {
+        /// The statistics file.
+        results: String,
+        /// The chart file, an SVG if it ends with `.svg` and a PNG otherwise (by default, the statistics file with
+        /// the extension `.svg`).
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+/// Runs the command-line interface with the protocols in the `registry` on the arguments of the process, and exits
+/// the process with an error message if the command fails.
+pub fn main(registry: &Registry) {
+    if let Err(error) = run_with_args(registry, std::env::args_os()) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+/// Runs the command-line interface with the protocols in the `registry` on the `args` (which start with the name of
+/// the binary). Exits the process with a usage message if the arguments are invalid.
+pub fn run_with_args<I, T>(registry: &Registry, args: I) -> Result<(), CliError>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    match Cli::parse_from(args).command {
+        Command::Run { config } => {
+            let config = RunConfig::load(&config)?;
+            let results = registry.run(&config)?;
+            results.print_metrics();
+
+            if let Some(directory) = &config.output {
+                create_dir_all(directory)?;
+                let path = |name: &str| Path::new(directory).join(file_name(name));
+                results.write_csv(&path(&format!("{}.csv", config.name)).to_string_lossy())?;
+                for (_, stats) in results.results() {
+                    stats.save(&path(&format!("{}.json", stats.name())).to_string_lossy())?;
+                }
+            }
+            Ok(())
+        }
+        Command::Report { results } => {
+            AggregatedStats::load(&results)?.summarize_timings().print();
+            Ok(())
+        }
+        Command::Compare { a, b } => {
+            let a = AggregatedStats::load(&a)?;
+            let b = AggregatedStats::load(&b)?;
+            AggregatedStats::compare(&[&a, &b]).print();
+            Ok(())
+        }
+        Command::Plot { results, output } => plot(&results, output),
+    }
+}
+
+#[cfg(feature = "plots")]
+fn plot(results: &str, output: Option<String>) -> Result<(), CliError> {
+    let stats = AggregatedStats::load(results)?;
+    let output = output.unwrap_or_else(|| {
+        Path::new(results)
+            .with_extension("svg")
+            .to_string_lossy()
+            .into_owned()
+    });
+    crate::plots::plot_phases(&stats, &output)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "plots"))]
+fn plot(_results: &str, _output: Option<String>) -> Result<(), CliError> {
+    Err(CliError::PlotsUnavailable)
+}
@@ -1,24 +1,68 @@
 use std::{
+    any::{type_name, Any},
     cmp,
-    sync::mpsc::{channel, Receiver, Sender},
-    thread::sleep,
+    collections::HashMap,
+    io, mem,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    panic::{panic_any, Location},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, sleep},
     time::{Duration, Instant},
-    vec::IntoIter,
 };
 
 use queues::{IsQueue, Queue};
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ChannelOperation, CommError, ProtocolError},
+    statistics::{MessageSizeHistogram, Progress, RoundRecord, TraceEvent},
+    tcp::{
+        content_length, decode_message, encode_message, forward_messages, TcpTransport,
+        HEADER_LENGTH,
+    },
+    watchdog::{Watchdog, POLL_INTERVAL},
+};
 
 /// A NetworkDescription is responsible for instantiating the networks it describes by spawning channels for each party.
 pub trait NetworkDescription {
     /// Instantiates the Channels for each party.
     fn instantiate(&self, n_parties: usize) -> Vec<Channels>;
+
+    /// Describes this network in the statistics of the experiments that run on it (see
+    /// `AggregatedStats::network`). By default, this is the name of the network's type.
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
 }
 
+/// Computes an extra delay for a message given its size in bytes, the sender's id and the receiver's id (in that
+/// order). This can be used to model e.g. per-message syscall costs or middlebox processing.
+pub type MessageDelay = Arc<dyn Fn(usize, usize, usize) -> Duration + Send + Sync>;
+
 #[derive(Default)]
 /// A full mesh network description.
 pub struct FullMesh {
     latency: Duration,
     seconds_per_byte: Duration,
+    message_delay: Option<MessageDelay>,
+    burst_bytes: usize,
+    half_duplex: bool,
+    mtu: Option<(usize, Duration)>,
+    handshake: Option<Duration>,
+    nat_relay: Option<(Vec<usize>, Duration)>,
+    receive_policy: ReceivePolicy,
+    broadcast_semantics: BroadcastSemantics,
+    encryption: Option<(EncryptionPackaging, Duration, usize)>,
+    encryption_bytes_per_second: Option<f64>,
+    #[cfg(feature = "compression")]
+    compression: Option<Compression>,
+    channel_backend: ChannelBackend,
+    link_transports: Option<LinkAssignment>,
 }
 
 impl FullMesh {
@@ -27,6 +71,20 @@ impl FullMesh {
         FullMesh {
             latency: Duration::ZERO,
             seconds_per_byte: Duration::ZERO,
+            message_delay: None,
+            burst_bytes: 0,
+            half_duplex: false,
+            mtu: None,
+            handshake: None,
+            nat_relay: None,
+            receive_policy: ReceivePolicy::Fifo,
+            broadcast_semantics: BroadcastSemantics::IncludeSelf,
+            encryption: None,
+            encryption_bytes_per_second: None,
+            #[cfg(feature = "compression")]
+            compression: None,
+            channel_backend: ChannelBackend::Std,
+            link_transports: None,
         }
     }
 
@@ -35,84 +93,778 @@ impl FullMesh {
         FullMesh {
             latency,
             seconds_per_byte: Duration::from_secs_f64(1. / bytes_per_second),
+            ..FullMesh::new()
+        }
+    }
+
+    /// Adds an extra delay to every message, computed by `message_delay` from the message size and the sender's
+    /// and receiver's ids. The delay is added on top of the latency.
+    pub fn with_message_delay<F>(mut self, message_delay: F) -> Self
+    where
+        F: Fn(usize, usize, usize) -> Duration + Send + Sync + 'static,
+    {
+        self.message_delay = Some(Arc::new(message_delay));
+        self
+    }
+
+    /// Replaces the strictly serial throughput model by a token bucket that allows bursts of up to `burst_bytes`
+    /// bytes to be received without throughput delays. The bucket refills at the throughput rate.
+    pub fn with_burst(mut self, burst_bytes: usize) -> Self {
+        self.burst_bytes = burst_bytes;
+        self
+    }
+
+    /// Makes all links half-duplex, so that a party's sent and received messages share the same capacity.
+    pub fn with_half_duplex(mut self) -> Self {
+        self.half_duplex = true;
+        self
+    }
+
+    /// Splits every message into fragments of at most `mtu` bytes, each of which adds `per_fragment_delay` to the
+    /// message's latency. The number of fragments per message is reported in the statistics. Panics if `mtu` is 0.
+    pub fn with_mtu(mut self, mtu: usize, per_fragment_delay: Duration) -> Self {
+        assert!(mtu > 0, "the MTU must be at least one byte");
+        self.mtu = Some((mtu, per_fragment_delay));
+        self
+    }
+
+    /// Charges a one-time `setup_delay` (e.g. the round trips of a TCP and TLS handshake) on the first message
+    /// between each pair of parties in a repetition, in either direction.
+    pub fn with_handshake(mut self, setup_delay: Duration) -> Self {
+        self.handshake = Some(setup_delay);
+        self
+    }
+
+    /// Places the parties with ids in `behind_nat` behind NAT, so that all their traffic (sent or received) is
+    /// relayed by a TURN-style relay node. Relaying adds `relay_latency` to each message and the relayed bytes are
+    /// reported in the statistics.
+    pub fn with_nat_relay(mut self, behind_nat: &[usize], relay_latency: Duration) -> Self {
+        self.nat_relay = Some((behind_nat.to_vec(), relay_latency));
+        self
+    }
+
+    /// Sets the order in which pending messages from multiple parties are serviced (see `ReceivePolicy`).
+    pub fn with_receive_policy(mut self, receive_policy: ReceivePolicy) -> Self {
+        self.receive_policy = receive_policy;
+        self
+    }
+
+    /// Sets whether a broadcast includes, excludes or echoes to its sender (see `BroadcastSemantics`).
+    pub fn with_broadcast_semantics(mut self, broadcast_semantics: BroadcastSemantics) -> Self {
+        self.broadcast_semantics = broadcast_semantics;
+        self
+    }
+
+    /// Models secure channels between the parties: every ciphertext costs its sender `encryption_delay` and adds
+    /// `byte_overhead` bytes (e.g. a nonce and tag) to the message. The `packaging` determines how many ciphertexts a
+    /// broadcast produces (see `EncryptionPackaging`).
+    pub fn with_encryption(
+        mut self,
+        packaging: EncryptionPackaging,
+        encryption_delay: Duration,
+        byte_overhead: usize,
+    ) -> Self {
+        self.encryption = Some((packaging, encryption_delay, byte_overhead));
+        self
+    }
+
+    /// Additionally charges the compute cost of encrypting each byte of a ciphertext (e.g. of an AEAD cipher), for
+    /// which the sender can encrypt at most `bytes_per_second`. Only applies if the secure channels are modeled (see
+    /// `with_encryption`).
+    pub fn with_encryption_throughput(mut self, bytes_per_second: f64) -> Self {
+        self.encryption_bytes_per_second = Some(bytes_per_second);
+        self
+    }
+
+    /// Compresses every message with `compression` before it is sent, so that only the compressed bytes are
+    /// transferred and counted. The recipients receive the original messages.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Sets the implementation of the in-memory channels that carry the messages (see `ChannelBackend`).
+    pub fn with_channel_backend(mut self, channel_backend: ChannelBackend) -> Self {
+        self.channel_backend = channel_backend;
+        self
+    }
+
+    /// Assigns a transport to the link between each pair of parties, which `link_transport` computes from their ids
+    /// (the lower id first). This allows mixing real and simulated communication in one experiment, e.g. servers
+    /// that communicate over real TCP sockets alongside simulated clients.
+    pub fn with_link_transports<F>(mut self, link_transport: F) -> Self
+    where
+        F: Fn(usize, usize) -> LinkTransport + Send + Sync + 'static,
+    {
+        self.link_transports = Some(Arc::new(link_transport));
+        self
+    }
+
+    /// Replaces the in-memory transports of the links that are assigned a TCP socket (see `with_link_transports`).
+    /// Returns the ids of the parties that each party is connected to over TCP.
+    fn connect_real_links(
+        &self,
+        senders: &mut [Vec<Box<dyn Transport>>],
+    ) -> io::Result<Vec<Vec<usize>>> {
+        let n_parties = senders.len();
+        let mut real_links = vec![vec![]; n_parties];
+        let Some(link_transports) = &self.link_transports else {
+            return Ok(real_links);
+        };
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let address = listener.local_addr()?;
+        for a in 0..n_parties {
+            for b in (a + 1)..n_parties {
+                if link_transports(a, b) != LinkTransport::Tcp {
+                    continue;
+                }
+
+                let stream_a = TcpStream::connect(address)?;
+                let (stream_b, _) = listener.accept()?;
+                stream_a.set_nodelay(true)?;
+                stream_b.set_nodelay(true)?;
+
+                for (id, other, stream, other_stream) in
+                    [(a, b, &stream_a, &stream_b), (b, a, &stream_b, &stream_a)]
+                {
+                    // The party's in-memory transport to the other party now forwards what arrives at the other end
+                    let transport = Box::new(TcpTransport(stream.try_clone()?));
+                    let queue = mem::replace(&mut senders[id][other], transport);
+                    let reader = other_stream.try_clone()?;
+                    thread::spawn(move || forward_messages(reader, id, queue));
+
+                    real_links[id].push(other);
+                }
+            }
         }
+
+        Ok(real_links)
     }
 }
 
 impl NetworkDescription for FullMesh {
+    /// Describes the latency and throughput of this network, but not its other options.
+    fn describe(&self) -> String {
+        let throughput = if self.seconds_per_byte.is_zero() {
+            "unlimited".to_string()
+        } else {
+            format!("{} B/s", 1. / self.seconds_per_byte.as_secs_f64())
+        };
+        format!(
+            "FullMesh (latency: {:?}, throughput: {})",
+            self.latency, throughput
+        )
+    }
+
     fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
         let mut receivers = vec![];
-        let mut senders: Vec<Vec<Sender<_>>> = (0..n_parties).map(|_| vec![]).collect();
+        let mut senders: Vec<Vec<Box<dyn Transport>>> = (0..n_parties).map(|_| vec![]).collect();
+        let connections = Arc::new(Connections::new(n_parties));
 
         for _ in 0..n_parties {
-            let (sender, receiver) = channel();
+            let (transports, receiver) = self.channel_backend.queue(n_parties);
 
             receivers.push(receiver);
 
-            for sender_vec in senders.iter_mut() {
-                sender_vec.push(sender.clone());
+            for (sender_vec, transport) in senders.iter_mut().zip(transports) {
+                sender_vec.push(transport);
             }
         }
+        let real_links = self
+            .connect_real_links(&mut senders)
+            .expect("could not set up the TCP sockets between the parties");
 
         receivers
             .into_iter()
             .enumerate()
             .zip(senders)
-            .map(|((id, r), s)| Channels::new(id, s, r, self.latency, self.seconds_per_byte))
+            .zip(real_links)
+            .map(|(((id, r), s), real_links)| {
+                let mut channels =
+                    Channels::with_inbox(id, s, r, self.latency, self.seconds_per_byte)
+                        .with_real_links(&real_links)
+                        .with_burst(self.burst_bytes)
+                        .with_receive_policy(self.receive_policy)
+                        .with_broadcast_semantics(self.broadcast_semantics);
+                if let Some(message_delay) = &self.message_delay {
+                    channels = channels.with_message_delay(message_delay.clone());
+                }
+                if self.half_duplex {
+                    channels = channels.with_half_duplex();
+                }
+                if let Some((mtu, per_fragment_delay)) = self.mtu {
+                    channels = channels.with_mtu(mtu, per_fragment_delay);
+                }
+                if let Some(setup_delay) = self.handshake {
+                    channels = channels.with_handshake(setup_delay, connections.clone());
+                }
+                if let Some((behind_nat, relay_latency)) = &self.nat_relay {
+                    channels = channels.with_nat_relay(behind_nat, *relay_latency);
+                }
+                if let Some((packaging, encryption_delay, byte_overhead)) = self.encryption {
+                    channels = channels.with_encryption(packaging, encryption_delay, byte_overhead);
+                }
+                if let Some(bytes_per_second) = self.encryption_bytes_per_second {
+                    channels = channels.with_encryption_throughput(bytes_per_second);
+                }
+                #[cfg(feature = "compression")]
+                if let Some(compression) = self.compression {
+                    channels = channels.with_compression(compression);
+                }
+                channels
+            })
             .collect()
     }
 }
 
+/// How a party packages a broadcast for its secure channels (see `Channels::with_encryption`). Messages to a single
+/// party are always encrypted once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncryptionPackaging {
+    /// Encrypts a broadcast once per recipient, so each recipient gets its own ciphertext.
+    #[default]
+    PerRecipient,
+    /// Encrypts a broadcast once (e.g. under a group key) and sends the same ciphertext to all recipients.
+    Broadcast,
+}
+
+/// An algorithm with which parties compress the messages that they send (see `Channels::with_compression`).
+#[cfg(feature = "compression")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// DEFLATE at the given `level`, from 0 (no compression) to 10 (the best but slowest compression).
+    Deflate {
+        /// The compression level.
+        level: u8,
+    },
+}
+
+#[cfg(feature = "compression")]
+impl Compression {
+    fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::Deflate { level } => miniz_oxide::deflate::compress_to_vec(bytes, *level),
+        }
+    }
+}
+
+/// Whether a broadcast reaches its sender (see `Channels::broadcast`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroadcastSemantics {
+    /// Counts the sender as one of the recipients: its copy passes through its own link and counts towards the sent
+    /// bytes, but it is discarded on arrival.
+    #[default]
+    IncludeSelf,
+    /// Sends the message to the other parties only.
+    ExcludeSelf,
+    /// Sends the message to the other parties and delivers a free copy to the sender right away, which it receives
+    /// with `Channels::receive` from its own id (as some protocol descriptions assume).
+    EchoToSelf,
+}
+
+/// The communication of a party since its previous checkpoint (see `Channels::checkpoint_comm`), or in one session
+/// (see `Channels::subsession`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct CommCheckpoint {
+    /// The label of the checkpoint.
+    pub label: String,
+    /// The number of bytes sent since the previous checkpoint, including padding.
+    pub sent_bytes: usize,
+    /// The number of messages sent since the previous checkpoint.
+    pub sent_messages: usize,
+    /// The number of bytes received since the previous checkpoint, including padding.
+    pub received_bytes: usize,
+    /// The number of messages received since the previous checkpoint.
+    pub received_messages: usize,
+}
+
+/// The order in which a party services messages when multiple parties have pending messages (see
+/// `Channels::next_pending_sender` and `Channels::receive_any`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReceivePolicy {
+    /// Services the message that arrives first.
+    #[default]
+    Fifo,
+    /// Services the parties in turn, starting after the party that was serviced last.
+    RoundRobin,
+    /// Services a random party, drawn using the given seed so that runs are reproducible.
+    Random(u64),
+}
+
+/// The tag of messages that are sent without one, e.g. by `Channels::send` (see `Channels::send_tagged`).
+pub const DEFAULT_TAG: u32 = 0;
+
+/// The tag of the messages that synchronize the parties at a barrier (see `Channels::barrier`), which protocols may
+/// not use themselves.
+pub const BARRIER_TAG: u32 = u32::MAX;
+
+/// The tag of the chunks of streamed messages (see `Channels::send_stream`), which protocols may not use themselves.
+pub const STREAM_TAG: u32 = u32::MAX - 1;
+
+/// The tag of out-of-band control messages (see `Channels::send_control`), which protocols may not use themselves.
+pub const CONTROL_TAG: u32 = u32::MAX - 2;
+
+/// Returns a `CommError` if a protocol may not use the `tag` itself, because it is reserved for the harness.
+fn check_tag(tag: u32) -> Result<(), CommError> {
+    match tag {
+        BARRIER_TAG | STREAM_TAG | CONTROL_TAG => Err(CommError::ReservedTag { tag }),
+        _ => Ok(()),
+    }
+}
+
+/// The number of bytes that a `MessageWriter` collects before it sends them as one chunk.
+pub const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// The number of bytes that a value takes when it is sent as a typed payload (see `Channels::send_payload`), which
+/// is charged like the length of a message without serializing the value.
+pub trait MessageSize {
+    /// Returns the number of bytes that this value takes when it is sent.
+    fn message_size(&self) -> usize;
+}
+
+macro_rules! impl_message_size {
+    ($($t:ty),*) => {
+        $(impl MessageSize for $t {
+            fn message_size(&self) -> usize {
+                mem::size_of::<$t>()
+            }
+        })*
+    };
+}
+
+impl_message_size!(bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+impl<T: MessageSize> MessageSize for Vec<T> {
+    fn message_size(&self) -> usize {
+        self.iter().map(MessageSize::message_size).sum()
+    }
+}
+
+impl<T: MessageSize, const N: usize> MessageSize for [T; N] {
+    fn message_size(&self) -> usize {
+        self.iter().map(MessageSize::message_size).sum()
+    }
+}
+
+impl<A: MessageSize, B: MessageSize> MessageSize for (A, B) {
+    fn message_size(&self) -> usize {
+        self.0.message_size() + self.1.message_size()
+    }
+}
+
+/// A typed payload that is passed between in-memory channels as is (see `Channels::send_payload`).
+pub(crate) type Payload = Arc<dyn Any + Send + Sync>;
+
 /// A message that is sent from the party with id `from_id` to another, containing a `Vec` of bytes.
+#[derive(Clone)]
 pub struct Message {
-    arrival_time: Instant,
-    from_id: usize,
-    contents: Vec<u8>,
+    pub(crate) arrival_time: Instant,
+    pub(crate) from_id: usize,
+    pub(crate) tag: u32,
+    pub(crate) contents: Arc<[u8]>,
+    pub(crate) padding: usize,
+    pub(crate) abort: bool,
+    pub(crate) compressed: bool,
+    pub(crate) payload: Option<Payload>,
+}
+
+impl Message {
+    /// Encodes this message as a frame of bytes, e.g. to carry it over a custom `Transport`. The simulated delay
+    /// that remains is encoded relative to now.
+    pub fn encode(&self) -> Vec<u8> {
+        encode_message(self)
+    }
+
+    /// Decodes a `frame` that was encoded by `encode` and sent by the party with `from_id`. The remaining delay
+    /// starts now. Returns `None` if the frame is malformed.
+    pub fn decode(frame: &[u8], from_id: usize) -> Option<Self> {
+        let header: &[u8; HEADER_LENGTH] = frame.get(..HEADER_LENGTH)?.try_into().unwrap();
+        let contents = &frame[HEADER_LENGTH..];
+        (content_length(header) == contents.len())
+            .then(|| decode_message(header, contents.to_vec(), from_id))
+    }
+}
+
+/// Computes the transport of the link between two parties from their ids (see `FullMesh::with_link_transports`).
+pub type LinkAssignment = Arc<dyn Fn(usize, usize) -> LinkTransport + Send + Sync>;
+
+/// The transport that carries the messages between a pair of parties in a `FullMesh`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkTransport {
+    /// An in-memory channel on which the network's latency, throughput and other overheads are simulated.
+    #[default]
+    Simulated,
+    /// A real TCP socket on localhost. Its costs are those of the kernel's networking stack, so the network's
+    /// overheads are not simulated on top of them.
+    Tcp,
+}
+
+/// The implementation of the in-memory channels that carry the messages in a `FullMesh`. The channels of the standard
+/// library can become a bottleneck for protocols that exchange millions of small messages, in which case the other
+/// backends (behind the `crossbeam` and `flume` features) may have less overhead per message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelBackend {
+    /// The channels of `std::sync::mpsc`.
+    #[default]
+    Std,
+    /// The channels of the `crossbeam-channel` crate.
+    #[cfg(feature = "crossbeam")]
+    Crossbeam,
+    /// The channels of the `flume` crate.
+    #[cfg(feature = "flume")]
+    Flume,
+}
+
+impl ChannelBackend {
+    /// Creates a queue, returning `copies` transports that deliver to it and the inbox from which it is received.
+    fn queue(self, copies: usize) -> (Vec<Box<dyn Transport>>, Inbox) {
+        fn boxed<T: Transport + Clone + 'static>(
+            sender: T,
+            copies: usize,
+        ) -> Vec<Box<dyn Transport>> {
+            (0..copies)
+                .map(|_| Box::new(sender.clone()) as Box<dyn Transport>)
+                .collect()
+        }
+
+        match self {
+            ChannelBackend::Std => {
+                let (sender, receiver) = channel();
+                (boxed(sender, copies), Inbox::Std(receiver))
+            }
+            #[cfg(feature = "crossbeam")]
+            ChannelBackend::Crossbeam => {
+                let (sender, receiver) = crossbeam_channel::unbounded();
+                (boxed(sender, copies), Inbox::Crossbeam(receiver))
+            }
+            #[cfg(feature = "flume")]
+            ChannelBackend::Flume => {
+                let (sender, receiver) = flume::unbounded();
+                (boxed(sender, copies), Inbox::Flume(receiver))
+            }
+        }
+    }
+}
+
+/// The queue from which a party receives the messages that the other parties' transports delivered.
+pub(crate) enum Inbox {
+    Std(Receiver<Message>),
+    #[cfg(feature = "crossbeam")]
+    Crossbeam(crossbeam_channel::Receiver<Message>),
+    #[cfg(feature = "flume")]
+    Flume(flume::Receiver<Message>),
+}
+
+impl Inbox {
+    /// Blocks until a message arrives. Returns `None` if all transports to this inbox were dropped.
+    fn recv(&self) -> Option<Message> {
+        match self {
+            Inbox::Std(receiver) => receiver.recv().ok(),
+            #[cfg(feature = "crossbeam")]
+            Inbox::Crossbeam(receiver) => receiver.recv().ok(),
+            #[cfg(feature = "flume")]
+            Inbox::Flume(receiver) => receiver.recv().ok(),
+        }
+    }
+
+    /// Blocks until a message arrives or the `timeout` elapsed.
+    fn recv_timeout(&self, timeout: Duration) -> Result<Message, RecvTimeoutError> {
+        match self {
+            Inbox::Std(receiver) => receiver.recv_timeout(timeout),
+            #[cfg(feature = "crossbeam")]
+            Inbox::Crossbeam(receiver) => receiver.recv_timeout(timeout).map_err(|error| {
+                if error.is_timeout() {
+                    RecvTimeoutError::Timeout
+                } else {
+                    RecvTimeoutError::Disconnected
+                }
+            }),
+            #[cfg(feature = "flume")]
+            Inbox::Flume(receiver) => receiver.recv_timeout(timeout).map_err(|error| match error {
+                flume::RecvTimeoutError::Timeout => RecvTimeoutError::Timeout,
+                flume::RecvTimeoutError::Disconnected => RecvTimeoutError::Disconnected,
+            }),
+        }
+    }
+
+    /// Blocks until a message arrives like `recv`. With a `watchdog`, the party with `id` is registered as waiting for
+    /// the party with `from_id` (or any party if `None`) whenever it is `blocked`, and it stops waiting (returning
+    /// `None`) once the parties deadlocked or the repetition exceeded its time limit.
+    fn recv_watched(
+        &self,
+        watchdog: Option<&Watchdog>,
+        id: usize,
+        from_id: Option<usize>,
+        blocked: impl Fn() -> bool,
+    ) -> Option<Message> {
+        let Some(watchdog) = watchdog else {
+            return self.recv();
+        };
+
+        loop {
+            if blocked() {
+                watchdog.wait(id, from_id);
+            }
+            match self.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => return Some(message),
+                Err(RecvTimeoutError::Timeout)
+                    if !watchdog.is_deadlocked() && !watchdog.is_expired() => {}
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Returns a message that already arrived, if any, without blocking.
+    fn try_recv(&self) -> Option<Message> {
+        match self {
+            Inbox::Std(receiver) => receiver.try_recv().ok(),
+            #[cfg(feature = "crossbeam")]
+            Inbox::Crossbeam(receiver) => receiver.try_recv().ok(),
+            #[cfg(feature = "flume")]
+            Inbox::Flume(receiver) => receiver.try_recv().ok(),
+        }
+    }
+}
+
+/// Carries the messages of a party to one other party, e.g. over an in-memory channel, a TCP socket or a QUIC
+/// connection. `Channels` simulate the network's costs on top of one transport per party, so a `Party` runs
+/// unchanged over any transport. Other transports can be plugged in with `Channels::with_transports`.
+///
+/// A transport only sends: the messages that arrive at the other party must end up in the queue from which its
+/// `Channels` receive, e.g. through a thread that reads them from a socket and decodes them with `Message::decode`.
+pub trait Transport: Send {
+    /// Delivers the `message` to the other party. Returns false if the other party can no longer receive messages.
+    fn deliver(&self, message: Message) -> bool;
+
+    /// Returns whether the messages are passed to the other party as is, so that they can carry a typed payload (see
+    /// `Channels::send_payload`). By default, this is false, because a transport that encodes the messages drops the
+    /// payloads.
+    fn carries_payloads(&self) -> bool {
+        false
+    }
+}
+
+/// The default transport, which puts messages directly in the other party's in-memory queue.
+impl Transport for Sender<Message> {
+    fn deliver(&self, message: Message) -> bool {
+        self.send(message).is_ok()
+    }
+
+    fn carries_payloads(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn deliver(&self, message: Message) -> bool {
+        (**self).deliver(message)
+    }
+
+    fn carries_payloads(&self) -> bool {
+        (**self).carries_payloads()
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl Transport for crossbeam_channel::Sender<Message> {
+    fn deliver(&self, message: Message) -> bool {
+        self.send(message).is_ok()
+    }
+
+    fn carries_payloads(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "flume")]
+impl Transport for flume::Sender<Message> {
+    fn deliver(&self, message: Message) -> bool {
+        self.send(message).is_ok()
+    }
+
+    fn carries_payloads(&self) -> bool {
+        true
+    }
+}
+
+/// The connections that have been set up between pairs of parties, which is shared by all parties' channels.
+#[derive(Debug)]
+pub struct Connections {
+    n_parties: usize,
+    established: Vec<AtomicBool>,
+}
+
+impl Connections {
+    /// Constructs the connections between `n_parties`, none of which are set up yet.
+    pub fn new(n_parties: usize) -> Self {
+        Connections {
+            n_parties,
+            established: (0..n_parties * n_parties)
+                .map(|_| AtomicBool::new(false))
+                .collect(),
+        }
+    }
+
+    /// Sets up the connection between the parties with ids `a` and `b`. Returns false if it was already set up.
+    fn establish(&self, a: usize, b: usize) -> bool {
+        let index = cmp::min(a, b) * self.n_parties + cmp::max(a, b);
+        !self.established[index].swap(true, Ordering::AcqRel)
+    }
+}
+
+/// The maximum size of the messages sent in each round, which all parties pad their messages to.
+#[derive(Debug, Default)]
+pub(crate) struct RoundSizes {
+    sizes: Mutex<Vec<usize>>,
+    padding: AtomicBool,
+}
+
+impl RoundSizes {
+    pub(crate) fn new() -> Self {
+        RoundSizes::default()
+    }
+
+    /// Starts padding messages to the recorded round sizes; before this, the round sizes are only recorded.
+    pub(crate) fn start_padding(&self) {
+        self.padding.store(true, Ordering::Release);
+    }
+
+    /// Records a message of `byte_count` bytes in `round` and returns the size that it should be padded to. Once
+    /// padding started, the round sizes are no longer updated, so that every message in a round is padded to the same
+    /// size regardless of the order in which the messages are sent.
+    fn pad(&self, round: usize, byte_count: usize) -> usize {
+        let mut sizes = self.sizes.lock().unwrap();
+
+        if self.padding.load(Ordering::Acquire) {
+            return cmp::max(sizes.get(round).copied().unwrap_or(0), byte_count);
+        }
+
+        if sizes.len() <= round {
+            sizes.resize(round + 1, 0);
+        }
+        sizes[round] = cmp::max(sizes[round], byte_count);
+        byte_count
+    }
+}
+
+/// A copy of a message that the protocol designated to be observed by the passive observer. Observed messages do
+/// not incur any communication costs.
+#[derive(Debug, Clone)]
+pub struct ObservedMessage {
+    /// The id of the party that sent the message.
+    pub from_id: usize,
+    /// The id of the party that the message was sent to, or `None` if it was broadcast.
+    pub to_id: Option<usize>,
+    /// The contents of the message.
+    pub contents: Vec<u8>,
 }
 
 /// Returns bytes with a delay, to simulate latency and throughput
 pub struct DelayedByteIterator {
     wake_time: Instant,
-    bytes: IntoIter<u8>,
+    bytes: Arc<[u8]>,
+    position: usize,
     seconds_per_byte: Duration,
 }
 
 impl DelayedByteIterator {
     /// Creates a DelayedByteIterator for the given `bytes`. Each byte is returned with `seconds_per_byte` delay.
-    pub fn new(bytes: Vec<u8>, start_time: Instant, seconds_per_byte: Duration) -> Self {
+    pub fn new(
+        bytes: impl Into<Arc<[u8]>>,
+        start_time: Instant,
+        seconds_per_byte: Duration,
+    ) -> Self {
         DelayedByteIterator {
             wake_time: start_time + seconds_per_byte,
-            bytes: bytes.into_iter(),
+            bytes: bytes.into(),
+            position: 0,
             seconds_per_byte,
         }
     }
+
+    /// Waits until all remaining bytes were transferred and returns them at once. If no bytes were taken from the
+    /// iterator yet, these are the bytes that the sender shared (see `Channels::send_shared`), without a copy.
+    pub fn into_shared(self) -> Arc<[u8]> {
+        let remaining = self.bytes.len() - self.position;
+        if remaining > 0 {
+            sleep(self.wake_time + self.seconds_per_byte * (remaining - 1) as u32 - Instant::now());
+        }
+
+        match self.position {
+            0 => self.bytes,
+            position => Arc::from(&self.bytes[position..]),
+        }
+    }
 }
 
 impl Iterator for DelayedByteIterator {
     type Item = u8;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.bytes.next().map(|byte| {
-            // Delays to fit the bandwidth constraints (returns immediately when the iterator is empty)
-            let dur = self.wake_time - Instant::now();
-            sleep(dur);
+        // Returns immediately when the iterator is empty
+        let byte = *self.bytes.get(self.position)?;
+        self.position += 1;
 
-            self.wake_time += self.seconds_per_byte;
-            byte
-        })
+        // Delays to fit the bandwidth constraints
+        let dur = self.wake_time - Instant::now();
+        sleep(dur);
+
+        self.wake_time += self.seconds_per_byte;
+        Some(byte)
     }
 }
 
 /// The communication channels for one party. These also keep track of how many bytes are sent. Channels are unidirectional.
 pub struct Channels {
     id: usize,
-    senders: Vec<Sender<Message>>,
-    receiver: Receiver<Message>,
-    buffer: Vec<Queue<(Instant, Vec<u8>)>>,
+    senders: Vec<Box<dyn Transport>>,
+    receiver: Inbox,
+    buffer: Vec<HashMap<u32, Queue<Message>>>,
     sent_bytes: Vec<usize>,
     latency: Duration,
     seconds_per_byte: Duration,
     next_vacancy: Instant,
+    burst_bytes: usize,
+    tokens: f64,
+    half_duplex: bool,
+    message_delay: Option<MessageDelay>,
+    observer: Option<Sender<ObservedMessage>>,
+    progress: Option<Arc<Progress>>,
+    watchdog: Option<Arc<Watchdog>>,
+    round: usize,
+    received_in_round: bool,
+    round_sizes: Option<Arc<RoundSizes>>,
+    mtu: Option<(usize, Duration)>,
+    fragment_counts: Vec<usize>,
+    handshake: Option<(Duration, Arc<Connections>)>,
+    nat_relay: Option<(Vec<usize>, Duration)>,
+    relayed_bytes: usize,
+    receive_policy: ReceivePolicy,
+    broadcast_semantics: BroadcastSemantics,
+    last_serviced: usize,
+    random_state: u64,
+    encryption: Option<(EncryptionPackaging, Duration, usize)>,
+    encryption_seconds_per_byte: Duration,
+    #[cfg(feature = "compression")]
+    compression: Option<Compression>,
+    #[cfg(feature = "compression")]
+    uncompressed_bytes: usize,
+    #[cfg(feature = "compression")]
+    compressed_bytes: usize,
+    sent_messages: usize,
+    sent_message_counts: Vec<usize>,
+    received_bytes: Vec<usize>,
+    received_messages: usize,
+    comm_checkpoints: Vec<CommCheckpoint>,
+    session_comm: Vec<CommCheckpoint>,
+    real_links: Vec<bool>,
+    trace: Option<(Instant, Vec<TraceEvent>)>,
+    message_sizes: Option<MessageSizeHistogram>,
+    round_timeline: Option<(Instant, Vec<RoundRecord>)>,
+    closed: bool,
 }
 
 impl Channels {
@@ -123,6 +875,34 @@ impl Channels {
         receiver: Receiver<Message>,
         latency: Duration,
         seconds_per_byte: Duration,
+    ) -> Self {
+        let transports = senders
+            .into_iter()
+            .map(|sender| Box::new(sender) as Box<dyn Transport>)
+            .collect();
+        Channels::with_transports(id, transports, receiver, latency, seconds_per_byte)
+    }
+
+    /// Constructs a new channel with communication overhead that sends messages to each party over the corresponding
+    /// transport in `senders`, and receives the messages that the other parties' transports put in the queue of
+    /// `receiver`.
+    pub fn with_transports(
+        id: usize,
+        senders: Vec<Box<dyn Transport>>,
+        receiver: Receiver<Message>,
+        latency: Duration,
+        seconds_per_byte: Duration,
+    ) -> Self {
+        Channels::with_inbox(id, senders, Inbox::Std(receiver), latency, seconds_per_byte)
+    }
+
+    /// Constructs a new channel like `with_transports`, which receives the messages from the `receiver` inbox.
+    pub(crate) fn with_inbox(
+        id: usize,
+        senders: Vec<Box<dyn Transport>>,
+        receiver: Inbox,
+        latency: Duration,
+        seconds_per_byte: Duration,
     ) -> Self {
         let sender_count = senders.len();
 
@@ -130,103 +910,1626 @@ impl Channels {
             id,
             senders,
             receiver,
-            buffer: (0..sender_count - 1).map(|_| Queue::new()).collect(),
+            buffer: (0..sender_count).map(|_| HashMap::new()).collect(),
             sent_bytes: vec![0; sender_count],
             latency,
             seconds_per_byte,
             next_vacancy: Instant::now(),
+            burst_bytes: 0,
+            tokens: 0.,
+            half_duplex: false,
+            message_delay: None,
+            observer: None,
+            progress: None,
+            watchdog: None,
+            round: 0,
+            received_in_round: false,
+            round_sizes: None,
+            mtu: None,
+            fragment_counts: vec![],
+            handshake: None,
+            nat_relay: None,
+            relayed_bytes: 0,
+            receive_policy: ReceivePolicy::Fifo,
+            broadcast_semantics: BroadcastSemantics::IncludeSelf,
+            last_serviced: id,
+            random_state: 0,
+            encryption: None,
+            encryption_seconds_per_byte: Duration::ZERO,
+            #[cfg(feature = "compression")]
+            compression: None,
+            #[cfg(feature = "compression")]
+            uncompressed_bytes: 0,
+            #[cfg(feature = "compression")]
+            compressed_bytes: 0,
+            sent_messages: 0,
+            sent_message_counts: vec![0; sender_count],
+            received_bytes: vec![0; sender_count],
+            received_messages: 0,
+            comm_checkpoints: vec![],
+            session_comm: vec![],
+            real_links: vec![false; sender_count],
+            trace: None,
+            message_sizes: None,
+            round_timeline: None,
+            closed: false,
         }
     }
 
-    fn add_sent_bytes(&mut self, byte_count: usize, to_id: &usize) {
-        self.sent_bytes[*to_id] += byte_count;
+    /// Stops simulating the network's overheads on the links to the parties with ids in `real_links`, because
+    /// their transports impose real costs (e.g. TCP sockets).
+    pub fn with_real_links(mut self, real_links: &[usize]) -> Self {
+        for &id in real_links {
+            self.real_links[id] = true;
+        }
+        self
     }
 
-    /// Blocks until this party receives a message from the party with `from_id`. A message is a
-    /// vector of bytes `Vec<u8>`. This can be achieved for example using `bincode` serialization.
-    /// The simulated delays are planned in such a way that they mimick the given throughput and latency constraints in the case where messages are scheduled first-in-first-out.
-    pub fn receive(&mut self, from_id: &usize) -> DelayedByteIterator {
-        debug_assert_ne!(
-            *from_id, self.id,
-            "`from_id = {}` may not be the same as `self.id = {}`",
-            from_id, self.id
-        );
+    /// Replaces the simulated `latency` of each message and the simulated time that each byte takes to transmit
+    /// (`seconds_per_byte`), e.g. to evaluate a protocol under a different network condition.
+    pub fn with_overhead(mut self, latency: Duration, seconds_per_byte: Duration) -> Self {
+        self.latency = latency;
+        self.seconds_per_byte = seconds_per_byte;
+        self
+    }
 
-        let reduced_id = if *from_id < self.id {
-            *from_id
-        } else {
-            *from_id - 1
-        };
+    /// Allows bursts of up to `burst_bytes` bytes to be received without throughput delays, following a token
+    /// bucket that refills at the throughput rate. With a burst of 0 bytes, messages are received strictly serially.
+    pub fn with_burst(mut self, burst_bytes: usize) -> Self {
+        self.burst_bytes = burst_bytes;
+        self.tokens = burst_bytes as f64;
+        self
+    }
 
-        let (arrival_time, bytes) = match self.buffer[reduced_id].size() {
-            0 => loop {
-                let message = self.receiver.recv().unwrap();
+    /// Splits every message that this party sends into fragments of at most `mtu` bytes, each of which adds
+    /// `per_fragment_delay` to the message's latency. Panics if `mtu` is 0.
+    pub fn with_mtu(mut self, mtu: usize, per_fragment_delay: Duration) -> Self {
+        assert!(mtu > 0, "the MTU must be at least one byte");
+        self.mtu = Some((mtu, per_fragment_delay));
+        self
+    }
 
-                if message.from_id == *from_id {
-                    break (message.arrival_time, message.contents);
-                }
+    /// Charges a one-time `setup_delay` on the first message that is sent between this party and another, unless
+    /// the other party already set up the connection in the shared `connections`.
+    pub fn with_handshake(mut self, setup_delay: Duration, connections: Arc<Connections>) -> Self {
+        self.handshake = Some((setup_delay, connections));
+        self
+    }
 
-                let message_reduced_id = if message.from_id < self.id {
-                    message.from_id
-                } else {
-                    message.from_id - 1
-                };
-                self.buffer[message_reduced_id]
-                    .add((message.arrival_time, message.contents))
-                    .unwrap();
-            },
-            _ => self.buffer[reduced_id].remove().unwrap(),
-        };
+    /// Relays all messages between this party and the parties with ids in `behind_nat` (or all messages, if this
+    /// party is behind NAT itself) via a relay node, which adds `relay_latency` to each relayed message.
+    pub fn with_nat_relay(mut self, behind_nat: &[usize], relay_latency: Duration) -> Self {
+        self.nat_relay = Some((behind_nat.to_vec(), relay_latency));
+        self
+    }
 
-        // Sleep until the next vacancy (the previously received message is only done transferring at that moment)
-        sleep(self.next_vacancy - Instant::now());
+    /// Sets the order in which pending messages from multiple parties are serviced. A random policy is seeded
+    /// differently for each party (but reproducibly).
+    pub fn with_receive_policy(mut self, receive_policy: ReceivePolicy) -> Self {
+        self.receive_policy = receive_policy;
+        if let ReceivePolicy::Random(seed) = receive_policy {
+            self.random_state = seed.wrapping_add(self.id as u64);
+        }
+        self
+    }
 
-        // The message must have arrived, so make sure to sleep until then (this sleep may be skipped if the message already arrived earlier)
-        sleep(arrival_time - Instant::now());
+    /// Sets whether this party's broadcasts include, exclude or echo to itself (see `BroadcastSemantics`).
+    pub fn with_broadcast_semantics(mut self, broadcast_semantics: BroadcastSemantics) -> Self {
+        self.broadcast_semantics = broadcast_semantics;
+        self
+    }
 
-        // If we already passed the next vacancy, we can skip the iterator ahead for the time we missed between the next vacancy/arrival time and now.
-        let start_time = cmp::max(self.next_vacancy, arrival_time);
+    /// Encrypts the messages that this party sends to other parties: every ciphertext costs `encryption_delay` (the
+    /// party is blocked meanwhile) and adds `byte_overhead` bytes to the message. With `EncryptionPackaging::Broadcast`,
+    /// a broadcast is only encrypted once.
+    pub fn with_encryption(
+        mut self,
+        packaging: EncryptionPackaging,
+        encryption_delay: Duration,
+        byte_overhead: usize,
+    ) -> Self {
+        self.encryption = Some((packaging, encryption_delay, byte_overhead));
+        self
+    }
 
-        // Set the next vacancy to be when this iterator finishes
-        self.next_vacancy = start_time + self.seconds_per_byte * bytes.len() as u32;
+    /// Additionally blocks the party for the time that it takes to encrypt each byte of a ciphertext, at most
+    /// `bytes_per_second`. Only applies to encrypted messages (see `with_encryption`).
+    pub fn with_encryption_throughput(mut self, bytes_per_second: f64) -> Self {
+        self.encryption_seconds_per_byte = Duration::from_secs_f64(1. / bytes_per_second);
+        self
+    }
 
-        // We subtract this time from the arrival time for simplicity.
-        DelayedByteIterator::new(bytes, start_time, self.seconds_per_byte)
+    /// Compresses the messages that this party sends with `compression` (before encrypting them, if the secure
+    /// channels are modeled), which costs the party the time that compressing actually takes. The recipients
+    /// decompress the messages transparently.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
     }
 
-    /// Sends a vector of bytes to the party with `to_id` and keeps track of the number of bits sent
-    /// to this party.
-    pub fn send(&mut self, message: &[u8], to_id: &usize) {
-        let byte_count = message.len();
+    /// Returns the number of bytes that this party's messages had before they were compressed, counted once per
+    /// recipient (see `with_compression`).
+    #[cfg(feature = "compression")]
+    pub fn uncompressed_bytes(&self) -> usize {
+        self.uncompressed_bytes
+    }
 
-        self.senders[*to_id]
-            .send(Message {
-                arrival_time: Instant::now() + self.latency,
-                from_id: self.id,
-                contents: message.to_vec(),
-            })
-            .unwrap();
+    /// Returns the number of bytes that this party's messages had after they were compressed, counted once per
+    /// recipient (see `with_compression`).
+    #[cfg(feature = "compression")]
+    pub fn compressed_bytes(&self) -> usize {
+        self.compressed_bytes
+    }
 
-        self.add_sent_bytes(byte_count, to_id);
+    /// Records the communication since the previous checkpoint (or since the start) under `label`, so that it can be
+    /// attributed to a milestone of the protocol (see `AggregatedStats::summarize_checkpoints`).
+    pub fn checkpoint_comm(&mut self, label: &str) {
+        // The counters are cumulative, so subtract what the previous checkpoints already accounted for
+        let checkpointed = |count: fn(&CommCheckpoint) -> usize| -> usize {
+            self.comm_checkpoints.iter().map(count).sum()
+        };
+        let checkpoint = CommCheckpoint {
+            label: label.to_string(),
+            sent_bytes: self.sent_bytes.iter().sum::<usize>() - checkpointed(|c| c.sent_bytes),
+            sent_messages: self.sent_messages - checkpointed(|c| c.sent_messages),
+            received_bytes: self.received_bytes.iter().sum::<usize>()
+                - checkpointed(|c| c.received_bytes),
+            received_messages: self.received_messages - checkpointed(|c| c.received_messages),
+        };
+
+        self.comm_checkpoints.push(checkpoint);
     }
 
-    /// Broadcasts a message (a vector of bytes) to all parties and keeps track of the number of
-    /// bits sent.
-    pub fn broadcast(&mut self, message: &[u8]) {
-        let byte_count = message.len();
-
-        for sender in &self.senders {
-            sender
-                .send(Message {
-                    arrival_time: Instant::now() + self.latency,
-                    from_id: self.id,
-                    contents: message.to_vec(),
-                })
-                .unwrap();
+    /// Returns the checkpoints that this party recorded so far, in the order they were recorded.
+    pub fn comm_checkpoints(&self) -> &[CommCheckpoint] {
+        &self.comm_checkpoints
+    }
+
+    /// Returns a handle for the session `session_id` of a nested sub-protocol. The session's messages are multiplexed
+    /// over the same links as all other messages (as messages tagged with `session_id`, see `send_tagged`), but its
+    /// communication is also tracked separately (see `AggregatedStats::summarize_sessions`). The `session_id` must
+    /// differ from the tags that the protocol uses itself. Returns a `CommError` if it is reserved like in
+    /// `send_tagged`, or if it is the `DEFAULT_TAG` of the messages outside of sessions.
+    pub fn subsession(&mut self, session_id: u32) -> Result<Subsession<'_>, CommError> {
+        check_tag(session_id)?;
+        if session_id == DEFAULT_TAG {
+            return Err(CommError::ReservedTag { tag: session_id });
         }
 
-        for i in 0..self.senders.len() {
-            self.add_sent_bytes(byte_count, &i);
+        Ok(Subsession {
+            channels: self,
+            session_id,
+        })
+    }
+
+    /// Returns the communication of each session that this party took part in so far, labeled by the session ids in
+    /// the order in which the sessions were first used.
+    pub fn session_comm(&self) -> &[CommCheckpoint] {
+        &self.session_comm
+    }
+
+    /// Returns the communication of this party so far, without a label.
+    fn comm_totals(&self) -> CommCheckpoint {
+        CommCheckpoint {
+            label: String::new(),
+            sent_bytes: self.sent_bytes.iter().sum(),
+            sent_messages: self.sent_messages,
+            received_bytes: self.received_bytes.iter().sum(),
+            received_messages: self.received_messages,
         }
     }
+
+    /// Attributes the communication since `before` (see `comm_totals`) to the session `session_id`.
+    fn record_session(&mut self, session_id: u32, before: CommCheckpoint) {
+        let after = self.comm_totals();
+        let label = session_id.to_string();
+        let i = match self.session_comm.iter().position(|s| s.label == label) {
+            Some(i) => i,
+            None => {
+                self.session_comm.push(CommCheckpoint {
+                    label,
+                    ..CommCheckpoint::default()
+                });
+                self.session_comm.len() - 1
+            }
+        };
+
+        let session = &mut self.session_comm[i];
+        session.sent_bytes += after.sent_bytes - before.sent_bytes;
+        session.sent_messages += after.sent_messages - before.sent_messages;
+        session.received_bytes += after.received_bytes - before.received_bytes;
+        session.received_messages += after.received_messages - before.received_messages;
+    }
+
+    /// Returns the number of bytes that this party sent via the relay node.
+    pub fn relayed_bytes(&self) -> usize {
+        self.relayed_bytes
+    }
+
+    /// Returns the number of bytes that this party sent to each party so far, indexed by party id.
+    pub fn sent_bytes(&self) -> &[usize] {
+        &self.sent_bytes
+    }
+
+    /// Returns the number of messages that this party sent to each party so far, indexed by party id.
+    pub fn sent_message_counts(&self) -> &[usize] {
+        &self.sent_message_counts
+    }
+
+    /// Returns the number of bytes that this party received from each party so far, indexed by party id.
+    pub fn received_bytes(&self) -> &[usize] {
+        &self.received_bytes
+    }
+
+    /// Returns the number of fragments that each message sent so far produced, in the order they were sent. This is
+    /// empty if no MTU is configured.
+    pub fn fragment_counts(&self) -> &[usize] {
+        &self.fragment_counts
+    }
+
+    /// Makes this party's link half-duplex: sending a message occupies the same capacity that is used for receiving,
+    /// so the message only departs once the link is vacant and subsequent messages are delayed until it was sent.
+    pub fn with_half_duplex(mut self) -> Self {
+        self.half_duplex = true;
+        self
+    }
+
+    pub(crate) fn attach_progress(&mut self, progress: Arc<Progress>) {
+        self.progress = Some(progress);
+    }
+
+    /// Starts recording the sent and received messages as `TraceEvent`s, relative to `start`.
+    pub(crate) fn attach_trace(&mut self, start: Instant) {
+        self.trace = Some((start, vec![]));
+    }
+
+    /// Returns the recorded `TraceEvent`s of the sent and received messages (see `attach_trace`).
+    pub(crate) fn trace(&self) -> &[TraceEvent] {
+        self.trace.as_ref().map_or(&[], |(_, events)| events)
+    }
+
+    /// Records a `TraceEvent` at the current time if tracing, which `event` constructs from the time since the start
+    /// of the trace.
+    fn record_trace(&mut self, event: impl FnOnce(Duration) -> TraceEvent) {
+        if let Some((start, events)) = &mut self.trace {
+            events.push(event(start.elapsed()));
+        }
+    }
+
+    /// Starts counting the sent messages in a histogram of their sizes with the given bucket `bounds`.
+    pub(crate) fn attach_message_size_histogram(&mut self, bounds: &[usize]) {
+        self.message_sizes = Some(MessageSizeHistogram::new(bounds.to_vec()));
+    }
+
+    /// Returns the histogram of the sizes of the sent messages, if one was attached.
+    pub(crate) fn message_sizes(&self) -> Option<&MessageSizeHistogram> {
+        self.message_sizes.as_ref()
+    }
+
+    /// Starts recording when each round started (relative to `start`) and how many bytes were sent and received in it.
+    pub(crate) fn attach_round_timeline(&mut self, start: Instant) {
+        self.round_timeline = Some((start, vec![(Duration::ZERO, 0, 0)]));
+    }
+
+    /// Returns the start, sent bytes and received bytes of each round, if the timeline was attached.
+    pub(crate) fn round_timeline(&self) -> &[RoundRecord] {
+        self.round_timeline
+            .as_ref()
+            .map_or(&[], |(_, rounds)| rounds)
+    }
+
+    pub(crate) fn attach_watchdog(&mut self, watchdog: Arc<Watchdog>) {
+        self.watchdog = Some(watchdog);
+    }
+
+    pub(crate) fn attach_round_sizes(&mut self, round_sizes: Arc<RoundSizes>) {
+        self.round_sizes = Some(round_sizes);
+    }
+
+    /// Notifies all other parties that this party failed, so that they stop waiting for its messages, and closes
+    /// this party's receiving end so that sending to it fails.
+    pub(crate) fn abort(&mut self) {
+        for (to_id, sender) in self.senders.iter().enumerate() {
+            if to_id != self.id {
+                // Parties that already stopped receiving do not need to be notified
+                sender.deliver(Message {
+                    arrival_time: Instant::now(),
+                    from_id: self.id,
+                    tag: DEFAULT_TAG,
+                    contents: Arc::new([]),
+                    padding: 0,
+                    abort: true,
+                    compressed: false,
+                    payload: None,
+                });
+            }
+        }
+
+        self.receiver = Inbox::Std(channel().1);
+    }
+
+    /// Splits these channels into a sending and a receiving half, so that e.g. worker threads of this party (see
+    /// `std::thread::scope`) can send messages while the party receives. The `ChannelSender` can be cloned for each
+    /// worker. The halves share the simulated link, so the overheads are the same as when using these channels.
+    pub fn split(&mut self) -> (ChannelSender<'_>, ChannelReceiver<'_>) {
+        let id = self.id;
+        let inbox = mem::replace(&mut self.receiver, Inbox::Std(channel().1));
+        let watchdog = self.watchdog.clone();
+        let shared = Arc::new(Mutex::new(self));
+
+        (
+            ChannelSender {
+                shared: shared.clone(),
+            },
+            ChannelReceiver {
+                id,
+                shared,
+                inbox,
+                watchdog,
+            },
+        )
+    }
+
+    /// Returns a writer that streams one message to the party with `to_id` in chunks of `STREAM_CHUNK_BYTES`, so that
+    /// large messages do not need to be materialized as a whole. Every chunk is sent (and charged) as a message of
+    /// its own, and the end of the message is marked by an empty chunk when the writer is finished or dropped. Only one
+    /// message should be streamed at a time between the same parties. Raises a `ProtocolError` if the party with
+    /// `to_id` is this party itself or does not exist.
+    #[track_caller]
+    pub fn send_stream(&mut self, to_id: usize) -> MessageWriter<'_> {
+        self.check_peer(to_id, ChannelOperation::Send);
+
+        MessageWriter {
+            channels: self,
+            to_id,
+            chunk: Vec::with_capacity(STREAM_CHUNK_BYTES),
+            finished: false,
+        }
+    }
+
+    /// Returns a reader for the next message that the party with `from_id` streams to this party (see
+    /// `send_stream`). Each chunk is received with the simulated latency and throughput when the reader reaches it,
+    /// and the reader returns the end of the file after the last chunk. Raises a `ProtocolError` if the party with
+    /// `from_id` is this party itself or does not exist.
+    #[track_caller]
+    pub fn receive_stream(&mut self, from_id: usize) -> MessageReader<'_> {
+        self.check_peer(from_id, ChannelOperation::Receive);
+
+        MessageReader {
+            channels: self,
+            from_id,
+            chunk: Arc::from([]),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the round that this party is in, starting from 0. Rounds are counted automatically: a new round
+    /// starts when the party sends a message after it received one.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub(crate) fn attach_observer(&mut self, observer: Sender<ObservedMessage>) {
+        self.observer = Some(observer);
+    }
+
+    /// Hands a copy of the message to the observer (if there is one) without incurring communication costs.
+    fn observe(&self, message: &[u8], to_id: Option<usize>) {
+        if let Some(observer) = &self.observer {
+            // The observer may not affect the protocol, so a missing observer is ignored
+            let _ = observer.send(ObservedMessage {
+                from_id: self.id,
+                to_id,
+                contents: message.to_vec(),
+            });
+        }
+    }
+
+    /// Adds an extra delay to every message sent through these channels, computed by `message_delay`.
+    pub fn with_message_delay(mut self, message_delay: MessageDelay) -> Self {
+        self.message_delay = Some(message_delay);
+        self
+    }
+
+    /// Computes when a message of `byte_count` bytes that is sent now arrives at the party with `to_id`. On a
+    /// half-duplex link, this occupies the link for the duration of the transfer.
+    fn arrival_time(&mut self, byte_count: usize, to_id: usize) -> Instant {
+        if self.real_links[to_id] {
+            return Instant::now();
+        }
+
+        let mut delay = match &self.message_delay {
+            Some(message_delay) => message_delay(byte_count, self.id, to_id),
+            None => Duration::ZERO,
+        };
+
+        if let Some((setup_delay, connections)) = &self.handshake {
+            if connections.establish(self.id, to_id) {
+                delay += *setup_delay;
+            }
+        }
+
+        // A message takes an extra hop through the relay if either endpoint is behind NAT
+        if let Some((behind_nat, relay_latency)) = &self.nat_relay {
+            if behind_nat.contains(&self.id) || behind_nat.contains(&to_id) {
+                delay += *relay_latency;
+                self.relayed_bytes += byte_count;
+            }
+        }
+
+        // Every fragment incurs its own delay (a message always consists of at least one fragment)
+        if let Some((mtu, per_fragment_delay)) = self.mtu {
+            let fragment_count = cmp::max(1, byte_count.div_ceil(mtu));
+            self.fragment_counts.push(fragment_count);
+            delay += per_fragment_delay * fragment_count as u32;
+        }
+
+        let departure_time = if self.half_duplex {
+            let departure_time = cmp::max(self.next_vacancy, Instant::now());
+            self.occupy_link(departure_time, byte_count);
+            departure_time
+        } else {
+            Instant::now()
+        };
+
+        departure_time + self.latency + delay
+    }
+
+    /// Occupies the link for transferring `byte_count` bytes starting at `start_time`, and sets the next vacancy to
+    /// when the transfer finishes. Returns how much earlier the transfer finishes due to a burst.
+    fn occupy_link(&mut self, start_time: Instant, byte_count: usize) -> Duration {
+        // The token bucket refills at the throughput rate while the link is idle, up to the burst size
+        if !self.seconds_per_byte.is_zero() && start_time > self.next_vacancy {
+            let refilled = (start_time - self.next_vacancy).as_secs_f64()
+                / self.seconds_per_byte.as_secs_f64();
+            self.tokens = (self.tokens + refilled).min(self.burst_bytes as f64);
+        }
+
+        // The bytes that are covered by tokens are transferred immediately, the rest is limited by the throughput
+        let burst = self.tokens.min(byte_count as f64);
+        self.tokens -= burst;
+        let burst_duration = self.seconds_per_byte.mul_f64(burst);
+
+        // Set the next vacancy to be when this transfer finishes
+        self.next_vacancy = start_time + self.seconds_per_byte * byte_count as u32 - burst_duration;
+
+        burst_duration
+    }
+
+    fn add_sent_bytes(&mut self, byte_count: usize, to_id: &usize) {
+        self.sent_bytes[*to_id] += byte_count;
+        self.sent_messages += 1;
+        self.sent_message_counts[*to_id] += 1;
+        if let Some(message_sizes) = &mut self.message_sizes {
+            message_sizes.record(byte_count);
+        }
+        if let Some((_, rounds)) = &mut self.round_timeline {
+            rounds.last_mut().unwrap().1 += byte_count;
+        }
+        self.record_trace(|time| TraceEvent::Sent {
+            to_id: *to_id,
+            bytes: byte_count,
+            time,
+        });
+
+        if let Some(progress) = &self.progress {
+            progress.add_sent_bytes(byte_count);
+        }
+    }
+
+    /// Returns the id of the party whose message should be received next according to the receive policy (see
+    /// `ReceivePolicy`), or `None` if no party has a pending message. This does not block.
+    pub fn next_pending_sender(&mut self) -> Option<usize> {
+        self.drain_inbox();
+
+        let pending: Vec<usize> = (0..self.senders.len())
+            .filter(|&id| id != self.id && self.buffered_count(id, DEFAULT_TAG) > 0)
+            .collect();
+        if pending.is_empty() {
+            return None;
+        }
+
+        let from_id = match self.receive_policy {
+            ReceivePolicy::Fifo => *pending
+                .iter()
+                .min_by_key(|&&id| self.buffer[id][&DEFAULT_TAG].peek().unwrap().arrival_time)
+                .unwrap(),
+            ReceivePolicy::RoundRobin => *pending
+                .iter()
+                .find(|&&id| id > self.last_serviced)
+                .unwrap_or(&pending[0]),
+            ReceivePolicy::Random(_) => {
+                pending[(self.next_random() % pending.len() as u64) as usize]
+            }
+        };
+        self.last_serviced = from_id;
+
+        Some(from_id)
+    }
+
+    /// Returns the number of messages from the party with `from_id` that arrived according to the simulated delays
+    /// but were not received yet, e.g. to decide whether to process a batch. This does not block or consume any
+    /// message. Raises a `ProtocolError` if the party with `from_id` is this party itself or does not exist.
+    #[track_caller]
+    pub fn pending_from(&mut self, from_id: &usize) -> usize {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+        self.drain_inbox();
+
+        let Some(queue) = self.buffer[*from_id].get(&DEFAULT_TAG) else {
+            return 0;
+        };
+        let now = Instant::now();
+        let mut queue = queue.clone();
+        let mut count = 0;
+        while let Ok(message) = queue.remove() {
+            if message.arrival_time <= now {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the contents of the next message from the party with `from_id` if it arrived according to the
+    /// simulated delays, without receiving it: it is still received by the next `receive`, and peeking does not
+    /// incur the costs of the transfer. Raises a `ProtocolError` if the party with `from_id` is this party itself or
+    /// does not exist.
+    #[track_caller]
+    pub fn peek(&mut self, from_id: &usize) -> Option<Arc<[u8]>> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+        self.drain_inbox();
+
+        let message = self.buffer[*from_id].get(&DEFAULT_TAG)?.peek().ok()?;
+        (message.arrival_time <= Instant::now()).then(|| self.restore(message).0)
+    }
+
+    /// Moves the messages that are in the inbox to the buffer, without blocking.
+    fn drain_inbox(&mut self) {
+        while let Some(message) = self.receiver.try_recv() {
+            self.dequeued(&message);
+            self.buffer_message(message);
+        }
+    }
+
+    /// Stores a message until the party receives from its sender, or marks the channel as closed if the sender
+    /// failed. The copies of this party's own broadcasts are discarded (see `BroadcastSemantics::IncludeSelf`).
+    fn buffer_message(&mut self, message: Message) {
+        if message.abort {
+            self.closed = true;
+            return;
+        }
+        if message.from_id == self.id {
+            return;
+        }
+
+        self.buffered(message.from_id, message.tag)
+            .add(message)
+            .unwrap();
+    }
+
+    /// Returns the buffered messages with `tag` from the party with `from_id`.
+    fn buffered(&mut self, from_id: usize, tag: u32) -> &mut Queue<Message> {
+        self.buffer[from_id].entry(tag).or_insert_with(Queue::new)
+    }
+
+    /// Returns the number of buffered messages with `tag` from the party with `from_id`.
+    fn buffered_count(&self, from_id: usize, tag: u32) -> usize {
+        self.buffer[from_id]
+            .get(&tag)
+            .map_or(0, |queue| queue.size())
+    }
+
+    /// Draws the next number from this party's seeded generator (SplitMix64).
+    fn next_random(&mut self) -> u64 {
+        self.random_state = self.random_state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.random_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Blocks until this party receives a message from the party with `from_id`. A message is a
+    /// vector of bytes `Vec<u8>`. This can be achieved for example using `bincode` serialization.
+    /// The simulated delays are planned in such a way that they mimick the given throughput and latency constraints in the case where messages are scheduled first-in-first-out.
+    /// Returns a `CommError` if the channel was closed because another party failed, so that a protocol can handle
+    /// the dropout of a party. Raises a `ProtocolError` if the party with `from_id` is this party itself or does not
+    /// exist.
+    #[track_caller]
+    pub fn receive(&mut self, from_id: &usize) -> Result<DelayedByteIterator, CommError> {
+        self.receive_with_tag(from_id, DEFAULT_TAG)
+    }
+
+    /// Blocks until this party receives a message with `tag` from the party with `from_id` (see `send_tagged`), like
+    /// `receive`. Messages with other tags are kept until they are received with their own tag, so interleaved
+    /// sub-protocols between the same parties do not need to frame their messages themselves. Returns a `CommError`
+    /// if the `tag` is reserved.
+    #[track_caller]
+    pub fn receive_tagged(
+        &mut self,
+        from_id: &usize,
+        tag: u32,
+    ) -> Result<DelayedByteIterator, CommError> {
+        check_tag(tag)?;
+        self.receive_with_tag(from_id, tag)
+    }
+
+    /// Receives a message with `tag` like `receive_tagged`, but also with the reserved tags.
+    #[track_caller]
+    fn receive_with_tag(
+        &mut self,
+        from_id: &usize,
+        tag: u32,
+    ) -> Result<DelayedByteIterator, CommError> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+
+        self.check_echo(*from_id, tag);
+        let message = self.next_message(*from_id, tag)?;
+
+        Ok(self.deliver(message, *from_id))
+    }
+
+    /// Panics if this party tries to receive an echo of its own broadcast (from `from_id`) that it never broadcast,
+    /// because it would wait forever.
+    fn check_echo(&self, from_id: usize, tag: u32) {
+        if from_id == self.id && self.buffered_count(self.id, tag) == 0 {
+            panic!(
+                "party {} tried to receive an echo of its own broadcast, but it did not broadcast",
+                self.id
+            );
+        }
+    }
+
+    /// Blocks until the next message with `tag` from the party with `from_id` is available, without accounting for
+    /// it. Returns a `CommError` if the channel was closed before.
+    fn next_message(&mut self, from_id: usize, tag: u32) -> Result<Message, CommError> {
+        if self.buffered_count(from_id, tag) > 0 {
+            return Ok(self.buffered(from_id, tag).remove().unwrap());
+        }
+
+        loop {
+            let message = self.next_unbuffered(Some(from_id))?;
+            if message.from_id == from_id && message.tag == tag {
+                return Ok(message);
+            }
+
+            self.buffer_message(message);
+        }
+    }
+
+    /// Blocks until the next message arrives in the inbox, while waiting for the party with `from_id` (or any party
+    /// if `None`). Returns a `CommError` if the channel was closed, because another party failed, the parties
+    /// deadlocked or the repetition exceeded its time limit (see `Watchdog`).
+    fn next_unbuffered(&mut self, from_id: Option<usize>) -> Result<Message, CommError> {
+        if !self.closed {
+            let wait_start = Instant::now();
+            let message =
+                self.receiver
+                    .recv_watched(self.watchdog.as_deref(), self.id, from_id, || true);
+            self.record_waiting(wait_start);
+
+            if let Some(message) = &message {
+                self.dequeued(message);
+            }
+            match message {
+                Some(message) if !message.abort => return Ok(message),
+                _ => self.closed = true,
+            }
+        }
+
+        Err(CommError::Closed { id: self.id })
+    }
+
+    /// Reports to the watchdog (if any) that this party took the `message` from its inbox.
+    fn dequeued(&self, message: &Message) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.received(self.id, !message.abort);
+        }
+    }
+
+    /// Reports to the watchdog (if any) that a message is about to be put in the inbox of the party with `to_id`.
+    fn enqueuing(&self, to_id: usize) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.sent(to_id);
+        }
+    }
+
+    /// Returns whether the channel was closed, because another party failed.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Blocks until all parties reached this barrier, so that the phases of a protocol can be separated and the
+    /// timings of the next phase start at the same moment for all parties. The parties synchronize by exchanging empty
+    /// messages, so the barrier takes (at least) the network's latency, but these messages do not count towards the
+    /// communication. Returns a `CommError` if a party disconnected or failed.
+    pub fn barrier(&mut self) -> Result<(), CommError> {
+        for to_id in 0..self.senders.len() {
+            if to_id == self.id {
+                continue;
+            }
+
+            let message = Message {
+                arrival_time: if self.real_links[to_id] {
+                    Instant::now()
+                } else {
+                    Instant::now() + self.latency
+                },
+                from_id: self.id,
+                tag: BARRIER_TAG,
+                contents: Arc::new([]),
+                padding: 0,
+                abort: false,
+                compressed: false,
+                payload: None,
+            };
+            self.enqueuing(to_id);
+            if !self.senders[to_id].deliver(message) {
+                return Err(CommError::Disconnected {
+                    from_id: self.id,
+                    to_id,
+                });
+            }
+        }
+
+        for from_id in 0..self.senders.len() {
+            if from_id != self.id {
+                let message = self.next_message(from_id, BARRIER_TAG)?;
+                self.wait_until(message.arrival_time);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until this party receives a message from any other party, and returns the sender's id together with
+    /// the message. If messages from multiple parties are pending, the receive policy decides which one is received
+    /// first (see `ReceivePolicy`). Returns a `CommError` if the channel was closed, and panics if there are no other
+    /// parties to receive from.
+    pub fn receive_any(&mut self) -> Result<(usize, DelayedByteIterator), CommError> {
+        assert!(
+            self.senders.len() > 1,
+            "party {} cannot receive from any other party, because it is the only party",
+            self.id
+        );
+
+        loop {
+            if let Some(from_id) = self.next_pending_sender() {
+                return Ok((from_id, self.receive(&from_id)?));
+            }
+
+            let message = self.next_unbuffered(None)?;
+            self.buffer_message(message);
+        }
+    }
+
+    /// Receives one message from every other party, in the order of their ids, and returns each together with the
+    /// sender's id. Returns a `CommError` if the channel was closed.
+    pub fn gather(&mut self) -> Result<Vec<(usize, DelayedByteIterator)>, CommError> {
+        let id = self.id;
+        (0..self.senders.len())
+            .filter(|&from_id| from_id != id)
+            .map(|from_id| Ok((from_id, self.receive(&from_id)?)))
+            .collect()
+    }
+
+    /// Receives a message from the party with `from_id` like `receive`, but returns `None` immediately if no message
+    /// from that party has arrived yet according to the simulated delays (or if the previous message is still being
+    /// transferred), so that a party can poll multiple parties without blocking on one of them. Returns a `CommError`
+    /// if no message from that party is pending and the channel was closed. Raises a `ProtocolError` if the party with
+    /// `from_id` is this party itself or does not exist.
+    #[track_caller]
+    pub fn try_receive(
+        &mut self,
+        from_id: &usize,
+    ) -> Result<Option<DelayedByteIterator>, CommError> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+
+        self.drain_inbox();
+
+        let Some(arrival_time) = self.buffer[*from_id]
+            .get(&DEFAULT_TAG)
+            .and_then(|queue| queue.peek().ok())
+            .map(|message| message.arrival_time)
+        else {
+            return if self.closed {
+                Err(CommError::Closed { id: self.id })
+            } else {
+                Ok(None)
+            };
+        };
+        if !self.real_links[*from_id] && cmp::max(self.next_vacancy, arrival_time) > Instant::now()
+        {
+            return Ok(None);
+        }
+
+        let message = self.buffered(*from_id, DEFAULT_TAG).remove().unwrap();
+        Ok(Some(self.deliver(message, *from_id)))
+    }
+
+    /// Sleeps until `time`, which counts as waiting for the network (see `record_waiting`).
+    fn wait_until(&self, time: Instant) {
+        let wait_start = Instant::now();
+        match &self.watchdog {
+            Some(watchdog) => watchdog.sleep_until(time),
+            None => sleep(time - wait_start),
+        }
+        self.record_waiting(wait_start);
+    }
+
+    /// Records that this party waited for the network since `wait_start`, which is attributed to its running timers
+    /// (see `AggregatedStats::summarize_idle_time`). Waiting in a split `ChannelReceiver` is not recorded, because it
+    /// overlaps with the work of the party's other threads.
+    fn record_waiting(&self, wait_start: Instant) {
+        if let Some(progress) = &self.progress {
+            progress.add_waiting_time(wait_start.elapsed());
+        }
+    }
+
+    /// Accounts for the `message` from the party with `from_id`, and waits until it can be transferred.
+    fn deliver(&mut self, message: Message, from_id: usize) -> DelayedByteIterator {
+        let (bytes, start_time) = self.schedule(message, from_id);
+
+        // Sleep until the message arrived and the previously received message is done transferring (this sleep may be
+        // skipped if both happened earlier)
+        self.wait_until(start_time);
+
+        bytes
+    }
+
+    /// Accounts for the `message` from the party with `from_id` and occupies the link for its transfer, without
+    /// waiting. Returns the message's bytes and the moment from which they can be received.
+    fn schedule(&mut self, message: Message, from_id: usize) -> (DelayedByteIterator, Instant) {
+        // An echo of this party's own broadcast is free and immediately available
+        if from_id == self.id {
+            let bytes = DelayedByteIterator::new(message.contents, Instant::now(), Duration::ZERO);
+            return (bytes, Instant::now());
+        }
+
+        let arrival_time = message.arrival_time;
+        let byte_count = message.contents.len() + message.padding;
+        self.received_in_round = true;
+        self.received_bytes[from_id] += byte_count;
+        if let Some((_, rounds)) = &mut self.round_timeline {
+            rounds.last_mut().unwrap().2 += byte_count;
+        }
+        self.received_messages += 1;
+        self.record_trace(|time| TraceEvent::Received {
+            from_id,
+            bytes: byte_count,
+            time,
+        });
+        let (bytes, seconds_per_byte) = self.restore(message);
+
+        if self.real_links[from_id] {
+            let bytes = DelayedByteIterator::new(bytes, Instant::now(), Duration::ZERO);
+            return (bytes, Instant::now());
+        }
+
+        // The transfer starts at the next vacancy (the previously received message is only done transferring at that
+        // moment), but not before the message arrived
+        let start_time = cmp::max(self.next_vacancy, arrival_time);
+
+        // Set the next vacancy to be when this iterator finishes
+        let burst_duration = self.occupy_link(start_time, byte_count);
+
+        // We subtract this time from the arrival time for simplicity.
+        let burst_start_time = start_time.checked_sub(burst_duration).unwrap_or(start_time);
+        let bytes = DelayedByteIterator::new(bytes, burst_start_time, seconds_per_byte);
+        (bytes, start_time)
+    }
+
+    /// Returns the original contents of the `message`, which are decompressed if the sender compressed them (see
+    /// `with_compression`), together with the time per original byte at which they are transferred.
+    fn restore(&self, message: Message) -> (Arc<[u8]>, Duration) {
+        #[cfg(feature = "compression")]
+        if message.compressed {
+            let bytes: Arc<[u8]> = miniz_oxide::inflate::decompress_to_vec(&message.contents)
+                .expect("a compressed message could not be decompressed")
+                .into();
+            let ratio = message.contents.len() as f64 / bytes.len().max(1) as f64;
+            return (bytes, self.seconds_per_byte.mul_f64(ratio));
+        }
+
+        (message.contents, self.seconds_per_byte)
+    }
+
+    /// Compresses the `contents` of a message to `recipient_count` parties if this party compresses its messages,
+    /// and counts the bytes before and after compression.
+    #[cfg(feature = "compression")]
+    fn compress(&mut self, contents: Arc<[u8]>, recipient_count: usize) -> Arc<[u8]> {
+        match self.compression {
+            Some(compression) => {
+                let compressed: Arc<[u8]> = compression.compress(&contents).into();
+                self.uncompressed_bytes += contents.len() * recipient_count;
+                self.compressed_bytes += compressed.len() * recipient_count;
+                compressed
+            }
+            None => contents,
+        }
+    }
+
+    /// Raises a `ProtocolError` that blames the caller if `peer` is not the id of another party. The party's name is
+    /// filled in when the error is reported.
+    #[track_caller]
+    fn check_peer(&self, peer: usize, operation: ChannelOperation) {
+        // Only the echoes of this party's own broadcasts can be received from itself
+        let echoes = operation == ChannelOperation::Receive
+            && self.broadcast_semantics == BroadcastSemantics::EchoToSelf;
+        if (peer == self.id && !echoes) || peer >= self.senders.len() {
+            panic_any(ProtocolError {
+                party: self.id,
+                party_name: String::new(),
+                operation,
+                peer,
+                n_parties: self.senders.len(),
+                location: Location::caller().to_string(),
+            });
+        }
+    }
+
+    /// Charges the simulated cost of producing `ciphertext_count` ciphertexts of `byte_count` bytes each, if the
+    /// secure channels are modeled.
+    fn encrypt(&self, ciphertext_count: usize, byte_count: usize) {
+        if let Some((_, encryption_delay, _)) = self.encryption {
+            let ciphertext_delay =
+                encryption_delay + self.encryption_seconds_per_byte * byte_count as u32;
+            sleep(ciphertext_delay * ciphertext_count as u32);
+        }
+    }
+
+    /// Puts the `contents` on the link to the party with `to_id`, returning a `CommError` if it disconnected.
+    /// Returns the number of bytes that were transferred, including padding.
+    fn transmit(
+        &mut self,
+        contents: Arc<[u8]>,
+        to_id: usize,
+        tag: u32,
+    ) -> Result<usize, CommError> {
+        self.transmit_with_payload(contents, None, to_id, tag)
+    }
+
+    /// Puts the `contents` on the link to the party with `to_id` like `transmit`, together with a typed `payload`
+    /// and its size in bytes, if any.
+    fn transmit_with_payload(
+        &mut self,
+        contents: Arc<[u8]>,
+        payload: Option<(Payload, usize)>,
+        to_id: usize,
+        tag: u32,
+    ) -> Result<usize, CommError> {
+        // A party that computes and sends without waiting for messages notices here that its repetition timed out
+        if self
+            .watchdog
+            .as_ref()
+            .is_some_and(|watchdog| watchdog.is_expired())
+        {
+            self.closed = true;
+            return Err(CommError::Closed { id: self.id });
+        }
+
+        if self.received_in_round {
+            self.round += 1;
+            self.received_in_round = false;
+            if let Some((start, rounds)) = &mut self.round_timeline {
+                rounds.push((start.elapsed(), 0, 0));
+            }
+            if let Some(progress) = &self.progress {
+                progress.set_round(self.round);
+            }
+        }
+
+        // A payload has no bytes, so its size is charged as padding
+        let (payload, payload_size) = payload.unzip();
+        let length = contents.len() + payload_size.unwrap_or(0);
+        let mut byte_count = match &self.round_sizes {
+            Some(round_sizes) => round_sizes.pad(self.round, length),
+            None => length,
+        };
+        // Messages to oneself do not pass through a secure channel
+        if let Some((_, _, byte_overhead)) = self.encryption {
+            if to_id != self.id {
+                byte_count += byte_overhead;
+            }
+        }
+        let arrival_time = self.arrival_time(byte_count, to_id);
+
+        let message = Message {
+            arrival_time,
+            from_id: self.id,
+            tag,
+            padding: byte_count - contents.len(),
+            contents,
+            abort: false,
+            #[cfg(feature = "compression")]
+            compressed: self.compression.is_some() && payload.is_none(),
+            #[cfg(not(feature = "compression"))]
+            compressed: false,
+            payload,
+        };
+
+        self.enqueuing(to_id);
+        if !self.senders[to_id].deliver(message) {
+            return Err(CommError::Disconnected {
+                from_id: self.id,
+                to_id,
+            });
+        }
+
+        Ok(byte_count)
+    }
+
+    /// Sends a vector of bytes to the party with `to_id` and keeps track of the number of bits sent
+    /// to this party. Returns a `CommError` if the party with `to_id` disconnected, so that a protocol can handle
+    /// the dropout of a party. Raises a `ProtocolError` if the party with `to_id` is this party itself or does not
+    /// exist.
+    #[track_caller]
+    pub fn send(&mut self, message: &[u8], to_id: &usize) -> Result<(), CommError> {
+        self.send_contents(Arc::from(message), to_id, DEFAULT_TAG)
+    }
+
+    /// Sends a message like `send`, but attaches `tag` to it so that the recipient can receive it with
+    /// `receive_tagged` regardless of the messages with other tags. Untagged messages carry the `DEFAULT_TAG`, and the
+    /// `BARRIER_TAG`, `STREAM_TAG` and `CONTROL_TAG` are reserved, so sending with them returns a `CommError`.
+    #[track_caller]
+    pub fn send_tagged(
+        &mut self,
+        message: &[u8],
+        to_id: &usize,
+        tag: u32,
+    ) -> Result<(), CommError> {
+        check_tag(tag)?;
+        self.send_contents(Arc::from(message), to_id, tag)
+    }
+
+    /// Sends a message like `send`, but without copying it: the recipient receives the same shared bytes (see
+    /// `DelayedByteIterator::into_shared`). This avoids copying large messages.
+    #[track_caller]
+    pub fn send_shared(&mut self, message: Arc<[u8]>, to_id: &usize) -> Result<(), CommError> {
+        self.send_contents(message, to_id, DEFAULT_TAG)
+    }
+
+    /// Sends the `contents` with `tag` to the party with `to_id`, charging the costs of sending them.
+    #[track_caller]
+    fn send_contents(
+        &mut self,
+        contents: Arc<[u8]>,
+        to_id: &usize,
+        tag: u32,
+    ) -> Result<(), CommError> {
+        self.check_peer(*to_id, ChannelOperation::Send);
+        #[cfg(feature = "compression")]
+        let contents = self.compress(contents, 1);
+        self.encrypt(1, contents.len());
+        let byte_count = self.transmit(contents, *to_id, tag)?;
+
+        self.add_sent_bytes(byte_count, to_id);
+        Ok(())
+    }
+
+    /// Broadcasts a message (a vector of bytes) to all parties and keeps track of the number of
+    /// bits sent. Whether the sender is one of the recipients depends on the `BroadcastSemantics`. If a party
+    /// disconnected, the message is still sent to the others and a `CommError` for the first such party is returned.
+    pub fn broadcast(&mut self, message: &[u8]) -> Result<(), CommError> {
+        self.broadcast_shared(Arc::from(message))
+    }
+
+    /// Broadcasts a message like `broadcast`, but without copying it for every recipient: they all receive the same
+    /// shared bytes (see `DelayedByteIterator::into_shared`).
+    pub fn broadcast_shared(&mut self, message: Arc<[u8]>) -> Result<(), CommError> {
+        // The message is compressed once, and the copy to oneself is only transmitted with `IncludeSelf`
+        #[cfg(feature = "compression")]
+        let contents = {
+            let recipient_count = match self.broadcast_semantics {
+                BroadcastSemantics::IncludeSelf => self.senders.len(),
+                _ => self.senders.len() - 1,
+            };
+            self.compress(message.clone(), recipient_count)
+        };
+        #[cfg(not(feature = "compression"))]
+        let contents = message.clone();
+
+        match self.encryption {
+            Some((EncryptionPackaging::PerRecipient, _, _)) => {
+                self.encrypt(self.senders.len() - 1, contents.len())
+            }
+            Some((EncryptionPackaging::Broadcast, _, _)) => self.encrypt(1, contents.len()),
+            None => {}
+        }
+        let mut result = Ok(());
+        for to_id in 0..self.senders.len() {
+            if to_id == self.id && self.broadcast_semantics != BroadcastSemantics::IncludeSelf {
+                if self.broadcast_semantics == BroadcastSemantics::EchoToSelf {
+                    let echo = Message {
+                        arrival_time: Instant::now(),
+                        from_id: self.id,
+                        tag: DEFAULT_TAG,
+                        contents: message.clone(),
+                        padding: 0,
+                        abort: false,
+                        compressed: false,
+                        payload: None,
+                    };
+                    self.buffered(self.id, DEFAULT_TAG).add(echo).unwrap();
+                }
+                continue;
+            }
+
+            match self.transmit(contents.clone(), to_id, DEFAULT_TAG) {
+                Ok(byte_count) => self.add_sent_bytes(byte_count, &to_id),
+                Err(error) => result = result.and(Err(error)),
+            }
+        }
+
+        result
+    }
+
+    /// Sends a different message to each other party: the message at index `to_id` of `messages` is sent to the
+    /// party with `to_id`, and the bytes are counted per recipient like with `send`. The message at this party's own
+    /// id is not sent. Returns a `CommError` like `broadcast` does, and panics if there is not exactly one message per
+    /// party.
+    pub fn scatter(&mut self, messages: Vec<Vec<u8>>) -> Result<(), CommError> {
+        assert_eq!(
+            messages.len(),
+            self.senders.len(),
+            "scatter requires one message per party"
+        );
+
+        let mut result = Ok(());
+        for (to_id, message) in messages.into_iter().enumerate() {
+            if to_id != self.id {
+                let message: Arc<[u8]> = message.into();
+                #[cfg(feature = "compression")]
+                let message = self.compress(message, 1);
+                self.encrypt(1, message.len());
+                match self.transmit(message, to_id, DEFAULT_TAG) {
+                    Ok(byte_count) => self.add_sent_bytes(byte_count, &to_id),
+                    Err(error) => result = result.and(Err(error)),
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Sends a typed `payload` to the party with `to_id` without serializing it, so that in-memory benchmarks measure
+    /// the protocol logic only. The payload is charged like a message of `MessageSize::message_size` bytes, and must
+    /// be received with `receive_payload`. Payloads can only be passed between in-memory channels (e.g. of a
+    /// `FullMesh`), so a `CommError` is returned if the transport to the party with `to_id` encodes the messages (see
+    /// `Transport::carries_payloads`). Otherwise, returns a `CommError` and raises a `ProtocolError` like `send`.
+    #[track_caller]
+    pub fn send_payload<M: MessageSize + Send + Sync + 'static>(
+        &mut self,
+        payload: M,
+        to_id: &usize,
+    ) -> Result<(), CommError> {
+        self.check_peer(*to_id, ChannelOperation::Send);
+        if !self.senders[*to_id].carries_payloads() {
+            return Err(CommError::PayloadUnsupported {
+                from_id: self.id,
+                to_id: *to_id,
+            });
+        }
+        let payload_size = payload.message_size();
+        self.encrypt(1, payload_size);
+        let byte_count = self.transmit_with_payload(
+            Arc::new([]),
+            Some((Arc::new(payload), payload_size)),
+            *to_id,
+            DEFAULT_TAG,
+        )?;
+
+        self.add_sent_bytes(byte_count, to_id);
+        Ok(())
+    }
+
+    /// Blocks until this party received a typed payload of type `M` from the party with `from_id` (see
+    /// `send_payload`), and its transfer finished. Returns a `CommError` if the next message from that party is not a
+    /// payload of type `M`, in which case the message is consumed. Otherwise, returns a `CommError` and raises a
+    /// `ProtocolError` like `receive`.
+    #[track_caller]
+    pub fn receive_payload<M: Send + Sync + 'static>(
+        &mut self,
+        from_id: &usize,
+    ) -> Result<M, CommError> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+
+        let mut message = self.next_message(*from_id, DEFAULT_TAG)?;
+        let payload = message.payload.take();
+        self.deliver(message, *from_id);
+        if !self.real_links[*from_id] {
+            self.wait_until(self.next_vacancy);
+        }
+
+        // The in-memory transports pass each payload to a single recipient, so this party holds its only reference
+        payload
+            .and_then(|payload| Arc::downcast::<M>(payload).ok())
+            .and_then(Arc::into_inner)
+            .ok_or(CommError::UnexpectedPayload {
+                id: self.id,
+                from_id: *from_id,
+                expected: type_name::<M>(),
+            })
+    }
+
+    /// Sends a message to every other party except the parties in `excluded` (e.g. everyone but the dealer), and
+    /// keeps track of the number of bytes sent to each recipient like `send`. Like `broadcast_shared`, the message is
+    /// only copied once and all recipients receive the same shared bytes. Returns a `CommError` like `broadcast` does.
+    /// Raises a `ProtocolError` if a party in `excluded` does not exist.
+    #[track_caller]
+    pub fn send_to_all_except(
+        &mut self,
+        message: &[u8],
+        excluded: &[usize],
+    ) -> Result<(), CommError> {
+        for &peer in excluded {
+            if peer != self.id {
+                self.check_peer(peer, ChannelOperation::Send);
+            }
+        }
+        let recipients: Vec<usize> = (0..self.senders.len())
+            .filter(|to_id| *to_id != self.id && !excluded.contains(to_id))
+            .collect();
+
+        let contents: Arc<[u8]> = Arc::from(message);
+        #[cfg(feature = "compression")]
+        let contents = self.compress(contents, recipients.len());
+        match self.encryption {
+            Some((EncryptionPackaging::PerRecipient, _, _)) => {
+                self.encrypt(recipients.len(), contents.len())
+            }
+            Some((EncryptionPackaging::Broadcast, _, _)) => self.encrypt(1, contents.len()),
+            None => {}
+        }
+        let mut result = Ok(());
+        for to_id in recipients {
+            match self.transmit(contents.clone(), to_id, DEFAULT_TAG) {
+                Ok(byte_count) => self.add_sent_bytes(byte_count, &to_id),
+                Err(error) => result = result.and(Err(error)),
+            }
+        }
+
+        result
+    }
+
+    /// Sends a message like `send`, but also hands a copy to the passive observer. The copy is free: it does not
+    /// count towards the sent bytes and it is not delayed.
+    #[track_caller]
+    pub fn send_observed(&mut self, message: &[u8], to_id: &usize) -> Result<(), CommError> {
+        self.check_peer(*to_id, ChannelOperation::Send);
+        self.observe(message, Some(*to_id));
+        self.send(message, to_id)
+    }
+
+    /// Broadcasts a message like `broadcast`, but also hands a copy to the passive observer. The copy is free: it
+    /// does not count towards the sent bytes and it is not delayed.
+    pub fn broadcast_observed(&mut self, message: &[u8]) -> Result<(), CommError> {
+        self.observe(message, None);
+        self.broadcast(message)
+    }
+
+    /// Sends a small out-of-band control message (e.g. an abort signal) to the party with `to_id`. Control messages
+    /// bypass the throughput limit and the other simulated overheads, so they only take the network's latency, and
+    /// are not padded, compressed or encrypted. Their bytes do count towards the communication. Returns a
+    /// `CommError` if the party with `to_id` disconnected, and raises a `ProtocolError` if the party with `to_id` is
+    /// this party itself or does not exist.
+    #[track_caller]
+    pub fn send_control(&mut self, signal: &[u8], to_id: &usize) -> Result<(), CommError> {
+        self.check_peer(*to_id, ChannelOperation::Send);
+
+        let message = Message {
+            arrival_time: if self.real_links[*to_id] {
+                Instant::now()
+            } else {
+                Instant::now() + self.latency
+            },
+            from_id: self.id,
+            tag: CONTROL_TAG,
+            contents: Arc::from(signal),
+            padding: 0,
+            abort: false,
+            compressed: false,
+            payload: None,
+        };
+        self.enqueuing(*to_id);
+        if !self.senders[*to_id].deliver(message) {
+            return Err(CommError::Disconnected {
+                from_id: self.id,
+                to_id: *to_id,
+            });
+        }
+
+        self.add_sent_bytes(signal.len(), to_id);
+        Ok(())
+    }
+
+    /// Sends a control message (see `send_control`) to every other party. If a party disconnected, the message is
+    /// still sent to the others and a `CommError` for the first such party is returned.
+    pub fn broadcast_control(&mut self, signal: &[u8]) -> Result<(), CommError> {
+        let mut result = Ok(());
+        for to_id in 0..self.senders.len() {
+            if to_id != self.id {
+                result = result.and(self.send_control(signal, &to_id));
+            }
+        }
+
+        result
+    }
+
+    /// Blocks until this party receives a control message from the party with `from_id` (see `send_control`). It
+    /// does not wait for the messages that this party is still receiving over the throughput-limited link. Returns a
+    /// `CommError` if the channel was closed, and raises a `ProtocolError` if the party with `from_id` is this party
+    /// itself or does not exist.
+    #[track_caller]
+    pub fn receive_control(&mut self, from_id: &usize) -> Result<DelayedByteIterator, CommError> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+
+        let message = self.next_message(*from_id, CONTROL_TAG)?;
+        self.wait_until(message.arrival_time);
+        Ok(self.account_control(message))
+    }
+
+    /// Receives a control message from the party with `from_id` like `receive_control`, but returns `None`
+    /// immediately if none has arrived yet, so that a party can check for e.g. abort signals between the steps of a
+    /// protocol. Returns a `CommError` if no control message is pending and the channel was closed.
+    #[track_caller]
+    pub fn try_receive_control(
+        &mut self,
+        from_id: &usize,
+    ) -> Result<Option<DelayedByteIterator>, CommError> {
+        self.check_peer(*from_id, ChannelOperation::Receive);
+
+        self.drain_inbox();
+
+        let Some(arrival_time) = self.buffer[*from_id]
+            .get(&CONTROL_TAG)
+            .and_then(|queue| queue.peek().ok())
+            .map(|message| message.arrival_time)
+        else {
+            return if self.closed {
+                Err(CommError::Closed { id: self.id })
+            } else {
+                Ok(None)
+            };
+        };
+        if arrival_time > Instant::now() {
+            return Ok(None);
+        }
+
+        let message = self.buffered(*from_id, CONTROL_TAG).remove().unwrap();
+        Ok(Some(self.account_control(message)))
+    }
+
+    /// Accounts for the received control `message`, which is available immediately.
+    fn account_control(&mut self, message: Message) -> DelayedByteIterator {
+        self.received_bytes[message.from_id] += message.contents.len();
+        self.received_messages += 1;
+        self.record_trace(|time| TraceEvent::Received {
+            from_id: message.from_id,
+            bytes: message.contents.len(),
+            time,
+        });
+
+        DelayedByteIterator::new(message.contents, Instant::now(), Duration::ZERO)
+    }
+}
+
+/// Locks the `channels` that are shared by the halves of split channels. A half that panicked while holding the lock
+/// did not leave the channels in an inconsistent state, so the lock is taken regardless.
+fn lock<'a, 'b>(channels: &'b Mutex<&'a mut Channels>) -> MutexGuard<'b, &'a mut Channels> {
+    channels.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The sending half of split channels (see `Channels::split`), which can be cloned and shared with other threads.
+#[derive(Clone)]
+pub struct ChannelSender<'a> {
+    shared: Arc<Mutex<&'a mut Channels>>,
+}
+
+impl ChannelSender<'_> {
+    /// Sends a message to the party with `to_id`, like `Channels::send`.
+    #[track_caller]
+    pub fn send(&self, message: &[u8], to_id: &usize) -> Result<(), CommError> {
+        lock(&self.shared).send(message, to_id)
+    }
+
+    /// Broadcasts a message to all parties, like `Channels::broadcast`.
+    pub fn broadcast(&self, message: &[u8]) -> Result<(), CommError> {
+        lock(&self.shared).broadcast(message)
+    }
+}
+
+/// The receiving half of split channels (see `Channels::split`). It only holds on to the shared channels while
+/// accounting for a message, so the sending halves are not blocked while it waits.
+pub struct ChannelReceiver<'a> {
+    id: usize,
+    shared: Arc<Mutex<&'a mut Channels>>,
+    inbox: Inbox,
+    watchdog: Option<Arc<Watchdog>>,
+}
+
+impl ChannelReceiver<'_> {
+    /// Blocks until this party receives a message from the party with `from_id`, like `Channels::receive`.
+    #[track_caller]
+    pub fn receive(&mut self, from_id: &usize) -> Result<DelayedByteIterator, CommError> {
+        {
+            let channels = lock(&self.shared);
+            channels.check_peer(*from_id, ChannelOperation::Receive);
+            channels.check_echo(*from_id, DEFAULT_TAG);
+        }
+
+        let message = loop {
+            {
+                let mut channels = lock(&self.shared);
+                if channels.buffered_count(*from_id, DEFAULT_TAG) > 0 {
+                    break channels.buffered(*from_id, DEFAULT_TAG).remove().unwrap();
+                }
+                if channels.closed {
+                    return Err(CommError::Closed { id: self.id });
+                }
+            }
+
+            // While a `ChannelSender` exists, another thread of this party may still send, so the party only blocks
+            // the others (as far as the watchdog is concerned) once all of them were dropped
+            let message =
+                self.inbox
+                    .recv_watched(self.watchdog.as_deref(), self.id, Some(*from_id), || {
+                        Arc::strong_count(&self.shared) == 1
+                    });
+            if let Some(message) = &message {
+                lock(&self.shared).dequeued(message);
+            }
+            match message {
+                Some(message)
+                    if !message.abort
+                        && message.from_id == *from_id
+                        && message.tag == DEFAULT_TAG =>
+                {
+                    break message
+                }
+                Some(message) => lock(&self.shared).buffer_message(message),
+                None => lock(&self.shared).closed = true,
+            }
+        };
+
+        let (bytes, start_time) = lock(&self.shared).schedule(message, *from_id);
+        sleep(start_time - Instant::now());
+        Ok(bytes)
+    }
+}
+
+impl Drop for ChannelReceiver<'_> {
+    /// Hands the inbox back to the channels, so that they can be used as a whole again.
+    fn drop(&mut self) {
+        let inbox = mem::replace(&mut self.inbox, Inbox::Std(channel().1));
+        lock(&self.shared).receiver = inbox;
+    }
+}
+
+/// A scoped handle for one session of a nested sub-protocol (see `Channels::subsession`). Its messages are only
+/// received by the same session of the other parties.
+pub struct Subsession<'a> {
+    channels: &'a mut Channels,
+    session_id: u32,
+}
+
+impl Subsession<'_> {
+    /// Returns the id of this session.
+    pub fn session_id(&self) -> u32 {
+        self.session_id
+    }
+
+    /// Sends a message to the same session of the party with `to_id`, like `Channels::send`.
+    #[track_caller]
+    pub fn send(&mut self, message: &[u8], to_id: &usize) -> Result<(), CommError> {
+        let before = self.channels.comm_totals();
+        let result = self.channels.send_tagged(message, to_id, self.session_id);
+        self.channels.record_session(self.session_id, before);
+        result
+    }
+
+    /// Blocks until this session receives a message from the same session of the party with `from_id`, like
+    /// `Channels::receive`.
+    #[track_caller]
+    pub fn receive(&mut self, from_id: &usize) -> Result<DelayedByteIterator, CommError> {
+        let before = self.channels.comm_totals();
+        let message = self.channels.receive_tagged(from_id, self.session_id);
+        self.channels.record_session(self.session_id, before);
+        message
+    }
+}
+
+/// A writer that streams one message to another party in chunks (see `Channels::send_stream`).
+pub struct MessageWriter<'a> {
+    channels: &'a mut Channels,
+    to_id: usize,
+    chunk: Vec<u8>,
+    finished: bool,
+}
+
+impl MessageWriter<'_> {
+    /// Sends the remaining bytes and marks the end of the message, so that the recipient's reader reaches its end.
+    /// Dropping the writer does the same, but ignores the `CommError` if the recipient disconnected.
+    pub fn finish(mut self) -> Result<(), CommError> {
+        self.end()
+    }
+
+    /// Sends the bytes that were written since the last chunk as a chunk of their own.
+    fn send_chunk(&mut self) -> Result<(), CommError> {
+        let chunk = Arc::from(mem::take(&mut self.chunk));
+        self.channels.send_contents(chunk, &self.to_id, STREAM_TAG)
+    }
+
+    fn end(&mut self) -> Result<(), CommError> {
+        if !self.finished {
+            self.finished = true;
+            if !self.chunk.is_empty() {
+                self.send_chunk()?;
+            }
+            // An empty chunk marks the end of the message
+            self.send_chunk()?;
+        }
+        Ok(())
+    }
+}
+
+impl io::Write for MessageWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let length = buf.len().min(STREAM_CHUNK_BYTES - self.chunk.len());
+        self.chunk.extend_from_slice(&buf[..length]);
+        if self.chunk.len() == STREAM_CHUNK_BYTES {
+            self.send_chunk().map_err(io::Error::other)?;
+        }
+        Ok(length)
+    }
+
+    /// Sends the bytes that were written so far as a (smaller) chunk.
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.chunk.is_empty() {
+            self.send_chunk().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MessageWriter<'_> {
+    /// Finishes the message, unless the party is already panicking (in which case the recipient fails anyway).
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            let _ = self.end();
+        }
+    }
+}
+
+/// A reader of one message that another party streams to this party in chunks (see `Channels::receive_stream`).
+pub struct MessageReader<'a> {
+    channels: &'a mut Channels,
+    from_id: usize,
+    chunk: Arc<[u8]>,
+    position: usize,
+    finished: bool,
+}
+
+impl io::Read for MessageReader<'_> {
+    /// Reads from the current chunk, blocking until the next chunk was received once it is exhausted.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if self.finished {
+                return Ok(0);
+            }
+
+            self.chunk = self
+                .channels
+                .receive_with_tag(&self.from_id, STREAM_TAG)
+                .map_err(io::Error::other)?
+                .into_shared();
+            self.position = 0;
+            self.finished = self.chunk.is_empty();
+        }
+
+        let length = buf.len().min(self.chunk.len() - self.position);
+        buf[..length].copy_from_slice(&self.chunk[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
 }
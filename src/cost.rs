@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+/// The resources that one party used in one repetition of an experiment, from which a `CostModel` estimates what
+/// running the party cost and an `EnergyModel` estimates the energy it consumed.
+#[derive(Debug, Clone, Copy)]
+pub struct Usage<'a> {
+    /// The id of the party.
+    pub party_id: usize,
+    /// The number of bytes that the party sent to each party, indexed by party id.
+    pub sent_bytes: &'a [usize],
+    /// The number of bytes that the party received from each party, indexed by party id.
+    pub received_bytes: &'a [usize],
+    /// The duration of the party's "Total" timer.
+    pub run_time: Duration,
+    /// The CPU time of the party's "Total" timer, if it was tracked (see `AggregatedStats::cpu_durations`).
+    pub cpu_time: Option<Duration>,
+}
+
+/// A model that estimates the monetary cost of a party's `Usage`, e.g. from the prices of a cloud provider (see
+/// `CloudCostModel`). The costs are summarized with `AggregatedStats::summarize_cost`.
+pub trait CostModel {
+    /// Returns the estimated cost in dollars of the party's `usage` in one repetition.
+    fn cost(&self, usage: &Usage) -> f64;
+}
+
+/// A `CostModel` for parties that run on cloud machines, which charges the egress of each byte (at a price that
+/// depends on the regions of the sender and the recipient) and each hour of CPU time. When the CPU time is not
+/// tracked, the party's run time is charged instead. All prices are zero unless they are set.
+#[derive(Debug, Clone, Default)]
+pub struct CloudCostModel {
+    dollars_per_cpu_hour: f64,
+    dollars_per_gb: f64,
+    regions: Vec<(usize, String)>,
+    region_prices: Vec<(String, String, f64)>,
+}
+
+impl CloudCostModel {
+    /// Constructs a CloudCostModel in which everything is free.
+    pub fn new() -> Self {
+        CloudCostModel::default()
+    }
+
+    /// Charges `dollars_per_cpu_hour` for each hour of CPU time.
+    pub fn with_cpu_price(mut self, dollars_per_cpu_hour: f64) -> Self {
+        self.dollars_per_cpu_hour = dollars_per_cpu_hour;
+        self
+    }
+
+    /// Charges `dollars_per_gb` for each GB (10^9 bytes) that is sent between parties whose pair of regions has no
+    /// price of its own.
+    pub fn with_egress_price(mut self, dollars_per_gb: f64) -> Self {
+        self.dollars_per_gb = dollars_per_gb;
+        self
+    }
+
+    /// Places the party with `party_id` in the `region` (e.g. "eu-west-1").
+    pub fn with_region(mut self, party_id: usize, region: &str) -> Self {
+        self.regions.retain(|(id, _)| *id != party_id);
+        self.regions.push((party_id, region.to_string()));
+        self
+    }
+
+    /// Charges `dollars_per_gb` for each GB that a party in the region `from` sends to a party in the region `to`.
+    pub fn with_egress_price_between(mut self, from: &str, to: &str, dollars_per_gb: f64) -> Self {
+        self.region_prices
+            .retain(|(existing_from, existing_to, _)| existing_from != from || existing_to != to);
+        self.region_prices
+            .push((from.to_string(), to.to_string(), dollars_per_gb));
+        self
+    }
+
+    /// Returns the price per GB that the party with `from_id` pays for sending to the party with `to_id`.
+    fn egress_price(&self, from_id: usize, to_id: usize) -> f64 {
+        let region = |party_id: usize| {
+            self.regions
+                .iter()
+                .find(|(id, _)| *id == party_id)
+                .map(|(_, region)| region)
+        };
+
+        match (region(from_id), region(to_id)) {
+            (Some(from), Some(to)) => self
+                .region_prices
+                .iter()
+                .find(|(price_from, price_to, _)| price_from == from && price_to == to)
+                .map_or(self.dollars_per_gb, |&(_, _, price)| price),
+            _ => self.dollars_per_gb,
+        }
+    }
+}
+
+impl CostModel for CloudCostModel {
+    fn cost(&self, usage: &Usage) -> f64 {
+        let egress: f64 = usage
+            .sent_bytes
+            .iter()
+            .enumerate()
+            .map(|(to_id, &bytes)| bytes as f64 / 1e9 * self.egress_price(usage.party_id, to_id))
+            .sum();
+        let cpu_time = usage.cpu_time.unwrap_or(usage.run_time);
+
+        egress + cpu_time.as_secs_f64() / 3600. * self.dollars_per_cpu_hour
+    }
+}
+
+/// A model that estimates the energy that a party consumed from its `Usage`, e.g. to evaluate protocols for
+/// battery-powered clients. The network interface consumes a fixed amount of energy for each byte that it sends or
+/// receives, and the CPU consumes a fixed power while it is active. When the CPU time is not tracked, the CPU is
+/// assumed to be active for the party's whole run time. The energy is summarized with
+/// `AggregatedStats::summarize_energy`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnergyModel {
+    joules_per_byte: f64,
+    cpu_watts: f64,
+}
+
+impl EnergyModel {
+    /// Constructs an EnergyModel in which the network interface consumes `joules_per_byte` and the active CPU
+    /// consumes `cpu_watts` (joules per second).
+    pub fn new(joules_per_byte: f64, cpu_watts: f64) -> Self {
+        EnergyModel {
+            joules_per_byte,
+            cpu_watts,
+        }
+    }
+
+    /// Returns the estimated energy in joules that the party consumed with its `usage` in one repetition.
+    pub fn energy(&self, usage: &Usage) -> f64 {
+        let bytes: usize = usage.sent_bytes.iter().chain(usage.received_bytes).sum();
+        let cpu_time = usage.cpu_time.unwrap_or(usage.run_time);
+
+        bytes as f64 * self.joules_per_byte + cpu_time.as_secs_f64() * self.cpu_watts
+    }
+}
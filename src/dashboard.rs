@@ -0,0 +1,264 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    widgets::{Block, Gauge, Paragraph, Row, Table},
+    Frame, Terminal,
+};
+
+use crate::statistics::{format_bytes, format_duration, Progress, Timings};
+
+/// A live terminal dashboard that shows the progress of running experiments: the current experiment and repetition,
+/// how many of its repetitions completed, and for each party its current round, its innermost running timer, the
+/// number of timers it completed and the bytes that it sent and the time that it waited for the network in the
+/// running repetition, together with its total duration in the last completed repetition. This shows e.g. which
+/// party holds up an experiment that is slower than expected.
+///
+/// Like a `MetricsEndpoint`, one dashboard can be shared by all evaluations of a sweep by passing it to each one's
+/// settings (see `EvaluationSettings::with_dashboard`). The dashboard stops redrawing when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct Dashboard {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: Arc<Mutex<DashboardState>>,
+    stopped: Arc<AtomicBool>,
+    renderer: Option<JoinHandle<()>>,
+}
+
+/// The progress that the dashboard shows.
+#[derive(Debug, Default)]
+struct DashboardState {
+    experiment: Option<String>,
+    /// The minimum number of measured repetitions of the running experiment.
+    repetitions: usize,
+    completed_repetitions: usize,
+    repetition: Option<usize>,
+    party_names: Vec<String>,
+    progress: Vec<Arc<Progress>>,
+    /// The "Total" duration of each party in the last completed repetition, indexed by party id.
+    last_totals: Vec<Option<Duration>>,
+}
+
+impl Dashboard {
+    /// Takes over the terminal (switching to its alternate screen) and redraws the dashboard every
+    /// `refresh_interval` on a background thread. The terminal is restored when the last clone is dropped, so the
+    /// parties should not print to it in the meantime.
+    pub fn start(refresh_interval: Duration) -> io::Result<Self> {
+        let terminal = ratatui::try_init()?;
+        Ok(Dashboard::spawn(terminal, refresh_interval, true))
+    }
+
+    /// Redraws the dashboard on the given `terminal` every `refresh_interval` instead, e.g. on a terminal with a
+    /// different backend. The terminal is left as is when the last clone is dropped.
+    pub fn with_terminal<B: Backend + Send + 'static>(
+        terminal: Terminal<B>,
+        refresh_interval: Duration,
+    ) -> Self {
+        Dashboard::spawn(terminal, refresh_interval, false)
+    }
+
+    fn spawn<B: Backend + Send + 'static>(
+        mut terminal: Terminal<B>,
+        refresh_interval: Duration,
+        restore: bool,
+    ) -> Self {
+        let state = Arc::new(Mutex::new(DashboardState::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let drawn_state = state.clone();
+        let drawn_stopped = stopped.clone();
+        let renderer = thread::spawn(move || {
+            loop {
+                let finished = drawn_stopped.load(Ordering::Acquire);
+                // A terminal that cannot be drawn on must not affect the experiment, so its errors are ignored
+                let _ = draw(&mut terminal, &drawn_state.lock().unwrap());
+                if finished {
+                    break;
+                }
+                thread::park_timeout(refresh_interval);
+            }
+
+            if restore {
+                ratatui::restore();
+            }
+        });
+
+        Dashboard {
+            inner: Arc::new(Inner {
+                state,
+                stopped,
+                renderer: Some(renderer),
+            }),
+        }
+    }
+
+    /// Draws the dashboard once on the `terminal`, e.g. to keep the final progress on screen after a sweep.
+    pub fn draw_on<B: Backend>(&self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        draw(terminal, &self.inner.state.lock().unwrap())
+    }
+
+    /// Records that the evaluation of the experiment named `experiment` started, which runs at least `repetitions`
+    /// measured repetitions of which `completed` already completed (e.g. in a resumed experiment).
+    pub(crate) fn start_evaluation(&self, experiment: &str, repetitions: usize, completed: usize) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.experiment = Some(experiment.to_string());
+        state.repetitions = repetitions;
+        state.completed_repetitions = completed;
+        state.repetition = None;
+        state.last_totals.clear();
+    }
+
+    /// Records that the `repetition` of the experiment named `experiment` started, in which the parties named
+    /// `party_names` report their `progress`. Repetitions without an index (e.g. warmups) are shown as well.
+    pub(crate) fn start_repetition(
+        &self,
+        experiment: &str,
+        repetition: Option<usize>,
+        party_names: Vec<String>,
+        progress: Vec<Arc<Progress>>,
+    ) {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.experiment.as_deref() != Some(experiment) {
+            state.experiment = Some(experiment.to_string());
+            state.repetitions = 0;
+            state.completed_repetitions = 0;
+            state.last_totals.clear();
+        }
+        state.repetition = repetition;
+        state.party_names = party_names;
+        state.progress = progress;
+    }
+
+    /// Records the statistics of each party in the measured repetition that completed.
+    pub(crate) fn finish_repetition(&self, party_timings: &[Timings]) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.completed_repetitions += 1;
+        state.last_totals = party_timings
+            .iter()
+            .map(|s| {
+                s.measured_durations()
+                    .iter()
+                    .find(|(name, _)| name == "Total")
+                    .map(|&(_, duration)| duration)
+            })
+            .collect();
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(renderer) = self.renderer.take() {
+            renderer.thread().unpark();
+            let _ = renderer.join();
+        }
+    }
+}
+
+/// Draws the progress in the `state` on the `terminal`.
+fn draw<B: Backend>(terminal: &mut Terminal<B>, state: &DashboardState) -> io::Result<()> {
+    terminal.draw(|frame| render(frame, state))?;
+    Ok(())
+}
+
+/// Renders the experiment and repetition, a gauge of the completed repetitions and a table of the parties.
+fn render(frame: &mut Frame, state: &DashboardState) {
+    let [header, gauge, table] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Min(0),
+    ])
+    .areas(frame.area());
+
+    let repetition = match state.repetition {
+        Some(repetition) => format!("repetition {}", repetition),
+        None => "warmup".to_string(),
+    };
+    let experiment = match &state.experiment {
+        Some(experiment) => format!("{}, {}", experiment, repetition),
+        None => "Waiting for an experiment".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(experiment).block(Block::bordered().title("mpc-bench")),
+        header,
+    );
+
+    // A precision target may run more repetitions than the minimum
+    let ratio = if state.repetitions > 0 {
+        (state.completed_repetitions as f64 / state.repetitions as f64).min(1.)
+    } else {
+        0.
+    };
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title("Repetitions"))
+            .ratio(ratio)
+            .label(format!(
+                "{}/{}",
+                state.completed_repetitions, state.repetitions
+            )),
+        gauge,
+    );
+
+    let rows = state
+        .party_names
+        .iter()
+        .zip(&state.progress)
+        .enumerate()
+        .map(|(id, (name, progress))| {
+            Row::new(vec![
+                name.clone(),
+                progress.round().to_string(),
+                progress.running_timer().unwrap_or_default(),
+                progress.completed_timers().to_string(),
+                format_bytes(progress.sent_bytes() as f64),
+                format_duration(progress.waiting_time().as_secs_f64()),
+                state
+                    .last_totals
+                    .get(id)
+                    .copied()
+                    .flatten()
+                    .map(|total| format_duration(total.as_secs_f64()))
+                    .unwrap_or_default(),
+            ])
+        });
+    let widths = [
+        Constraint::Fill(2),
+        Constraint::Fill(1),
+        Constraint::Fill(2),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+    ];
+    frame.render_widget(
+        Table::new(rows, widths)
+            .header(
+                Row::new(vec![
+                    "Party",
+                    "Round",
+                    "Running timer",
+                    "Timers",
+                    "Sent",
+                    "Waiting",
+                    "Last total",
+                ])
+                .style(Style::new().bold()),
+            )
+            .block(Block::bordered().title("Parties")),
+        table,
+    );
+}
@@ -0,0 +1,183 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::de::DeserializeOwned;
+
+use crate::error::DatasetError;
+
+/// The records of a dataset file, from which a protocol can generate realistic inputs instead of synthetic ones.
+/// Each record is deserialized into a `T`: a row of a CSV file with a header (whose columns are matched to the fields
+/// of `T` by name), or an element of the array in a JSON file. The records can be split among the parties, e.g. with
+/// `slice` in `Protocol::generate_inputs`.
+///
+/// A protocol that reads its inputs from a dataset should return its path from `Protocol::dataset`, so that the path
+/// is recorded in the statistics (see `AggregatedStats::dataset`).
+#[derive(Debug, Clone)]
+pub struct Dataset<T> {
+    path: String,
+    records: Vec<T>,
+}
+
+impl<T: DeserializeOwned> Dataset<T> {
+    /// Loads the dataset file at `path`, as CSV if it ends with `.csv` and as JSON if it ends with `.json`.
+    pub fn load(path: &str) -> Result<Self, DatasetError> {
+        match Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("csv") => Dataset::from_csv(path),
+            Some("json") => Dataset::from_json(path),
+            _ => Err(DatasetError::UnknownFormat(path.to_string())),
+        }
+    }
+
+    /// Loads the rows of the CSV file at `path`, which starts with a header.
+    pub fn from_csv(path: &str) -> Result<Self, DatasetError> {
+        let mut csv_reader = csv::Reader::from_path(path)?;
+        let records = csv_reader.deserialize().collect::<Result<_, _>>()?;
+
+        Ok(Dataset {
+            path: path.to_string(),
+            records,
+        })
+    }
+
+    /// Loads the elements of the array in the JSON file at `path`.
+    pub fn from_json(path: &str) -> Result<Self, DatasetError> {
+        let reader = BufReader::new(File::open(path)?);
+        let records = serde_json::from_reader(reader)?;
+
+        Ok(Dataset {
+            path: path.to_string(),
+            records,
+        })
+    }
+}
+
+impl<T> Dataset<T> {
+    /// Returns the path of the dataset file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns all records, in the order of the file.
+    pub fn records(&self) -> &[T] {
+        &self.records
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns whether the dataset has no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the records of the party with id `party` when the records are split into `n_parties` consecutive
+    /// slices of nearly equal size, where the first `len() % n_parties` slices have one record more.
+    pub fn slice(&self, party: usize, n_parties: usize) -> &[T] {
+        assert!(
+            party < n_parties,
+            "party {} is not one of the {} parties",
+            party,
+            n_parties
+        );
+
+        let (size, remainder) = (self.len() / n_parties, self.len() % n_parties);
+        let start = party * size + party.min(remainder);
+        let end = start + size + usize::from(party < remainder);
+        &self.records[start..end]
+    }
+
+    /// Returns the slice of each party (see `slice`), indexed by party id.
+    pub fn slices(&self, n_parties: usize) -> Vec<&[T]> {
+        (0..n_parties)
+            .map(|party| self.slice(party, n_parties))
+            .collect()
+    }
+
+    /// Returns the records of each party, indexed by party id, when each record belongs to the party with id
+    /// `party_of(record)`, e.g. because a column assigns the records to parties. Panics if a record belongs to a
+    /// party that is not one of the `n_parties`.
+    pub fn partition<F: Fn(&T) -> usize>(&self, n_parties: usize, party_of: F) -> Vec<Vec<&T>> {
+        let mut partition: Vec<Vec<&T>> = (0..n_parties).map(|_| vec![]).collect();
+        for record in &self.records {
+            let party = party_of(record);
+            assert!(
+                party < n_parties,
+                "a record belongs to party {}, which is not one of the {} parties",
+                party,
+                n_parties
+            );
+            partition[party].push(record);
+        }
+        partition
+    }
+
+    /// Returns `count` distinct records (or all records if there are fewer), chosen uniformly at random with `rng`,
+    /// e.g. to vary the inputs of each repetition reproducibly with the `rng` of `Protocol::generate_inputs`.
+    pub fn sample(&self, count: usize, rng: &mut impl Rng) -> Vec<&T> {
+        self.records.choose_multiple(rng, count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::Dataset;
+    use crate::{comm::FullMesh, error::DatasetError, testing::TestProtocol, Protocol};
+
+    #[test]
+    fn inputs_are_read_from_datasets() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Record {
+            party: usize,
+            value: u64,
+        }
+
+        let csv_filename = std::env::temp_dir().join("mpc-bench-dataset-test.csv");
+        let rows: String = (0..10)
+            .map(|value| format!("{},{}\n", value % 2, value))
+            .collect();
+        std::fs::write(&csv_filename, format!("party,value\n{}", rows)).unwrap();
+        let json_filename = std::env::temp_dir().join("mpc-bench-dataset-test.json");
+        std::fs::write(&json_filename, "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]").unwrap();
+
+        let records: Dataset<Record> = Dataset::load(&csv_filename.to_string_lossy()).unwrap();
+        assert_eq!(records.len(), 10);
+        assert_eq!(records.records()[3], Record { party: 1, value: 3 });
+        let partition = records.partition(2, |record| record.party);
+        assert!(partition[1].iter().all(|record| record.value % 2 == 1));
+        assert_eq!(records.sample(4, &mut StdRng::seed_from_u64(0)).len(), 4);
+
+        // The first parties get one record more when the records cannot be split equally
+        let json_path = json_filename.to_string_lossy().to_string();
+        let values: Dataset<u64> = Dataset::load(&json_path).unwrap();
+        let lengths: Vec<_> = values.slices(3).iter().map(|slice| slice.len()).collect();
+        assert_eq!(lengths, [4, 3, 3]);
+        assert_eq!(values.slice(2, 3), [7, 8, 9]);
+
+        assert!(matches!(
+            Dataset::<u64>::load("inputs.txt"),
+            Err(DatasetError::UnknownFormat(_))
+        ));
+
+        // Each party sums the records of its slice
+        let stats = TestProtocol::new(|input: &u64, _| *input)
+            .with_inputs(move |n_parties, _| {
+                values
+                    .slices(n_parties)
+                    .into_iter()
+                    .map(|slice| slice.iter().sum())
+                    .collect()
+            })
+            .with_validation(|inputs, outputs| inputs == outputs)
+            .with_dataset(&json_path)
+            .evaluate("Experiment (dataset)".to_string(), 3, &FullMesh::new(), 2)
+            .unwrap();
+        assert_eq!(stats.dataset(), Some(json_path.as_str()));
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    process,
+    sync::mpsc::channel,
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    comm::{Channels, Transport},
+    error::{CommError, EvaluationError},
+    evaluation::{check_repetition, derive_seed, run_party, Repetition},
+    statistics::{AggregatedStats, Timings},
+    tcp::{forward_messages, frame_length, TcpTransport},
+    Party, Protocol,
+};
+
+/// The controller of a distributed experiment, in which each party runs in a separate process (possibly on a
+/// different machine) by calling `run_worker`. The controller assigns the party ids in the order in which the
+/// workers connect, so that the workers only need to know the controller's address. It distributes the inputs, starts
+/// each repetition once all parties are connected to each other, and collects the outputs and statistics.
+pub struct Controller {
+    listener: TcpListener,
+    n_parties: usize,
+}
+
+/// A message from the controller to a worker.
+#[derive(Serialize, Deserialize)]
+enum Job {
+    /// Assigns the worker its party `id` and the addresses of all parties (indexed by id).
+    Assign {
+        id: usize,
+        n_parties: usize,
+        peers: Vec<SocketAddr>,
+    },
+    /// Prepares one repetition with the given serialized input, by connecting to the other parties.
+    Run(Vec<u8>),
+    /// Starts the repetition, once all parties are connected to each other.
+    Start,
+    /// Ends the experiment.
+    Stop,
+}
+
+/// A message from a worker to the controller.
+#[derive(Serialize, Deserialize)]
+pub(crate) enum Report {
+    /// Announces the address on which the worker accepts connections from the other parties, and the id of the
+    /// worker's process.
+    Hello {
+        address: SocketAddr,
+        process_id: u32,
+    },
+    /// Announces the name of the party that the worker runs.
+    Ready(String),
+    /// Announces that the worker is connected to all other parties for the next repetition.
+    Connected,
+    /// Reports the serialized output, the measured durations and the invariant checks of one repetition.
+    Done {
+        output: Vec<u8>,
+        durations: Vec<(String, Duration)>,
+        invariants: Vec<(String, bool)>,
+    },
+    /// Reports that the party failed in this repetition, or that it was `aborted` because another party failed.
+    Failed { message: String, aborted: bool },
+}
+
+impl Controller {
+    /// Binds the controller to `address`, on which it waits for `n_parties` workers.
+    pub fn bind<A: ToSocketAddrs>(address: A, n_parties: usize) -> io::Result<Self> {
+        Ok(Controller {
+            listener: TcpListener::bind(address)?,
+            n_parties,
+        })
+    }
+
+    /// Returns the address that the workers should connect to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Waits for all workers to connect, and then evaluates multiple `repetitions` of the `protocol` like
+    /// `Protocol::evaluate` does. The workers must run the same protocol.
+    pub fn evaluate<P>(
+        &self,
+        protocol: &P,
+        experiment_name: String,
+        repetitions: usize,
+    ) -> Result<AggregatedStats, EvaluationError>
+    where
+        P: Protocol,
+        <P::Party as Party>::Input: Serialize,
+        <P::Party as Party>::Output: DeserializeOwned,
+    {
+        self.evaluate_processes(protocol, experiment_name, repetitions, &mut vec![])
+    }
+
+    /// Evaluates the `protocol` like `evaluate`, and pushes the process id of each worker onto `process_ids` (in the
+    /// order of the party ids) as soon as it connected.
+    pub(crate) fn evaluate_processes<P>(
+        &self,
+        protocol: &P,
+        experiment_name: String,
+        repetitions: usize,
+        process_ids: &mut Vec<u32>,
+    ) -> Result<AggregatedStats, EvaluationError>
+    where
+        P: Protocol,
+        <P::Party as Party>::Input: Serialize,
+        <P::Party as Party>::Output: DeserializeOwned,
+    {
+        let mut workers = vec![];
+        let mut peers = vec![];
+        for _ in 0..self.n_parties {
+            let (mut worker, _) = self.listener.accept()?;
+            match receive_frame(&mut worker)? {
+                Report::Hello {
+                    address,
+                    process_id,
+                } => {
+                    peers.push(address);
+                    process_ids.push(process_id);
+                }
+                _ => return Err(unexpected_report().into()),
+            }
+            workers.push(worker);
+        }
+
+        let mut party_names = vec![];
+        for (id, worker) in workers.iter_mut().enumerate() {
+            let assignment = Job::Assign {
+                id,
+                n_parties: self.n_parties,
+                peers: peers.clone(),
+            };
+            send_frame(worker, &assignment)?;
+        }
+        for worker in workers.iter_mut() {
+            match receive_frame(worker)? {
+                Report::Ready(name) => party_names.push(name),
+                _ => return Err(unexpected_report().into()),
+            }
+        }
+
+        // The workers instantiate their own networks, so only the protocol's parameters are known here
+        let mut stats = AggregatedStats::new(experiment_name, party_names);
+        stats.set_parameters(format!("{:?}", protocol));
+        if let Some(dataset) = protocol.dataset() {
+            stats.set_dataset(dataset.to_string());
+        }
+        let master_seed = rand::random();
+        let result = (0..repetitions).try_for_each(|repetition| {
+            let seed = derive_seed(master_seed, repetition);
+            let result = self.run_repetition(protocol, &mut workers, seed)?;
+            check_repetition(protocol, &result, repetition)?;
+            stats.incorporate_party_stats(result.timings);
+            stats.incorporate_makespan(result.makespan);
+            stats.incorporate_seed(seed);
+            Ok(())
+        });
+
+        // The workers are stopped regardless of whether the experiment succeeded
+        for worker in workers.iter_mut() {
+            let _ = send_frame(worker, &Job::Stop);
+        }
+
+        result.map(|_| stats)
+    }
+
+    /// Runs one repetition on all workers with inputs generated from the `seed`, and collects their results.
+    fn run_repetition<P>(
+        &self,
+        protocol: &P,
+        workers: &mut [TcpStream],
+        seed: u64,
+    ) -> Result<Repetition<P::Party>, EvaluationError>
+    where
+        P: Protocol,
+        <P::Party as Party>::Input: Serialize,
+        <P::Party as Party>::Output: DeserializeOwned,
+    {
+        let inputs = protocol.generate_inputs(self.n_parties, &mut StdRng::seed_from_u64(seed));
+        for (worker, input) in workers.iter_mut().zip(&inputs) {
+            send_frame(worker, &Job::Run(encode(input)?))?;
+        }
+
+        // The parties start simultaneously, so that no party's run time includes waiting for the others to connect
+        for worker in workers.iter_mut() {
+            match receive_frame(worker)? {
+                Report::Connected => {}
+                _ => return Err(unexpected_report().into()),
+            }
+        }
+        let start_time = Instant::now();
+        for worker in workers.iter_mut() {
+            send_frame(worker, &Job::Start)?;
+        }
+
+        let reports = workers
+            .iter_mut()
+            .map(receive_frame)
+            .collect::<io::Result<_>>()?;
+        // The makespan also includes the round trips between the controller and the workers
+        let makespan = start_time.elapsed();
+
+        let (outputs, timings) = collect_reports(reports)?;
+        Ok(Repetition {
+            inputs,
+            outputs,
+            timings,
+            makespan,
+            snapshots: None,
+            transcript: vec![],
+        })
+    }
+}
+
+/// Collects the outputs and statistics of one repetition from the final report of each party (indexed by id). Like in
+/// `Protocol::evaluate`, the original failure is reported instead of the parties that were aborted.
+pub(crate) fn collect_reports<O: DeserializeOwned>(
+    reports: Vec<Report>,
+) -> Result<(Vec<O>, Vec<Timings>), EvaluationError> {
+    let mut outputs = vec![];
+    let mut timings = vec![];
+    let mut failure = None;
+    for (party, report) in reports.into_iter().enumerate() {
+        match report {
+            Report::Done {
+                output,
+                durations,
+                invariants,
+            } => {
+                outputs.push(decode(&output)?);
+
+                let mut s = Timings::new();
+                for (name, duration) in durations {
+                    s.write_duration(name, duration);
+                }
+                for (name, holds) in invariants {
+                    s.assert_invariant(&name, holds);
+                }
+                timings.push(s);
+            }
+            Report::Failed { message, aborted } => {
+                if failure.is_none() || !aborted {
+                    failure = Some(EvaluationError::PartyPanicked { party, message });
+                }
+            }
+            _ => return Err(unexpected_report().into()),
+        }
+    }
+
+    match failure {
+        Some(failure) => Err(failure),
+        None => Ok((outputs, timings)),
+    }
+}
+
+/// Runs one party of the `protocol` in a distributed experiment that is coordinated by the controller at
+/// `controller`. The worker accepts connections from the other parties on `address` (e.g. `0.0.0.0:0`), which must
+/// be reachable under the IP address that the worker uses to connect to the controller. Returns once the
+/// controller ends the experiment.
+pub fn run_worker<P, A, B>(protocol: &P, controller: A, address: B) -> Result<(), EvaluationError>
+where
+    P: Protocol,
+    <P::Party as Party>::Input: DeserializeOwned,
+    <P::Party as Party>::Output: Serialize,
+    A: ToSocketAddrs,
+    B: ToSocketAddrs,
+{
+    let mut controller = TcpStream::connect(controller)?;
+    let listener = TcpListener::bind(address)?;
+
+    // Parties listening on an unspecified address are reached via the address that they use towards the controller
+    let mut listen_address = listener.local_addr()?;
+    if listen_address.ip().is_unspecified() {
+        listen_address.set_ip(controller.local_addr()?.ip());
+    }
+    let hello = Report::Hello {
+        address: listen_address,
+        process_id: process::id(),
+    };
+    send_frame(&mut controller, &hello)?;
+
+    let (id, n_parties, peers) = match receive_frame(&mut controller)? {
+        Job::Assign {
+            id,
+            n_parties,
+            peers,
+        } => (id, n_parties, peers),
+        _ => return Err(unexpected_job().into()),
+    };
+
+    let mut party = protocol
+        .setup_parties(n_parties)
+        .into_iter()
+        .nth(id)
+        .expect("`setup_parties` must set up `n_parties` parties");
+    send_frame(&mut controller, &Report::Ready(party.get_name(id)))?;
+
+    loop {
+        let input: <P::Party as Party>::Input = match receive_frame(&mut controller)? {
+            Job::Run(input) => decode(&input)?,
+            Job::Stop => return Ok(()),
+            _ => return Err(unexpected_job().into()),
+        };
+
+        let mut channel = connect_peers(id, &peers, &listener)?;
+        send_frame(&mut controller, &Report::Connected)?;
+        match receive_frame(&mut controller)? {
+            Job::Start => {}
+            _ => return Err(unexpected_job().into()),
+        }
+
+        let mut s = Timings::new();
+        let result = run_party(&mut party, id, n_parties, &input, &mut channel, &mut s);
+        let report = report_outcome(result, &mut channel, &s)?;
+        send_frame(&mut controller, &report)?;
+    }
+}
+
+/// Reports the outcome of one repetition of a party, whose `channel` is aborted if it failed so that the other parties
+/// do not wait for it.
+pub(crate) fn report_outcome<O: Serialize>(
+    result: Result<O, EvaluationError>,
+    channel: &mut Channels,
+    s: &Timings,
+) -> io::Result<Report> {
+    Ok(match result {
+        Ok(output) => Report::Done {
+            output: encode(&output)?,
+            durations: s.measured_durations().to_vec(),
+            invariants: s.invariants().to_vec(),
+        },
+        Err(error) => {
+            // Like in `Protocol::evaluate`, a party that failed after its channel was closed is reported as aborted
+            let aborted = channel.is_closed()
+                || matches!(error, EvaluationError::Transport(CommError::Closed { .. }));
+            channel.abort();
+            Report::Failed {
+                aborted,
+                message: match error {
+                    EvaluationError::PartyPanicked { message, .. } => message,
+                    error => error.to_string(),
+                },
+            }
+        }
+    })
+}
+
+/// Connects the party with `id` to all other parties for one repetition: it connects to the parties with lower ids
+/// and accepts connections from the parties with higher ids.
+fn connect_peers(id: usize, peers: &[SocketAddr], listener: &TcpListener) -> io::Result<Channels> {
+    let (queue, receiver) = channel();
+    let mut links: Vec<Option<Box<dyn Transport>>> = (0..peers.len()).map(|_| None).collect();
+    links[id] = Some(Box::new(queue.clone()));
+
+    let mut streams = vec![];
+    for (other, peer) in peers.iter().enumerate().take(id) {
+        let mut stream = TcpStream::connect(peer)?;
+        stream.write_all(&(id as u64).to_le_bytes())?;
+        streams.push((other, stream));
+    }
+    for _ in (id + 1)..peers.len() {
+        let (mut stream, _) = listener.accept()?;
+        let mut other = [0; 8];
+        stream.read_exact(&mut other)?;
+        streams.push((u64::from_le_bytes(other) as usize, stream));
+    }
+
+    for (other, stream) in streams {
+        stream.set_nodelay(true)?;
+
+        let reader = stream.try_clone()?;
+        let queue = queue.clone();
+        thread::spawn(move || forward_messages(reader, other, queue));
+
+        links[other] = Some(Box::new(TcpTransport(stream)));
+    }
+
+    let links = links.into_iter().map(Option::unwrap).collect();
+    Ok(Channels::with_transports(
+        id,
+        links,
+        receiver,
+        Duration::ZERO,
+        Duration::ZERO,
+    ))
+}
+
+/// Writes `value` to the `stream`, prefixed by its length.
+pub(crate) fn send_frame<T: Serialize>(stream: &mut TcpStream, value: &T) -> io::Result<()> {
+    let bytes = encode(value)?;
+    stream.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    stream.write_all(&bytes)
+}
+
+/// Reads a value that was written by `send_frame`.
+pub(crate) fn receive_frame<T: DeserializeOwned>(stream: &mut TcpStream) -> io::Result<T> {
+    let mut length = [0; 8];
+    stream.read_exact(&mut length)?;
+    let mut bytes = vec![0; frame_length(u64::from_le_bytes(length))?];
+    stream.read_exact(&mut bytes)?;
+    decode(&bytes)
+}
+
+pub(crate) fn encode<T: Serialize>(value: &T) -> io::Result<Vec<u8>> {
+    bincode::serialize(value).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> io::Result<T> {
+    bincode::deserialize(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+fn unexpected_report() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "a worker sent an unexpected report",
+    )
+}
+
+fn unexpected_job() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "the controller sent an unexpected job",
+    )
+}
@@ -0,0 +1,552 @@
+use std::{error::Error, fmt, io, time::Duration};
+
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
+
+use crate::regression::MetricDiff;
+
+/// An error that occurred while evaluating a protocol.
+#[derive(Debug)]
+pub enum EvaluationError {
+    /// A party could not communicate with another party.
+    Transport(CommError),
+    /// The outputs of a repetition were invalid.
+    Validation(ValidationError),
+    /// A party used its channels incorrectly, e.g. by sending a message to itself.
+    Protocol(ProtocolError),
+    /// A party panicked while running the protocol.
+    PartyPanicked {
+        /// The id of the party that panicked.
+        party: usize,
+        /// The panic message, if it was a string.
+        message: String,
+    },
+    /// Every party that did not finish waited for a message that no party would send.
+    Deadlock {
+        /// The id of each waiting party together with the id of the party that it waited for (or `None` if it waited
+        /// for any party).
+        waits: Vec<(usize, Option<usize>)>,
+        /// The ids of the parties that already finished.
+        finished: Vec<usize>,
+    },
+    /// The parties did not complete a repetition within the time limit (see
+    /// `EvaluationSettings::with_repetition_timeout`), so they were aborted.
+    Timeout(TimeoutError),
+    /// A party's slowdown factor was not a finite, positive number.
+    InvalidSlowdownFactor {
+        /// The id of the party with the invalid slowdown factor.
+        party: usize,
+        /// The invalid slowdown factor.
+        slowdown_factor: f64,
+    },
+    /// Writing results or profiles to disk failed.
+    Io(io::Error),
+    /// The sampling profiler failed.
+    #[cfg(feature = "profiling")]
+    Profiling(pprof::Error),
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluationError::Transport(error) => write!(f, "transport error: {}", error),
+            EvaluationError::Validation(error) => write!(f, "validation error: {}", error),
+            EvaluationError::Protocol(error) => write!(f, "protocol error: {}", error),
+            EvaluationError::PartyPanicked { party, message } => {
+                write!(f, "party {} panicked: {}", party, message)
+            }
+            EvaluationError::Deadlock { waits, finished } => {
+                write!(f, "the parties deadlocked: ")?;
+                for (i, (party, from_id)) in waits.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match from_id {
+                        Some(from_id) if finished.contains(from_id) => write!(
+                            f,
+                            "party {} waits for party {}, which finished",
+                            party, from_id
+                        )?,
+                        Some(from_id) => write!(f, "party {} waits for party {}", party, from_id)?,
+                        None => write!(f, "party {} waits for any party", party)?,
+                    }
+                }
+                Ok(())
+            }
+            EvaluationError::Timeout(error) => write!(f, "timeout: {}", error),
+            EvaluationError::InvalidSlowdownFactor {
+                party,
+                slowdown_factor,
+            } => write!(
+                f,
+                "party {} has an invalid slowdown factor: {}",
+                party, slowdown_factor
+            ),
+            EvaluationError::Io(error) => write!(f, "i/o error: {}", error),
+            #[cfg(feature = "profiling")]
+            EvaluationError::Profiling(error) => write!(f, "profiling error: {}", error),
+        }
+    }
+}
+
+impl Error for EvaluationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EvaluationError::Transport(error) => Some(error),
+            EvaluationError::Validation(error) => Some(error),
+            EvaluationError::Protocol(error) => Some(error),
+            EvaluationError::PartyPanicked { .. } => None,
+            EvaluationError::Deadlock { .. } => None,
+            EvaluationError::Timeout(error) => Some(error),
+            EvaluationError::InvalidSlowdownFactor { .. } => None,
+            EvaluationError::Io(error) => Some(error),
+            #[cfg(feature = "profiling")]
+            EvaluationError::Profiling(error) => Some(error),
+        }
+    }
+}
+
+impl From<CommError> for EvaluationError {
+    fn from(error: CommError) -> Self {
+        EvaluationError::Transport(error)
+    }
+}
+
+impl From<ProtocolError> for EvaluationError {
+    fn from(error: ProtocolError) -> Self {
+        EvaluationError::Protocol(error)
+    }
+}
+
+impl From<ValidationError> for EvaluationError {
+    fn from(error: ValidationError) -> Self {
+        EvaluationError::Validation(error)
+    }
+}
+
+impl From<TimeoutError> for EvaluationError {
+    fn from(error: TimeoutError) -> Self {
+        EvaluationError::Timeout(error)
+    }
+}
+
+impl From<io::Error> for EvaluationError {
+    fn from(error: io::Error) -> Self {
+        EvaluationError::Io(error)
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl From<pprof::Error> for EvaluationError {
+    fn from(error: pprof::Error) -> Self {
+        EvaluationError::Profiling(error)
+    }
+}
+
+/// An error that occurred while a party sent or received a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommError {
+    /// The party with id `to_id` can no longer receive messages.
+    Disconnected {
+        /// The id of the sending party.
+        from_id: usize,
+        /// The id of the party that could not be reached.
+        to_id: usize,
+    },
+    /// The channel of the party with id `id` was closed, because another party failed while this party was waiting for
+    /// a message, or because the repetition exceeded its time limit.
+    Closed {
+        /// The id of the party whose channel was closed.
+        id: usize,
+    },
+    /// A party used a tag that is reserved for the harness (e.g. the `BARRIER_TAG`), whose messages it would corrupt.
+    ReservedTag {
+        /// The reserved tag.
+        tag: u32,
+    },
+    /// A typed payload could not be sent to the party with id `to_id`, because its transport encodes the messages
+    /// (see `Transport::carries_payloads`).
+    PayloadUnsupported {
+        /// The id of the sending party.
+        from_id: usize,
+        /// The id of the party that the payload was meant for.
+        to_id: usize,
+    },
+    /// The party with id `id` expected a typed payload of type `expected` from the party with id `from_id`, but
+    /// received a different message.
+    UnexpectedPayload {
+        /// The id of the receiving party.
+        id: usize,
+        /// The id of the party that sent the message.
+        from_id: usize,
+        /// The name of the expected type.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for CommError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommError::Disconnected { from_id, to_id } => write!(
+                f,
+                "party {} could not send to party {}, which disconnected",
+                from_id, to_id
+            ),
+            CommError::Closed { id } => {
+                write!(f, "the channel of party {} was closed while receiving", id)
+            }
+            CommError::ReservedTag { tag } => write!(f, "the tag {} is reserved", tag),
+            CommError::PayloadUnsupported { from_id, to_id } => write!(
+                f,
+                "party {} could not send a payload to party {}, whose transport encodes the messages",
+                from_id, to_id
+            ),
+            CommError::UnexpectedPayload {
+                id,
+                from_id,
+                expected,
+            } => write!(
+                f,
+                "party {} expected a payload of type {} from party {}, but received a different message",
+                id, expected, from_id
+            ),
+        }
+    }
+}
+
+impl Error for CommError {}
+
+/// The operation on `Channels` during which a `ProtocolError` occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub enum ChannelOperation {
+    /// Sending a message to another party.
+    Send,
+    /// Receiving a message from another party.
+    Receive,
+}
+
+impl fmt::Display for ChannelOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChannelOperation::Send => "send to",
+            ChannelOperation::Receive => "receive from",
+        })
+    }
+}
+
+/// A party addressed itself or a party that does not exist when sending or receiving a message. This usually
+/// points to an off-by-one mistake in the ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct ProtocolError {
+    /// The id of the party that made the mistake.
+    pub party: usize,
+    /// The name of the party that made the mistake (see `Party::get_name`).
+    pub party_name: String,
+    /// The operation that the party attempted.
+    pub operation: ChannelOperation,
+    /// The id that the party passed to the operation.
+    pub peer: usize,
+    /// The number of parties in the protocol.
+    pub n_parties: usize,
+    /// The location in the source code of the call to the operation.
+    pub location: String,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.peer == self.party {
+            write!(
+                f,
+                "{} (id {}) tried to {} itself at {}",
+                self.party_name, self.party, self.operation, self.location
+            )
+        } else {
+            write!(
+                f,
+                "{} (id {}) tried to {} party {} at {}, but the ids range from 0 to {}",
+                self.party_name,
+                self.party,
+                self.operation,
+                self.peer,
+                self.location,
+                self.n_parties - 1
+            )
+        }
+    }
+}
+
+impl Error for ProtocolError {}
+
+/// The outputs of one repetition of a protocol did not pass validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The index of the invalid repetition (starting from 0).
+    pub repetition: usize,
+    /// A description of what was invalid.
+    pub reason: String,
+    /// The ids of the parties whose outputs differ from the expected outputs (see `Protocol::expected_outputs`),
+    /// which is empty if the repetition is invalid for another reason.
+    pub mismatching_parties: Vec<usize>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "repetition {} is invalid: {}",
+            self.repetition, self.reason
+        )
+    }
+}
+
+impl Error for ValidationError {}
+
+/// One repetition of a protocol did not finish within the time limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutError {
+    /// The index of the repetition that timed out (starting from 0), or `None` if it was a warmup repetition.
+    pub repetition: Option<usize>,
+    /// The time limit of each repetition.
+    pub limit: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.repetition {
+            Some(repetition) => write!(
+                f,
+                "repetition {} did not finish within {:?}",
+                repetition, self.limit
+            ),
+            None => write!(
+                f,
+                "a warmup repetition did not finish within {:?}",
+                self.limit
+            ),
+        }
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// Two `AggregatedStats` could not be merged, because they do not describe the same experiment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// The statistics are of different parties.
+    PartyNames {
+        /// The names of the parties in the statistics that were merged into.
+        expected: Vec<String>,
+        /// The names of the parties in the merged statistics.
+        found: Vec<String>,
+    },
+    /// The parties measured different timings.
+    TimingNames {
+        /// The names of the timings in the statistics that were merged into, sorted.
+        expected: Vec<String>,
+        /// The names of the timings in the merged statistics, sorted.
+        found: Vec<String>,
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::PartyNames { expected, found } => write!(
+                f,
+                "the parties differ: expected {:?}, found {:?}",
+                expected, found
+            ),
+            MergeError::TimingNames { expected, found } => write!(
+                f,
+                "the timings differ: expected {:?}, found {:?}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// A new run of an experiment did not pass the check against its baseline (see `Baseline::check`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegressionError {
+    /// A chosen metric is not in the baseline.
+    UnknownMetric(String),
+    /// Chosen metrics exceeded the baseline by more than the tolerance, or were not measured by the new run.
+    Regressed {
+        /// The relative tolerance of the check.
+        tolerance: f64,
+        /// The diffs of the metrics that regressed.
+        regressions: Vec<MetricDiff>,
+    },
+}
+
+impl fmt::Display for RegressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegressionError::UnknownMetric(metric) => {
+                write!(f, "the baseline does not contain the metric '{}'", metric)
+            }
+            RegressionError::Regressed {
+                tolerance,
+                regressions,
+            } => {
+                write!(
+                    f,
+                    "{} metric(s) regressed by more than {:.1}%",
+                    regressions.len(),
+                    tolerance * 100.
+                )?;
+                for regression in regressions {
+                    write!(f, "; {}", regression)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for RegressionError {}
+
+/// An error that occurred while running a command of the command-line interface (see `cli::main`).
+#[cfg(feature = "cli")]
+#[derive(Debug)]
+pub enum CliError {
+    /// The configuration names a protocol that is not in the registry.
+    UnknownProtocol {
+        /// The name of the protocol in the configuration.
+        protocol: String,
+        /// The names of the protocols in the registry.
+        registered: Vec<String>,
+    },
+    /// A configuration could not be parsed.
+    Config(serde_json::Error),
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// Evaluating the protocol failed.
+    Evaluation(EvaluationError),
+    /// Plotting requires the `plots` feature.
+    PlotsUnavailable,
+}
+
+#[cfg(feature = "cli")]
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownProtocol {
+                protocol,
+                registered,
+            } => write!(
+                f,
+                "the protocol '{}' is not registered (registered: {})",
+                protocol,
+                registered.join(", ")
+            ),
+            CliError::Config(error) => write!(f, "invalid configuration: {}", error),
+            CliError::Io(error) => write!(f, "i/o error: {}", error),
+            CliError::Evaluation(error) => write!(f, "evaluation error: {}", error),
+            CliError::PlotsUnavailable => {
+                write!(f, "plotting requires the 'plots' feature of mpc-bench")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::UnknownProtocol { .. } => None,
+            CliError::Config(error) => Some(error),
+            CliError::Io(error) => Some(error),
+            CliError::Evaluation(error) => Some(error),
+            CliError::PlotsUnavailable => None,
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<serde_json::Error> for CliError {
+    fn from(error: serde_json::Error) -> Self {
+        CliError::Config(error)
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+#[cfg(feature = "cli")]
+impl From<EvaluationError> for CliError {
+    fn from(error: EvaluationError) -> Self {
+        CliError::Evaluation(error)
+    }
+}
+
+/// A dataset file could not be loaded (see `Dataset::load`).
+#[cfg(feature = "datasets")]
+#[derive(Debug)]
+pub enum DatasetError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// A record of a CSV file could not be parsed.
+    Csv(csv::Error),
+    /// A JSON file could not be parsed.
+    Json(serde_json::Error),
+    /// The file's extension is neither `.csv` nor `.json`.
+    UnknownFormat(String),
+}
+
+#[cfg(feature = "datasets")]
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatasetError::Io(error) => write!(f, "i/o error: {}", error),
+            DatasetError::Csv(error) => write!(f, "invalid csv: {}", error),
+            DatasetError::Json(error) => write!(f, "invalid json: {}", error),
+            DatasetError::UnknownFormat(path) => write!(
+                f,
+                "the format of '{}' is unknown (expected a .csv or .json file)",
+                path
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "datasets")]
+impl Error for DatasetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DatasetError::Io(error) => Some(error),
+            DatasetError::Csv(error) => Some(error),
+            DatasetError::Json(error) => Some(error),
+            DatasetError::UnknownFormat(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "datasets")]
+impl From<io::Error> for DatasetError {
+    fn from(error: io::Error) -> Self {
+        DatasetError::Io(error)
+    }
+}
+
+#[cfg(feature = "datasets")]
+impl From<csv::Error> for DatasetError {
+    fn from(error: csv::Error) -> Self {
+        DatasetError::Csv(error)
+    }
+}
+
+#[cfg(feature = "datasets")]
+impl From<serde_json::Error> for DatasetError {
+    fn from(error: serde_json::Error) -> Self {
+        DatasetError::Json(error)
+    }
+}
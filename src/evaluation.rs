@@ -0,0 +1,804 @@
+use std::{
+    any::Any,
+    fmt::Debug,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::channel,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::{
+    prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
+#[cfg(any(feature = "priority", feature = "persistence"))]
+use std::io;
+
+#[cfg(feature = "profiling")]
+use crate::profiling::PartyProfiler;
+use crate::{
+    comm::{Channels, NetworkDescription, ObservedMessage, RoundSizes},
+    error::{CommError, EvaluationError, ProtocolError, ValidationError},
+    settings::EvaluationSettings,
+    statistics::{take_snapshots, AggregatedStats, Progress, Snapshot, Timings},
+    watchdog::Watchdog,
+    ExpectedOutputs, Party, PartyContext, Protocol,
+};
+
+/// The outputs of all parties of a protocol.
+pub(crate) type Outputs<P> = Vec<<<P as Protocol>::Party as Party>::Output>;
+
+/// The results of running one repetition of a protocol.
+pub(crate) struct Repetition<P: Party> {
+    pub(crate) inputs: Vec<P::Input>,
+    pub(crate) outputs: Vec<P::Output>,
+    pub(crate) timings: Vec<Timings>,
+    /// The wall-clock time from starting the parties until the last party finished.
+    pub(crate) makespan: Duration,
+    pub(crate) snapshots: Option<Vec<Snapshot>>,
+    pub(crate) transcript: Vec<ObservedMessage>,
+}
+
+pub(crate) fn evaluate_protocol<P: Protocol + ?Sized, N: NetworkDescription>(
+    protocol: &P,
+    experiment_name: String,
+    n_parties: usize,
+    network_description: &N,
+    repetitions: usize,
+    settings: &EvaluationSettings,
+) -> Result<AggregatedStats, EvaluationError> {
+    evaluate_protocol_with_outputs(
+        protocol,
+        experiment_name,
+        n_parties,
+        network_description,
+        repetitions,
+        settings,
+    )
+    .map(|(stats, _)| stats)
+}
+
+/// Evaluates the `protocol` like `evaluate_protocol`, but also returns the parties' outputs of the last repetition
+/// (which are empty without repetitions).
+pub(crate) fn evaluate_protocol_with_outputs<P: Protocol + ?Sized, N: NetworkDescription>(
+    protocol: &P,
+    experiment_name: String,
+    n_parties: usize,
+    network_description: &N,
+    repetitions: usize,
+    settings: &EvaluationSettings,
+) -> Result<(AggregatedStats, Outputs<P>), EvaluationError> {
+    let parties = protocol.setup_parties(n_parties);
+    debug_assert_eq!(parties.len(), n_parties);
+
+    let mut stats = AggregatedStats::new(
+        experiment_name,
+        parties
+            .iter()
+            .enumerate()
+            .map(|(id, party)| party.get_name(id))
+            .collect(),
+    );
+    stats.set_parameters(format!("{:?}", protocol));
+    stats.set_network(network_description.describe());
+    if let Some(dataset) = protocol.dataset() {
+        stats.set_dataset(dataset.to_string());
+    }
+
+    // A resumed experiment continues after the repetitions in its checkpoint, and skips the warmup if it is complete
+    let mut repetition = 0;
+    #[cfg(feature = "persistence")]
+    if let Some(checkpoint) = &settings.checkpoint {
+        if let Some(resumed) = load_checkpoint(&stats, checkpoint)? {
+            stats = resumed;
+            repetition = stats.repetitions().count()
+                + stats.timed_out_repetitions()
+                + stats.protocol_errors().len();
+            if !settings.needs_repetition(repetition, repetitions, &stats) {
+                return Ok((stats, vec![]));
+            }
+        }
+    }
+
+    #[cfg(feature = "dashboard")]
+    if let Some(dashboard) = &settings.dashboard {
+        dashboard.start_evaluation(stats.name(), repetitions, repetition);
+    }
+
+    // The inputs of each repetition are generated from a seed of its own, so that a repetition can be replayed
+    let master_seed = settings.master_seed();
+    let generate_inputs =
+        |seed: u64| protocol.generate_inputs(n_parties, &mut StdRng::seed_from_u64(seed));
+    let mut unmeasured_seeds = (0..).map(|index| settings.repetition_seed(!master_seed, index));
+
+    // Each concurrent repetition needs parties of its own, and a thread pool of its own so that its parties are not
+    // starved by those of the other repetitions
+    let concurrency = settings.concurrent_repetitions(n_parties);
+    let mut slots = vec![parties];
+    slots.extend((1..concurrency).map(|_| protocol.setup_parties(n_parties)));
+    let pools: Vec<ThreadPool> = if concurrency > 1 {
+        (0..concurrency)
+            .map(|_| {
+                ThreadPoolBuilder::new()
+                    .num_threads(n_parties)
+                    .build()
+                    .expect("could not build a thread pool for a repetition")
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    // Padding to fixed round sizes requires knowing the maximum message size in each round, so we first run one
+    // unpadded repetition to find these sizes
+    let round_sizes = if settings.round_padding {
+        let round_sizes = Arc::new(RoundSizes::new());
+        run_repetition(
+            &mut slots[0],
+            generate_inputs(unmeasured_seeds.next().unwrap()),
+            network_description.instantiate(n_parties),
+            settings,
+            stats.name(),
+            None,
+            Some(&round_sizes),
+        )?;
+        round_sizes.start_padding();
+        Some(round_sizes)
+    } else {
+        None
+    };
+
+    // The parties of every slot are warmed up, and a timed out warmup repetition only warms them up less
+    for _ in 0..settings.warmup_repetitions {
+        let batch = (0..concurrency)
+            .map(|_| {
+                let inputs = generate_inputs(unmeasured_seeds.next().unwrap());
+                (inputs, network_description.instantiate(n_parties), None)
+            })
+            .collect();
+        for result in run_batch(
+            &mut slots,
+            batch,
+            &pools,
+            settings,
+            stats.name(),
+            round_sizes.as_ref(),
+        ) {
+            match result {
+                Ok(_) | Err(EvaluationError::Timeout(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    let mut outputs = vec![];
+    while settings.needs_repetition(repetition, repetitions, &stats) {
+        // Beyond the minimum number of repetitions, the precision target is checked after every repetition
+        let batch_size = concurrency.min(repetitions.saturating_sub(repetition).max(1));
+        let batch = (repetition..repetition + batch_size)
+            .map(|repetition| {
+                let inputs = generate_inputs(settings.repetition_seed(master_seed, repetition));
+                (
+                    inputs,
+                    network_description.instantiate(n_parties),
+                    Some(repetition),
+                )
+            })
+            .collect();
+        let results = run_batch(
+            &mut slots,
+            batch,
+            &pools,
+            settings,
+            stats.name(),
+            round_sizes.as_ref(),
+        );
+
+        // The statistics are only incorporated on this thread, in the order of the repetitions
+        for result in results {
+            match result {
+                Ok(result) => {
+                    let valid = match check_repetition(protocol, &result, repetition) {
+                        Ok(()) => true,
+                        Err(EvaluationError::Validation(_)) if settings.record_invalid => false,
+                        Err(error) => return Err(error),
+                    };
+
+                    stats.incorporate_party_stats(result.timings);
+                    stats.incorporate_makespan(result.makespan);
+                    stats.incorporate_seed(settings.repetition_seed(master_seed, repetition));
+                    if !valid {
+                        stats.mark_invalid();
+                    }
+                    if let Some(snapshots) = result.snapshots {
+                        stats.incorporate_snapshots(snapshots);
+                    }
+                    outputs = result.outputs;
+                }
+                // Only a repetition that timed out is skipped, so that the evaluation continues
+                Err(EvaluationError::Timeout(_)) => stats.mark_timed_out(),
+                Err(EvaluationError::Protocol(error)) if settings.record_invalid => {
+                    stats.record_protocol_error(error)
+                }
+                Err(error) => return Err(error),
+            }
+            repetition += 1;
+
+            #[cfg(feature = "persistence")]
+            if let Some(checkpoint) = &settings.checkpoint {
+                save_checkpoint(&stats, checkpoint)?;
+            }
+        }
+    }
+
+    Ok((stats, outputs))
+}
+
+/// Derives the seed of the inputs of the `repetition` of an evaluation from its `master_seed`. Unmeasured repetitions
+/// (e.g. warmups) use the seeds of the complement of the master seed, so that they do not repeat measured inputs.
+pub(crate) fn derive_seed(master_seed: u64, repetition: usize) -> u64 {
+    // SplitMix64, so that the seeds of consecutive repetitions are unrelated
+    let mut z = master_seed.wrapping_add(
+        (repetition as u64)
+            .wrapping_add(1)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// The inputs, channels and (optional) index of one repetition in a batch of concurrent repetitions.
+type BatchedRepetition<P> = (Vec<<P as Party>::Input>, Vec<Channels>, Option<usize>);
+
+/// Runs the repetitions of the `batch` concurrently, each with the parties of one of the `slots` on one of the
+/// `pools`, and returns their results in the order of the batch. A single repetition runs on the calling thread.
+fn run_batch<P: Party + Send>(
+    slots: &mut [Vec<P>],
+    batch: Vec<BatchedRepetition<P>>,
+    pools: &[ThreadPool],
+    settings: &EvaluationSettings,
+    experiment_name: &str,
+    round_sizes: Option<&Arc<RoundSizes>>,
+) -> Vec<Result<Repetition<P>, EvaluationError>> {
+    debug_assert!(batch.len() <= slots.len());
+    if batch.len() == 1 {
+        let (inputs, channels, repetition) = batch.into_iter().next().unwrap();
+        return vec![run_repetition(
+            &mut slots[0],
+            inputs,
+            channels,
+            settings,
+            experiment_name,
+            repetition,
+            round_sizes,
+        )];
+    }
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = slots
+            .iter_mut()
+            .zip(batch)
+            .zip(pools)
+            .map(|((parties, (inputs, channels, repetition)), pool)| {
+                scope.spawn(move || {
+                    pool.install(|| {
+                        run_repetition(
+                            parties,
+                            inputs,
+                            channels,
+                            settings,
+                            experiment_name,
+                            repetition,
+                            round_sizes,
+                        )
+                    })
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Loads the statistics that an earlier run of the experiment with the given (empty) `stats` saved to the file named
+/// `checkpoint`, or `None` if the file does not exist yet. Fails if the file was saved by a different experiment.
+#[cfg(feature = "persistence")]
+fn load_checkpoint(
+    stats: &AggregatedStats,
+    checkpoint: &str,
+) -> io::Result<Option<AggregatedStats>> {
+    if !std::path::Path::new(checkpoint).exists() {
+        return Ok(None);
+    }
+
+    let resumed = AggregatedStats::load(checkpoint)?;
+    if resumed.name() != stats.name() || resumed.party_names() != stats.party_names() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the checkpoint '{}' was saved by the experiment '{}' instead of '{}'",
+                checkpoint,
+                resumed.name(),
+                stats.name()
+            ),
+        ));
+    }
+    Ok(Some(resumed))
+}
+
+/// Saves the `stats` to the file named `checkpoint` through a temporary file, so that an interrupt while saving does
+/// not corrupt the previous checkpoint.
+#[cfg(feature = "persistence")]
+fn save_checkpoint(stats: &AggregatedStats, checkpoint: &str) -> io::Result<()> {
+    let temporary = format!("{}.tmp", checkpoint);
+    stats.save(&temporary)?;
+    std::fs::rename(temporary, checkpoint)
+}
+
+/// Compares the `outputs` of one repetition of the `protocol` against its `expected_outputs`, and returns a
+/// `ValidationError` that identifies the parties whose outputs differ.
+#[cfg_attr(not(feature = "verbose"), allow(unused_variables))]
+pub(crate) fn compare_outputs<O: Debug>(
+    protocol: &(impl Debug + ?Sized),
+    outputs: &[O],
+    expected_outputs: &ExpectedOutputs<O>,
+    repetition: usize,
+) -> Result<(), ValidationError> {
+    let Some(mismatching_parties) = expected_outputs.mismatching_parties(outputs) else {
+        return Err(ValidationError {
+            repetition,
+            reason: format!(
+                "`expected_outputs` returned {} outputs for {} parties",
+                expected_outputs.outputs().len(),
+                outputs.len()
+            ),
+            mismatching_parties: vec![],
+        });
+    };
+    if mismatching_parties.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(feature = "verbose")]
+    println!(
+        "The outputs differ from the expected outputs:\n{:?} ...expected:\n{:?} ...for these parameters:\n{:?}",
+        outputs,
+        expected_outputs.outputs(),
+        protocol
+    );
+
+    let parties: Vec<String> = mismatching_parties.iter().map(usize::to_string).collect();
+    Err(ValidationError {
+        repetition,
+        reason: format!(
+            "the outputs of parties {} differ from `expected_outputs`",
+            parties.join(", ")
+        ),
+        mismatching_parties,
+    })
+}
+
+/// Checks the outputs and the observed transcript of one repetition of the `protocol`.
+pub(crate) fn check_repetition<P: Protocol + ?Sized>(
+    protocol: &P,
+    result: &Repetition<P::Party>,
+    repetition: usize,
+) -> Result<(), EvaluationError> {
+    if !protocol.validate_outputs(&result.inputs, &result.outputs) {
+        #[cfg(feature = "verbose")]
+        println!(
+            "The outputs are invalid:\n{:?} ...for these parameters:\n{:?}",
+            result.outputs, protocol
+        );
+
+        return Err(ValidationError {
+            repetition,
+            reason: "the outputs were rejected by `validate_outputs`".to_string(),
+            mismatching_parties: vec![],
+        }
+        .into());
+    }
+
+    if let Some(expected_outputs) = protocol.expected_outputs(&result.inputs) {
+        compare_outputs(protocol, &result.outputs, &expected_outputs, repetition)?;
+    }
+
+    if !protocol.observe(&result.transcript) {
+        #[cfg(feature = "verbose")]
+        println!(
+            "The observed transcript is invalid for these parameters:\n{:?}",
+            protocol
+        );
+
+        return Err(ValidationError {
+            repetition,
+            reason: "the observed transcript was rejected by `observe`".to_string(),
+            mismatching_parties: vec![],
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Runs one repetition of a protocol with the given `parties` and `inputs` over the instantiated `channels` of a
+/// network. Repetitions without an index are not part of the results, so they are not profiled.
+pub(crate) fn run_repetition<P: Party + Send>(
+    parties: &mut [P],
+    mut inputs: Vec<P::Input>,
+    mut channels: Vec<Channels>,
+    settings: &EvaluationSettings,
+    experiment_name: &str,
+    repetition: Option<usize>,
+    round_sizes: Option<&Arc<RoundSizes>>,
+) -> Result<Repetition<P>, EvaluationError> {
+    let n_parties = parties.len();
+    debug_assert_eq!(inputs.len(), n_parties);
+
+    debug_assert_eq!(channels.len(), n_parties);
+
+    // The watchdog aborts the repetition if the parties deadlock, instead of letting them wait forever
+    let watchdog = Arc::new(Watchdog::new(n_parties, settings.repetition_timeout));
+    let (observer, transcript) = channel();
+    for channel in channels.iter_mut() {
+        channel.attach_observer(observer.clone());
+        channel.attach_watchdog(watchdog.clone());
+
+        if let Some(round_sizes) = round_sizes {
+            channel.attach_round_sizes(round_sizes.clone());
+        }
+    }
+    drop(observer);
+
+    let mut party_timings: Vec<Timings> = (0..n_parties).map(|_| Timings::new()).collect();
+
+    #[cfg(feature = "profiling")]
+    let profiler = settings.profiler.as_ref().filter(|profiler| {
+        repetition
+            .map(|repetition| profiler.profiles(repetition))
+            .unwrap_or(false)
+    });
+    #[cfg(feature = "profiling")]
+    let guard = profiler.map(|profiler| profiler.start()).transpose()?;
+
+    // The progress is also used to attribute the sent bytes to the running timers, so it is always tracked
+    let progress: Vec<Arc<Progress>> = (0..n_parties).map(|_| Arc::default()).collect();
+    for ((channel, s), party_progress) in channels.iter_mut().zip(&mut party_timings).zip(&progress)
+    {
+        channel.attach_progress(party_progress.clone());
+        s.attach_progress(party_progress.clone());
+        s.attach_watchdog(watchdog.clone());
+    }
+    let party_names = || {
+        parties
+            .iter()
+            .enumerate()
+            .map(|(id, party)| party.get_name(id))
+            .collect()
+    };
+    if let Some(endpoint) = &settings.metrics_endpoint {
+        endpoint.start_repetition(experiment_name, repetition, party_names(), progress.clone());
+    }
+    #[cfg(feature = "dashboard")]
+    if let Some(dashboard) = &settings.dashboard {
+        dashboard.start_repetition(experiment_name, repetition, party_names(), progress.clone());
+    }
+    if let Some(bounds) = &settings.message_size_buckets {
+        for channel in channels.iter_mut() {
+            channel.attach_message_size_histogram(bounds);
+        }
+    }
+    let done = &AtomicBool::new(false);
+    let progress = &progress;
+
+    let start_time = Instant::now();
+    if settings.tracing {
+        for (channel, s) in channels.iter_mut().zip(&mut party_timings) {
+            channel.attach_trace(start_time);
+            s.attach_trace(start_time);
+        }
+    }
+    if settings.round_statistics {
+        for channel in channels.iter_mut() {
+            channel.attach_round_timeline(start_time);
+        }
+    }
+    let (results, snapshots) = thread::scope(|scope| {
+        let snapshotter = settings
+            .snapshot_interval
+            .map(|interval| scope.spawn(move || take_snapshots(progress, interval, done)));
+
+        let start_party =
+            |(((id, party), input), channel): (((usize, &mut P), &mut P::Input), &mut _),
+             s: &mut Timings| {
+                let mut run = || -> Result<_, EvaluationError> {
+                    #[cfg(feature = "priority")]
+                    if let Some(niceness) = settings.niceness(id) {
+                        set_niceness(niceness)?;
+                    }
+
+                    #[cfg(feature = "profiling")]
+                    if profiler.is_some() {
+                        s.attach_profiler(PartyProfiler::new(id));
+                    }
+
+                    let result = run_party(party, id, n_parties, input, channel, s);
+
+                    // The thread may belong to the user's thread pool, so it must not keep the party's tag
+                    #[cfg(feature = "profiling")]
+                    s.restore_thread_name();
+
+                    result
+                };
+                // A party that failed after its channel was closed (e.g. because it unwrapped the `CommError`) failed
+                // due to another party, so it is reported like the error that it received
+                let result = run().map_err(|error| {
+                    if channel.is_closed() {
+                        EvaluationError::Transport(CommError::Closed { id })
+                    } else {
+                        error
+                    }
+                });
+
+                // The other parties would wait for this party forever, so they are notified that it failed
+                if result.is_err() {
+                    channel.abort();
+                }
+                watchdog.finish(id);
+                result
+            };
+
+        // A thread's niceness cannot always be restored, so when any niceness is set, every party runs on a thread
+        // of its own that exits afterwards instead of on a shared (reused) thread
+        #[cfg(feature = "priority")]
+        let results: Vec<Result<_, EvaluationError>> = if settings.niceness.is_empty() {
+            run_pooled(
+                parties,
+                &mut inputs,
+                &mut channels,
+                &mut party_timings,
+                &start_party,
+            )
+        } else {
+            thread::scope(|party_scope| {
+                let handles: Vec<_> = parties
+                    .iter_mut()
+                    .enumerate()
+                    .zip(inputs.iter_mut())
+                    .zip(channels.iter_mut())
+                    .zip(party_timings.iter_mut())
+                    .map(|(args, s)| party_scope.spawn(|| start_party(args, s)))
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect()
+            })
+        };
+        #[cfg(not(feature = "priority"))]
+        let results: Vec<Result<_, EvaluationError>> = run_pooled(
+            parties,
+            &mut inputs,
+            &mut channels,
+            &mut party_timings,
+            &start_party,
+        );
+
+        done.store(true, Ordering::Release);
+        let snapshots = snapshotter.map(|snapshotter| {
+            snapshotter.thread().unpark();
+            snapshotter.join().unwrap()
+        });
+
+        (results, snapshots)
+    });
+    let makespan = start_time.elapsed();
+
+    // The parties of a repetition that timed out fail once they are aborted, so the timeout is reported instead
+    if let Some(timeout) = watchdog.timeout(repetition) {
+        return Err(timeout.into());
+    }
+
+    // A failing party closes the other parties' channels, so the original failure is reported instead
+    let mut results = results;
+    if let Some(position) = results.iter().position(|result| {
+        !matches!(
+            result,
+            Ok(_) | Err(EvaluationError::Transport(CommError::Closed { .. }))
+        )
+    }) {
+        return Err(results.swap_remove(position).unwrap_err());
+    }
+    if let Some(deadlock) = watchdog.deadlock() {
+        return Err(deadlock);
+    }
+    let outputs: Vec<_> = results.into_iter().collect::<Result<_, _>>()?;
+
+    #[cfg(feature = "profiling")]
+    if let (Some(profiler), Some(guard), Some(repetition)) = (profiler, guard, repetition) {
+        let party_profilers: Vec<PartyProfiler> = party_timings
+            .iter_mut()
+            .filter_map(|s| s.detach_profiler())
+            .collect();
+        profiler.write_flamegraphs(guard, experiment_name, repetition, &party_profilers)?;
+    }
+
+    for (channel, s) in channels.iter().zip(&mut party_timings) {
+        s.write_bytes(channel.sent_bytes(), channel.received_bytes());
+        s.write_message_counts(channel.sent_message_counts());
+        s.write_message_sizes(channel.message_sizes());
+        s.write_rounds(channel.round_timeline());
+        s.write_fragment_counts(channel.fragment_counts());
+        s.write_relayed_bytes(channel.relayed_bytes());
+        #[cfg(feature = "compression")]
+        s.write_compression(channel.uncompressed_bytes(), channel.compressed_bytes());
+        s.write_comm_checkpoints(channel.comm_checkpoints());
+        s.write_session_comm(channel.session_comm());
+        s.write_trace(channel.trace());
+    }
+    if let (Some(endpoint), Some(_)) = (&settings.metrics_endpoint, repetition) {
+        endpoint.finish_repetition(&party_timings);
+    }
+    #[cfg(feature = "dashboard")]
+    if let (Some(dashboard), Some(_)) = (&settings.dashboard, repetition) {
+        dashboard.finish_repetition(&party_timings);
+    }
+
+    // All senders were dropped together with the channels, so this collects the complete transcript
+    drop(channels);
+    let transcript: Vec<ObservedMessage> = transcript.into_iter().collect();
+
+    Ok(Repetition {
+        inputs,
+        outputs,
+        timings: party_timings,
+        makespan,
+        snapshots,
+        transcript,
+    })
+}
+
+/// Runs the `party` with the given `id`, measuring its total run time and turning a panic into an `EvaluationError`.
+pub(crate) fn run_party<P: Party>(
+    party: &mut P,
+    id: usize,
+    n_parties: usize,
+    input: &P::Input,
+    channel: &mut Channels,
+    s: &mut Timings,
+) -> Result<P::Output, EvaluationError> {
+    let slowdown_factor = party.slowdown_factor(id);
+    if !(slowdown_factor.is_finite() && slowdown_factor > 0.) {
+        return Err(EvaluationError::InvalidSlowdownFactor {
+            party: id,
+            slowdown_factor,
+        });
+    }
+    s.set_slowdown_factor(slowdown_factor);
+    s.track_cpu_time();
+
+    let total_timer = s.create_timer("Total");
+    let output = catch_unwind(AssertUnwindSafe(|| {
+        party.run(input, &mut PartyContext::new(id, n_parties, channel, s))
+    }))
+    .map_err(|payload| party_failure(id, party.get_name(id), payload))?;
+    s.stop_unscaled_timer(total_timer);
+
+    Ok(output)
+}
+
+/// Runs `start_party` for every party on the shared thread pool.
+fn run_pooled<P, I, C, T, F>(
+    parties: &mut [P],
+    inputs: &mut [I],
+    channels: &mut [C],
+    party_timings: &mut [Timings],
+    start_party: &F,
+) -> Vec<T>
+where
+    P: Send,
+    I: Send,
+    C: Send,
+    T: Send,
+    F: Fn((((usize, &mut P), &mut I), &mut C), &mut Timings) -> T + Sync,
+{
+    parties
+        .par_iter_mut()
+        .enumerate()
+        .zip(inputs.par_iter_mut())
+        .zip(channels.par_iter_mut())
+        .zip(party_timings.par_iter_mut())
+        .map(|(args, s)| start_party(args, s))
+        .collect()
+}
+
+/// Sets the OS scheduling niceness of the calling thread.
+#[cfg(all(feature = "priority", target_os = "linux"))]
+fn set_niceness(niceness: i32) -> io::Result<()> {
+    // On Linux, passing a thread id instead of a process id only affects that thread
+    let thread_id = unsafe { libc::gettid() };
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, thread_id as libc::id_t, niceness) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Sets the OS scheduling niceness of the calling thread. Only Linux supports the niceness of individual threads.
+#[cfg(all(feature = "priority", not(target_os = "linux")))]
+fn set_niceness(_niceness: i32) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "per-party niceness is only supported on Linux",
+    ))
+}
+
+/// Turns the payload of a party's panic into an `EvaluationError`.
+pub(crate) fn party_failure(
+    party: usize,
+    party_name: String,
+    payload: Box<dyn Any + Send>,
+) -> EvaluationError {
+    let payload = match payload.downcast::<CommError>() {
+        Ok(error) => return EvaluationError::Transport(*error),
+        Err(payload) => payload,
+    };
+    let payload = match payload.downcast::<ProtocolError>() {
+        Ok(error) => {
+            return EvaluationError::Protocol(ProtocolError {
+                party_name,
+                ..*error
+            })
+        }
+        Err(payload) => payload,
+    };
+
+    let message = match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic payload".to_string(),
+        },
+    };
+
+    EvaluationError::PartyPanicked { party, message }
+}
+
+#[cfg(all(test, feature = "priority", target_os = "linux"))]
+mod tests {
+    use crate::{comm::FullMesh, settings::EvaluationSettings, testing::TestProtocol, Protocol};
+
+    #[test]
+    fn niceness_only_affects_its_party() {
+        let protocol = TestProtocol::new(|_: &(), _| unsafe {
+            libc::getpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t)
+        })
+        .with_validation(|_, outputs| {
+            // Only the deprioritized party runs at a different niceness
+            let niceness = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+            outputs[0] == 19 && outputs[1..].iter().all(|&output| output == niceness)
+        });
+
+        let network = FullMesh::new();
+        let settings = EvaluationSettings::new().with_niceness(0, 19);
+        protocol
+            .evaluate_with_settings(
+                "Experiment (w/ niceness)".to_string(),
+                3,
+                &network,
+                2,
+                &settings,
+            )
+            .unwrap();
+    }
+}
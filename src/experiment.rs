@@ -0,0 +1,573 @@
+use std::{fmt, fs::File, io, time::Duration};
+#[cfg(feature = "persistence")]
+use std::{fs::create_dir_all, path::Path};
+
+use tabled::{builder::Builder, Style};
+
+use crate::{
+    comm::{Channels, FullMesh, NetworkDescription},
+    error::EvaluationError,
+    settings::EvaluationSettings,
+    statistics::{format_bytes, format_duration, AggregatedStats},
+    Parameterized, Parameters, Protocol,
+};
+
+/// The latency and throughput of a network under which an `Experiment` evaluates a protocol (see
+/// `Experiment::conditions`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkCondition {
+    /// The one-way latency of each message.
+    pub latency: Duration,
+    /// The maximum throughput of each party, or `None` if it is unlimited.
+    pub bytes_per_second: Option<f64>,
+}
+
+impl NetworkCondition {
+    /// Constructs a NetworkCondition with the specified `latency` and throughput (maximum `bytes_per_second`).
+    pub fn new(latency: Duration, bytes_per_second: f64) -> Self {
+        NetworkCondition {
+            latency,
+            bytes_per_second: Some(bytes_per_second),
+        }
+    }
+
+    /// Constructs a NetworkCondition with the specified `latency` and an unlimited throughput.
+    pub fn unlimited(latency: Duration) -> Self {
+        NetworkCondition {
+            latency,
+            bytes_per_second: None,
+        }
+    }
+
+    /// Returns every combination of one of the `latencies` and one of the throughputs (`None` meaning unlimited),
+    /// ordered by latency first.
+    pub fn grid(latencies: &[Duration], bytes_per_second: &[Option<f64>]) -> Vec<Self> {
+        latencies
+            .iter()
+            .flat_map(|&latency| {
+                bytes_per_second
+                    .iter()
+                    .map(move |&bytes_per_second| NetworkCondition {
+                        latency,
+                        bytes_per_second,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the round-trip time of a message, i.e. twice the latency.
+    pub fn rtt(&self) -> Duration {
+        self.latency * 2
+    }
+}
+
+impl fmt::Display for NetworkCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.bytes_per_second {
+            Some(bytes_per_second) => write!(
+                f,
+                "{:?} latency, {}/s",
+                self.latency,
+                format_bytes(bytes_per_second)
+            ),
+            None => write!(f, "{:?} latency, unlimited", self.latency),
+        }
+    }
+}
+
+/// A network that simulates a `NetworkCondition` on the channels of another network, replacing the latency and
+/// throughput that the other network simulates itself.
+struct ConditionedNetwork<'a, N> {
+    network: &'a N,
+    condition: NetworkCondition,
+}
+
+impl<N: NetworkDescription> NetworkDescription for ConditionedNetwork<'_, N> {
+    fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
+        let seconds_per_byte = self
+            .condition
+            .bytes_per_second
+            .map_or(Duration::ZERO, |bytes_per_second| {
+                Duration::from_secs_f64(1. / bytes_per_second)
+            });
+        self.network
+            .instantiate(n_parties)
+            .into_iter()
+            .map(|channels| channels.with_overhead(self.condition.latency, seconds_per_byte))
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("{} under {}", self.network.describe(), self.condition)
+    }
+}
+
+/// A sweep of a protocol over a range of party counts, network conditions and parameters, configured fluently (e.g.
+/// `Experiment::new("psi").protocol(MyPsi).parties(3..=10).repetitions(30).warmup(3).run()`) instead of with a
+/// hand-written loop over `Protocol::evaluate_with_settings`. An experiment can only be run once a protocol was set.
+/// By default, it runs one repetition with two parties on a `FullMesh` without communication overhead.
+#[derive(Debug, Clone)]
+pub struct Experiment<P = (), N = FullMesh> {
+    name: String,
+    protocol: P,
+    /// The parameterizations of the protocol, with the columns of their parameters.
+    variants: Vec<(Vec<(String, String)>, P)>,
+    party_counts: Vec<usize>,
+    network: N,
+    conditions: Vec<NetworkCondition>,
+    repetitions: usize,
+    settings: EvaluationSettings,
+    #[cfg(feature = "persistence")]
+    checkpoint_directory: Option<String>,
+}
+
+impl Experiment {
+    /// Constructs an experiment named `name` without a protocol.
+    pub fn new(name: &str) -> Self {
+        Experiment {
+            name: name.to_string(),
+            protocol: (),
+            variants: vec![],
+            party_counts: vec![2],
+            network: FullMesh::new(),
+            conditions: vec![],
+            repetitions: 1,
+            settings: EvaluationSettings::default(),
+            #[cfg(feature = "persistence")]
+            checkpoint_directory: None,
+        }
+    }
+}
+
+impl<P, N> Experiment<P, N> {
+    /// Returns the name of the experiment.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Evaluates the `protocol` in this experiment, which discards the parameters that were configured before.
+    pub fn protocol<Q: Protocol>(self, protocol: Q) -> Experiment<Q, N> {
+        Experiment {
+            name: self.name,
+            protocol,
+            variants: vec![],
+            party_counts: self.party_counts,
+            network: self.network,
+            conditions: self.conditions,
+            repetitions: self.repetitions,
+            settings: self.settings,
+            #[cfg(feature = "persistence")]
+            checkpoint_directory: self.checkpoint_directory,
+        }
+    }
+
+    /// Evaluates the protocol on the `network`.
+    pub fn network<M: NetworkDescription>(self, network: M) -> Experiment<P, M> {
+        Experiment {
+            name: self.name,
+            protocol: self.protocol,
+            variants: self.variants,
+            party_counts: self.party_counts,
+            network,
+            conditions: self.conditions,
+            repetitions: self.repetitions,
+            settings: self.settings,
+            #[cfg(feature = "persistence")]
+            checkpoint_directory: self.checkpoint_directory,
+        }
+    }
+
+    /// Evaluates the protocol with each of the `party_counts` (e.g. `3..=10` or `[2, 4, 8]`), in the given order.
+    pub fn parties(mut self, party_counts: impl IntoIterator<Item = usize>) -> Self {
+        self.party_counts = party_counts.into_iter().collect();
+        self
+    }
+
+    /// Evaluates the protocol under each of the network `conditions` (e.g. the combinations of
+    /// `NetworkCondition::grid`), in the given order, for each number of parties. The conditions replace the latency
+    /// and throughput that the network simulates itself. Without conditions, the network is used as is.
+    pub fn conditions(mut self, conditions: impl IntoIterator<Item = NetworkCondition>) -> Self {
+        self.conditions = conditions.into_iter().collect();
+        self
+    }
+
+    /// Runs `repetitions` measured repetitions for each number of parties and network condition.
+    pub fn repetitions(mut self, repetitions: usize) -> Self {
+        self.repetitions = repetitions;
+        self
+    }
+
+    /// Runs `warmup_repetitions` unmeasured repetitions before the measured ones (see
+    /// `EvaluationSettings::with_warmup`).
+    pub fn warmup(mut self, warmup_repetitions: usize) -> Self {
+        self.settings = self.settings.with_warmup(warmup_repetitions);
+        self
+    }
+
+    /// Evaluates the protocol with the given optional `settings`, which replace any that were configured before
+    /// (including the warmup).
+    pub fn settings(mut self, settings: EvaluationSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Saves the completed repetitions of each evaluation to a checkpoint in the `directory` as they finish, and
+    /// skips the repetitions in the checkpoints of an earlier run (see `EvaluationSettings::with_checkpoint`), so
+    /// that an interrupted sweep can be resumed by running it again. The directory is created if it does not exist.
+    #[cfg(feature = "persistence")]
+    pub fn resume(mut self, directory: &str) -> Self {
+        self.checkpoint_directory = Some(directory.to_string());
+        self
+    }
+
+    /// Returns the settings of the evaluation named `experiment_name`, which saves a checkpoint of its own if the
+    /// experiment is resumable.
+    #[cfg_attr(not(feature = "persistence"), allow(unused_variables))]
+    fn settings_for(&self, experiment_name: &str) -> EvaluationSettings {
+        #[cfg(feature = "persistence")]
+        if let Some(directory) = &self.checkpoint_directory {
+            let checkpoint =
+                Path::new(directory).join(format!("{}.json", file_name(experiment_name)));
+            return self
+                .settings
+                .clone()
+                .with_checkpoint(&checkpoint.to_string_lossy());
+        }
+        self.settings.clone()
+    }
+}
+
+impl<P: Parameterized, N> Experiment<P, N> {
+    /// Evaluates each parameterization of the protocol with the given `params` (see `Parameterized::with_params`),
+    /// in the given order, for each number of parties and network condition. The parameters are recorded as columns
+    /// in the exports of the statistics and the results.
+    pub fn params(mut self, params: impl IntoIterator<Item = P::Params>) -> Self {
+        self.variants = params
+            .into_iter()
+            .map(|params| (params.columns(), self.protocol.with_params(&params)))
+            .collect();
+        self
+    }
+}
+
+impl<P: Protocol, N: NetworkDescription> Experiment<P, N> {
+    /// Evaluates each parameterization of the protocol with each number of parties under each network condition, and
+    /// returns the statistics of each. The statistics are named after the experiment, the number of parties, the
+    /// parameters and the condition, e.g. "psi (3 parties)" or "psi (3 parties, bits=64, 10ms latency, unlimited)".
+    /// Stops at the first evaluation that fails.
+    pub fn run(&self) -> Result<ExperimentResults, EvaluationError> {
+        let conditions: Vec<Option<NetworkCondition>> = if self.conditions.is_empty() {
+            vec![None]
+        } else {
+            self.conditions.iter().copied().map(Some).collect()
+        };
+        let variants: Vec<(&[(String, String)], &P)> = if self.variants.is_empty() {
+            vec![(&[], &self.protocol)]
+        } else {
+            self.variants
+                .iter()
+                .map(|(params, protocol)| (params.as_slice(), protocol))
+                .collect()
+        };
+
+        #[cfg(feature = "persistence")]
+        if let Some(directory) = &self.checkpoint_directory {
+            create_dir_all(directory)?;
+        }
+
+        let mut results = vec![];
+        for &(params, protocol) in &variants {
+            for &n_parties in &self.party_counts {
+                for &condition in &conditions {
+                    let point = ExperimentPoint {
+                        n_parties,
+                        params: params.to_vec(),
+                        condition,
+                    };
+                    let experiment_name = format!("{} ({})", self.name, point);
+                    let settings = self.settings_for(&experiment_name);
+                    let mut stats = match condition {
+                        Some(condition) => protocol.evaluate_with_settings(
+                            experiment_name,
+                            n_parties,
+                            &ConditionedNetwork {
+                                network: &self.network,
+                                condition,
+                            },
+                            self.repetitions,
+                            &settings,
+                        ),
+                        None => protocol.evaluate_with_settings(
+                            experiment_name,
+                            n_parties,
+                            &self.network,
+                            self.repetitions,
+                            &settings,
+                        ),
+                    }?;
+                    stats.set_params(params.to_vec());
+                    results.push((point, stats));
+                }
+            }
+        }
+
+        Ok(ExperimentResults {
+            name: self.name.clone(),
+            results,
+        })
+    }
+}
+
+/// The number of parties, the parameters and the network condition (if any) of one evaluation in an `Experiment`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExperimentPoint {
+    /// The number of parties.
+    pub n_parties: usize,
+    /// The name and value of each of the protocol's parameters, which are empty if the experiment did not sweep any.
+    pub params: Vec<(String, String)>,
+    /// The network condition, or `None` if the experiment did not sweep any.
+    pub condition: Option<NetworkCondition>,
+}
+
+impl ExperimentPoint {
+    /// Returns the value of the parameter named `name`, if it was swept.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for ExperimentPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} parties", self.n_parties)?;
+        for (name, value) in &self.params {
+            write!(f, ", {}={}", name, value)?;
+        }
+        if let Some(condition) = self.condition {
+            write!(f, ", {}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+/// The statistics of each evaluation of an `Experiment`, keyed by its number of parties, parameters and network
+/// condition.
+#[derive(Debug)]
+pub struct ExperimentResults {
+    name: String,
+    results: Vec<(ExperimentPoint, AggregatedStats)>,
+}
+
+impl ExperimentResults {
+    /// Returns the name of the experiment.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns each evaluated point together with its statistics, in the order in which they were evaluated.
+    pub fn results(&self) -> &[(ExperimentPoint, AggregatedStats)] {
+        &self.results
+    }
+
+    /// Returns the statistics of the first evaluation with `n_parties`, if it was part of the experiment.
+    pub fn stats(&self, n_parties: usize) -> Option<&AggregatedStats> {
+        self.results
+            .iter()
+            .find(|(point, _)| point.n_parties == n_parties)
+            .map(|(_, stats)| stats)
+    }
+
+    /// Returns the statistics of the first evaluation with `n_parties` under the `condition`, if it was part of the
+    /// experiment.
+    pub fn stats_under(
+        &self,
+        n_parties: usize,
+        condition: &NetworkCondition,
+    ) -> Option<&AggregatedStats> {
+        self.results
+            .iter()
+            .find(|(point, _)| {
+                point.n_parties == n_parties && point.condition.as_ref() == Some(condition)
+            })
+            .map(|(_, stats)| stats)
+    }
+
+    /// Returns the statistics of each evaluation with `n_parties` together with the latency of its network condition,
+    /// in the order in which they were evaluated, e.g. for `plots::plot_runtime_vs_latency`. Evaluations without a
+    /// condition are left out.
+    pub fn by_latency(&self, n_parties: usize) -> Vec<(Duration, &AggregatedStats)> {
+        self.results
+            .iter()
+            .filter(|(point, _)| point.n_parties == n_parties)
+            .filter_map(|(point, stats)| Some((point.condition?.latency, stats)))
+            .collect()
+    }
+
+    /// Returns the names of the metrics that were measured in any evaluation, in the order in which they were first
+    /// measured (see `AggregatedStats::headline_metrics`).
+    pub fn metric_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for (_, stats) in &self.results {
+            for (name, _) in stats.headline_metrics() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the names of the parameters that were swept, in the order in which they were first recorded.
+    pub fn param_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for (point, _) in &self.results {
+            for (name, _) in &point.params {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the value of the `metric` (e.g. "timer:Total" or "sent") for each evaluated point that measured it, so
+    /// that it can be plotted against the number of parties, the parameters or the network condition.
+    pub fn metric(&self, metric: &str) -> Vec<(ExperimentPoint, f64)> {
+        self.results
+            .iter()
+            .filter_map(|(point, stats)| {
+                stats
+                    .headline_metrics()
+                    .into_iter()
+                    .find(|(name, _)| name == metric)
+                    .map(|(_, value)| (point.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Prints a pretty table that compares the evaluations side by side, relative to the first one (see
+    /// `AggregatedStats::compare`).
+    pub fn print(&self) {
+        let stats: Vec<&AggregatedStats> = self.results.iter().map(|(_, stats)| stats).collect();
+        AggregatedStats::compare(&stats).print();
+    }
+
+    /// Prints a pretty table with the value of each metric (in the columns) for each evaluated point (in the rows).
+    pub fn print_metrics(&self) {
+        let metric_names = self.metric_names();
+        let param_names = self.param_names();
+        let has_conditions = self
+            .results
+            .iter()
+            .any(|(point, _)| point.condition.is_some());
+
+        let mut builder = Builder::default();
+
+        // Add header
+        let mut header = vec!["Parties".to_string()];
+        header.extend(param_names.iter().cloned());
+        if has_conditions {
+            header.push("Condition".to_string());
+        }
+        builder.add_record(header.into_iter().chain(metric_names.iter().cloned()));
+
+        for (point, stats) in &self.results {
+            let mut record = vec![point.n_parties.to_string()];
+            record.extend(
+                param_names
+                    .iter()
+                    .map(|name| point.param(name).unwrap_or("-").to_string()),
+            );
+            if has_conditions {
+                record.push(point.condition.map_or("-".to_string(), |c| c.to_string()));
+            }
+
+            let metrics = stats.headline_metrics();
+            builder.add_record(record.into_iter().chain(metric_names.iter().map(|metric| {
+                match metrics.iter().find(|(name, _)| name == metric) {
+                    Some((_, value)) if metric == "sent" => format_bytes(*value),
+                    Some((_, value)) => format_duration(*value),
+                    None => "-".to_string(),
+                }
+            })));
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", self.name);
+        println!("{}", table);
+    }
+
+    /// Outputs the value of each metric for each evaluated point to a csv named `csv_filename`, with one row per
+    /// point and metric, and a column for each parameter after the number of parties. Durations (including the
+    /// latency) are in seconds, the sent data is in bytes and the throughput is in bytes per second. The latency and
+    /// throughput are empty for points without a network condition, and so is the throughput of an unlimited
+    /// condition.
+    pub fn write_csv(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        let param_names = self.param_names();
+        csv_writer.write_record(csv_header(&param_names))?;
+        for record in self.csv_records(&param_names) {
+            csv_writer.write_record(&record)?;
+        }
+
+        csv_writer.flush()
+    }
+
+    /// Returns the rows of `write_csv`, with a column for each of the `param_names` (which may include parameters that
+    /// this experiment did not sweep, e.g. to combine the rows of multiple experiments).
+    pub(crate) fn csv_records(&self, param_names: &[String]) -> Vec<Vec<String>> {
+        let mut records = vec![];
+        for (point, stats) in &self.results {
+            let latency = point
+                .condition
+                .map_or(String::new(), |c| c.latency.as_secs_f64().to_string());
+            let bytes_per_second = point
+                .condition
+                .and_then(|c| c.bytes_per_second)
+                .map_or(String::new(), |bytes_per_second| {
+                    bytes_per_second.to_string()
+                });
+
+            for (metric, value) in stats.headline_metrics() {
+                let mut record = vec![self.name.clone(), point.n_parties.to_string()];
+                record.extend(
+                    param_names
+                        .iter()
+                        .map(|name| point.param(name).unwrap_or("").to_string()),
+                );
+                record.extend([
+                    latency.clone(),
+                    bytes_per_second.clone(),
+                    metric,
+                    value.to_string(),
+                ]);
+                records.push(record);
+            }
+        }
+        records
+    }
+}
+
+/// Returns the header of the csv that `ExperimentResults::write_csv` outputs, with a column for each of the
+/// `param_names`.
+pub(crate) fn csv_header(param_names: &[String]) -> Vec<String> {
+    let mut header = vec!["experiment".to_string(), "n_parties".to_string()];
+    header.extend(param_names.iter().cloned());
+    header.extend(["latency", "bytes_per_second", "metric", "value"].map(str::to_string));
+    header
+}
+
+/// Replaces the characters of an experiment's `name` that are not allowed in file names on some platforms.
+pub(crate) fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect()
+}
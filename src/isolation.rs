@@ -0,0 +1,246 @@
+use std::{
+    env,
+    ffi::OsString,
+    io,
+    net::{Ipv4Addr, SocketAddr, TcpStream},
+    path::PathBuf,
+    process::{self, Command},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    distributed::{run_worker, Controller},
+    error::EvaluationError,
+    statistics::AggregatedStats,
+    Party, Protocol,
+};
+
+/// The environment variable that tells a spawned process which controller to serve as a party.
+const CONTROLLER_VARIABLE: &str = "MPC_BENCH_CONTROLLER";
+
+/// The resources that a party's process used over the whole experiment.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessUsage {
+    /// The peak resident memory of the process, in bytes.
+    pub max_resident_bytes: u64,
+    /// The CPU time that the process spent in user mode.
+    pub user_time: Duration,
+    /// The CPU time that the process spent in the kernel.
+    pub system_time: Duration,
+}
+
+/// The statistics of an isolated experiment, together with the resources used by each party's process.
+#[derive(Debug)]
+pub struct IsolatedStats {
+    /// The statistics of the experiment, like `Protocol::evaluate` returns them.
+    pub stats: AggregatedStats,
+    /// The resources used by each party's process, indexed by party id.
+    pub usage: Vec<ProcessUsage>,
+}
+
+/// Runs every party in an OS process of its own, so that the memory and CPU usage of each party can be measured
+/// separately and a party that crashes (e.g. by aborting or running out of memory) fails the experiment instead of
+/// taking down the benchmark. The parties communicate over sockets on localhost (see `distributed`).
+///
+/// The processes run the program again, so the program must call `serve_if_party` with the same protocol before it
+/// does anything else.
+#[derive(Debug)]
+pub struct Isolation {
+    program: PathBuf,
+    args: Vec<OsString>,
+}
+
+impl Isolation {
+    /// Spawns the parties by running the current executable with the current arguments.
+    pub fn new() -> io::Result<Self> {
+        Ok(Isolation {
+            program: env::current_exe()?,
+            args: env::args_os().skip(1).collect(),
+        })
+    }
+
+    /// Spawns the parties by running `program` with `args`.
+    pub fn with_command<S: Into<OsString>>(
+        program: PathBuf,
+        args: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Isolation {
+            program,
+            args: args.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Evaluates multiple `repetitions` of the `protocol` with `n_parties` like `Protocol::evaluate` does, but with
+    /// each party in a process of its own.
+    pub fn evaluate<P>(
+        &self,
+        protocol: &P,
+        experiment_name: String,
+        n_parties: usize,
+        repetitions: usize,
+    ) -> Result<IsolatedStats, EvaluationError>
+    where
+        P: Protocol,
+        <P::Party as Party>::Input: Serialize,
+        <P::Party as Party>::Output: DeserializeOwned,
+    {
+        let controller = Controller::bind((Ipv4Addr::LOCALHOST, 0), n_parties)?;
+        let address = controller.local_addr()?;
+
+        let mut children = vec![];
+        for _ in 0..n_parties {
+            let spawned = Command::new(&self.program)
+                .args(&self.args)
+                .env(CONTROLLER_VARIABLE, address.to_string())
+                .spawn();
+            match spawned {
+                Ok(child) => children.push(child.id()),
+                Err(error) => {
+                    kill_all(&children);
+                    reap_all(&children);
+                    return Err(error.into());
+                }
+            }
+        }
+
+        let monitor = Arc::new(Monitor {
+            process_ids: children.clone(),
+            reaped: Mutex::new(vec![false; n_parties]),
+            controller: address,
+        });
+        let waiters: Vec<_> = (0..n_parties)
+            .map(|i| {
+                let monitor = monitor.clone();
+                thread::spawn(move || monitor.wait(i))
+            })
+            .collect();
+
+        let mut process_ids = vec![];
+        let result =
+            controller.evaluate_processes(protocol, experiment_name, repetitions, &mut process_ids);
+        if result.is_err() {
+            monitor.kill_remaining();
+        }
+
+        let usage: Vec<_> = waiters
+            .into_iter()
+            .map(|waiter| waiter.join().unwrap())
+            .collect::<io::Result<_>>()?;
+        let stats = result?;
+
+        Ok(IsolatedStats {
+            stats,
+            usage: process_ids
+                .iter()
+                .map(|process_id| {
+                    let i = children
+                        .iter()
+                        .position(|child| child == process_id)
+                        .unwrap();
+                    usage[i]
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Serves as a party of an isolated experiment if this process was spawned by `Isolation`, and exits afterwards.
+/// Otherwise, this returns immediately.
+pub fn serve_if_party<P>(protocol: &P)
+where
+    P: Protocol,
+    <P::Party as Party>::Input: DeserializeOwned,
+    <P::Party as Party>::Output: Serialize,
+{
+    if let Ok(controller) = env::var(CONTROLLER_VARIABLE) {
+        let result = run_worker(protocol, controller, (Ipv4Addr::LOCALHOST, 0));
+        process::exit(if result.is_ok() { 0 } else { 1 });
+    }
+}
+
+/// Reaps the parties' processes. When a process fails, the others are killed so that they do not wait for it forever.
+struct Monitor {
+    process_ids: Vec<u32>,
+    reaped: Mutex<Vec<bool>>,
+    controller: SocketAddr,
+}
+
+impl Monitor {
+    /// Waits until the `i`-th process exits and returns its resource usage.
+    fn wait(&self, i: usize) -> io::Result<ProcessUsage> {
+        let (succeeded, usage) = wait_for(self.process_ids[i])?;
+        self.reaped.lock().unwrap()[i] = true;
+
+        if !succeeded {
+            self.kill_remaining();
+            // The controller may still be waiting for the failed process to connect
+            let _ = TcpStream::connect(self.controller);
+        }
+
+        Ok(usage)
+    }
+
+    /// Kills the processes that have not exited yet (processes that were reaped may have had their id reused).
+    fn kill_remaining(&self) {
+        let reaped = self.reaped.lock().unwrap();
+        let remaining: Vec<u32> = self
+            .process_ids
+            .iter()
+            .zip(reaped.iter())
+            .filter(|(_, &reaped)| !reaped)
+            .map(|(&process_id, _)| process_id)
+            .collect();
+        kill_all(&remaining);
+    }
+}
+
+fn kill_all(process_ids: &[u32]) {
+    for &process_id in process_ids {
+        unsafe { libc::kill(process_id as libc::pid_t, libc::SIGKILL) };
+    }
+}
+
+fn reap_all(process_ids: &[u32]) {
+    for &process_id in process_ids {
+        let _ = wait_for(process_id);
+    }
+}
+
+/// Waits until the process with `process_id` exits. Returns whether it exited successfully and its resource usage.
+fn wait_for(process_id: u32) -> io::Result<(bool, ProcessUsage)> {
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        if unsafe { libc::wait4(process_id as libc::pid_t, &mut status, 0, &mut usage) } != -1 {
+            break;
+        }
+
+        let error = io::Error::last_os_error();
+        if error.kind() != io::ErrorKind::Interrupted {
+            return Err(error);
+        }
+    }
+
+    // Linux reports the peak resident memory in kilobytes, macOS in bytes
+    let max_resident_bytes = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64
+    } else {
+        usage.ru_maxrss as u64 * 1024
+    };
+    let time = |time: libc::timeval| {
+        Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+    };
+
+    Ok((
+        libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0,
+        ProcessUsage {
+            max_resident_bytes,
+            user_time: time(usage.ru_utime),
+            system_time: time(usage.ru_stime),
+        },
+    ))
+}
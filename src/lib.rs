@@ -1,18 +1,109 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs, unused_imports)]
 
-use comm::{Channels, NetworkDescription};
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use comm::{Channels, FullMesh, NetworkDescription, ObservedMessage};
+use error::EvaluationError;
+use evaluation::{evaluate_protocol, evaluate_protocol_with_outputs, Outputs};
+use rand::Rng;
 use std::fmt::Debug;
 
+use settings::EvaluationSettings;
 use statistics::{AggregatedStats, Timings};
 
 /// Communication module, allows parties to send and receive messages.
 pub mod comm;
 
+/// TCP module, allows parties to communicate over real sockets on localhost.
+pub mod tcp;
+
+/// Netem module, allows shaping real TCP traffic between the parties with netem (on Linux).
+#[cfg(all(feature = "netem", target_os = "linux"))]
+pub mod netem;
+
+/// QUIC module, allows parties to communicate over real QUIC connections on localhost.
+#[cfg(feature = "quic")]
+pub mod quic;
+
+/// Asynchronous module, allows running the parties as lightweight tokio tasks instead of threads.
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
 /// Statistics module, allows parties to track timings and bandwidth costs.
 pub mod statistics;
 
+/// Cost module, allows estimating the monetary cost and energy consumption of running a protocol from its measured
+/// usage.
+pub mod cost;
+
+/// Error module, describes the failures that can occur while evaluating a protocol.
+pub mod error;
+
+/// Settings module, allows configuring optional behavior when evaluating a protocol.
+pub mod settings;
+
+mod evaluation;
+
+mod watchdog;
+
+#[cfg(test)]
+mod testing;
+
+/// Experiment module, allows sweeping a protocol over a range of party counts and network conditions with a fluent
+/// builder.
+pub mod experiment;
+
+/// Pipeline module, allows benchmarking chains of protocols where the outputs of one become the inputs of the next.
+pub mod pipeline;
+
+/// Distributed module, allows running each party in a separate process on a different machine.
+#[cfg(feature = "distributed")]
+pub mod distributed;
+
+/// MPI module, allows running each party on an MPI rank of its own, e.g. to benchmark hundreds of parties on an HPC
+/// cluster.
+#[cfg(feature = "mpi")]
+pub mod mpi;
+
+/// Scheduler module, allows running many parameter points across a pool of machines.
+#[cfg(feature = "distributed")]
+pub mod scheduler;
+
+/// Isolation module, allows running each party in an OS process of its own (on Unix).
+#[cfg(all(feature = "isolation", unix))]
+pub mod isolation;
+
+/// Monitoring module, allows exposing the progress of long experiments as Prometheus metrics.
+pub mod monitoring;
+
+/// Dashboard module, allows following the progress of running experiments live in the terminal.
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+
+/// Dataset module, allows reading protocol inputs from CSV and JSON files.
+#[cfg(feature = "datasets")]
+pub mod dataset;
+
+/// Regression module, allows checking new runs of an experiment against a recorded baseline.
+pub mod regression;
+
+/// CLI module, allows running and reporting experiments from the command line with protocols from a registry.
+#[cfg(feature = "cli")]
+pub mod cli;
+
+/// Suite module, allows running many experiments together and reporting their results in one place.
+pub mod suite;
+
+/// Variants module, allows comparing the security variants of a protocol (e.g. semi-honest and malicious).
+pub mod variants;
+
+/// Plots module, allows rendering charts of the results as SVG or PNG.
+#[cfg(feature = "plots")]
+pub mod plots;
+
+/// Profiling module, allows sampling the parties' threads to find hotspots in a protocol's phases.
+#[cfg(feature = "profiling")]
+pub mod profiling;
+
 /// A `Party` that takes part in a protocol. The party will receive a unique `id` when it is running the protocol, as well as
 /// communication channels to and from all the other parties. A party keeps track of its own stats.
 pub trait Party {
@@ -26,15 +117,127 @@ pub trait Party {
         format!("Party {}", id)
     }
 
-    /// Runs the code for this party in the given protocol. The `id` starts from 0.
-    fn run(
-        &mut self,
+    /// Gets the compute slowdown factor of this party, e.g. to model a weak device participating alongside servers.
+    /// The durations measured by this party's timers and simulated by `Timings::simulate_compute` are scaled by this
+    /// factor, but the harness's "Total" timer is not. The factor must be finite and positive. By default, this is 1 (no
+    /// slowdown).
+    fn slowdown_factor(&self, _id: usize) -> f64 {
+        1.
+    }
+
+    /// Runs the code for this party in the given protocol, with the party's id, channels and timings in the
+    /// `context`.
+    fn run(&mut self, input: &Self::Input, context: &mut PartyContext) -> Self::Output;
+}
+
+/// The context in which a `Party` runs: its `id` (starting from 0), the number of parties, and its `channels` and
+/// `timings`.
+pub struct PartyContext<'a> {
+    id: usize,
+    n_parties: usize,
+    /// The communication channels to and from all the other parties.
+    pub channels: &'a mut Channels,
+    /// The statistics that this party keeps track of.
+    pub timings: &'a mut Timings,
+}
+
+impl<'a> PartyContext<'a> {
+    pub(crate) fn new(
         id: usize,
         n_parties: usize,
-        input: &Self::Input,
-        channels: &mut Channels,
-        timings: &mut Timings,
-    ) -> Self::Output;
+        channels: &'a mut Channels,
+        timings: &'a mut Timings,
+    ) -> Self {
+        PartyContext {
+            id,
+            n_parties,
+            channels,
+            timings,
+        }
+    }
+
+    /// Returns the id of this party, which starts from 0.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the number of parties in the protocol.
+    pub fn n_parties(&self) -> usize {
+        self.n_parties
+    }
+
+    /// Returns an iterator over the ids of all other parties, in increasing order.
+    pub fn others(&self) -> impl Iterator<Item = usize> {
+        let id = self.id;
+        (0..self.n_parties).filter(move |&other| other != id)
+    }
+
+    /// Returns whether this party is the leader, i.e. the party with id 0.
+    pub fn is_leader(&self) -> bool {
+        self.id == 0
+    }
+}
+
+/// The parameters of a `Protocol` that a sweep varies (see `Parameterized::Params`), e.g. the bit length, the
+/// threshold or the statistical security.
+pub trait Parameters: Debug {
+    /// Returns the name and value of each parameter, which are recorded as columns in the exports of a sweep.
+    fn columns(&self) -> Vec<(String, String)>;
+}
+
+impl Parameters for () {
+    fn columns(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+}
+
+/// A `Protocol` with parameters that a sweep can vary (see `Experiment::params`).
+pub trait Parameterized: Protocol + Sized {
+    /// The parameters that distinguish the parameterizations of the Protocol.
+    type Params: Parameters;
+
+    /// Returns the parameterization of the Protocol with the given `params`.
+    fn with_params(&self, params: &Self::Params) -> Self;
+}
+
+/// The output that each party of a protocol should produce, indexed by party id (see `Protocol::expected_outputs`).
+/// The outputs are compared with `PartialEq`, which only these outputs require of the parties' `Output` type.
+#[derive(Debug, Clone)]
+pub struct ExpectedOutputs<O> {
+    outputs: Vec<O>,
+    equals: fn(&O, &O) -> bool,
+}
+
+impl<O> ExpectedOutputs<O> {
+    /// Expects the party with each id to produce the output at that index of `outputs`.
+    pub fn new(outputs: Vec<O>) -> Self
+    where
+        O: PartialEq,
+    {
+        ExpectedOutputs {
+            outputs,
+            equals: O::eq,
+        }
+    }
+
+    /// Returns the expected outputs, indexed by party id.
+    pub fn outputs(&self) -> &[O] {
+        &self.outputs
+    }
+
+    /// Returns the ids of the parties whose `outputs` differ from the expected outputs, or `None` if there is not
+    /// one expected output per party.
+    pub(crate) fn mismatching_parties(&self, outputs: &[O]) -> Option<Vec<usize>> {
+        (outputs.len() == self.outputs.len()).then(|| {
+            outputs
+                .iter()
+                .zip(&self.outputs)
+                .enumerate()
+                .filter(|(_, (output, expected))| !(self.equals)(output, expected))
+                .map(|(id, _)| id)
+                .collect()
+        })
+    }
 }
 
 /// MPC protocols are described by the `Protocol` trait for a given `Party` type that can be sent accross threads. An implementation should hold the protocol-specific parameters.
@@ -48,10 +251,17 @@ where
     /// Sets up `n_parties` according to this parameterization of the Protocol.
     fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party>;
 
-    /// Generates each party's potentially random input for this parameterization of the Protocol.
-    fn generate_inputs(&self, n_parties: usize) -> Vec<<Self::Party as Party>::Input>;
+    /// Generates each party's potentially random input for this parameterization of the Protocol. All randomness
+    /// should be drawn from `rng`, which is seeded for each repetition (see `EvaluationSettings::with_seed`), so that
+    /// a repetition can be replayed from its seed (see `replay`).
+    fn generate_inputs(
+        &self,
+        n_parties: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<<Self::Party as Party>::Input>;
 
-    /// Validates the outputs of one run of the Protocol. If false, `evaluate` will print a warning.
+    /// Validates the outputs of one run of the Protocol. If false, `evaluate` returns a `ValidationError`, unless
+    /// invalid repetitions are recorded (see `EvaluationSettings::with_invalid_repetitions_recorded`).
     fn validate_outputs(
         &self,
         _inputs: &[<Self::Party as Party>::Input],
@@ -60,6 +270,30 @@ where
         true
     }
 
+    /// Verifies (or logs) the messages that the parties designated to be observed during one run of the Protocol,
+    /// in the order in which they were sent. This acts as a passive observer that joins after the run, and does not
+    /// affect the costs. If false, `evaluate` returns a `ValidationError`.
+    fn observe(&self, _transcript: &[ObservedMessage]) -> bool {
+        true
+    }
+
+    /// Computes the output that each party should produce for the `inputs` in the clear, indexed by party id. If
+    /// `Some`, the outputs of each run are compared against these expected outputs after `validate_outputs`, and
+    /// `evaluate` returns a `ValidationError` that identifies the parties whose outputs differ. Returns `None` by
+    /// default, which only validates the outputs with `validate_outputs`.
+    fn expected_outputs(
+        &self,
+        _inputs: &[<Self::Party as Party>::Input],
+    ) -> Option<ExpectedOutputs<<Self::Party as Party>::Output>> {
+        None
+    }
+
+    /// Returns the path of the dataset file that `generate_inputs` reads the inputs from (e.g. with a `Dataset`),
+    /// which is recorded in the statistics. Returns `None` by default, for synthetic inputs.
+    fn dataset(&self) -> Option<&str> {
+        None
+    }
+
     /// Evaluates multiple `repetitions` of the protocol with this parameterization of the Protocol.
     fn evaluate<N: NetworkDescription>(
         &self,
@@ -67,66 +301,130 @@ where
         n_parties: usize,
         network_description: &N,
         repetitions: usize,
-    ) -> AggregatedStats {
-        let mut parties = self.setup_parties(n_parties);
-        debug_assert_eq!(parties.len(), n_parties);
-
-        let mut stats = AggregatedStats::new(
+    ) -> Result<AggregatedStats, EvaluationError> {
+        self.evaluate_with_settings(
             experiment_name,
-            parties
-                .iter()
-                .enumerate()
-                .map(|(id, party)| party.get_name(id))
-                .collect(),
-        );
-
-        for _ in 0..repetitions {
-            let mut inputs = self.generate_inputs(n_parties);
-            debug_assert_eq!(inputs.len(), n_parties);
-
-            let mut channels = network_description.instantiate(n_parties);
-            debug_assert_eq!(channels.len(), n_parties);
-
-            let mut party_timings: Vec<Timings> = (0..n_parties).map(|_| Timings::new()).collect();
-
-            let outputs: Vec<_> = parties
-                .par_iter_mut()
-                .enumerate()
-                .zip(inputs.par_iter_mut())
-                .zip(channels.par_iter_mut())
-                .zip(party_timings.par_iter_mut())
-                .map(|((((id, party), input), channel), s)| {
-                    let total_timer = s.create_timer("Total");
-                    let output = party.run(id, n_parties, input, channel, s);
-                    s.stop_timer(total_timer);
-                    output
-                })
-                .collect();
-
-            if !self.validate_outputs(&inputs, &outputs) {
-                #[cfg(feature = "verbose")]
-                println!(
-                    "The outputs are invalid:\n{:?} ...for these parameters:\n{:?}",
-                    outputs, self
-                );
-                // TODO: Mark invalid in stats
-            }
+            n_parties,
+            network_description,
+            repetitions,
+            &EvaluationSettings::default(),
+        )
+    }
 
-            // TODO: Incorporate communication costs
-            stats.incorporate_party_stats(party_timings);
-        }
+    /// Evaluates multiple `repetitions` of the protocol like `evaluate`, using the given optional `settings`.
+    fn evaluate_with_settings<N: NetworkDescription>(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &N,
+        repetitions: usize,
+        settings: &EvaluationSettings,
+    ) -> Result<AggregatedStats, EvaluationError> {
+        evaluate_protocol(
+            self,
+            experiment_name,
+            n_parties,
+            network_description,
+            repetitions,
+            settings,
+        )
+    }
 
-        stats
+    /// Replays the repetition whose inputs were generated from the `seed` (see `AggregatedStats::seeds`) with
+    /// `n_parties` on the network, e.g. to reproduce a repetition that failed validation. Returns the statistics of
+    /// the replayed repetition together with the parties' outputs.
+    fn replay<N: NetworkDescription>(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &N,
+        seed: u64,
+    ) -> Result<(AggregatedStats, Outputs<Self>), EvaluationError> {
+        let settings = EvaluationSettings {
+            replayed_seed: Some(seed),
+            ..EvaluationSettings::default()
+        };
+        evaluate_protocol_with_outputs(
+            self,
+            experiment_name,
+            n_parties,
+            network_description,
+            1,
+            &settings,
+        )
     }
 }
 
+/// Runs the `protocol` once with `n_parties` on a `FullMesh` without communication overhead, and returns the
+/// statistics (named after the protocol's parameters) together with the parties' outputs. This is meant for quick
+/// experiments in tests and examples; use `Protocol::evaluate` to choose the network, the number of repetitions and
+/// the experiment name. Panics if the evaluation fails.
+pub fn run_local<P: Protocol>(
+    protocol: &P,
+    n_parties: usize,
+) -> (AggregatedStats, Vec<<P::Party as Party>::Output>) {
+    evaluate_protocol_with_outputs(
+        protocol,
+        format!("{:?}", protocol),
+        n_parties,
+        &FullMesh::new(),
+        1,
+        &EvaluationSettings::default(),
+    )
+    .unwrap_or_else(|error| panic!("the local run of {:?} failed: {}", protocol, error))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::time::{Duration, Instant};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use rayon::ThreadPoolBuilder;
+    use std::{
+        io::{Read, Write},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc::{channel, Sender},
+            Arc,
+        },
+        thread::{self, sleep, yield_now},
+        time::{Duration, Instant},
+    };
+
+    #[cfg(feature = "async")]
+    use crate::asynchronous::{AsyncNetwork, AsyncParty, AsyncPartyContext, AsyncProtocol};
+    #[cfg(feature = "compression")]
+    use crate::comm::Compression;
+    #[cfg(all(feature = "isolation", unix))]
+    use crate::isolation::{serve_if_party, Isolation};
+    #[cfg(all(feature = "netem", target_os = "linux"))]
+    use crate::netem::NetemNetwork;
+    #[cfg(feature = "quic")]
+    use crate::quic::QuicNetwork;
+    #[cfg(feature = "distributed")]
+    use crate::{
+        distributed::{run_worker, Controller},
+        scheduler::{run_machine, ParameterPoint, Scheduler},
+    };
 
     use crate::{
-        comm::{Channels, FullMesh},
-        Party, Protocol, Timings,
+        comm::{
+            BroadcastSemantics, ChannelBackend, Channels, EncryptionPackaging, FullMesh,
+            LinkTransport, Message, NetworkDescription, ObservedMessage, ReceivePolicy, Transport,
+            BARRIER_TAG, DEFAULT_TAG,
+        },
+        cost::{CloudCostModel, EnergyModel},
+        error::{ChannelOperation, CommError, EvaluationError, MergeError, RegressionError},
+        experiment::{Experiment, NetworkCondition},
+        monitoring::MetricsEndpoint,
+        pipeline::{Pipeline, Start},
+        regression::Baseline,
+        run_local,
+        settings::EvaluationSettings,
+        statistics::{AggregatedStats, Statistic},
+        suite::ExperimentSuite,
+        tcp::TcpNetwork,
+        testing::TestProtocol,
+        variants::{SecurityModel, Variants},
+        ExpectedOutputs, Parameterized, Parameters, Party, PartyContext, Protocol, Timings,
     };
 
     struct ExampleParty;
@@ -135,38 +433,32 @@ mod tests {
         type Input = usize;
         type Output = usize;
 
-        fn run(
-            &mut self,
-            id: usize,
-            n_parties: usize,
-            input: &Self::Input,
-            channels: &mut Channels,
-            stats: &mut Timings,
-        ) -> Self::Output {
-            println!("Hi! I am {}/{}", id, n_parties - 1);
+        fn run(&mut self, input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            println!("Hi! I am {}/{}", context.id(), context.n_parties() - 1);
 
-            let sending_timer = stats.create_timer("Sending");
-            for i in (id + 1)..n_parties {
-                channels.send(&vec![id as u8], &i);
+            let sending_timer = context.timings.create_timer("Sending");
+            for i in (context.id() + 1)..context.n_parties() {
+                context.channels.send(&[context.id() as u8], &i).unwrap();
             }
-            stats.stop_timer(sending_timer);
+            context.timings.stop_timer(sending_timer);
 
-            let receiving_timer = stats.create_timer("Receiving");
-            for j in 0..id {
+            let receiving_timer = context.timings.create_timer("Receiving");
+            for j in 0..context.id() {
                 println!(
                     "I am {}/{} and I received a message from {}",
-                    id,
-                    n_parties - 1,
-                    channels.receive(&j).collect::<Vec<_>>()[0]
+                    context.id(),
+                    context.n_parties() - 1,
+                    context.channels.receive(&j).unwrap().collect::<Vec<_>>()[0]
                 );
             }
-            stats.stop_timer(receiving_timer);
+            context.timings.stop_timer(receiving_timer);
 
-            id + input
+            context.id() + input
         }
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "distributed", derive(serde::Serialize, serde::Deserialize))]
     struct ExampleProtocol;
 
     impl Protocol for ExampleProtocol {
@@ -176,7 +468,7 @@ mod tests {
             (0..n_parties).map(|_| ExampleParty).collect()
         }
 
-        fn generate_inputs(&self, n_parties: usize) -> Vec<usize> {
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<usize> {
             (0..n_parties).map(|_| 10).collect()
         }
 
@@ -195,36 +487,3703 @@ mod tests {
         }
     }
 
+    #[test]
+    fn local_run_returns_outputs() {
+        let (stats, outputs) = run_local(&ExampleProtocol, 3);
+
+        assert_eq!(outputs, vec![10, 11, 12]);
+        assert_eq!(stats.name(), "ExampleProtocol");
+        stats.summarize_timings().print();
+    }
+
+    fn context_protocol() -> TestProtocol<(), (bool, Vec<usize>)> {
+        TestProtocol::new(|_, context| (context.is_leader(), context.others().collect()))
+    }
+
+    #[test]
+    fn context_lists_the_other_parties() {
+        let (_, outputs) = run_local(&context_protocol(), 3);
+
+        assert_eq!(
+            outputs,
+            vec![(true, vec![1, 2]), (false, vec![0, 2]), (false, vec![0, 1])]
+        );
+    }
+
     #[test]
     fn it_works() {
         let example = ExampleProtocol;
         let network = FullMesh::new();
-        let stats = example.evaluate("Experiment".to_string(), 5, &network, 1);
+        let stats = example
+            .evaluate("Experiment".to_string(), 5, &network, 1)
+            .unwrap();
 
         println!("stats: {:?}", stats);
-        // FIXME: All rows are aggregated instead of party-by-party
         stats.summarize_timings().print();
+    }
+
+    #[test]
+    fn summaries_render_as_markdown_and_latex() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (tables)".to_string(), 3, &network, 2)
+            .unwrap();
+        let summary = stats.summarize_timings();
+
+        let markdown = summary.to_markdown();
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("| Parties | "));
+        assert!(lines[1].starts_with("|---|"));
+        assert!(lines[2].starts_with("| Party 0 | "));
 
-        //stats.output_party_csv(3, "test.csv");
+        let latex = summary.to_latex();
+        assert!(latex.starts_with("\\begin{tabular}{l"));
+        assert!(latex.contains("\\toprule\nParties & "));
+        assert!(latex.contains("\\midrule\nParty 0 & "));
+        assert!(latex.contains("$\\pm$"));
+        assert!(latex.ends_with("\\bottomrule\n\\end{tabular}\n"));
     }
 
     #[test]
-    fn takes_longer() {
-        let example = ExampleProtocol;
+    fn summaries_scale_their_units() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (units)".to_string(), 3, &network, 2)
+            .unwrap();
 
-        let start = Instant::now();
+        // The parties only send a byte or two, within far less than a second
+        let markdown = stats.summarize_timings().to_markdown();
+        let party_0 = markdown.lines().nth(2).unwrap();
+        assert!(!party_0.contains(" s ["));
+        assert!(party_0.contains("µs") || party_0.contains("ms"));
+        assert!(party_0.ends_with("| 2.0 ± 0.0 B | 0.0 ± 0.0 B |"));
+    }
+
+    #[test]
+    fn costs_are_estimated_from_the_usage() {
         let network = FullMesh::new();
-        let _ = example.evaluate("Experiment".to_string(), 5, &network, 1);
-        let duration_1 = start.elapsed();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (cost)".to_string(), 3, &network, 2)
+            .unwrap();
 
-        let start = Instant::now();
-        let network = FullMesh::new_with_overhead(Duration::from_secs(1), 1.);
-        let stats = example.evaluate("Experiment (w/ overhead)".to_string(), 5, &network, 1);
-        let duration_2 = start.elapsed();
+        // Party 0 sends a byte to each other party, and party 1 sends a byte to party 2
+        let model = CloudCostModel::new()
+            .with_egress_price(1e9)
+            .with_region(0, "eu")
+            .with_region(1, "eu")
+            .with_region(2, "us")
+            .with_egress_price_between("eu", "eu", 0.);
+        let summary = stats.summarize_cost(&model);
+        summary.print();
+        assert_eq!(summary.party_costs(), [(1., 0.), (1., 0.), (0., 0.)]);
+        assert_eq!(summary.total_cost(), (2., 0.));
 
-        assert!(duration_2 > duration_1);
-        assert!(duration_2 > Duration::from_secs(5));
+        // Without an egress price, only the time is charged, which is at most the run time
+        let model = CloudCostModel::new().with_cpu_price(3600.);
+        let summary = stats.summarize_cost(&model);
+        let run_time: f64 = stats
+            .durations(0, "Total")
+            .iter()
+            .map(Duration::as_secs_f64)
+            .sum::<f64>()
+            / 2.;
+        assert!(summary.party_costs()[0].0 > 0.);
+        assert!(summary.party_costs()[0].0 <= run_time * (1. + 1e-9));
+    }
 
-        stats.summarize_timings().print();
+    #[test]
+    fn energy_is_estimated_from_the_usage() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (energy)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Every party sends and receives two bytes in total
+        let summary = stats.summarize_energy(&EnergyModel::new(0.5, 0.));
+        summary.print();
+        assert_eq!(summary.party_energy(), [(1., 0.), (1., 0.), (1., 0.)]);
+
+        let summary = stats.summarize_energy(&EnergyModel::new(0., 2.));
+        assert!(summary.party_energy().iter().all(|&(mean, _)| mean > 0.));
+    }
+
+    #[test]
+    fn pgfplots_files_are_written_for_each_metric() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (pgfplots)".to_string(), 3, &network, 3)
+            .unwrap();
+
+        let directory = std::env::temp_dir().join("mpc-bench-pgfplots-test");
+        let directory = directory.to_str().unwrap();
+        stats.summarize_timings().write_pgfplots(directory).unwrap();
+
+        let read = |filename: &str| {
+            std::fs::read_to_string(std::path::Path::new(directory).join(filename)).unwrap()
+        };
+        let dat = read("Total.dat");
+        let lines: Vec<&str> = dat.lines().collect();
+        assert_eq!(lines[0], "party mean stdev error");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[3].starts_with("2 "));
+
+        let tex = read("Total.tex");
+        assert!(tex.contains("xticklabels={{Party 0},{Party 1},{Party 2}}"));
+        assert!(tex.contains("table[x=party, y=mean, y error=error] {Total.dat};"));
+        assert!(read("Sent.dat").starts_with("party mean stdev error\n0 "));
+    }
+
+    #[test]
+    fn communication_matrix_shows_who_sent_to_whom() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (matrix)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Every party sends a one-byte message to each party with a higher id
+        let matrix = stats.summarize_communication();
+        matrix.print();
+        assert_eq!(
+            matrix.bytes(),
+            [vec![0., 1., 1.], vec![0., 0., 1.], vec![0., 0., 0.]]
+        );
+        assert_eq!(matrix.messages(), matrix.bytes());
+
+        let filename = std::env::temp_dir().join("mpc-bench-matrix-test.csv");
+        let filename = filename.to_str().unwrap();
+        matrix.write_csv(filename).unwrap();
+        let csv = std::fs::read_to_string(filename).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "from,to,bytes,messages");
+        assert_eq!(lines[2], "Party 0,Party 1,1,1");
+        assert_eq!(lines.len(), 10);
+    }
+
+    #[test]
+    fn criterion_estimates_are_written_for_each_timing() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (criterion)".to_string(), 3, &network, 4)
+            .unwrap();
+
+        let directory = std::env::temp_dir().join("mpc-bench-criterion-test");
+        stats.write_criterion(directory.to_str().unwrap()).unwrap();
+
+        let new = directory
+            .join("Experiment (criterion)")
+            .join("Total")
+            .join("new");
+        let read = |filename: &str| std::fs::read_to_string(new.join(filename)).unwrap();
+        assert!(read("benchmark.json").contains(r#""full_id":"Experiment (criterion)/Total""#));
+        assert!(read("sample.json")
+            .starts_with(r#"{"sampling_mode":"Flat","iters":[1.0,1.0,1.0,1.0],"#));
+
+        let estimates = read("estimates.json");
+        let mean_ns = stats
+            .slowest_durations("Total")
+            .iter()
+            .map(|duration| duration.as_secs_f64() * 1e9)
+            .sum::<f64>()
+            / 4.;
+        assert!(
+            estimates.starts_with(r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"#)
+        );
+        let point_estimate: f64 = estimates
+            .split(r#""point_estimate":"#)
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((point_estimate - mean_ns).abs() < 1e-6 * mean_ns);
+        assert!(estimates.contains(r#""slope":null"#));
+    }
+
+    #[test]
+    fn csv_covers_all_parties_and_metrics() {
+        let network = FullMesh::new();
+        let stats = invariant_protocol()
+            .evaluate("Experiment (csv)".to_string(), 2, &network, 3)
+            .unwrap();
+
+        let filename = std::env::temp_dir().join("mpc-bench-csv-test.csv");
+        stats.write_csv(filename.to_str().unwrap()).unwrap();
+
+        let mut reader = csv::Reader::from_path(&filename).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["repetition", "party", "metric", "value", "unit"]
+        );
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        let count = |metric: &str| records.iter().filter(|r| &r[2] == metric).count();
+        assert_eq!(count("makespan"), 3);
+        assert_eq!(count("timer:Total"), 6);
+        assert_eq!(count("sent:Party 1"), 6);
+        assert!(records.iter().any(|r| r[2].starts_with("counter:")));
+    }
+
+    fn invariant_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            context.timings.increment_counter("Triples", 2);
+            context.timings.increment_counter("Triples", 3);
+            context
+                .timings
+                .record_value("Gates", (10 * context.id()) as f64);
+            context.timings.record_value("Gates", 0.);
+            context.timings.assert_invariant("Always holds", true);
+            context
+                .timings
+                .assert_invariant("Even id", context.id().is_multiple_of(2));
+        })
+    }
+
+    #[test]
+    fn invariant_failures_are_counted() {
+        let network = FullMesh::new();
+        let stats = invariant_protocol()
+            .evaluate("Experiment (w/ invariants)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Party 1 violates the second invariant in both repetitions, without failing the experiment
+        let summary = stats.summarize_invariants();
+        assert_eq!(summary.invariant_names(), ["Always holds", "Even id"]);
+        assert_eq!(summary.passed(), [6, 4]);
+        assert_eq!(summary.failed(), [0, 2]);
+        assert_eq!(summary.failed_repetitions(), [0, 2]);
+        summary.print();
+    }
+
+    #[test]
+    fn metrics_are_aggregated() {
+        let network = FullMesh::new();
+        let stats = invariant_protocol()
+            .evaluate("Experiment (w/ metrics)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // The counters are summed per repetition, whereas every recorded value is a sample
+        let summary = stats.summarize_metrics();
+        assert_eq!(summary.metric_names(), ["Triples", "Gates"]);
+        assert_eq!(summary.means()[1], [Some(5.), Some(5.)]);
+        assert_eq!(summary.stdevs()[0], [Some(0.), Some(0.)]);
+        assert_eq!(summary.stdevs()[2], [Some(0.), Some(10.)]);
+        summary.print();
+    }
+
+    #[test]
+    fn stable_rendering_sorts_and_rounds() {
+        let mut stats = AggregatedStats::new(
+            "Experiment".to_string(),
+            vec!["Party 0".to_string(), "Party 1".to_string()],
+        );
+        for millis in [100, 300] {
+            let mut first = Timings::new();
+            first.write_duration("Total".to_string(), Duration::from_millis(millis));
+            first.write_duration("Sending".to_string(), Duration::from_millis(millis / 2));
+            let mut second = Timings::new();
+            second.write_duration("Total".to_string(), Duration::from_millis(2 * millis));
+            stats.incorporate_party_stats(vec![first, second]);
+        }
+
+        assert_eq!(
+            stats.summarize_timings().render_stable(2),
+            "Parties | Sending | Total\n\
+             Party 0 | 0.10 ± 0.05 | 0.20 ± 0.10\n\
+             Party 1 | - | 0.40 ± 0.20\n"
+        );
+    }
+
+    #[test]
+    fn percentiles_reveal_the_tail() {
+        let mut stats = AggregatedStats::new("Experiment".to_string(), vec!["Party 0".to_string()]);
+        for millis in [10, 20, 30, 40, 1000] {
+            let mut s = Timings::new();
+            s.write_duration("Total".to_string(), Duration::from_millis(millis));
+            stats.incorporate_party_stats(vec![s]);
+        }
+
+        let summary = stats.summarize_timings();
+        assert_eq!(summary.statistic(Statistic::Median), [[Some(0.03)]]);
+        assert_eq!(summary.statistic(Statistic::Min), [[Some(0.01)]]);
+        assert_eq!(summary.statistic(Statistic::Max), [[Some(1.)]]);
+        let p95 = summary.statistic(Statistic::P95)[0][0].unwrap();
+        assert!((p95 - 0.808).abs() < 1e-9);
+        summary.print_statistics(&[Statistic::Mean, Statistic::Median, Statistic::P95]);
+    }
+
+    #[test]
+    fn confidence_intervals_follow_the_t_distribution() {
+        let mut stats = AggregatedStats::new(
+            "Experiment".to_string(),
+            vec!["Party 0".to_string(), "Party 1".to_string()],
+        );
+        for seconds in [1, 2, 3] {
+            let mut first = Timings::new();
+            first.write_duration("Total".to_string(), Duration::from_secs(seconds));
+            let mut second = Timings::new();
+            if seconds == 1 {
+                second.write_duration("Total".to_string(), Duration::from_secs(seconds));
+            }
+            stats.incorporate_party_stats(vec![first, second]);
+        }
+
+        // With 2 degrees of freedom, the interval is 4.303 sample standard deviations (of 1 s) divided by sqrt(3)
+        let summary = stats.summarize_timings();
+        let (lower, upper) = summary.confidence_intervals()[0][0].unwrap();
+        assert!((lower - (2. - 4.303 / 3f64.sqrt())).abs() < 1e-9);
+        assert!((upper - (2. + 4.303 / 3f64.sqrt())).abs() < 1e-9);
+
+        // A single repetition does not determine an interval
+        assert_eq!(summary.confidence_intervals()[1][0], None);
+        summary.print();
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn profiler_writes_to_output_dir() {
+        use crate::profiling::ProfilerConfig;
+
+        let output_dir = std::env::temp_dir().join("mpc-bench-profiler-test");
+        let settings = EvaluationSettings::new().with_profiler(
+            ProfilerConfig::new(output_dir.to_str().unwrap()).with_repetitions(vec![1]),
+        );
+        ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (w/ profiler)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        assert!(output_dir.is_dir());
+    }
+
+    #[test]
+    fn tcp_network_delivers_messages() {
+        let example = ExampleProtocol;
+        let network = TcpNetwork::new();
+        example
+            .evaluate("Experiment (over TCP)".to_string(), 4, &network, 2)
+            .unwrap();
+    }
+
+    /// A transport that passes every message through its encoding and counts the delivered messages.
+    struct CountingTransport {
+        from_id: usize,
+        queue: Sender<Message>,
+        delivered: Arc<AtomicUsize>,
+    }
+
+    impl Transport for CountingTransport {
+        fn deliver(&self, message: Message) -> bool {
+            self.delivered.fetch_add(1, Ordering::Relaxed);
+            let decoded = Message::decode(&message.encode(), self.from_id).unwrap();
+            self.queue.send(decoded).is_ok()
+        }
+    }
+
+    struct CountingNetwork {
+        delivered: Arc<AtomicUsize>,
+    }
+
+    impl NetworkDescription for CountingNetwork {
+        fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
+            let (senders, receivers): (Vec<_>, Vec<_>) = (0..n_parties).map(|_| channel()).unzip();
+
+            receivers
+                .into_iter()
+                .enumerate()
+                .map(|(id, receiver)| {
+                    let transports = senders
+                        .iter()
+                        .map(|queue| {
+                            Box::new(CountingTransport {
+                                from_id: id,
+                                queue: queue.clone(),
+                                delivered: self.delivered.clone(),
+                            }) as Box<dyn Transport>
+                        })
+                        .collect();
+                    Channels::with_transports(
+                        id,
+                        transports,
+                        receiver,
+                        Duration::ZERO,
+                        Duration::ZERO,
+                    )
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn custom_transport_delivers_messages() {
+        let network = CountingNetwork {
+            delivered: Arc::default(),
+        };
+        let stats = ExampleProtocol
+            .evaluate("Experiment (custom transport)".to_string(), 4, &network, 2)
+            .unwrap();
+
+        assert_eq!(stats.durations(3, "Receiving").len(), 2);
+        assert_eq!(network.delivered.load(Ordering::Relaxed), 2 * 6);
+
+        // The transport encodes the messages, so it cannot carry a typed payload
+        let mut channels = network.instantiate(2);
+        assert_eq!(
+            channels[0].send_payload(7u64, &1),
+            Err(CommError::PayloadUnsupported {
+                from_id: 0,
+                to_id: 1
+            })
+        );
+    }
+
+    #[test]
+    fn channel_backends_deliver_messages() {
+        let backends = [
+            ChannelBackend::Std,
+            #[cfg(feature = "crossbeam")]
+            ChannelBackend::Crossbeam,
+            #[cfg(feature = "flume")]
+            ChannelBackend::Flume,
+        ];
+
+        for backend in backends {
+            // The outputs are validated, so each backend must have delivered every message
+            let network = FullMesh::new().with_channel_backend(backend);
+            ExampleProtocol
+                .evaluate(
+                    format!("Experiment ({:?} channels)", backend),
+                    4,
+                    &network,
+                    2,
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn real_links_are_not_simulated() {
+        // Parties 0 and 1 are servers that communicate over TCP, party 2 is a simulated client
+        let network = FullMesh::new_with_overhead(Duration::from_millis(100), 1000.)
+            .with_link_transports(|a, b| match (a, b) {
+                (0, 1) => LinkTransport::Tcp,
+                _ => LinkTransport::Simulated,
+            });
+        let stats = ExampleProtocol
+            .evaluate("Experiment (hybrid)".to_string(), 3, &network, 1)
+            .unwrap();
+
+        assert!(stats.durations(1, "Receiving")[0] < Duration::from_millis(100));
+        assert!(stats.durations(2, "Receiving")[0] >= Duration::from_millis(100));
+    }
+
+    #[cfg(feature = "quic")]
+    #[test]
+    fn quic_network_delivers_messages() {
+        let example = ExampleProtocol;
+        for network in [QuicNetwork::new(), QuicNetwork::new().with_zero_rtt()] {
+            example
+                .evaluate("Experiment (over QUIC)".to_string(), 4, &network, 2)
+                .unwrap();
+        }
+    }
+
+    #[cfg(all(feature = "netem", target_os = "linux"))]
+    #[test]
+    fn netem_matches_the_simulated_model() {
+        let network = NetemNetwork::new_with_overhead(Duration::from_millis(10), 125_000.);
+        assert_eq!(
+            network.netem_arguments(),
+            ["netem", "delay", "10000us", "rate", "1000000bit"]
+        );
+
+        let lossy = NetemNetwork::new(Duration::from_micros(500)).with_loss(0.01);
+        assert_eq!(
+            lossy.netem_arguments(),
+            ["netem", "delay", "500us", "loss", "1%"]
+        );
+    }
+
+    #[test]
+    fn tcp_network_reports_panicking_party() {
+        let network = TcpNetwork::new();
+        let result = panicking_protocol().evaluate(
+            "Experiment (panic over TCP)".to_string(),
+            3,
+            &network,
+            1,
+        );
+
+        assert!(matches!(
+            result,
+            Err(EvaluationError::PartyPanicked { party: 0, .. })
+        ));
+    }
+
+    #[cfg(feature = "distributed")]
+    #[test]
+    fn distributed_workers_run_the_protocol() {
+        let controller = Controller::bind("127.0.0.1:0", 3).unwrap();
+        let address = controller.local_addr().unwrap();
+
+        let workers: Vec<_> = (0..3)
+            .map(|_| thread::spawn(move || run_worker(&ExampleProtocol, address, "127.0.0.1:0")))
+            .collect();
+
+        let stats = controller
+            .evaluate(&ExampleProtocol, "Experiment (distributed)".to_string(), 2)
+            .unwrap();
+        assert_eq!(stats.durations(2, "Total").len(), 2);
+
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+    }
+
+    /// The entry points of the parties' processes in the isolation tests, which do nothing in a normal test run.
+    #[cfg(all(feature = "isolation", unix))]
+    #[test]
+    fn isolated_example_party() {
+        serve_if_party(&ExampleProtocol);
+    }
+
+    #[cfg(all(feature = "isolation", unix))]
+    #[test]
+    fn isolated_crashing_party() {
+        serve_if_party(&CrashingProtocol);
+    }
+
+    /// Runs the parties in the test entry point `entry`.
+    #[cfg(all(feature = "isolation", unix))]
+    fn isolation(entry: &str) -> Isolation {
+        Isolation::with_command(
+            std::env::current_exe().unwrap(),
+            [entry, "--exact", "--test-threads=1"],
+        )
+    }
+
+    #[cfg(all(feature = "isolation", unix))]
+    #[test]
+    fn isolated_parties_run_in_processes() {
+        let result = isolation("tests::isolated_example_party")
+            .evaluate(&ExampleProtocol, "Experiment (isolated)".to_string(), 3, 2)
+            .unwrap();
+
+        assert_eq!(result.stats.durations(2, "Total").len(), 2);
+        assert_eq!(result.usage.len(), 3);
+        assert!(result
+            .usage
+            .iter()
+            .all(|usage| usage.max_resident_bytes > 0));
+    }
+
+    #[cfg(feature = "distributed")]
+    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    struct CrashingProtocol;
+
+    #[cfg(feature = "distributed")]
+    struct CrashingParty;
+
+    #[cfg(feature = "distributed")]
+    impl Party for CrashingParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            // The other parties wait for a message that never arrives
+            if context.id() == 1 {
+                std::process::abort();
+            }
+            context.channels.receive(&1).unwrap().for_each(drop);
+        }
+    }
+
+    #[cfg(feature = "distributed")]
+    impl Protocol for CrashingProtocol {
+        type Party = CrashingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| CrashingParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[cfg(all(feature = "isolation", unix))]
+    #[test]
+    fn crashing_isolated_party_fails_the_experiment() {
+        let result = isolation("tests::isolated_crashing_party").evaluate(
+            &CrashingProtocol,
+            "Experiment (isolated crash)".to_string(),
+            3,
+            1,
+        );
+
+        assert!(matches!(result, Err(EvaluationError::Io(_))));
+    }
+
+    #[cfg(feature = "distributed")]
+    #[test]
+    fn scheduler_respects_machine_requirements() {
+        let scheduler = Scheduler::bind("127.0.0.1:0", 3).unwrap();
+        let address = scheduler.local_addr().unwrap();
+
+        let machines: Vec<_> = [&["gpu"][..], &["gpu"], &[]]
+            .into_iter()
+            .map(|labels| thread::spawn(move || run_machine::<ExampleProtocol, _>(address, labels)))
+            .collect();
+
+        let schedule = scheduler
+            .run(vec![
+                ParameterPoint::new("Two parties", ExampleProtocol, 2, 1),
+                ParameterPoint::new("Two parties (gpu)", ExampleProtocol, 2, 1)
+                    .with_requirement("gpu"),
+                ParameterPoint::new("Three parties", ExampleProtocol, 3, 2),
+                ParameterPoint::new("Unschedulable", ExampleProtocol, 2, 1).with_requirement("tpu"),
+            ])
+            .unwrap();
+        schedule.print_utilization();
+
+        let succeeded: Vec<bool> = schedule
+            .results
+            .iter()
+            .map(|(_, result)| result.is_ok())
+            .collect();
+        assert_eq!(succeeded, vec![true, true, true, false]);
+
+        // The machine without a gpu cannot run the gpu point
+        let parties_run: Vec<usize> = schedule
+            .utilization
+            .iter()
+            .map(|machine| machine.parties_run)
+            .collect();
+        assert_eq!(parties_run.iter().sum::<usize>(), 2 + 2 + 3);
+        assert!(parties_run[2] <= 2);
+
+        for machine in machines {
+            machine.join().unwrap().unwrap();
+        }
+    }
+
+    #[test]
+    fn takes_longer() {
+        let example = ExampleProtocol;
+
+        let start = Instant::now();
+        let network = FullMesh::new();
+        example
+            .evaluate("Experiment".to_string(), 5, &network, 1)
+            .unwrap();
+        let duration_1 = start.elapsed();
+
+        let start = Instant::now();
+        let network = FullMesh::new_with_overhead(Duration::from_secs(1), 1.);
+        let stats = example
+            .evaluate("Experiment (w/ overhead)".to_string(), 5, &network, 1)
+            .unwrap();
+        let duration_2 = start.elapsed();
+
+        assert!(duration_2 > duration_1);
+        assert!(duration_2 > Duration::from_secs(5));
+
+        stats.summarize_timings().print();
+    }
+
+    #[test]
+    fn message_delay_takes_longer() {
+        let example = ExampleProtocol;
+
+        let start = Instant::now();
+        let network = FullMesh::new()
+            .with_message_delay(|bytes, _, _| Duration::from_millis(200 * bytes as u64));
+        example
+            .evaluate("Experiment (w/ message delay)".to_string(), 5, &network, 1)
+            .unwrap();
+
+        assert!(start.elapsed() > Duration::from_millis(200));
+    }
+
+    #[test]
+    fn burst_is_faster() {
+        let example = ExampleProtocol;
+
+        let start = Instant::now();
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 1.).with_burst(10);
+        example
+            .evaluate("Experiment (w/ burst)".to_string(), 5, &network, 1)
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    fn ping_pong_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            // Both parties send two messages over the same connection, alternating directions
+            let other = 1 - context.id();
+            for round in 0..4 {
+                if round % 2 == context.id() {
+                    context.channels.send(&[round as u8], &other).unwrap();
+                } else {
+                    context.channels.receive(&other).unwrap().for_each(drop);
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn handshake_is_charged_once() {
+        // The parties wait for each other, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let start = Instant::now();
+        let network = FullMesh::new().with_handshake(Duration::from_millis(300));
+        pool.install(|| {
+            ping_pong_protocol()
+                .evaluate("Experiment (w/ handshake)".to_string(), 2, &network, 1)
+                .unwrap()
+        });
+
+        // Charging every message or every direction would take at least 600 ms
+        let elapsed = start.elapsed();
+        assert!(elapsed > Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(600));
+    }
+
+    fn broadcasting_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            if context.is_leader() {
+                context.channels.broadcast(&[0; 4]).unwrap();
+            } else {
+                context.channels.receive(&0).unwrap().for_each(drop);
+            }
+        })
+    }
+
+    struct EchoingParty {
+        semantics: BroadcastSemantics,
+    }
+
+    impl Party for EchoingParty {
+        type Input = ();
+        type Output = Vec<(usize, Vec<u8>)>;
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if context.id() != context.n_parties() - 1 {
+                context
+                    .channels
+                    .send(&[context.id() as u8], &(context.n_parties() - 1))
+                    .unwrap();
+                return vec![];
+            }
+
+            // The last party broadcasts before it receives the other parties' messages in arrival order
+            context.channels.broadcast(&[9; 4]).unwrap();
+            context.channels.checkpoint_comm("Broadcast");
+            let mut received = vec![];
+            if self.semantics == BroadcastSemantics::EchoToSelf {
+                received.push((
+                    context.id(),
+                    context.channels.receive(&context.id()).unwrap().collect(),
+                ));
+            }
+            for _ in 0..context.n_parties() - 1 {
+                let (from_id, message) = context.channels.receive_any().unwrap();
+                received.push((from_id, message.collect()));
+            }
+            received
+        }
+    }
+
+    #[derive(Debug)]
+    struct EchoingProtocol {
+        semantics: BroadcastSemantics,
+    }
+
+    impl Protocol for EchoingProtocol {
+        type Party = EchoingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|_| EchoingParty {
+                    semantics: self.semantics,
+                })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+
+        fn validate_outputs(&self, _inputs: &[()], outputs: &[Vec<(usize, Vec<u8>)>]) -> bool {
+            let mut expected = vec![(0, vec![0]), (1, vec![1])];
+            if self.semantics == BroadcastSemantics::EchoToSelf {
+                expected.insert(0, (2, vec![9; 4]));
+            }
+            outputs[2] == expected
+        }
+    }
+
+    #[test]
+    fn broadcast_semantics_determine_the_copy_to_oneself() {
+        for (semantics, sent_bytes) in [
+            (BroadcastSemantics::IncludeSelf, 12.),
+            (BroadcastSemantics::ExcludeSelf, 8.),
+            (BroadcastSemantics::EchoToSelf, 8.),
+        ] {
+            let network = FullMesh::new().with_broadcast_semantics(semantics);
+            let stats = EchoingProtocol { semantics }
+                .evaluate("Experiment (broadcast)".to_string(), 3, &network, 2)
+                .unwrap();
+
+            let summary = stats.summarize_checkpoints();
+            assert_eq!(summary.checkpoints()[0], (2, "Broadcast".to_string()));
+            assert_eq!(summary.sent_bytes()[0], sent_bytes);
+        }
+    }
+
+    fn dealing_protocol() -> TestProtocol<(), Option<Vec<u8>>> {
+        TestProtocol::new(|_, context| {
+            // The dealer sends to everyone but party 2
+            match context.id() {
+                0 => {
+                    context.channels.send_to_all_except(&[5; 10], &[2]).unwrap();
+                    context.channels.checkpoint_comm("Deal");
+                    None
+                }
+                2 => None,
+                _ => Some(context.channels.receive(&0).unwrap().collect()),
+            }
+        })
+        .with_validation(|_, outputs| {
+            outputs[1] == Some(vec![5; 10]) && outputs[2].is_none() && outputs[3] == outputs[1]
+        })
+    }
+
+    #[test]
+    fn sending_to_all_except_skips_the_excluded() {
+        let network = FullMesh::new();
+        let stats = dealing_protocol()
+            .evaluate("Experiment (dealing)".to_string(), 4, &network, 1)
+            .unwrap();
+
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.checkpoints()[0], (0, "Deal".to_string()));
+        assert_eq!(summary.sent_bytes()[0], 20.);
+    }
+
+    fn signaling_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            if context.is_leader() {
+                context.channels.send(&[0; 100], &1).unwrap();
+                context.channels.send_control(&[1], &1).unwrap();
+                context.channels.checkpoint_comm("Signal");
+            } else {
+                // The abort signal overtakes the message that is still being transferred
+                let message = context.channels.receive(&0).unwrap();
+                let timer = context.timings.create_timer("Signal");
+                let signal: Vec<u8> = context.channels.receive_control(&0).unwrap().collect();
+                context.timings.stop_timer(timer);
+                assert_eq!(signal, [1]);
+                assert!(context.channels.try_receive_control(&0).unwrap().is_none());
+                message.for_each(drop);
+            }
+        })
+    }
+
+    #[test]
+    fn control_messages_bypass_the_throughput_limit() {
+        let network = FullMesh::new_with_overhead(Duration::from_millis(10), 1000.);
+        let stats = signaling_protocol()
+            .evaluate("Experiment (control)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        // Transferring the message takes 100 ms, but the signal is only delayed by the latency
+        assert!(stats.durations(1, "Signal")[0] < Duration::from_millis(50));
+        assert!(stats.durations(1, "Total")[0] >= Duration::from_millis(100));
+
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.sent_bytes()[0], 101.);
+    }
+
+    fn peeking_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            if context.is_leader() {
+                for i in 1..=3 {
+                    context.channels.send(&[i], &1).unwrap();
+                }
+                context.channels.barrier().unwrap();
+            } else {
+                // The messages were sent before the barrier, so they are all pending afterwards
+                context.channels.barrier().unwrap();
+                assert_eq!(context.channels.pending_from(&0), 3);
+                assert_eq!(context.channels.peek(&0).as_deref(), Some(&[1][..]));
+
+                context.channels.receive(&0).unwrap().for_each(drop);
+                assert_eq!(context.channels.pending_from(&0), 2);
+                assert_eq!(context.channels.peek(&0).as_deref(), Some(&[2][..]));
+
+                context.channels.receive(&0).unwrap().for_each(drop);
+                context.channels.receive(&0).unwrap().for_each(drop);
+                assert_eq!(context.channels.pending_from(&0), 0);
+                assert_eq!(context.channels.peek(&0), None);
+            }
+        })
+    }
+
+    #[test]
+    fn peeking_does_not_consume_messages() {
+        // The parties wait for each other at the barrier, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let network = FullMesh::new();
+        pool.install(|| {
+            peeking_protocol()
+                .evaluate("Experiment (peeking)".to_string(), 2, &network, 1)
+                .unwrap()
+        });
+    }
+
+    fn payload_protocol() -> TestProtocol<(), Option<Vec<u64>>> {
+        TestProtocol::new(|_, context| {
+            if context.is_leader() {
+                context.channels.send_payload(vec![7u64; 100], &1).unwrap();
+                context.channels.checkpoint_comm("Payload");
+                context.channels.send_payload(7u32, &1).unwrap();
+                None
+            } else {
+                let payload = context.channels.receive_payload(&0).unwrap();
+                assert_eq!(
+                    context.channels.receive_payload::<u64>(&0),
+                    Err(CommError::UnexpectedPayload {
+                        id: 1,
+                        from_id: 0,
+                        expected: "u64",
+                    })
+                );
+                Some(payload)
+            }
+        })
+        .with_validation(|_, outputs| outputs[1] == Some(vec![7; 100]))
+    }
+
+    #[test]
+    fn payloads_are_charged_by_their_size() {
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 100_000.);
+        let stats = payload_protocol()
+            .evaluate("Experiment (payload)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        // The 100 integers take 800 bytes, which take 8 ms to transfer
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.sent_bytes()[0], 800.);
+        assert!(stats.durations(1, "Total")[0] >= Duration::from_millis(8));
+    }
+
+    struct SharingParty;
+
+    impl Party for SharingParty {
+        type Input = ();
+        type Output = Option<Arc<[u8]>>;
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if context.is_leader() {
+                context
+                    .channels
+                    .broadcast_shared(Arc::from(vec![7; 1000]))
+                    .unwrap();
+                None
+            } else {
+                Some(context.channels.receive(&0).unwrap().into_shared())
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct SharingProtocol;
+
+    impl Protocol for SharingProtocol {
+        type Party = SharingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| SharingParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+
+        fn validate_outputs(&self, _inputs: &[()], outputs: &[Option<Arc<[u8]>>]) -> bool {
+            // Every recipient received the very same buffer
+            let first = outputs[1].as_ref().unwrap();
+            first[..] == [7; 1000]
+                && outputs[2..]
+                    .iter()
+                    .all(|output| Arc::ptr_eq(first, output.as_ref().unwrap()))
+        }
+    }
+
+    #[test]
+    fn shared_broadcast_is_not_copied() {
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 100_000.);
+        let stats = SharingProtocol
+            .evaluate("Experiment (shared broadcast)".to_string(), 4, &network, 1)
+            .unwrap();
+
+        // Receiving the bytes at once still takes as long as transferring them
+        assert!(stats.durations(1, "Total")[0] >= Duration::from_millis(9));
+    }
+
+    fn streaming_protocol() -> TestProtocol<(), Vec<u8>> {
+        TestProtocol::new(|_, context| {
+            if context.is_leader() {
+                let mut writer = context.channels.send_stream(1);
+                for i in 0..200 {
+                    writer.write_all(&[i as u8; 1000]).unwrap();
+                }
+                writer.finish().unwrap();
+                context.channels.checkpoint_comm("Stream");
+                vec![]
+            } else {
+                let mut message = vec![];
+                context
+                    .channels
+                    .receive_stream(0)
+                    .read_to_end(&mut message)
+                    .unwrap();
+                message
+            }
+        })
+        .with_validation(|_, outputs| {
+            outputs[1].len() == 200_000
+                && outputs[1]
+                    .chunks(1000)
+                    .enumerate()
+                    .all(|(i, chunk)| chunk.iter().all(|&byte| byte == i as u8))
+        })
+    }
+
+    #[test]
+    fn streamed_messages_are_sent_in_chunks() {
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 10_000_000.);
+        let stats = streaming_protocol()
+            .evaluate("Experiment (streaming)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        // The chunks are transferred at the throughput of the link
+        assert!(stats.durations(1, "Total")[0] >= Duration::from_millis(19));
+
+        // The 200 kB are sent in four chunks, followed by an empty chunk that ends the message
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.checkpoints()[0], (0, "Stream".to_string()));
+        assert_eq!(summary.sent_bytes()[0], 200_000.);
+        assert_eq!(summary.sent_messages()[0], 5.);
+    }
+
+    #[test]
+    fn encryption_packaging_determines_broadcast_cost() {
+        let settings = EvaluationSettings::new().with_snapshot_interval(Duration::from_millis(10));
+
+        let mut totals = vec![];
+        for packaging in [
+            EncryptionPackaging::PerRecipient,
+            EncryptionPackaging::Broadcast,
+        ] {
+            let network =
+                FullMesh::new().with_encryption(packaging, Duration::from_millis(100), 16);
+            let stats = broadcasting_protocol()
+                .evaluate_with_settings(
+                    "Experiment (w/ encryption)".to_string(),
+                    4,
+                    &network,
+                    1,
+                    &settings,
+                )
+                .unwrap();
+
+            // Every ciphertext carries the overhead, but the copy to oneself is not encrypted
+            let last = stats.snapshots()[0]
+                .iter()
+                .rev()
+                .find(|snapshot| snapshot.party == 0)
+                .unwrap();
+            assert_eq!(last.sent_bytes, 3 * (4 + 16) + 4);
+
+            totals.push(stats.durations(0, "Total")[0]);
+        }
+
+        // Encrypting per recipient takes 300 ms, whereas encrypting once takes 100 ms
+        assert!(totals[0] >= Duration::from_millis(300));
+        assert!(totals[1] >= Duration::from_millis(100));
+        assert!(totals[1] < Duration::from_millis(300));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_messages_are_transferred_faster() {
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 100_000.)
+            .with_compression(Compression::Deflate { level: 6 });
+        let stats = SharingProtocol
+            .evaluate("Experiment (w/ compression)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        // The 1000 equal bytes (sent to both parties) compress well, so transferring them takes far less than 10 ms
+        let summary = stats.summarize_compression();
+        assert_eq!(summary.mean_uncompressed_bytes()[0], 2000.);
+        assert!(summary.mean_compressed_bytes()[0] < 200.);
+        assert_eq!(summary.ratios()[1], None);
+        assert!(stats.durations(1, "Total")[0] < Duration::from_millis(9));
+        summary.print();
+    }
+
+    #[test]
+    fn encryption_throughput_charges_each_byte() {
+        let network = FullMesh::new()
+            .with_encryption(EncryptionPackaging::PerRecipient, Duration::ZERO, 16)
+            .with_encryption_throughput(100_000.);
+        let stats = SharingProtocol
+            .evaluate("Experiment (w/ AEAD)".to_string(), 4, &network, 1)
+            .unwrap();
+
+        // Encrypting the 1000 bytes for each of the 3 recipients takes 10 ms each
+        let total = stats.durations(0, "Total")[0];
+        assert!(total >= Duration::from_millis(30));
+        assert!(total < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn received_bytes_are_tracked_per_peer() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Every party sends one byte to each party with a higher id
+        assert_eq!(stats.sent_bytes(0), vec![vec![0, 1, 1]; 2]);
+        assert_eq!(stats.received_bytes(0), vec![vec![0, 0, 0]; 2]);
+        assert_eq!(stats.sent_bytes(2), vec![vec![0, 0, 0]; 2]);
+        assert_eq!(stats.received_bytes(2), vec![vec![1, 1, 0]; 2]);
+    }
+
+    #[test]
+    fn timing_summary_includes_bandwidth() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let summary = stats.summarize_timings();
+        assert_eq!(summary.sent_bytes(), [(2., 0.), (1., 0.), (0., 0.)]);
+        assert_eq!(summary.received_bytes(), [(0., 0.), (1., 0.), (2., 0.)]);
+        assert_eq!(summary.peer_sent_bytes()[0], [(0., 0.), (1., 0.), (1., 0.)]);
+        summary.print();
+    }
+
+    #[test]
+    fn sent_bytes_are_attributed_to_running_timers() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Party 0 only sends while its "Sending" timer runs, which the "Total" timer also spans
+        let summary = stats.summarize_timings();
+        assert_eq!(
+            summary.phase_sent_bytes()[0],
+            [Some(2.), Some(0.), Some(2.)]
+        );
+        summary.print();
+    }
+
+    fn phased_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            let setup = context.timings.create_timer("Offline/Setup");
+            context.timings.stop_timer(setup);
+
+            let online = context.timings.create_timer("Online");
+            for round in ["Round 1", "Round 2"] {
+                let timer = context.timings.create_nested_timer(&online, round);
+                context.timings.stop_timer(timer);
+            }
+            context.timings.stop_timer(online);
+        })
+    }
+
+    #[test]
+    fn nested_timers_form_a_hierarchy() {
+        let network = FullMesh::new();
+        let stats = phased_protocol()
+            .evaluate("Experiment (phases)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        // The unmeasured "Offline" groups its nested timer, and "Online" is stopped after its nested timers
+        let summary = stats.summarize_timings();
+        assert_eq!(
+            summary.hierarchy(),
+            [
+                (0, "Offline".to_string()),
+                (1, "Offline/Setup".to_string()),
+                (0, "Online".to_string()),
+                (1, "Online/Round 1".to_string()),
+                (1, "Online/Round 2".to_string()),
+                (0, "Total".to_string()),
+            ]
+        );
+        summary.print_hierarchy();
+    }
+
+    struct EarlyReturningParty;
+
+    impl EarlyReturningParty {
+        fn online(&mut self, context: &mut PartyContext) -> Result<u8, CommError> {
+            let id = context.id();
+            let mut timings = context.timings.scoped_timer("Online");
+            timings.simulate_compute(Duration::from_millis(10));
+            if id == 0 {
+                return Err(CommError::Closed { id });
+            }
+            let _round = timings.scoped_timer("Online/Round 1");
+            Ok(1)
+        }
+    }
+
+    impl Party for EarlyReturningParty {
+        type Input = ();
+        type Output = Option<u8>;
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            let sum = context.timings.time("Offline", || (0..100u32).sum::<u32>());
+            assert_eq!(sum, 4950);
+            self.online(context).ok()
+        }
+    }
+
+    #[derive(Debug)]
+    struct EarlyReturningProtocol;
+
+    impl Protocol for EarlyReturningProtocol {
+        type Party = EarlyReturningParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| EarlyReturningParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[test]
+    fn scoped_timers_stop_on_early_returns() {
+        let network = FullMesh::new();
+        let stats = EarlyReturningProtocol
+            .evaluate("Experiment (scoped timers)".to_string(), 2, &network, 1)
+            .unwrap();
+
+        for id in 0..2 {
+            assert_eq!(stats.durations(id, "Offline").len(), 1);
+            assert!(stats.durations(id, "Online")[0] >= Duration::from_millis(10));
+        }
+
+        // Party 0 returned before its nested timer was created
+        assert!(stats.durations(0, "Online/Round 1").is_empty());
+        assert_eq!(stats.durations(1, "Online/Round 1").len(), 1);
+    }
+
+    #[test]
+    fn makespan_spans_the_slowest_party() {
+        let network = FullMesh::new_with_overhead(Duration::from_millis(10), 1000.);
+        let stats = ExampleProtocol
+            .evaluate("Experiment (makespan)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        assert_eq!(stats.makespans().len(), 2);
+        for (repetition, &makespan) in stats.makespans().iter().enumerate() {
+            for id in 0..3 {
+                assert!(makespan >= stats.durations(id, "Total")[repetition]);
+            }
+        }
+
+        let (mean, _) = stats.summarize_timings().makespan().unwrap();
+        assert!(mean >= 0.01);
+    }
+
+    #[cfg(all(feature = "cpu-time", unix))]
+    struct BusyParty;
+
+    #[cfg(all(feature = "cpu-time", unix))]
+    impl Party for BusyParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            context.timings.time("Computing", || {
+                let start = Instant::now();
+                while start.elapsed() < Duration::from_millis(50) {}
+            });
+            context.timings.time("Sleeping", || {
+                sleep(Duration::from_millis(50));
+            });
+        }
+    }
+
+    #[cfg(all(feature = "cpu-time", unix))]
+    #[derive(Debug)]
+    struct BusyProtocol;
+
+    #[cfg(all(feature = "cpu-time", unix))]
+    impl Protocol for BusyProtocol {
+        type Party = BusyParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| BusyParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[cfg(all(feature = "cpu-time", unix))]
+    #[test]
+    fn cpu_time_excludes_sleeping() {
+        let network = FullMesh::new();
+        let stats = BusyProtocol
+            .evaluate("Experiment (CPU time)".to_string(), 1, &network, 1)
+            .unwrap();
+
+        // Busy waiting consumes CPU time, while sleeping only takes wall time
+        assert!(stats.cpu_durations(0, "Computing")[0] >= Duration::from_millis(25));
+        assert!(stats.durations(0, "Sleeping")[0] >= Duration::from_millis(50));
+        assert!(stats.cpu_durations(0, "Sleeping")[0] < Duration::from_millis(25));
+
+        let summary = stats.summarize_timings();
+        assert!(summary.cpu_means()[0].iter().all(Option::is_some));
+        summary.print();
+    }
+
+    #[test]
+    fn raw_repetitions_are_retained() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (raw data)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let repetitions: Vec<_> = stats.repetitions().collect();
+        assert_eq!(repetitions.len(), 2);
+        for (index, repetition) in repetitions.iter().enumerate() {
+            assert_eq!(repetition.index(), index);
+            assert_eq!(repetition.makespan(), Some(stats.makespans()[index]));
+            assert_eq!(repetition.parties().len(), 3);
+            for (id, party) in repetition.parties().iter().enumerate() {
+                assert!(party
+                    .measured_durations()
+                    .iter()
+                    .any(|(name, _)| name == "Total"));
+                assert_eq!(party.sent_bytes(), stats.sent_bytes(id)[index]);
+            }
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn saved_stats_can_be_loaded() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (persistence)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let filename = std::env::temp_dir().join("mpc-bench-persistence-test.json");
+        let filename = filename.to_str().unwrap();
+        stats.save(filename).unwrap();
+        let loaded = AggregatedStats::load(filename).unwrap();
+
+        assert_eq!(loaded.name(), stats.name());
+        assert_eq!(loaded.makespans(), stats.makespans());
+        for id in 0..3 {
+            assert_eq!(loaded.durations(id, "Total"), stats.durations(id, "Total"));
+            assert_eq!(loaded.sent_bytes(id), stats.sent_bytes(id));
+        }
+        loaded.summarize_timings().print();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn checkpoints_resume_completed_repetitions() {
+        let network = FullMesh::new();
+        let filename = std::env::temp_dir().join("mpc-bench-checkpoint-test.json");
+        let _ = std::fs::remove_file(&filename);
+        let settings = EvaluationSettings::new().with_checkpoint(filename.to_str().unwrap());
+
+        let interrupted = ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (checkpoint)".to_string(),
+                3,
+                &network,
+                2,
+                &settings,
+            )
+            .unwrap();
+        assert!(filename.exists());
+
+        // The resumed run only adds the missing repetition
+        let resumed = ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (checkpoint)".to_string(),
+                3,
+                &network,
+                3,
+                &settings,
+            )
+            .unwrap();
+        assert_eq!(resumed.makespans().len(), 3);
+        assert_eq!(&resumed.makespans()[..2], interrupted.makespans());
+        assert_eq!(
+            &resumed.durations(0, "Total")[..2],
+            interrupted.durations(0, "Total")
+        );
+
+        // A checkpoint of a different experiment is rejected
+        assert!(ExampleProtocol
+            .evaluate_with_settings("Other experiment".to_string(), 3, &network, 3, &settings)
+            .is_err());
+
+        std::fs::remove_file(filename).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn experiments_resume_from_their_checkpoints() {
+        let directory = std::env::temp_dir().join("mpc-bench-resume-test");
+        let _ = std::fs::remove_dir_all(&directory);
+        let experiment = || {
+            Experiment::new("Resumable")
+                .protocol(ExampleProtocol)
+                .parties([2, 3])
+                .repetitions(2)
+                .resume(directory.to_str().unwrap())
+        };
+
+        let first = experiment().run().unwrap();
+        assert!(directory.join("Resumable (2 parties).json").exists());
+        assert!(directory.join("Resumable (3 parties).json").exists());
+
+        // Every evaluation was complete, so nothing is measured again
+        let second = experiment().run().unwrap();
+        for n_parties in [2, 3] {
+            assert_eq!(
+                second.stats(n_parties).unwrap().makespans(),
+                first.stats(n_parties).unwrap().makespans()
+            );
+        }
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn cli_runs_registered_protocols() {
+        use crate::cli::{run_with_args, Registry, RunConfig};
+        use crate::error::CliError;
+
+        let directory = std::env::temp_dir().join("mpc-bench-cli-test");
+        std::fs::create_dir_all(&directory).unwrap();
+        let config = directory.join("config.json");
+        std::fs::write(
+            &config,
+            format!(
+                r#"{{"name": "CLI", "protocol": "example", "parties": [2, 3], "latencies_ms": [1], "output": {:?}}}"#,
+                directory.join("results").to_str().unwrap()
+            ),
+        )
+        .unwrap();
+
+        let registry = Registry::new().with_protocol("example", ExampleProtocol);
+        assert_eq!(registry.protocol_names(), ["example"]);
+        let loaded = RunConfig::load(config.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.repetitions, 1);
+        assert_eq!(
+            loaded.conditions(),
+            [NetworkCondition::unlimited(Duration::from_millis(1))]
+        );
+
+        run_with_args(&registry, ["mpc-bench", "run", config.to_str().unwrap()]).unwrap();
+        let results = directory.join("results");
+        assert!(results.join("CLI.csv").exists());
+        let a = results.join("CLI (2 parties, 1ms latency, unlimited).json");
+        let b = results.join("CLI (3 parties, 1ms latency, unlimited).json");
+        run_with_args(&registry, ["mpc-bench", "report", a.to_str().unwrap()]).unwrap();
+        run_with_args(
+            &registry,
+            [
+                "mpc-bench",
+                "compare",
+                a.to_str().unwrap(),
+                b.to_str().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // Configurations can only refer to registered protocols
+        let error = run_with_args(
+            &Registry::new(),
+            ["mpc-bench", "run", config.to_str().unwrap()],
+        );
+        assert!(matches!(error, Err(CliError::UnknownProtocol { .. })));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn json_documents_the_experiment() {
+        let network = FullMesh::new();
+        let stats = ExampleProtocol
+            .evaluate("Experiment (json)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let json = stats.to_json();
+        assert_eq!(json["experiment"], "Experiment (json)");
+        assert_eq!(json["parameters"], "ExampleProtocol");
+        assert_eq!(
+            json["network"],
+            "FullMesh (latency: 0ns, throughput: unlimited)"
+        );
+        assert_eq!(json["repetitions"].as_array().unwrap().len(), 2);
+        assert_eq!(json["repetitions"][1]["parties"][2]["name"], "Party 2");
+        assert!(json["repetitions"][0]["parties"][0]["durations"]["Total"].is_f64());
+        assert_eq!(
+            json["summary"]["timings"][0]["parties"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(json["summary"]["makespan"]["mean"].is_f64());
+    }
+
+    #[cfg(feature = "plots")]
+    #[test]
+    fn results_can_be_plotted() {
+        let network = FullMesh::new();
+        let stats: Vec<_> = [2, 3]
+            .map(|n_parties| {
+                ExampleProtocol
+                    .evaluate("Experiment (plots)".to_string(), n_parties, &network, 2)
+                    .unwrap()
+            })
+            .into();
+
+        let directory = std::env::temp_dir();
+        let lines = directory.join("mpc-bench-plots-test-parties.svg");
+        let lines = lines.to_str().unwrap();
+        plots::plot_runtime_vs_parties(&[(2, &stats[0]), (3, &stats[1])], "Total", lines).unwrap();
+        let bars = directory.join("mpc-bench-plots-test-phases.png");
+        let bars = bars.to_str().unwrap();
+        plots::plot_phases(&stats[1], bars).unwrap();
+
+        assert!(std::fs::read_to_string(lines).unwrap().starts_with("<svg"));
+        assert!(std::fs::metadata(bars).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn merged_stats_combine_the_repetitions() {
+        let network = FullMesh::new();
+        let evaluate = |name: &str, repetitions| {
+            ExampleProtocol
+                .evaluate(name.to_string(), 3, &network, repetitions)
+                .unwrap()
+        };
+
+        let mut stats = evaluate("Experiment (first run)", 2);
+        stats.merge(evaluate("Experiment (second run)", 3)).unwrap();
+        assert_eq!(stats.name(), "Experiment (first run)");
+        assert_eq!(stats.durations(0, "Total").len(), 5);
+        assert_eq!(stats.makespans().len(), 5);
+
+        // Statistics of other parties cannot be merged
+        let other = ExampleProtocol
+            .evaluate("Experiment (other parties)".to_string(), 2, &network, 1)
+            .unwrap();
+        assert!(matches!(
+            stats.merge(other),
+            Err(MergeError::PartyNames { .. })
+        ));
+
+        // Statistics with other timings cannot be merged either
+        let mut other = AggregatedStats::new(
+            "Experiment (other timings)".to_string(),
+            (0..3).map(|id| format!("Party {}", id)).collect(),
+        );
+        other.incorporate_party_stats((0..3).map(|_| Timings::new()).collect());
+        assert!(matches!(
+            stats.merge(other),
+            Err(MergeError::TimingNames { .. })
+        ));
+    }
+
+    #[test]
+    fn nat_traffic_is_relayed() {
+        let example = ExampleProtocol;
+        let network = FullMesh::new().with_nat_relay(&[1], Duration::from_millis(10));
+        let stats = example
+            .evaluate("Experiment (w/ NAT relay)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        // Party 0 only relays its message to party 1, whereas party 1 relays everything
+        assert_eq!(stats.mean_relayed_bytes(), vec![1., 1., 0.]);
+    }
+
+    #[test]
+    fn mtu_fragments_messages() {
+        // Party 0 sends 1 byte and party 1 sends 5 bytes, which is split into 3 fragments
+        let start = Instant::now();
+        let network = FullMesh::new().with_mtu(2, Duration::from_millis(100));
+        let stats = padded_protocol()
+            .evaluate("Experiment (w/ MTU)".to_string(), 3, &network, 1)
+            .unwrap();
+
+        let summary = stats.summarize_fragments();
+        assert_eq!(summary.mean_fragments()[0], Some(1.));
+        assert_eq!(summary.max_fragments()[1], Some(3));
+        assert_eq!(summary.max_fragments()[2], None);
+        summary.print();
+
+        // Each fragment adds its own delay
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    #[should_panic(expected = "the MTU must be at least one byte")]
+    fn zero_mtu_is_rejected() {
+        FullMesh::new().with_mtu(0, Duration::from_millis(10));
+    }
+
+    fn checkpoint_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            // Every party sends 2 bytes to each later party, then passes 3 bytes on to the next party in two messages
+            for j in (context.id() + 1)..context.n_parties() {
+                context.channels.send(&[0; 2], &j).unwrap();
+            }
+            for j in 0..context.id() {
+                context.channels.receive(&j).unwrap().for_each(drop);
+            }
+            context.channels.checkpoint_comm("Sharing");
+
+            if context.id() + 1 < context.n_parties() {
+                context.channels.send(&[0; 1], &(context.id() + 1)).unwrap();
+                context.channels.send(&[0; 2], &(context.id() + 1)).unwrap();
+            }
+            if context.id() > 0 {
+                context
+                    .channels
+                    .receive(&(context.id() - 1))
+                    .unwrap()
+                    .for_each(drop);
+                context
+                    .channels
+                    .receive(&(context.id() - 1))
+                    .unwrap()
+                    .for_each(drop);
+            }
+            context.channels.checkpoint_comm("Passing");
+        })
+        .with_validation(|_, _outputs| true)
+    }
+
+    #[test]
+    fn checkpoints_attribute_communication() {
+        let network = FullMesh::new();
+        let stats = checkpoint_protocol()
+            .evaluate("Experiment (w/ checkpoints)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.checkpoints().len(), 6);
+        assert_eq!(summary.checkpoints()[1], (0, "Passing".to_string()));
+
+        assert_eq!(summary.sent_bytes()[0], 4.);
+        assert_eq!(summary.sent_bytes()[1], 3.);
+        assert_eq!(summary.sent_messages()[1], 2.);
+        assert_eq!(summary.received_messages()[2], 1.);
+        assert_eq!(summary.received_bytes()[3], 3.);
+        assert_eq!(summary.received_messages()[3], 2.);
+        summary.print();
+    }
+
+    #[test]
+    fn pipeline_chains_outputs() {
+        let pipeline = Start::new("First", ExampleProtocol).then(
+            "Second",
+            ExampleProtocol,
+            |outputs: Vec<usize>| outputs.into_iter().map(|_| 10).collect(),
+        );
+        let network = FullMesh::new();
+        let stats = pipeline
+            .evaluate("Experiment (pipeline)".to_string(), 3, &network, 2)
+            .unwrap();
+
+        assert_eq!(stats.stages().len(), 2);
+        assert_eq!(stats.stages()[1].name(), "Experiment (pipeline) / Second");
+        assert_eq!(stats.end_to_end().len(), 2);
+        stats.print();
+    }
+
+    #[derive(Debug)]
+    struct CountingProtocol {
+        setups: Arc<AtomicUsize>,
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl Protocol for CountingProtocol {
+        type Party = ExampleParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            self.setups.fetch_add(1, Ordering::Relaxed);
+            ExampleProtocol.setup_parties(n_parties)
+        }
+
+        fn generate_inputs(&self, n_parties: usize, rng: &mut impl Rng) -> Vec<usize> {
+            self.runs.fetch_add(1, Ordering::Relaxed);
+            ExampleProtocol.generate_inputs(n_parties, rng)
+        }
+    }
+
+    #[test]
+    fn warmup_repetitions_are_not_measured() {
+        let protocol = CountingProtocol {
+            setups: Arc::new(AtomicUsize::new(0)),
+            runs: Arc::new(AtomicUsize::new(0)),
+        };
+        let settings = EvaluationSettings::new().with_warmup(2);
+        let stats = protocol
+            .evaluate_with_settings(
+                "Experiment (w/ warmup)".to_string(),
+                3,
+                &FullMesh::new(),
+                3,
+                &settings,
+            )
+            .unwrap();
+
+        assert_eq!(protocol.runs.load(Ordering::Relaxed), 5);
+        assert_eq!(stats.durations(0, "Total").len(), 3);
+        assert_eq!(stats.makespans().len(), 3);
+    }
+
+    fn random_input_protocol() -> TestProtocol<u64, u64> {
+        TestProtocol::new(|input, _| *input)
+            .with_inputs(|n_parties, rng| (0..n_parties).map(|_| rng.gen()).collect())
+            .with_validation(|inputs, outputs| inputs == outputs)
+    }
+
+    #[test]
+    fn repetitions_are_replayed_from_their_seeds() {
+        let evaluate = |seed| {
+            let settings = EvaluationSettings::new().with_warmup(1).with_seed(seed);
+            random_input_protocol()
+                .evaluate_with_settings(
+                    "Experiment (seeds)".to_string(),
+                    3,
+                    &FullMesh::new(),
+                    3,
+                    &settings,
+                )
+                .unwrap()
+        };
+
+        // The same master seed derives the same seed for each repetition
+        let stats = evaluate(42);
+        let seeds = stats.seeds().to_vec();
+        assert_eq!(seeds.len(), 3);
+        assert!(seeds[0] != seeds[1] && seeds[1] != seeds[2]);
+        assert_eq!(evaluate(42).seeds(), seeds);
+        assert_ne!(evaluate(43).seeds(), seeds);
+        assert_eq!(stats.repetitions().nth(1).unwrap().seed(), Some(seeds[1]));
+
+        let (replayed, outputs) = random_input_protocol()
+            .replay(
+                "Experiment (replay)".to_string(),
+                3,
+                &FullMesh::new(),
+                seeds[1],
+            )
+            .unwrap();
+        assert_eq!(replayed.seeds(), [seeds[1]]);
+        assert_eq!(
+            outputs,
+            random_input_protocol().generate_inputs(3, &mut StdRng::seed_from_u64(seeds[1]))
+        );
+    }
+
+    #[test]
+    fn repetitions_run_concurrently_within_the_core_budget() {
+        let protocol = CountingProtocol {
+            setups: Arc::new(AtomicUsize::new(0)),
+            runs: Arc::new(AtomicUsize::new(0)),
+        };
+        let network = FullMesh::new_with_overhead(Duration::from_millis(20), 1e9);
+
+        // Two repetitions with three parties fit in six cores
+        let settings = EvaluationSettings::new().with_parallel_repetitions(6);
+        let start = Instant::now();
+        let stats = protocol
+            .evaluate_with_settings(
+                "Experiment (parallel repetitions)".to_string(),
+                3,
+                &network,
+                4,
+                &settings,
+            )
+            .unwrap();
+        let elapsed = start.elapsed();
+
+        assert_eq!(protocol.setups.load(Ordering::Relaxed), 2);
+        assert_eq!(protocol.runs.load(Ordering::Relaxed), 4);
+        assert_eq!(stats.makespans().len(), 4);
+        assert_eq!(stats.durations(2, "Total").len(), 4);
+        assert!(stats
+            .makespans()
+            .iter()
+            .all(|&makespan| makespan >= Duration::from_millis(20)));
+
+        // Sequential repetitions would take at least as long as all makespans together
+        assert!(elapsed < stats.makespans().iter().sum::<Duration>());
+    }
+
+    #[test]
+    fn message_sizes_are_counted_in_buckets() {
+        let settings = EvaluationSettings::new().with_message_size_histogram(vec![0, 1, 64]);
+        let stats = ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (message sizes)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        // Every message of the example protocol is a single byte
+        let summary = stats.summarize_message_sizes();
+        summary.print();
+        assert_eq!(summary.labels(), ["<=0", "<=1", "<=64", ">64"]);
+        assert_eq!(summary.mean_counts()[0], [0., 2., 0., 0.]);
+        assert_eq!(summary.mean_counts()[2], [0., 0., 0., 0.]);
+
+        let filename = std::env::temp_dir().join("mpc-bench-message-sizes-test.csv");
+        let filename = filename.to_str().unwrap();
+        stats.write_csv(filename).unwrap();
+        let csv = std::fs::read_to_string(filename).unwrap();
+        assert!(csv.contains("0,Party 1,message_size:<=1,1,messages"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the bucket bounds must be increasing")]
+    fn decreasing_message_size_buckets_are_rejected() {
+        EvaluationSettings::new().with_message_size_histogram(vec![64, 1]);
+    }
+
+    #[test]
+    fn traces_show_timers_and_messages() {
+        let settings = EvaluationSettings::new().with_tracing();
+        let stats = ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (tracing)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        let filename = std::env::temp_dir().join("mpc-bench-trace-test.json");
+        let filename = filename.to_str().unwrap();
+        stats.write_chrome_trace(filename).unwrap();
+        let trace = std::fs::read_to_string(filename).unwrap();
+
+        assert!(trace.starts_with("{\"traceEvents\":["));
+        assert!(trace.contains(r#""args":{"name":"Repetition 1"}"#));
+        assert!(trace.contains(r#""pid":1,"tid":2,"args":{"name":"Party 2"}"#));
+        assert_eq!(
+            trace
+                .matches(r#"{"name":"Total","cat":"timer","ph":"X""#)
+                .count(),
+            6
+        );
+        // Party 0 sends to parties 1 and 2, and party 1 sends to party 2
+        assert_eq!(trace.matches(r#""name":"Send to Party 2""#).count(), 4);
+        assert_eq!(trace.matches(r#""name":"Receive from Party 0""#).count(), 4);
+    }
+
+    #[test]
+    fn message_flow_can_be_diagrammed() {
+        let settings = EvaluationSettings::new().with_tracing();
+        let stats = ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (diagrams)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        let mermaid = stats.to_mermaid(Some(0));
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!(lines[0], "sequenceDiagram");
+        assert_eq!(lines[1], "    participant P0 as Party 0");
+        assert_eq!(lines.len(), 4 + 3);
+        assert!(lines[4..]
+            .iter()
+            .any(|line| line.starts_with("    P1->>P2: 1 B @ ")));
+        assert!(!mermaid.contains("Note over"));
+        assert_eq!(stats.to_mermaid(None).matches("Note over P0,P2").count(), 2);
+
+        let dot = stats.to_dot(None);
+        assert!(dot.starts_with("digraph \"Experiment (diagrams)\" {\n"));
+        assert!(dot.contains("    p2 [label=\"Party 2\"];"));
+        assert_eq!(dot.matches(" -> ").count(), 6);
+        assert!(dot.contains("[label=\"repetition 1, #3: 1 B @ "));
+    }
+
+    #[test]
+    fn metrics_endpoint_reports_progress() {
+        let endpoint = MetricsEndpoint::bind("127.0.0.1:0").unwrap();
+        let settings = EvaluationSettings::new()
+            .with_warmup(1)
+            .with_metrics_endpoint(endpoint.clone());
+        ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (metrics)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        let scrape = |path: &str| {
+            let mut stream = std::net::TcpStream::connect(endpoint.local_addr()).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = scrape("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        // The warmup repetition is not counted, and party 0 sends one byte to each of the others per repetition
+        assert!(response.contains("\nmpc_bench_repetitions_completed_total 2\n"));
+        assert!(
+            response.contains("\nmpc_bench_repetition{experiment=\"Experiment (metrics)\"} 1\n")
+        );
+        assert!(response.contains(
+            "\nmpc_bench_last_duration_seconds{experiment=\"Experiment (metrics)\",party=\"Party 2\",timing=\"Total\"} "
+        ));
+        assert!(response.contains("\nmpc_bench_sent_bytes_total{party=\"Party 0\"} 4\n"));
+        assert!(scrape("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[cfg(feature = "dashboard")]
+    #[test]
+    fn dashboard_shows_the_progress_of_each_party() {
+        use crate::dashboard::Dashboard;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let terminal = || Terminal::new(TestBackend::new(120, 12)).unwrap();
+        let dashboard = Dashboard::with_terminal(terminal(), Duration::from_millis(10));
+        let settings = EvaluationSettings::new()
+            .with_warmup(1)
+            .with_dashboard(dashboard.clone());
+        ExampleProtocol
+            .evaluate_with_settings(
+                "Experiment (dashboard)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        let mut terminal = terminal();
+        dashboard.draw_on(&mut terminal).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Experiment (dashboard), repetition 1"));
+        assert!(screen.contains("2/2"));
+        assert!(screen.contains("Party 2"));
+        // Party 0 sent one byte to each of the others in the last repetition
+        assert!(screen.contains("2.0 B"));
+    }
+
+    #[test]
+    fn repetitions_continue_until_the_precision_target() {
+        let evaluate = |relative_width| {
+            let settings =
+                EvaluationSettings::new().with_precision_target("Total", relative_width, 5);
+            ExampleProtocol
+                .evaluate_with_settings(
+                    "Experiment (w/ precision target)".to_string(),
+                    3,
+                    &FullMesh::new(),
+                    0,
+                    &settings,
+                )
+                .unwrap()
+        };
+
+        // Any interval is narrow enough, but it takes two repetitions to determine one
+        assert_eq!(evaluate(f64::INFINITY).makespans().len(), 2);
+
+        // An interval without any width cannot be reached, so the maximum number of repetitions is run
+        assert_eq!(evaluate(0.).makespans().len(), 5);
+    }
+
+    #[test]
+    fn pipeline_sets_up_parties_once_and_uses_settings() {
+        let setups = Arc::new(AtomicUsize::new(0));
+        let counting = || CountingProtocol {
+            setups: setups.clone(),
+            runs: Arc::default(),
+        };
+        let pipeline =
+            Start::new("First", counting())
+                .then("Second", counting(), |outputs: Vec<usize>| outputs);
+        let network = FullMesh::new();
+        let settings = EvaluationSettings::new()
+            .with_round_padding()
+            .with_snapshot_interval(Duration::from_millis(20));
+        let stats = pipeline
+            .evaluate_with_settings(
+                "Experiment (pipeline w/ settings)".to_string(),
+                3,
+                &network,
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        assert_eq!(setups.load(Ordering::Relaxed), 2);
+        for stage in stats.stages() {
+            assert_eq!(stage.snapshots().len(), 2);
+        }
+    }
+
+    fn gather_protocol() -> TestProtocol<(), Vec<usize>> {
+        TestProtocol::new(|_, context| {
+            // The last party gathers the messages, so that this also works when the parties run sequentially
+            if context.id() != context.n_parties() - 1 {
+                context
+                    .channels
+                    .send(&[context.id() as u8], &(context.n_parties() - 1))
+                    .unwrap();
+                return vec![];
+            }
+
+            let mut senders = vec![];
+            while senders.len() < context.n_parties() - 1 {
+                match context.channels.next_pending_sender() {
+                    Some(from_id) => {
+                        assert_eq!(
+                            context.channels.receive(&from_id).unwrap().next(),
+                            Some(from_id as u8)
+                        );
+                        senders.push(from_id);
+                    }
+                    None => yield_now(),
+                }
+            }
+            senders
+        })
+        .with_validation(|_, outputs| {
+            let mut senders = outputs[outputs.len() - 1].clone();
+            senders.sort();
+            senders == (0..outputs.len() - 1).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn receive_policies_service_all_parties() {
+        for policy in [
+            ReceivePolicy::Fifo,
+            ReceivePolicy::RoundRobin,
+            ReceivePolicy::Random(42),
+        ] {
+            let network = FullMesh::new().with_receive_policy(policy);
+            gather_protocol()
+                .evaluate("Experiment (receive policy)".to_string(), 4, &network, 2)
+                .unwrap();
+        }
+    }
+
+    fn aggregating_protocol() -> TestProtocol<(), Vec<usize>> {
+        TestProtocol::new(|_, context| {
+            if context.id() != context.n_parties() - 1 {
+                context
+                    .channels
+                    .send(&[context.id() as u8], &(context.n_parties() - 1))
+                    .unwrap();
+                return vec![];
+            }
+
+            (0..context.n_parties() - 1)
+                .map(|_| {
+                    let (from_id, mut message) = context.channels.receive_any().unwrap();
+                    assert_eq!(message.next(), Some(from_id as u8));
+                    from_id
+                })
+                .collect()
+        })
+        .with_validation(|_, outputs| {
+            let mut senders = outputs[outputs.len() - 1].clone();
+            senders.sort();
+            senders == (0..outputs.len() - 1).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn receive_any_returns_messages_from_all_parties() {
+        let network = FullMesh::new_with_overhead(Duration::from_millis(10), 1000.);
+        aggregating_protocol()
+            .evaluate("Experiment (receive any)".to_string(), 4, &network, 2)
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "because it is the only party")]
+    fn receive_any_requires_other_parties() {
+        FullMesh::new().instantiate(1)[0].receive_any().unwrap();
+    }
+
+    #[test]
+    fn comm_errors_are_returned_to_the_party() {
+        let mut channels = FullMesh::new().instantiate(3);
+        let third = channels.pop().unwrap();
+        drop(third);
+
+        // Sending to a party that dropped out fails, but a broadcast still reaches the others
+        assert_eq!(
+            channels[0].send(&[1], &2),
+            Err(CommError::Disconnected {
+                from_id: 0,
+                to_id: 2
+            })
+        );
+        assert!(channels[0].broadcast(&[2]).is_err());
+        assert_eq!(channels[1].receive(&0).unwrap().collect::<Vec<_>>(), [2]);
+
+        // Once a party failed, receiving fails instead of waiting forever
+        channels[1].abort();
+        assert_eq!(
+            channels[0].receive(&1).err(),
+            Some(CommError::Closed { id: 0 })
+        );
+    }
+
+    fn collective_protocol() -> TestProtocol<(), Vec<(usize, Vec<u8>)>> {
+        TestProtocol::new(|_, context| {
+            // Every party scatters to the others, and the last party gathers what was sent to it
+            if context.id() != context.n_parties() - 1 {
+                context
+                    .channels
+                    .scatter(
+                        (0..context.n_parties())
+                            .map(|to_id| vec![context.id() as u8; to_id])
+                            .collect(),
+                    )
+                    .unwrap();
+                context.channels.checkpoint_comm("Scatter");
+                return vec![];
+            }
+
+            let gathered = context
+                .channels
+                .gather()
+                .unwrap()
+                .into_iter()
+                .map(|(from_id, message)| (from_id, message.collect()))
+                .collect();
+            context.channels.checkpoint_comm("Gather");
+            gathered
+        })
+        .with_validation(|_, outputs| {
+            let n_parties = outputs.len();
+            outputs[n_parties - 1]
+                == (0..n_parties - 1)
+                    .map(|from_id| (from_id, vec![from_id as u8; n_parties - 1]))
+                    .collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn gather_and_scatter_charge_each_party() {
+        let network = FullMesh::new();
+        let stats = collective_protocol()
+            .evaluate("Experiment (collectives)".to_string(), 4, &network, 1)
+            .unwrap();
+
+        // Every party sends `to_id` bytes to the party with `to_id`, but nothing to itself
+        let summary = stats.summarize_checkpoints();
+        assert_eq!(summary.sent_bytes()[..3], [6., 5., 4.]);
+        assert_eq!(summary.sent_messages()[..3], [3., 3., 3.]);
+        assert_eq!(summary.checkpoints()[3], (3, "Gather".to_string()));
+        assert_eq!(summary.received_bytes()[3], 9.);
+        assert_eq!(summary.received_messages()[3], 3.);
+    }
+
+    fn tagging_protocol() -> TestProtocol<(), Vec<Vec<u8>>> {
+        TestProtocol::new(|_, context| {
+            // Two interleaved sub-protocols (tags 1 and 2) share the link with untagged messages
+            if context.is_leader() {
+                context.channels.send_tagged(b"a", &1, 1).unwrap();
+                context.channels.send_tagged(b"b", &1, 2).unwrap();
+                context.channels.send_tagged(b"c", &1, 1).unwrap();
+                context.channels.send(b"d", &1).unwrap();
+                assert_eq!(
+                    context.channels.send_tagged(b"e", &1, BARRIER_TAG),
+                    Err(CommError::ReservedTag { tag: BARRIER_TAG })
+                );
+                return vec![];
+            }
+
+            vec![
+                context.channels.receive_tagged(&0, 2).unwrap().collect(),
+                context.channels.receive(&0).unwrap().collect(),
+                context.channels.receive_tagged(&0, 1).unwrap().collect(),
+                context.channels.receive_tagged(&0, 1).unwrap().collect(),
+            ]
+        })
+        .with_validation(|_, outputs| outputs[1] == [b"b", b"d", b"a", b"c"])
+    }
+
+    #[test]
+    fn tagged_messages_are_received_by_tag() {
+        tagging_protocol()
+            .evaluate("Experiment (tags)".to_string(), 2, &FullMesh::new(), 2)
+            .unwrap();
+        tagging_protocol()
+            .evaluate(
+                "Experiment (tags over TCP)".to_string(),
+                2,
+                &TcpNetwork::new(),
+                1,
+            )
+            .unwrap();
+    }
+
+    fn composed_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            // The outer protocol runs a sub-protocol in session 7 between its own messages
+            if context.is_leader() {
+                context.channels.send(&[0; 10], &1).unwrap();
+                let mut session = context.channels.subsession(7).unwrap();
+                session.send(&[1; 3], &1).unwrap();
+                session.send(&[2; 4], &1).unwrap();
+                context.channels.send(&[3; 10], &1).unwrap();
+            } else {
+                assert_eq!(
+                    context.channels.subsession(DEFAULT_TAG).err(),
+                    Some(CommError::ReservedTag { tag: DEFAULT_TAG })
+                );
+                let mut session = context.channels.subsession(7).unwrap();
+                assert_eq!(session.receive(&0).unwrap().collect::<Vec<_>>(), [1; 3]);
+                assert_eq!(session.receive(&0).unwrap().collect::<Vec<_>>(), [2; 4]);
+                context.channels.receive(&0).unwrap().for_each(drop);
+                context.channels.receive(&0).unwrap().for_each(drop);
+            }
+        })
+    }
+
+    #[test]
+    fn subsessions_are_tracked_separately() {
+        let stats = composed_protocol()
+            .evaluate("Experiment (sessions)".to_string(), 2, &FullMesh::new(), 2)
+            .unwrap();
+
+        let summary = stats.summarize_sessions();
+        assert_eq!(
+            summary.checkpoints(),
+            [(0, "7".to_string()), (1, "7".to_string())]
+        );
+        assert_eq!(summary.sent_bytes(), [7., 0.]);
+        assert_eq!(summary.sent_messages(), [2., 0.]);
+        assert_eq!(summary.received_bytes(), [0., 7.]);
+        assert_eq!(summary.received_messages(), [0., 2.]);
+        summary.print();
+    }
+
+    fn staggered_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            // The parties reach the barrier at different times, but leave it together
+            sleep(Duration::from_millis(100 * context.id() as u64));
+            context.channels.barrier().unwrap();
+            let timer = context.timings.create_timer("After barrier");
+            context.channels.barrier().unwrap();
+            context.timings.stop_timer(timer);
+        })
+    }
+
+    #[test]
+    fn barrier_waits_for_all_parties() {
+        // The parties wait for each other, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+
+        let network = FullMesh::new_with_overhead(Duration::from_millis(50), 1000.);
+        let stats = pool.install(|| {
+            staggered_protocol()
+                .evaluate("Experiment (barrier)".to_string(), 3, &network, 1)
+                .unwrap()
+        });
+
+        for id in 0..3 {
+            // The first party waits for the last one, which starts 200 ms later
+            let total = stats.durations(id, "Total")[0];
+            assert!(total >= Duration::from_millis(300));
+            assert!(total < Duration::from_millis(500));
+
+            // A barrier that all parties reach together only takes the latency
+            let after = stats.durations(id, "After barrier")[0];
+            assert!(after >= Duration::from_millis(50));
+            assert!(after < Duration::from_millis(150));
+        }
+    }
+
+    struct DeadlockingParty {
+        cyclic: bool,
+        split: bool,
+    }
+
+    impl Party for DeadlockingParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if self.cyclic && self.split && context.is_leader() {
+                // The first party waits in a split receiver, without any thread left that could send
+                let (sender, mut receiver) = context.channels.split();
+                drop(sender);
+                receiver.receive(&1).unwrap();
+            } else if self.cyclic {
+                // Every party waits for the next one before sending anything
+                context
+                    .channels
+                    .receive(&((context.id() + 1) % context.n_parties()))
+                    .unwrap();
+            } else if context.id() > 0 {
+                // The first party never sends the message
+                context.channels.receive(&0).unwrap();
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct DeadlockingProtocol {
+        cyclic: bool,
+        split: bool,
+    }
+
+    impl Protocol for DeadlockingProtocol {
+        type Party = DeadlockingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|_| DeadlockingParty {
+                    cyclic: self.cyclic,
+                    split: self.split,
+                })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[test]
+    fn deadlocks_are_detected() {
+        // The parties wait for each other, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+
+        for split in [false, true] {
+            let error = pool.install(|| {
+                DeadlockingProtocol {
+                    cyclic: true,
+                    split,
+                }
+                .evaluate("Experiment (deadlock)".to_string(), 3, &FullMesh::new(), 1)
+                .unwrap_err()
+            });
+            assert!(matches!(
+                &error,
+                EvaluationError::Deadlock { waits, finished }
+                    if waits == &[(0, Some(1)), (1, Some(2)), (2, Some(0))] && finished.is_empty()
+            ));
+            assert_eq!(
+                error.to_string(),
+                "the parties deadlocked: party 0 waits for party 1, party 1 waits for party 2, party 2 waits for party 0"
+            );
+        }
+
+        let error = DeadlockingProtocol {
+            cyclic: false,
+            split: false,
+        }
+        .evaluate("Experiment (deadlock)".to_string(), 2, &FullMesh::new(), 1)
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the parties deadlocked: party 1 waits for party 0, which finished"
+        );
+    }
+
+    struct StallingParty {
+        stalls: usize,
+    }
+
+    impl Party for StallingParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if context.id() == 0 {
+                context.channels.receive(&1).unwrap();
+            } else {
+                // The first party waits while this party stalls, which is not a deadlock
+                if self.stalls > 0 {
+                    self.stalls -= 1;
+                    context.timings.simulate_compute(Duration::from_secs(10));
+                }
+                let _ = context.channels.send(&[1], &0);
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct StallingProtocol {
+        stalls: usize,
+    }
+
+    impl Protocol for StallingProtocol {
+        type Party = StallingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|_| StallingParty {
+                    stalls: self.stalls,
+                })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[test]
+    fn repetitions_that_exceed_the_timeout_are_aborted() {
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let settings =
+            EvaluationSettings::new().with_repetition_timeout(Duration::from_millis(100));
+
+        // Only the first repetition stalls, and the evaluation continues after it without sitting out the stall
+        let start_time = Instant::now();
+        let stats = pool.install(|| {
+            StallingProtocol { stalls: 1 }
+                .evaluate_with_settings(
+                    "Experiment (timeout)".to_string(),
+                    2,
+                    &FullMesh::new(),
+                    3,
+                    &settings,
+                )
+                .unwrap()
+        });
+        assert!(start_time.elapsed() < Duration::from_secs(5));
+        assert_eq!(stats.timed_out_repetitions(), 1);
+        assert_eq!(stats.makespans().len(), 2);
+        assert_eq!(stats.summarize_timings().timed_out_repetitions(), 1);
+
+        // A warmup repetition that timed out is ignored
+        let stats = pool.install(|| {
+            StallingProtocol { stalls: 1 }
+                .evaluate_with_settings(
+                    "Experiment (timeout)".to_string(),
+                    2,
+                    &FullMesh::new(),
+                    1,
+                    &settings.clone().with_warmup(1),
+                )
+                .unwrap()
+        });
+        assert_eq!(stats.timed_out_repetitions(), 0);
+        assert_eq!(stats.makespans().len(), 1);
+    }
+
+    fn worker_protocol() -> TestProtocol<(), u8> {
+        TestProtocol::new(|_, context| {
+            if context.id() == 1 {
+                let sum = context.channels.receive(&0).unwrap().next().unwrap()
+                    + context.channels.receive(&0).unwrap().next().unwrap();
+                context.channels.send(&[sum], &0).unwrap();
+                return sum;
+            }
+
+            // Two worker threads send while the main thread waits for the reply
+            let (sender, mut receiver) = context.channels.split();
+            thread::scope(|scope| {
+                for worker in 1..=2 {
+                    let sender = sender.clone();
+                    scope.spawn(move || sender.send(&[worker], &1).unwrap());
+                }
+                receiver.receive(&1).unwrap().next().unwrap()
+            })
+        })
+        .with_validation(|_, outputs| outputs == [3, 3])
+    }
+
+    #[test]
+    fn split_channels_send_from_worker_threads() {
+        // The parties wait for each other, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let network = FullMesh::new_with_overhead(Duration::from_millis(10), 1000.);
+        pool.install(|| {
+            worker_protocol()
+                .evaluate("Experiment (split)".to_string(), 2, &network, 2)
+                .unwrap()
+        });
+    }
+
+    fn polling_protocol() -> TestProtocol<(), (Vec<usize>, bool)> {
+        TestProtocol::new(|_, context| {
+            if context.id() != context.n_parties() - 1 {
+                context
+                    .channels
+                    .send(&[context.id() as u8], &(context.n_parties() - 1))
+                    .unwrap();
+                return (vec![], false);
+            }
+
+            // The messages are still in flight when the last party starts polling
+            let polled_early = (0..context.n_parties() - 1)
+                .all(|from_id| context.channels.try_receive(&from_id).unwrap().is_none());
+
+            let mut senders = vec![];
+            while senders.len() < context.n_parties() - 1 {
+                for from_id in 0..context.n_parties() - 1 {
+                    if let Some(mut message) = context.channels.try_receive(&from_id).unwrap() {
+                        assert_eq!(message.next(), Some(from_id as u8));
+                        senders.push(from_id);
+                    }
+                }
+                yield_now();
+            }
+            (senders, polled_early)
+        })
+        .with_validation(|_, outputs| {
+            let (mut senders, polled_early) = outputs[outputs.len() - 1].clone();
+            senders.sort();
+            polled_early && senders == (0..outputs.len() - 1).collect::<Vec<_>>()
+        })
+    }
+
+    #[test]
+    fn try_receive_respects_arrival_times() {
+        let network = FullMesh::new_with_overhead(Duration::from_millis(50), 1000.);
+        polling_protocol()
+            .evaluate("Experiment (polling)".to_string(), 4, &network, 2)
+            .unwrap();
+    }
+
+    fn relay_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| {
+            // Party 1 sends to party 2 while it is receiving from party 0
+            match context.id() {
+                0 => context.channels.send(&[0; 100], &1).unwrap(),
+                1 => {
+                    context.channels.send(&[1; 100], &2).unwrap();
+                    context.channels.receive(&0).unwrap().for_each(drop);
+                }
+                _ => context.channels.receive(&1).unwrap().for_each(drop),
+            }
+        })
+    }
+
+    #[test]
+    fn half_duplex_serializes_sending_and_receiving() {
+        let start = Instant::now();
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 1000.);
+        relay_protocol()
+            .evaluate("Experiment (full-duplex)".to_string(), 3, &network, 1)
+            .unwrap();
+        let full_duplex = start.elapsed();
+
+        let start = Instant::now();
+        let network = FullMesh::new_with_overhead(Duration::ZERO, 1000.).with_half_duplex();
+        relay_protocol()
+            .evaluate("Experiment (half-duplex)".to_string(), 3, &network, 1)
+            .unwrap();
+        let half_duplex = start.elapsed();
+
+        // Sending 100 bytes takes 100 ms, which half-duplex adds before party 1 can receive
+        assert!(half_duplex > full_duplex + Duration::from_millis(50));
+        assert!(half_duplex > Duration::from_millis(200));
+    }
+
+    #[derive(Debug)]
+    struct InvalidProtocol;
+
+    impl Protocol for InvalidProtocol {
+        type Party = ExampleParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| ExampleParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<usize> {
+            (0..n_parties).map(|_| 10).collect()
+        }
+
+        fn validate_outputs(&self, _inputs: &[usize], _outputs: &[usize]) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn invalid_outputs_are_reported() {
+        let network = FullMesh::new();
+        let result = InvalidProtocol.evaluate("Experiment (invalid)".to_string(), 3, &network, 2);
+
+        match result {
+            Err(EvaluationError::Validation(error)) => assert_eq!(error.repetition, 0),
+            _ => panic!("expected a validation error"),
+        }
+    }
+
+    struct EchoParty;
+
+    impl Party for EchoParty {
+        type Input = u64;
+        type Output = u64;
+
+        fn run(&mut self, input: &Self::Input, _context: &mut PartyContext) -> Self::Output {
+            *input
+        }
+    }
+
+    #[derive(Debug)]
+    struct GroundTruthProtocol {
+        /// The ids of the parties whose expected output is computed incorrectly.
+        wrong_parties: Vec<usize>,
+        /// The number of parties at the end whose expected output is left out.
+        missing_outputs: usize,
+    }
+
+    impl Protocol for GroundTruthProtocol {
+        type Party = EchoParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| EchoParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, rng: &mut impl Rng) -> Vec<u64> {
+            random_input_protocol().generate_inputs(n_parties, rng)
+        }
+
+        fn expected_outputs(&self, inputs: &[u64]) -> Option<ExpectedOutputs<u64>> {
+            let outputs = inputs[..inputs.len() - self.missing_outputs]
+                .iter()
+                .enumerate()
+                .map(|(id, &input)| {
+                    if self.wrong_parties.contains(&id) {
+                        input.wrapping_add(1)
+                    } else {
+                        input
+                    }
+                })
+                .collect();
+            Some(ExpectedOutputs::new(outputs))
+        }
+    }
+
+    #[test]
+    fn outputs_are_compared_against_the_expected_outputs() {
+        let network = FullMesh::new();
+        let protocol = GroundTruthProtocol {
+            wrong_parties: vec![],
+            missing_outputs: 0,
+        };
+        assert!(protocol
+            .evaluate("Experiment (expected)".to_string(), 4, &network, 2)
+            .is_ok());
+
+        let protocol = GroundTruthProtocol {
+            wrong_parties: vec![1, 3],
+            missing_outputs: 0,
+        };
+        match protocol.evaluate("Experiment (unexpected)".to_string(), 4, &network, 2) {
+            Err(EvaluationError::Validation(error)) => {
+                assert_eq!(error.repetition, 0);
+                assert_eq!(error.mismatching_parties, [1, 3]);
+                assert!(error.to_string().contains("parties 1, 3"));
+            }
+            _ => panic!("expected a validation error"),
+        }
+
+        // Expected outputs that do not cover every party are rejected instead of compared
+        let protocol = GroundTruthProtocol {
+            wrong_parties: vec![],
+            missing_outputs: 1,
+        };
+        match protocol.evaluate("Experiment (incomplete)".to_string(), 4, &network, 2) {
+            Err(EvaluationError::Validation(error)) => {
+                assert!(error.mismatching_parties.is_empty());
+                assert!(error.to_string().contains("3 outputs for 4 parties"));
+            }
+            _ => panic!("expected a validation error"),
+        }
+    }
+
+    #[derive(Debug)]
+    struct FlakyProtocol {
+        validations: AtomicUsize,
+    }
+
+    impl Protocol for FlakyProtocol {
+        type Party = ExampleParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            ExampleProtocol.setup_parties(n_parties)
+        }
+
+        fn generate_inputs(&self, n_parties: usize, rng: &mut impl Rng) -> Vec<usize> {
+            ExampleProtocol.generate_inputs(n_parties, rng)
+        }
+
+        fn validate_outputs(&self, _inputs: &[usize], _outputs: &[usize]) -> bool {
+            // Every second repetition is rejected
+            self.validations
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(2)
+        }
+    }
+
+    #[test]
+    fn invalid_repetitions_can_be_recorded() {
+        let protocol = FlakyProtocol {
+            validations: AtomicUsize::new(0),
+        };
+        let settings = EvaluationSettings::new().with_invalid_repetitions_recorded();
+        let stats = protocol
+            .evaluate_with_settings(
+                "Experiment (invalid recorded)".to_string(),
+                3,
+                &FullMesh::new(),
+                4,
+                &settings,
+            )
+            .unwrap();
+
+        assert_eq!(stats.invalid_repetitions(), [1, 3]);
+        assert_eq!(stats.durations(0, "Total").len(), 4);
+
+        let summary = stats.summarize_timings();
+        assert_eq!(summary.invalid_repetitions(), 2);
+        assert_eq!(summary.statistic(Statistic::Mean).len(), 3);
+        summary.print();
+
+        // The valid summary only aggregates the durations of the valid repetitions
+        let valid = stats.summarize_valid_timings();
+        let total = stats.timing_names().iter().position(|name| name == "Total");
+        let valid_mean = valid.statistic(Statistic::Mean)[0][total.unwrap()].unwrap();
+        let expected =
+            [0, 2].map(|repetition| stats.durations(0, "Total")[repetition].as_secs_f64());
+        assert!((valid_mean - (expected[0] + expected[1]) / 2.).abs() < 1e-9);
+    }
+
+    struct ObservedParty;
+
+    impl Party for ObservedParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            match context.id() {
+                0 => {
+                    context.channels.send_observed(&[1, 2], &1).unwrap();
+                    context.channels.send(&[3], &1).unwrap();
+                    context.channels.send_observed(&[4], &1).unwrap();
+                }
+                _ => {
+                    for _ in 0..3 {
+                        context.channels.receive(&0).unwrap().for_each(drop);
+                    }
+                }
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct ObservedProtocol;
+
+    impl Protocol for ObservedProtocol {
+        type Party = ObservedParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| ObservedParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+
+        fn observe(&self, transcript: &[ObservedMessage]) -> bool {
+            let observed: Vec<_> = transcript
+                .iter()
+                .map(|message| (message.from_id, message.to_id, message.contents.clone()))
+                .collect();
+            observed == vec![(0, Some(1), vec![1, 2]), (0, Some(1), vec![4])]
+        }
+    }
+
+    #[test]
+    fn observer_sees_designated_messages_for_free() {
+        let network = FullMesh::new();
+        let settings = EvaluationSettings::new().with_snapshot_interval(Duration::from_millis(10));
+        let stats = ObservedProtocol
+            .evaluate_with_settings(
+                "Experiment (w/ observer)".to_string(),
+                2,
+                &network,
+                1,
+                &settings,
+            )
+            .unwrap();
+
+        // Observed copies do not count towards the bytes sent
+        let last = stats.snapshots()[0]
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.party == 0)
+            .unwrap();
+        assert_eq!(last.sent_bytes, 4);
+    }
+
+    fn panicking_protocol() -> TestProtocol<(), ()> {
+        TestProtocol::new(|_, context| match context.id() {
+            0 => panic!("party 0 gives up"),
+            _ => context.channels.receive(&0).unwrap().for_each(drop),
+        })
+    }
+
+    #[test]
+    fn panicking_party_is_reported() {
+        let network = FullMesh::new();
+        let result =
+            panicking_protocol().evaluate("Experiment (panic)".to_string(), 3, &network, 1);
+
+        match result {
+            Err(EvaluationError::PartyPanicked { party, message }) => {
+                assert_eq!(party, 0);
+                assert_eq!(message, "party 0 gives up");
+            }
+            _ => panic!("expected party 0 to have panicked"),
+        }
+    }
+
+    struct MisaddressingParty {
+        peer: usize,
+    }
+
+    impl Party for MisaddressingParty {
+        type Input = ();
+        type Output = ();
+
+        fn get_name(&self, id: usize) -> String {
+            format!("Dealer {}", id)
+        }
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if context.is_leader() {
+                context.channels.send(&[0], &self.peer).unwrap();
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct MisaddressingProtocol {
+        peer: usize,
+    }
+
+    impl Protocol for MisaddressingProtocol {
+        type Party = MisaddressingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|_| MisaddressingParty { peer: self.peer })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[test]
+    fn misaddressed_messages_are_reported() {
+        let network = FullMesh::new();
+
+        for (peer, description) in [
+            (0, "Dealer 0 (id 0) tried to send to itself at src/lib.rs:"),
+            (3, "Dealer 0 (id 0) tried to send to party 3 at src/lib.rs:"),
+        ] {
+            let result = MisaddressingProtocol { peer }.evaluate(
+                "Experiment (misaddressed)".to_string(),
+                3,
+                &network,
+                1,
+            );
+
+            match result {
+                Err(EvaluationError::Protocol(error)) => {
+                    assert_eq!(error.operation, ChannelOperation::Send);
+                    assert!(error.to_string().starts_with(description));
+                }
+                _ => panic!("expected a protocol error for peer {}", peer),
+            }
+        }
+    }
+
+    #[test]
+    fn misaddressed_messages_can_be_recorded() {
+        let settings = EvaluationSettings::new().with_invalid_repetitions_recorded();
+        let stats = MisaddressingProtocol { peer: 3 }
+            .evaluate_with_settings(
+                "Experiment (misaddressed recorded)".to_string(),
+                3,
+                &FullMesh::new(),
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        assert_eq!(stats.protocol_errors().len(), 2);
+        assert_eq!(stats.protocol_errors()[0].peer, 3);
+        assert_eq!(stats.repetitions().count(), 0);
+        assert_eq!(stats.summarize_timings().protocol_errors(), 2);
+    }
+
+    struct SlowParty {
+        slowdown_factor: f64,
+    }
+
+    impl Party for SlowParty {
+        type Input = ();
+        type Output = ();
+
+        fn slowdown_factor(&self, _id: usize) -> f64 {
+            self.slowdown_factor
+        }
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            let compute_timer = context.timings.create_timer("Compute");
+            sleep(Duration::from_millis(20));
+            context.timings.stop_timer(compute_timer);
+
+            context.timings.simulate_compute(Duration::from_millis(20));
+        }
+    }
+
+    #[derive(Debug)]
+    struct SlowProtocol {
+        slowdown_factor: f64,
+    }
+
+    impl Protocol for SlowProtocol {
+        type Party = SlowParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|id| SlowParty {
+                    slowdown_factor: if id == 1 { self.slowdown_factor } else { 1. },
+                })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    #[test]
+    fn variants_report_overhead_of_stronger_security() {
+        let network = FullMesh::new();
+        let variant_stats = Variants::new("Slow protocol")
+            .with_variant(
+                SecurityModel::Malicious,
+                SlowProtocol {
+                    slowdown_factor: 4.,
+                },
+            )
+            .with_variant(
+                SecurityModel::SemiHonest,
+                SlowProtocol {
+                    slowdown_factor: 1.,
+                },
+            )
+            .evaluate(2, &network, 2)
+            .unwrap();
+        assert!(variant_stats.stats(SecurityModel::Covert).is_none());
+
+        // The variants are ordered from weakest to strongest, regardless of the registration order
+        let summary = variant_stats.summarize_overhead();
+        summary.print();
+        assert_eq!(
+            summary.models(),
+            [SecurityModel::SemiHonest, SecurityModel::Malicious]
+        );
+
+        let compute = summary
+            .timing_names()
+            .iter()
+            .position(|name| name == "Compute")
+            .unwrap();
+        assert_eq!(summary.overheads()[0][compute], Some(1.));
+        // The measured sleeps overshoot by a few milliseconds on a busy machine, which skews the factor
+        let overhead = summary.overheads()[1][compute].unwrap();
+        assert!(overhead > 3. && overhead < 5.);
+    }
+
+    #[test]
+    fn experiments_are_compared_side_by_side() {
+        let network = FullMesh::new();
+        let fast = SlowProtocol {
+            slowdown_factor: 1.,
+        }
+        .evaluate("Fast".to_string(), 2, &network, 2)
+        .unwrap();
+        let slow = SlowProtocol {
+            slowdown_factor: 4.,
+        }
+        .evaluate("Slow".to_string(), 2, &network, 2)
+        .unwrap();
+        let example = ExampleProtocol
+            .evaluate("Example".to_string(), 3, &network, 2)
+            .unwrap();
+
+        let comparison = AggregatedStats::compare(&[&fast, &slow, &example]);
+        comparison.print();
+        assert_eq!(comparison.experiment_names(), ["Fast", "Slow", "Example"]);
+
+        let compute = comparison
+            .timing_names()
+            .iter()
+            .position(|name| name == "Compute")
+            .unwrap();
+        assert_eq!(comparison.overheads()[0][compute], Some(1.));
+        // The measured sleeps overshoot by a few milliseconds on a busy machine, which skews the factor
+        let overhead = comparison.overheads()[1][compute].unwrap();
+        assert!(overhead > 3. && overhead < 5.);
+        assert_eq!(comparison.means()[2][compute], None);
+
+        // Only the example protocol communicates, so there is no factor relative to the baseline
+        assert_eq!(comparison.sent_bytes(), [0., 0., 3.]);
+        assert_eq!(comparison.sent_overheads(), [None, None, None]);
+    }
+
+    #[test]
+    fn regressions_are_checked_against_a_baseline() {
+        let network = FullMesh::new();
+        let fast = SlowProtocol {
+            slowdown_factor: 1.,
+        }
+        .evaluate("Fast".to_string(), 2, &network, 2)
+        .unwrap();
+        let slow = SlowProtocol {
+            slowdown_factor: 4.,
+        }
+        .evaluate("Slow".to_string(), 2, &network, 2)
+        .unwrap();
+
+        let filename = std::env::temp_dir().join("mpc-bench-baseline-test.csv");
+        let filename = filename.to_str().unwrap();
+        Baseline::record(&fast).save(filename).unwrap();
+        let baseline = Baseline::load(filename).unwrap();
+        assert_eq!(baseline, Baseline::record(&fast));
+
+        let diff = baseline
+            .check(&fast, &["timer:Compute", "sent"], 0.1)
+            .unwrap();
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[0].relative_change(), Some(0.));
+
+        match baseline.check(&slow, &["timer:Compute", "sent"], 0.1) {
+            Err(RegressionError::Regressed { regressions, .. }) => {
+                assert_eq!(regressions.len(), 1);
+                assert_eq!(regressions[0].metric, "timer:Compute");
+                assert!(regressions[0].relative_change().unwrap() > 2.);
+            }
+            result => panic!("expected a regression, got {:?}", result),
+        }
+        assert_eq!(
+            baseline.check(&slow, &["timer:Missing"], 0.1),
+            Err(RegressionError::UnknownMetric("timer:Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn experiments_sweep_the_number_of_parties() {
+        let network = FullMesh::new_with_overhead(Duration::from_millis(1), 1e6);
+        let results = Experiment::new("Sweep")
+            .protocol(ExampleProtocol)
+            .parties(2..=4)
+            .network(network)
+            .repetitions(3)
+            .warmup(1)
+            .run()
+            .unwrap();
+        results.print();
+
+        assert_eq!(results.name(), "Sweep");
+        let party_counts: Vec<usize> = results
+            .results()
+            .iter()
+            .map(|(point, _)| point.n_parties)
+            .collect();
+        assert_eq!(party_counts, [2, 3, 4]);
+
+        let stats = results.stats(3).unwrap();
+        assert_eq!(stats.name(), "Sweep (3 parties)");
+        assert_eq!(stats.party_names().len(), 3);
+        assert_eq!(stats.repetitions().count(), 3);
+        assert!(results.stats(5).is_none());
+    }
+
+    #[test]
+    fn sweeps_tabulate_metrics_against_the_number_of_parties() {
+        let network = FullMesh::new();
+        let results = Experiment::new("Scaling")
+            .protocol(ExampleProtocol)
+            .parties([2, 4])
+            .network(network)
+            .repetitions(2)
+            .run()
+            .unwrap();
+        results.print_metrics();
+
+        let metric_names = results.metric_names();
+        assert!(metric_names.contains(&"timer:Total".to_string()));
+        assert!(metric_names.contains(&"sent".to_string()));
+
+        // Every party sends one byte to each party with a higher id
+        let sent: Vec<(usize, f64)> = results
+            .metric("sent")
+            .into_iter()
+            .map(|(point, value)| (point.n_parties, value))
+            .collect();
+        assert_eq!(sent, [(2, 1.), (4, 6.)]);
+        assert!(results.metric("unknown").is_empty());
+
+        let filename = std::env::temp_dir().join("mpc-bench-sweep-test.csv");
+        results.write_csv(filename.to_str().unwrap()).unwrap();
+
+        let mut reader = csv::Reader::from_path(&filename).unwrap();
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec![
+                "experiment",
+                "n_parties",
+                "latency",
+                "bytes_per_second",
+                "metric",
+                "value"
+            ]
+        );
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(records.len(), 2 * metric_names.len());
+        assert!(records
+            .iter()
+            .any(|record| &record[1] == "4" && &record[4] == "sent" && &record[5] == "6"));
+
+        std::fs::remove_file(filename).unwrap();
+    }
+
+    #[test]
+    fn sweeps_cover_each_network_condition() {
+        let conditions = NetworkCondition::grid(
+            &[Duration::from_millis(1), Duration::from_millis(20)],
+            &[None, Some(1e6)],
+        );
+        assert_eq!(conditions.len(), 4);
+        assert_eq!(
+            conditions[1],
+            NetworkCondition::new(Duration::from_millis(1), 1e6)
+        );
+        assert_eq!(conditions[2].rtt(), Duration::from_millis(40));
+
+        // The parties must run concurrently for the latency to delay the last party
+        let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let results = pool.install(|| {
+            Experiment::new("Conditions")
+                .protocol(ExampleProtocol)
+                .parties([2, 3])
+                .conditions(conditions.clone())
+                .run()
+                .unwrap()
+        });
+        results.print_metrics();
+
+        assert_eq!(results.results().len(), 8);
+        let slow = NetworkCondition::unlimited(Duration::from_millis(20));
+        let stats = results.stats_under(3, &slow).unwrap();
+        assert_eq!(
+            stats.name(),
+            "Conditions (3 parties, 20ms latency, unlimited)"
+        );
+        assert!(stats
+            .network()
+            .unwrap()
+            .ends_with("under 20ms latency, unlimited"));
+
+        // The last party waits for a message, so its run time grows with the latency
+        let by_latency = results.by_latency(3);
+        assert_eq!(by_latency.len(), 4);
+        let fast_total = by_latency[0].1.durations(2, "Total")[0];
+        let slow_total = by_latency[2].1.durations(2, "Total")[0];
+        assert!(fast_total < Duration::from_millis(15));
+        assert!(slow_total >= Duration::from_millis(20));
+    }
+
+    struct SendingParty {
+        length: usize,
+    }
+
+    impl Party for SendingParty {
+        type Input = ();
+        type Output = ();
+
+        fn run(&mut self, _input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+            if context.is_leader() {
+                for i in context.others() {
+                    context.channels.send(&vec![0; self.length], &i).unwrap();
+                }
+            } else {
+                context.channels.receive(&0).unwrap().for_each(drop);
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct LengthParams {
+        length: usize,
+    }
+
+    impl Parameters for LengthParams {
+        fn columns(&self) -> Vec<(String, String)> {
+            vec![("length".to_string(), self.length.to_string())]
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct SendingProtocol {
+        length: usize,
+    }
+
+    impl Protocol for SendingProtocol {
+        type Party = SendingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties)
+                .map(|_| SendingParty {
+                    length: self.length,
+                })
+                .collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, _rng: &mut impl Rng) -> Vec<()> {
+            vec![(); n_parties]
+        }
+    }
+
+    impl Parameterized for SendingProtocol {
+        type Params = LengthParams;
+
+        fn with_params(&self, params: &LengthParams) -> Self {
+            SendingProtocol {
+                length: params.length,
+            }
+        }
+    }
+
+    #[test]
+    fn sweeps_record_the_protocol_parameters() {
+        let results = Experiment::new("Lengths")
+            .protocol(SendingProtocol { length: 1 })
+            .params([LengthParams { length: 8 }, LengthParams { length: 64 }])
+            .parties([2, 3])
+            .run()
+            .unwrap();
+        results.print_metrics();
+
+        assert_eq!(results.param_names(), ["length"]);
+        let metric = results.metric("sent");
+        let sent: Vec<(usize, Option<&str>, f64)> = metric
+            .iter()
+            .map(|(point, value)| (point.n_parties, point.param("length"), *value))
+            .collect();
+        assert_eq!(
+            sent,
+            [
+                (2, Some("8"), 8.),
+                (3, Some("8"), 16.),
+                (2, Some("64"), 64.),
+                (3, Some("64"), 128.)
+            ]
+        );
+
+        let stats = &results.results()[3].1;
+        assert_eq!(stats.name(), "Lengths (3 parties, length=64)");
+        assert_eq!(stats.params(), [("length".to_string(), "64".to_string())]);
+
+        let filename = std::env::temp_dir().join("mpc-bench-params-test.csv");
+        stats.write_csv(filename.to_str().unwrap()).unwrap();
+        let mut reader = csv::Reader::from_path(&filename).unwrap();
+        assert_eq!(&reader.headers().unwrap()[0], "length");
+        assert!(reader.records().all(|record| &record.unwrap()[0] == "64"));
+
+        results.write_csv(filename.to_str().unwrap()).unwrap();
+        let mut reader = csv::Reader::from_path(&filename).unwrap();
+        assert_eq!(&reader.headers().unwrap()[2], "length");
+
+        std::fs::remove_file(filename).unwrap();
+    }
+
+    #[test]
+    fn suites_run_and_persist_every_experiment() {
+        let directory = std::env::temp_dir().join("mpc-bench-suite-test");
+        let suite = ExperimentSuite::new("Paper")
+            .with_experiment(
+                Experiment::new("Example")
+                    .protocol(ExampleProtocol)
+                    .parties([2, 3]),
+            )
+            .with_experiment(
+                Experiment::new("Sending")
+                    .protocol(SendingProtocol { length: 1 })
+                    .params([LengthParams { length: 8 }])
+                    .network(FullMesh::new_with_overhead(Duration::from_millis(1), 1e6)),
+            )
+            .in_parallel()
+            .with_output_directory(directory.to_str().unwrap());
+        assert_eq!(suite.experiment_names(), ["Example", "Sending"]);
+
+        let results = suite.run().unwrap();
+        results.print();
+
+        assert_eq!(results.name(), "Paper");
+        assert_eq!(results.results().len(), 2);
+        assert_eq!(results.experiment("Example").unwrap().results().len(), 2);
+        assert!(results.experiment("Unknown").is_none());
+
+        // Each experiment was persisted on its own
+        assert!(directory.join("Example.csv").exists());
+        assert!(directory.join("Sending.csv").exists());
+
+        let filename = directory.join("combined.csv");
+        results.write_csv(filename.to_str().unwrap()).unwrap();
+        let mut reader = csv::Reader::from_path(&filename).unwrap();
+        assert_eq!(&reader.headers().unwrap()[2], "length");
+        let records: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert!(records
+            .iter()
+            .any(|record| &record[0] == "Example" && record[2].is_empty()));
+        assert!(records
+            .iter()
+            .any(|record| &record[0] == "Sending" && &record[2] == "8"));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn slowdown_scales_timers_and_simulated_compute() {
+        let network = FullMesh::new();
+        let stats = SlowProtocol {
+            slowdown_factor: 4.,
+        }
+        .evaluate("Experiment (slowdown)".to_string(), 2, &network, 1)
+        .unwrap();
+
+        // The measured computation is scaled, but the total run time is not
+        let fast = stats.durations(0, "Compute")[0];
+        let slow = stats.durations(1, "Compute")[0];
+        assert!(fast >= Duration::from_millis(20) && fast < Duration::from_millis(80));
+        assert!(slow >= Duration::from_millis(80));
+
+        // The simulated computation takes 80 ms for the slow party, which is not hidden by scaling "Total"
+        let total = stats.durations(1, "Total")[0];
+        assert!(total >= Duration::from_millis(100));
+        assert!(total < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn invalid_slowdown_factor_is_reported() {
+        let network = FullMesh::new();
+        let result = SlowProtocol {
+            slowdown_factor: -1.,
+        }
+        .evaluate("Experiment (negative slowdown)".to_string(), 2, &network, 1);
+
+        match result {
+            Err(EvaluationError::InvalidSlowdownFactor {
+                party,
+                slowdown_factor,
+            }) => {
+                assert_eq!(party, 1);
+                assert_eq!(slowdown_factor, -1.);
+            }
+            _ => panic!("expected the slowdown factor of party 1 to be rejected"),
+        }
+    }
+
+    fn padded_protocol() -> TestProtocol<(), usize> {
+        TestProtocol::new(|_, context| {
+            // In the first round, the smaller message is sent before the larger one
+            let last = context.n_parties() - 1;
+            if context.id() < last {
+                context
+                    .channels
+                    .send(&vec![0; 4 * context.id() + 1], &last)
+                    .unwrap();
+                return 0;
+            }
+
+            (0..last)
+                .map(|from_id| context.channels.receive(&from_id).unwrap().count())
+                .sum()
+        })
+        .with_validation(|_, outputs| {
+            // The receiver gets the original messages
+            outputs[2] == 1 + 5
+        })
+    }
+
+    #[test]
+    fn round_padding_pads_to_the_largest_message() {
+        let network = FullMesh::new();
+        let settings = EvaluationSettings::new()
+            .with_round_padding()
+            .with_snapshot_interval(Duration::from_millis(20));
+        let stats = padded_protocol()
+            .evaluate_with_settings(
+                "Experiment (w/ padding)".to_string(),
+                3,
+                &network,
+                2,
+                &settings,
+            )
+            .unwrap();
+
+        for snapshots in stats.snapshots() {
+            for party in 0..2 {
+                let last = snapshots
+                    .iter()
+                    .rev()
+                    .find(|snapshot| snapshot.party == party)
+                    .unwrap();
+                assert_eq!(last.sent_bytes, 5);
+            }
+        }
+    }
+
+    #[test]
+    fn rounds_are_summarized_per_party() {
+        // The parties wait for each other, so they need a thread each
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let settings = EvaluationSettings::new().with_round_statistics();
+        let stats = pool.install(|| {
+            ping_pong_protocol()
+                .evaluate_with_settings(
+                    "Experiment (rounds)".to_string(),
+                    2,
+                    &FullMesh::new(),
+                    3,
+                    &settings,
+                )
+                .unwrap()
+        });
+
+        // Party 0 sends before it receives in both of its rounds, while party 1 starts by receiving
+        let rounds = stats.repetitions().next().unwrap().parties()[1].rounds();
+        assert_eq!(rounds.len(), 3);
+        assert_eq!((rounds[0].sent_bytes(), rounds[0].received_bytes()), (0, 1));
+        assert_eq!((rounds[1].sent_bytes(), rounds[1].received_bytes()), (1, 1));
+        assert_eq!((rounds[2].sent_bytes(), rounds[2].received_bytes()), (1, 0));
+
+        let summary = stats.summarize_rounds();
+        summary.print();
+        assert_eq!(summary.round_count(), 3);
+        assert_eq!(summary.mean_sent_bytes()[0], [Some(1.), Some(1.), None]);
+        assert_eq!(summary.straggler_counts()[2], [0, 3]);
+        assert!(summary
+            .straggler_counts()
+            .iter()
+            .all(|counts| counts.iter().sum::<usize>() == 3));
+    }
+
+    #[test]
+    fn idle_time_is_spent_waiting_for_messages() {
+        // The parties must run concurrently, so that party 2 cannot start after its messages arrived
+        let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+
+        let network = FullMesh::new_with_overhead(Duration::from_millis(50), 1e9);
+        let stats = pool.install(|| {
+            ExampleProtocol
+                .evaluate("Experiment (idle time)".to_string(), 3, &network, 2)
+                .unwrap()
+        });
+
+        let summary = stats.summarize_idle_time();
+        summary.print();
+        let receiving = summary
+            .timing_names()
+            .iter()
+            .position(|name| name == "Receiving")
+            .unwrap();
+
+        // Party 0 never receives, while party 2 waits for the latency of its messages
+        let idle_percentages = summary.idle_percentages();
+        assert!(idle_percentages[0].unwrap() < 50.);
+        assert!(idle_percentages[2].unwrap() > 50.);
+        assert!(summary.mean_waiting()[2][receiving].unwrap() >= 0.05);
+        assert!(summary.idle_fractions()[2][receiving].unwrap() > 0.9);
+    }
+
+    #[test]
+    fn snapshots_track_progress() {
+        let example = ExampleProtocol;
+        let network = FullMesh::new_with_overhead(Duration::from_millis(100), 1000.);
+        let settings = EvaluationSettings::new().with_snapshot_interval(Duration::from_millis(20));
+        let stats = example
+            .evaluate_with_settings(
+                "Experiment (w/ snapshots)".to_string(),
+                5,
+                &network,
+                1,
+                &settings,
+            )
+            .unwrap();
+
+        let snapshots = &stats.snapshots()[0];
+        assert!(snapshots.len() > 5);
+
+        let last = snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.party == 0)
+            .unwrap();
+        assert_eq!(last.sent_bytes, 4);
+        assert_eq!(last.completed_timers, 3);
+    }
+
+    #[cfg(feature = "async")]
+    #[derive(Debug)]
+    struct AsyncRingProtocol;
+
+    #[cfg(feature = "async")]
+    struct AsyncRingParty;
+
+    #[cfg(feature = "async")]
+    impl AsyncParty for AsyncRingParty {
+        type Input = u16;
+        type Output = u16;
+
+        async fn run(
+            &mut self,
+            input: &Self::Input,
+            context: &mut AsyncPartyContext<'_>,
+        ) -> Self::Output {
+            // Every party passes its input to the next party in the ring
+            let (id, n_parties) = (context.id(), context.n_parties());
+            context
+                .channels
+                .send(input.to_le_bytes().to_vec(), &((id + 1) % n_parties))
+                .unwrap();
+            let received = context
+                .channels
+                .receive(&((id + n_parties - 1) % n_parties))
+                .await
+                .unwrap();
+            u16::from_le_bytes([received[0], received[1]])
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncProtocol for AsyncRingProtocol {
+        type Party = AsyncRingParty;
+
+        fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+            (0..n_parties).map(|_| AsyncRingParty).collect()
+        }
+
+        fn generate_inputs(&self, n_parties: usize, rng: &mut impl Rng) -> Vec<u16> {
+            (0..n_parties).map(|_| rng.gen()).collect()
+        }
+
+        fn validate_outputs(&self, inputs: &[u16], outputs: &[u16]) -> bool {
+            (0..inputs.len()).all(|id| outputs[(id + 1) % inputs.len()] == inputs[id])
+        }
+
+        fn expected_outputs(&self, inputs: &[u16]) -> Option<ExpectedOutputs<u16>> {
+            let n_parties = inputs.len();
+            let outputs = (0..n_parties)
+                .map(|id| inputs[(id + n_parties - 1) % n_parties])
+                .collect();
+            Some(ExpectedOutputs::new(outputs))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_parties_scale_beyond_threads() {
+        let network = AsyncNetwork::new_with_overhead(Duration::from_millis(100), 1000.);
+        let stats = AsyncRingProtocol
+            .evaluate_async("Experiment (async)".to_string(), 1000, &network, 2)
+            .unwrap();
+
+        // The parties wait for their messages concurrently instead of occupying a thread each
+        for id in [0, 999] {
+            let durations = stats.durations(id, "Total");
+            assert_eq!(durations.len(), 2);
+            assert!(durations
+                .iter()
+                .all(|duration| *duration < Duration::from_secs(2)));
+        }
+
+        let summary = stats.summarize_timings();
+        assert_eq!(summary.sent_bytes()[0], (2., 0.));
+        assert_eq!(summary.received_bytes()[999], (2., 0.));
+
+        // A repetition is replayed with the same inputs, which the parties pass on to the next party
+        let seed = stats.seeds()[1];
+        let (_, outputs) = AsyncRingProtocol
+            .replay_async("Experiment (async)".to_string(), 1000, &network, seed)
+            .unwrap();
+        let inputs = AsyncRingProtocol.generate_inputs(1000, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(outputs[1], inputs[0]);
+        assert_eq!(outputs[0], inputs[999]);
     }
 }
@@ -0,0 +1,7 @@
+use mpc_bench::cli::{self, Registry};
+
+/// Runs the command-line interface without registered protocols, which can report, compare and plot saved results.
+/// To run protocols, a binary registers them and calls `cli::main` itself.
+fn main() {
+    cli::main(&Registry::new());
+}
@@ -0,0 +1,258 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::statistics::{Progress, Timings};
+
+/// An HTTP endpoint that serves the progress of running experiments in the Prometheus text format on `/metrics`, so
+/// that long sweeps can be monitored on existing dashboards. It reports the number of completed repetitions, the
+/// current experiment and repetition, the live bytes sent and timers completed by each party, the durations that each
+/// party measured in the last completed repetition and the total bytes that each party sent.
+///
+/// One endpoint can be shared by all evaluations of a sweep by passing it to each one's settings (see
+/// `EvaluationSettings::with_metrics_endpoint`). The endpoint stops serving when the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct MetricsEndpoint {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    address: SocketAddr,
+    state: Arc<Mutex<MetricsState>>,
+    stopped: Arc<AtomicBool>,
+}
+
+/// The metrics that the endpoint serves.
+#[derive(Debug, Default)]
+struct MetricsState {
+    completed_repetitions: usize,
+    experiment: Option<String>,
+    repetition: Option<usize>,
+    party_names: Vec<String>,
+    progress: Vec<Arc<Progress>>,
+    /// The durations that each party measured in the last completed repetition.
+    last_durations: Vec<(String, Vec<(String, Duration)>)>,
+    /// The total bytes sent by each party (by name) over all completed repetitions of all experiments.
+    sent_bytes: Vec<(String, usize)>,
+}
+
+impl MetricsEndpoint {
+    /// Binds the endpoint to `address` (e.g. `0.0.0.0:9184`) and starts serving on a background thread.
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MetricsState::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let served_state = state.clone();
+        let served_stopped = stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if served_stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                // A failing scraper must not affect the experiment, so its errors are ignored
+                if let Ok(stream) = stream {
+                    let _ = serve(stream, &served_state);
+                }
+            }
+        });
+
+        Ok(MetricsEndpoint {
+            inner: Arc::new(Inner {
+                address,
+                state,
+                stopped,
+            }),
+        })
+    }
+
+    /// Returns the address that the endpoint is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.address
+    }
+
+    /// Records that the `repetition` of the experiment named `experiment` started, in which the parties named
+    /// `party_names` report their `progress`. Repetitions without an index (e.g. warmups) are reported as well.
+    pub(crate) fn start_repetition(
+        &self,
+        experiment: &str,
+        repetition: Option<usize>,
+        party_names: Vec<String>,
+        progress: Vec<Arc<Progress>>,
+    ) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.experiment = Some(experiment.to_string());
+        state.repetition = repetition;
+        state.party_names = party_names;
+        state.progress = progress;
+    }
+
+    /// Records the statistics of each party in the measured repetition that completed.
+    pub(crate) fn finish_repetition(&self, party_timings: &[Timings]) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.completed_repetitions += 1;
+
+        let party_names = state.party_names.clone();
+        state.last_durations = party_names
+            .iter()
+            .zip(party_timings)
+            .map(|(name, s)| (name.clone(), s.measured_durations().to_vec()))
+            .collect();
+        for (name, s) in party_names.iter().zip(party_timings) {
+            let sent_bytes: usize = s.sent_bytes().iter().sum();
+            match state.sent_bytes.iter_mut().find(|(party, _)| party == name) {
+                Some((_, total)) => *total += sent_bytes,
+                None => state.sent_bytes.push((name.clone(), sent_bytes)),
+            }
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // The serving thread only notices that it should stop when it accepts a connection
+        self.stopped.store(true, Ordering::Release);
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// Answers one HTTP request on the `stream` with the metrics in the `state`.
+fn serve(mut stream: TcpStream, state: &Mutex<MetricsState>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // The headers are not used, but they are read so that the client does not see a reset connection
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = match request_line.split_whitespace().nth(1) {
+        Some("/metrics") => ("200 OK", render(&state.lock().unwrap())),
+        _ => ("404 Not Found", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Renders the metrics in the Prometheus text exposition format.
+fn render(state: &MetricsState) -> String {
+    let mut body = String::new();
+    let experiment = &escape_label(state.experiment.as_deref().unwrap_or(""));
+    let party_labels = |party: &str| {
+        format!(
+            "{{experiment=\"{}\",party=\"{}\"}}",
+            experiment,
+            escape_label(party)
+        )
+    };
+
+    let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+        let _ = writeln!(body, "# HELP mpc_bench_{} {}", name, help);
+        let _ = writeln!(body, "# TYPE mpc_bench_{} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(body, "mpc_bench_{}{} {}", name, labels, value);
+        }
+    };
+
+    metric(
+        "repetitions_completed_total",
+        "counter",
+        "The number of repetitions that completed.",
+        vec![(String::new(), state.completed_repetitions.to_string())],
+    );
+    metric(
+        "repetition",
+        "gauge",
+        "The index of the running repetition, or -1 for a warmup repetition.",
+        vec![(
+            format!("{{experiment=\"{}\"}}", experiment),
+            state
+                .repetition
+                .map_or(-1, |repetition| repetition as i64)
+                .to_string(),
+        )],
+    );
+    metric(
+        "party_sent_bytes",
+        "gauge",
+        "The number of bytes that the party sent so far in the running repetition.",
+        state
+            .party_names
+            .iter()
+            .zip(&state.progress)
+            .map(|(party, progress)| (party_labels(party), progress.sent_bytes().to_string()))
+            .collect(),
+    );
+    metric(
+        "party_completed_timers",
+        "gauge",
+        "The number of timers that the party completed so far in the running repetition.",
+        state
+            .party_names
+            .iter()
+            .zip(&state.progress)
+            .map(|(party, progress)| (party_labels(party), progress.completed_timers().to_string()))
+            .collect(),
+    );
+    metric(
+        "last_duration_seconds",
+        "gauge",
+        "The duration that the party measured with the timer in the last completed repetition.",
+        state
+            .last_durations
+            .iter()
+            .flat_map(|(party, durations)| {
+                durations.iter().map(move |(timing, duration)| {
+                    let labels = format!(
+                        "{{experiment=\"{}\",party=\"{}\",timing=\"{}\"}}",
+                        experiment,
+                        escape_label(party),
+                        escape_label(timing)
+                    );
+                    (labels, duration.as_secs_f64().to_string())
+                })
+            })
+            .collect(),
+    );
+    metric(
+        "sent_bytes_total",
+        "counter",
+        "The number of bytes that the party sent in all completed repetitions.",
+        state
+            .sent_bytes
+            .iter()
+            .map(|(party, bytes)| {
+                let labels = format!("{{party=\"{}\"}}", escape_label(party));
+                (labels, bytes.to_string())
+            })
+            .collect(),
+    );
+
+    body
+}
+
+/// Escapes a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,344 @@
+use std::{
+    io,
+    sync::{
+        mpsc::{channel, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use ::mpi::{
+    datatype::{Partition, PartitionMut},
+    environment::threading_support,
+    topology::{Rank, SimpleCommunicator},
+    traits::*,
+    Count, Threading,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    comm::{Channels, Message, Transport},
+    distributed::{collect_reports, decode, encode, report_outcome, Report},
+    error::EvaluationError,
+    evaluation::{check_repetition, derive_seed, run_party, Repetition},
+    statistics::{AggregatedStats, Timings},
+    Party, Protocol,
+};
+
+/// The rank that generates the inputs and collects the outputs and statistics.
+const ROOT_RANK: Rank = 0;
+
+/// A communicator that is shared by the thread that runs a rank's party, which sends, and the rank's receiving thread.
+struct SharedCommunicator(SimpleCommunicator);
+
+// SAFETY: `evaluate` only runs once MPI is initialized with `Threading::Multiple`, under which MPI may be called from
+// multiple threads concurrently
+unsafe impl Send for SharedCommunicator {}
+unsafe impl Sync for SharedCommunicator {}
+
+/// A transport to the party at another MPI rank, which sends each message as a frame that was encoded by
+/// `Message::encode`.
+pub(crate) struct MpiTransport {
+    communicator: Arc<SharedCommunicator>,
+    rank: Rank,
+}
+
+impl Transport for MpiTransport {
+    fn deliver(&self, message: Message) -> bool {
+        // With MPI's default error handler a failed send aborts the job, so a send that returns succeeded
+        self.communicator
+            .0
+            .process_at_rank(self.rank)
+            .send(&message.encode()[..]);
+        true
+    }
+}
+
+/// Evaluates multiple `repetitions` of the `protocol` like `Protocol::evaluate` does, where each party runs on the MPI
+/// rank of the `world` communicator that equals its id, so that there are as many parties as ranks (e.g. hundreds of
+/// parties on an HPC cluster). Every rank must call this function with the same arguments. The root (rank 0)
+/// generates the inputs, scatters them to the ranks, and gathers the outputs and statistics of every repetition, so it
+/// returns the statistics while the other ranks return `None`. MPI must be initialized with `Threading::Multiple`,
+/// because every rank receives its messages on a thread of its own.
+pub fn evaluate<P>(
+    protocol: &P,
+    world: &SimpleCommunicator,
+    experiment_name: String,
+    repetitions: usize,
+) -> Result<Option<AggregatedStats>, EvaluationError>
+where
+    P: Protocol,
+    <P::Party as Party>::Input: Serialize + DeserializeOwned,
+    <P::Party as Party>::Output: Serialize + DeserializeOwned,
+{
+    if threading_support() != Threading::Multiple {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "MPI must be initialized with `Threading::Multiple`",
+        )
+        .into());
+    }
+
+    let id = world.rank() as usize;
+    let n_parties = world.size() as usize;
+    let mut parties = protocol.setup_parties(n_parties);
+    assert_eq!(
+        parties.len(),
+        n_parties,
+        "`setup_parties` must set up `n_parties` parties"
+    );
+
+    // Only the root incorporates the statistics, so only the root has them
+    let mut stats = (id == ROOT_RANK as usize).then(|| {
+        let party_names = parties
+            .iter()
+            .enumerate()
+            .map(|(id, party)| party.get_name(id))
+            .collect();
+        let mut stats = AggregatedStats::new(experiment_name, party_names);
+        stats.set_parameters(format!("{:?}", protocol));
+        if let Some(dataset) = protocol.dataset() {
+            stats.set_dataset(dataset.to_string());
+        }
+        stats
+    });
+
+    // The messages of the parties are kept apart from the collective operations of the harness
+    let communicator = Arc::new(SharedCommunicator(world.duplicate()));
+    let root = world.process_at_rank(ROOT_RANK);
+    let master_seed = rand::random();
+    for repetition in 0..repetitions {
+        let seed = derive_seed(master_seed, repetition);
+        let inputs = stats
+            .is_some()
+            .then(|| protocol.generate_inputs(n_parties, &mut StdRng::seed_from_u64(seed)));
+        let encoded_inputs = inputs
+            .as_ref()
+            .map(|inputs| inputs.iter().map(encode).collect::<io::Result<Vec<_>>>())
+            .transpose()?;
+        let input = scatter_inputs(world, encoded_inputs.as_deref())?;
+
+        // The parties start simultaneously, so that no party's run time includes waiting for the others
+        world.barrier();
+        let start_time = Instant::now();
+        let report = run_rank(&mut parties[id], &communicator, decode(&input))?;
+        let reports = gather_reports(world, &report)?;
+        // The makespan also includes gathering the reports at the root
+        let makespan = start_time.elapsed();
+
+        // The root decides whether the other ranks proceed, so that they stop once a repetition failed
+        let mut failure = None;
+        if let (Some(stats), Some(inputs), Some(reports)) = (stats.as_mut(), inputs, reports) {
+            match collect_repetition(protocol, inputs, reports, makespan, repetition) {
+                Ok(result) => {
+                    stats.incorporate_party_stats(result.timings);
+                    stats.incorporate_makespan(result.makespan);
+                    stats.incorporate_seed(seed);
+                }
+                Err(error) => failure = Some(error),
+            }
+        }
+        let mut proceed = u8::from(failure.is_none());
+        root.broadcast_into(&mut proceed);
+
+        if let Some(failure) = failure {
+            return Err(failure);
+        }
+        if proceed == 0 {
+            return Ok(None);
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Runs the `party` of this rank in one repetition with its `input`, and returns its encoded report.
+fn run_rank<P: Party>(
+    party: &mut P,
+    communicator: &Arc<SharedCommunicator>,
+    input: io::Result<P::Input>,
+) -> io::Result<Vec<u8>>
+where
+    P::Output: Serialize,
+{
+    let id = communicator.0.rank() as usize;
+    let n_parties = communicator.0.size() as usize;
+
+    let (queue, receiver) = channel();
+    let links = (0..n_parties)
+        .map(|other| -> Box<dyn Transport> {
+            if other == id {
+                Box::new(queue.clone())
+            } else {
+                Box::new(MpiTransport {
+                    communicator: communicator.clone(),
+                    rank: other as Rank,
+                })
+            }
+        })
+        .collect();
+    let mut channel =
+        Channels::with_transports(id, links, receiver, Duration::ZERO, Duration::ZERO);
+    let receiving_thread = {
+        let communicator = communicator.clone();
+        thread::spawn(move || forward_messages(&communicator, queue))
+    };
+
+    let mut s = Timings::new();
+    let result = input
+        .map_err(EvaluationError::from)
+        .and_then(|input| run_party(party, id, n_parties, &input, &mut channel, &mut s));
+    let report = report_outcome(result, &mut channel, &s).unwrap_or_else(|error| Report::Failed {
+        message: error.to_string(),
+        aborted: false,
+    });
+
+    // MPI does not reorder the messages between two ranks, so an empty frame marks the end of this party's messages
+    for other in (0..n_parties).filter(|&other| other != id) {
+        communicator
+            .0
+            .process_at_rank(other as Rank)
+            .send(&[0u8; 0][..]);
+    }
+    receiving_thread
+        .join()
+        .expect("the receiving thread does not panic");
+
+    encode(&report)
+}
+
+/// Puts the messages that the other ranks send to this rank in its `queue`, until every other rank marked the end of
+/// its messages in this repetition with an empty frame.
+fn forward_messages(communicator: &SharedCommunicator, queue: Sender<Message>) {
+    let mut remaining = communicator.0.size() - 1;
+    while remaining > 0 {
+        let (frame, status) = communicator.0.any_process().receive_vec::<u8>();
+        if frame.is_empty() {
+            remaining -= 1;
+        } else if let Some(message) = Message::decode(&frame, status.source_rank() as usize) {
+            // The party may already have finished, in which case its remaining messages are dropped
+            let _ = queue.send(message);
+        }
+    }
+}
+
+/// Scatters the encoded `inputs` (indexed by rank) from the root, which is the only rank that passes them, and returns
+/// the encoded input of this rank.
+fn scatter_inputs(world: &SimpleCommunicator, inputs: Option<&[Vec<u8>]>) -> io::Result<Vec<u8>> {
+    let root = world.process_at_rank(ROOT_RANK);
+    let mut length: Count = 0;
+
+    let input = match inputs {
+        Some(inputs) => {
+            let lengths = inputs
+                .iter()
+                .map(|input| count(input.len()))
+                .collect::<io::Result<Vec<_>>>()?;
+            root.scatter_into_root(&lengths[..], &mut length);
+
+            let displacements = displacements(&lengths)?;
+            let bytes = inputs.concat();
+            let mut input = vec![0; length as usize];
+            root.scatter_varcount_into_root(
+                &Partition::new(&bytes[..], &lengths[..], &displacements[..]),
+                &mut input[..],
+            );
+            input
+        }
+        None => {
+            root.scatter_into(&mut length);
+
+            let mut input = vec![0; length as usize];
+            root.scatter_varcount_into(&mut input[..]);
+            input
+        }
+    };
+
+    Ok(input)
+}
+
+/// Gathers the encoded `report` of every rank at the root, where they are returned (indexed by rank).
+fn gather_reports(world: &SimpleCommunicator, report: &[u8]) -> io::Result<Option<Vec<Vec<u8>>>> {
+    let root = world.process_at_rank(ROOT_RANK);
+    let length = count(report.len())?;
+
+    if world.rank() != ROOT_RANK {
+        root.gather_into(&length);
+        root.gather_varcount_into(report);
+        return Ok(None);
+    }
+
+    let mut lengths = vec![0; world.size() as usize];
+    root.gather_into_root(&length, &mut lengths[..]);
+
+    let displacements = displacements(&lengths)?;
+    let mut bytes = vec![0u8; lengths.iter().map(|&length| length as usize).sum()];
+    root.gather_varcount_into_root(
+        report,
+        &mut PartitionMut::new(&mut bytes[..], &lengths[..], &displacements[..]),
+    );
+
+    let mut offset = 0;
+    let reports = lengths
+        .iter()
+        .map(|&length| {
+            offset += length as usize;
+            bytes[offset - length as usize..offset].to_vec()
+        })
+        .collect();
+    Ok(Some(reports))
+}
+
+/// Decodes the `reports` of all ranks into the result of one repetition, and checks it like `Protocol::evaluate` does.
+fn collect_repetition<P: Protocol>(
+    protocol: &P,
+    inputs: Vec<<P::Party as Party>::Input>,
+    reports: Vec<Vec<u8>>,
+    makespan: Duration,
+    repetition: usize,
+) -> Result<Repetition<P::Party>, EvaluationError>
+where
+    <P::Party as Party>::Output: DeserializeOwned,
+{
+    let reports = reports
+        .iter()
+        .map(|report| decode(report))
+        .collect::<io::Result<_>>()?;
+    let (outputs, timings) = collect_reports(reports)?;
+
+    let result = Repetition {
+        inputs,
+        outputs,
+        timings,
+        makespan,
+        snapshots: None,
+        transcript: vec![],
+    };
+    check_repetition(protocol, &result, repetition)?;
+    Ok(result)
+}
+
+/// Converts a buffer `length` into an MPI count, which is limited to `Count::MAX` elements.
+fn count(length: usize) -> io::Result<Count> {
+    Count::try_from(length).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} bytes exceed the maximum size of an MPI buffer", length),
+        )
+    })
+}
+
+/// Returns the offset of each buffer with the given `lengths` when they are concatenated.
+fn displacements(lengths: &[Count]) -> io::Result<Vec<Count>> {
+    let mut offset: usize = 0;
+    lengths
+        .iter()
+        .map(|&length| {
+            let displacement = count(offset)?;
+            offset += length as usize;
+            Ok(displacement)
+        })
+        .collect()
+}
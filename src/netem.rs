@@ -0,0 +1,147 @@
+use std::{
+    fs::File,
+    io,
+    os::fd::AsRawFd,
+    process::{self, Command},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    comm::{Channels, NetworkDescription},
+    tcp::{connect_parties, Connected},
+};
+
+/// Numbers the network namespaces of this process, so that concurrent runs do not share one.
+static NAMESPACE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A network description that connects every pair of parties through a real TCP socket like `TcpNetwork`, but in a
+/// dedicated network namespace per repetition whose loopback interface is shaped by netem. The latency, loss and
+/// throughput are therefore imposed by the kernel instead of being simulated, which allows validating the simulated
+/// model of `FullMesh` against a real transport.
+///
+/// Requires Linux, the `ip` and `tc` commands, the `sch_netem` kernel module and the privileges to create network
+/// namespaces (e.g. root or `CAP_NET_ADMIN` and `CAP_SYS_ADMIN`). Note that the throughput limit applies to the
+/// loopback interface, so it is shared by all parties' traffic rather than being per party as in `FullMesh`.
+#[derive(Debug, Default)]
+pub struct NetemNetwork {
+    latency: Duration,
+    bytes_per_second: Option<f64>,
+    loss: f64,
+}
+
+impl NetemNetwork {
+    /// Constructs a NetemNetwork with the specified one-way `latency` and no throughput limit.
+    pub fn new(latency: Duration) -> Self {
+        NetemNetwork {
+            latency,
+            ..NetemNetwork::default()
+        }
+    }
+
+    /// Constructs a NetemNetwork with the specified one-way `latency` and throughput (maximum `bytes_per_second`),
+    /// like `FullMesh::new_with_overhead`.
+    pub fn new_with_overhead(latency: Duration, bytes_per_second: f64) -> Self {
+        NetemNetwork {
+            latency,
+            bytes_per_second: Some(bytes_per_second),
+            loss: 0.,
+        }
+    }
+
+    /// Drops a fraction `loss` (between 0 and 1) of the packets, which TCP retransmits.
+    pub fn with_loss(mut self, loss: f64) -> Self {
+        self.loss = loss;
+        self
+    }
+
+    /// Returns the arguments of the netem qdisc that shapes the loopback interface.
+    pub(crate) fn netem_arguments(&self) -> Vec<String> {
+        let mut arguments = vec![
+            "netem".to_string(),
+            "delay".to_string(),
+            format!("{}us", self.latency.as_micros()),
+        ];
+        if self.loss > 0. {
+            arguments.extend(["loss".to_string(), format!("{}%", 100. * self.loss)]);
+        }
+        if let Some(bytes_per_second) = self.bytes_per_second {
+            arguments.extend([
+                "rate".to_string(),
+                format!("{}bit", (8. * bytes_per_second).round() as u64),
+            ]);
+        }
+        arguments
+    }
+}
+
+impl NetworkDescription for NetemNetwork {
+    /// Instantiates the Channels for each party. Panics if the network namespace cannot be set up.
+    fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
+        let namespace = format!(
+            "mpc-bench-{}-{}",
+            process::id(),
+            NAMESPACE_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+
+        // The sockets keep the namespace alive after it was deleted, until the parties close them
+        run("ip", &["netns", "add", &namespace])
+            .expect("could not create a network namespace for netem");
+        let connected = run("ip", &["-n", &namespace, "link", "set", "lo", "up"])
+            .and_then(|_| {
+                let mut arguments = vec!["-n", &namespace, "qdisc", "add", "dev", "lo", "root"];
+                let netem_arguments = self.netem_arguments();
+                arguments.extend(netem_arguments.iter().map(String::as_str));
+                run("tc", &arguments)
+            })
+            .and_then(|_| connect_in_namespace(&namespace, n_parties));
+        let _ = run("ip", &["netns", "delete", &namespace]);
+
+        connected
+            .expect("could not set up the TCP sockets in the netem namespace")
+            .into_iter()
+            .enumerate()
+            .map(|(id, (transports, receiver))| {
+                Channels::with_transports(id, transports, receiver, Duration::ZERO, Duration::ZERO)
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// Connects the parties like `TcpNetwork` does, but from a thread that entered the network `namespace`, so that the
+/// sockets belong to that namespace.
+fn connect_in_namespace(namespace: &str, n_parties: usize) -> io::Result<Vec<Connected>> {
+    let namespace = File::open(format!("/var/run/netns/{}", namespace))?;
+
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if unsafe { libc::setns(namespace.as_raw_fd(), libc::CLONE_NEWNET) } == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                connect_parties(n_parties)
+            })
+            .join()
+            .unwrap()
+    })
+}
+
+/// Runs the `program` with `arguments`, returning an error with its output if it fails.
+fn run(program: &str, arguments: &[&str]) -> io::Result<()> {
+    let output = Command::new(program).args(arguments).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "`{} {}` failed: {}",
+            program,
+            arguments.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
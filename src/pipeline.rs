@@ -0,0 +1,355 @@
+use std::{fmt::Debug, sync::Arc, time::Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    comm::{NetworkDescription, RoundSizes},
+    error::EvaluationError,
+    evaluation::{check_repetition, run_repetition},
+    settings::EvaluationSettings,
+    statistics::{AggregatedStats, PipelineStats},
+    Party, Protocol,
+};
+
+/// A `Pipeline` chains multiple protocols, where the outputs of one protocol become the inputs of the next (e.g. key
+/// setup, then preprocessing, then an online query). A pipeline is started with `Start::new` and extended with
+/// `Pipeline::then`.
+pub trait Pipeline: Debug + Sized {
+    /// The output type of the parties in the last stage of this pipeline.
+    type Output: Debug + Send;
+    /// The parties of all stages of this pipeline, which are set up once and reused in every repetition.
+    type Parties;
+
+    /// Returns the number of stages in this pipeline.
+    fn stage_count(&self) -> usize;
+
+    /// Sets up the parties of all stages of this pipeline with `n_parties`, and pushes empty statistics for each stage
+    /// onto `stage_stats` (in the order of the stages).
+    fn setup_stages(
+        &self,
+        experiment_name: &str,
+        n_parties: usize,
+        stage_stats: &mut Vec<AggregatedStats>,
+    ) -> Self::Parties;
+
+    /// Runs one repetition of all stages of this pipeline with the given `parties`, incorporating the statistics of
+    /// each stage into `stage_stats`. Returns the outputs of the last stage.
+    fn run_stages<N: NetworkDescription>(
+        &self,
+        parties: &mut Self::Parties,
+        run: &StageRun<N>,
+        stage_stats: &mut [AggregatedStats],
+    ) -> Result<Vec<Self::Output>, EvaluationError>;
+
+    /// Appends a stage named `name` that runs `protocol`, whose inputs are computed from the outputs of the previous
+    /// stage by `connect`.
+    fn then<P, F>(self, name: &str, protocol: P, connect: F) -> Then<Self, P, F>
+    where
+        P: Protocol,
+        F: Fn(Vec<Self::Output>) -> Vec<<P::Party as Party>::Input>,
+    {
+        Then {
+            previous: self,
+            name: name.to_string(),
+            protocol,
+            connect,
+        }
+    }
+
+    /// Evaluates multiple `repetitions` of the whole pipeline, gathering the statistics of each stage as well as the
+    /// end-to-end durations.
+    fn evaluate<N: NetworkDescription>(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &N,
+        repetitions: usize,
+    ) -> Result<PipelineStats, EvaluationError> {
+        self.evaluate_with_settings(
+            experiment_name,
+            n_parties,
+            network_description,
+            repetitions,
+            &EvaluationSettings::default(),
+        )
+    }
+
+    /// Evaluates the pipeline like `evaluate`, but with additional `settings` that apply to every stage.
+    fn evaluate_with_settings<N: NetworkDescription>(
+        &self,
+        experiment_name: String,
+        n_parties: usize,
+        network_description: &N,
+        repetitions: usize,
+        settings: &EvaluationSettings,
+    ) -> Result<PipelineStats, EvaluationError> {
+        let mut stage_stats = Vec::with_capacity(self.stage_count());
+        let mut parties = self.setup_stages(&experiment_name, n_parties, &mut stage_stats);
+        debug_assert_eq!(stage_stats.len(), self.stage_count());
+        for stats in &mut stage_stats {
+            stats.set_network(network_description.describe());
+        }
+
+        // Like in `Protocol::evaluate`, the inputs of each repetition are generated from a seed of its own
+        let master_seed = settings.master_seed();
+        let mut unmeasured_seeds = (0..).map(|index| settings.repetition_seed(!master_seed, index));
+
+        // Like in `Protocol::evaluate`, one unpadded repetition determines the round sizes of each stage
+        let round_sizes: Option<Vec<Arc<RoundSizes>>> = settings.round_padding.then(|| {
+            (0..self.stage_count())
+                .map(|_| Arc::new(RoundSizes::new()))
+                .collect()
+        });
+        if let Some(round_sizes) = &round_sizes {
+            let run = StageRun {
+                network_description,
+                settings,
+                repetition: None,
+                seed: unmeasured_seeds.next().unwrap(),
+                round_sizes: Some(round_sizes),
+            };
+            self.run_stages(&mut parties, &run, &mut stage_stats)?;
+
+            for stage_round_sizes in round_sizes {
+                stage_round_sizes.start_padding();
+            }
+        }
+
+        for _ in 0..settings.warmup_repetitions {
+            let run = StageRun {
+                network_description,
+                settings,
+                repetition: None,
+                seed: unmeasured_seeds.next().unwrap(),
+                round_sizes: round_sizes.as_deref(),
+            };
+            self.run_stages(&mut parties, &run, &mut stage_stats)?;
+        }
+
+        let mut end_to_end = Vec::with_capacity(repetitions);
+        for repetition in 0..repetitions {
+            let run = StageRun {
+                network_description,
+                settings,
+                repetition: Some(repetition),
+                seed: settings.repetition_seed(master_seed, repetition),
+                round_sizes: round_sizes.as_deref(),
+            };
+
+            let start = Instant::now();
+            self.run_stages(&mut parties, &run, &mut stage_stats)?;
+            end_to_end.push(start.elapsed());
+        }
+
+        Ok(PipelineStats::new(experiment_name, stage_stats, end_to_end))
+    }
+}
+
+/// Describes how the stages of a `Pipeline` are run in one repetition.
+pub struct StageRun<'a, N: NetworkDescription> {
+    network_description: &'a N,
+    settings: &'a EvaluationSettings,
+    repetition: Option<usize>,
+    /// The seed that the inputs of the first stage are generated from.
+    seed: u64,
+    round_sizes: Option<&'a [Arc<RoundSizes>]>,
+}
+
+/// The first stage of a `Pipeline`, whose inputs are generated by its protocol.
+#[derive(Debug)]
+pub struct Start<P: Protocol> {
+    name: String,
+    protocol: P,
+}
+
+impl<P: Protocol> Start<P> {
+    /// Starts a pipeline with a stage named `name` that runs `protocol`.
+    pub fn new(name: &str, protocol: P) -> Self {
+        Start {
+            name: name.to_string(),
+            protocol,
+        }
+    }
+}
+
+impl<P: Protocol> Pipeline for Start<P> {
+    type Output = <P::Party as Party>::Output;
+    type Parties = Vec<P::Party>;
+
+    fn stage_count(&self) -> usize {
+        1
+    }
+
+    fn setup_stages(
+        &self,
+        experiment_name: &str,
+        n_parties: usize,
+        stage_stats: &mut Vec<AggregatedStats>,
+    ) -> Self::Parties {
+        setup_stage(
+            &self.protocol,
+            experiment_name,
+            &self.name,
+            n_parties,
+            stage_stats,
+        )
+    }
+
+    fn run_stages<N: NetworkDescription>(
+        &self,
+        parties: &mut Self::Parties,
+        run: &StageRun<N>,
+        stage_stats: &mut [AggregatedStats],
+    ) -> Result<Vec<Self::Output>, EvaluationError> {
+        let inputs = self
+            .protocol
+            .generate_inputs(parties.len(), &mut StdRng::seed_from_u64(run.seed));
+        run_stage(&self.protocol, parties, inputs, run, &mut stage_stats[0])
+    }
+}
+
+/// A stage of a `Pipeline` whose inputs are computed from the outputs of the `previous` stages.
+pub struct Then<Prev, P, F> {
+    previous: Prev,
+    name: String,
+    protocol: P,
+    connect: F,
+}
+
+impl<Prev: Debug, P: Debug, F> Debug for Then<Prev, P, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Then")
+            .field("previous", &self.previous)
+            .field("name", &self.name)
+            .field("protocol", &self.protocol)
+            .finish()
+    }
+}
+
+impl<Prev, P, F> Pipeline for Then<Prev, P, F>
+where
+    Prev: Pipeline,
+    P: Protocol,
+    F: Fn(Vec<Prev::Output>) -> Vec<<P::Party as Party>::Input>,
+{
+    type Output = <P::Party as Party>::Output;
+    type Parties = (Prev::Parties, Vec<P::Party>);
+
+    fn stage_count(&self) -> usize {
+        self.previous.stage_count() + 1
+    }
+
+    fn setup_stages(
+        &self,
+        experiment_name: &str,
+        n_parties: usize,
+        stage_stats: &mut Vec<AggregatedStats>,
+    ) -> Self::Parties {
+        let previous_parties = self
+            .previous
+            .setup_stages(experiment_name, n_parties, stage_stats);
+
+        (
+            previous_parties,
+            setup_stage(
+                &self.protocol,
+                experiment_name,
+                &self.name,
+                n_parties,
+                stage_stats,
+            ),
+        )
+    }
+
+    fn run_stages<N: NetworkDescription>(
+        &self,
+        (previous_parties, parties): &mut Self::Parties,
+        run: &StageRun<N>,
+        stage_stats: &mut [AggregatedStats],
+    ) -> Result<Vec<Self::Output>, EvaluationError> {
+        let stage = self.previous.stage_count();
+        let previous_outputs =
+            self.previous
+                .run_stages(previous_parties, run, &mut stage_stats[..stage])?;
+
+        run_stage(
+            &self.protocol,
+            parties,
+            (self.connect)(previous_outputs),
+            &StageRun {
+                round_sizes: run.round_sizes.map(|round_sizes| &round_sizes[stage..]),
+                ..*run
+            },
+            &mut stage_stats[stage],
+        )
+    }
+}
+
+/// Sets up the parties of the stage named `name` and pushes its empty statistics onto `stage_stats`.
+fn setup_stage<P: Protocol>(
+    protocol: &P,
+    experiment_name: &str,
+    name: &str,
+    n_parties: usize,
+    stage_stats: &mut Vec<AggregatedStats>,
+) -> Vec<P::Party> {
+    let parties = protocol.setup_parties(n_parties);
+    debug_assert_eq!(parties.len(), n_parties);
+
+    let mut stats = AggregatedStats::new(
+        format!("{} / {}", experiment_name, name),
+        parties
+            .iter()
+            .enumerate()
+            .map(|(id, party)| party.get_name(id))
+            .collect(),
+    );
+    stats.set_parameters(format!("{:?}", protocol));
+    if let Some(dataset) = protocol.dataset() {
+        stats.set_dataset(dataset.to_string());
+    }
+    stage_stats.push(stats);
+
+    parties
+}
+
+/// Runs one repetition of a stage with the given `parties` and `inputs`, and incorporates its statistics into
+/// `stats` unless the repetition only determines the round sizes or warms up.
+fn run_stage<P: Protocol, N: NetworkDescription>(
+    protocol: &P,
+    parties: &mut [P::Party],
+    inputs: Vec<<P::Party as Party>::Input>,
+    run: &StageRun<N>,
+    stats: &mut AggregatedStats,
+) -> Result<Vec<<P::Party as Party>::Output>, EvaluationError> {
+    let channels = run.network_description.instantiate(parties.len());
+    let result = run_repetition(
+        parties,
+        inputs,
+        channels,
+        run.settings,
+        stats.name(),
+        run.repetition,
+        run.round_sizes.map(|round_sizes| &round_sizes[0]),
+    )?;
+
+    if let Some(repetition) = run.repetition {
+        let valid = match check_repetition(protocol, &result, repetition) {
+            Ok(()) => true,
+            Err(EvaluationError::Validation(_)) if run.settings.record_invalid => false,
+            Err(error) => return Err(error),
+        };
+
+        stats.incorporate_party_stats(result.timings);
+        stats.incorporate_makespan(result.makespan);
+        stats.incorporate_seed(run.seed);
+        if !valid {
+            stats.mark_invalid();
+        }
+        if let Some(snapshots) = result.snapshots {
+            stats.incorporate_snapshots(snapshots);
+        }
+    }
+
+    Ok(result.outputs)
+}
@@ -0,0 +1,268 @@
+use std::{io, time::Duration};
+
+use plotters::{
+    coord::{ranged1d::SegmentValue, Shift},
+    prelude::*,
+};
+use stats::mean;
+
+use crate::statistics::{confidence_interval, AggregatedStats, Statistic};
+
+/// The size of the rendered charts in pixels.
+const SIZE: (u32, u32) = (800, 600);
+
+/// Renders a line chart of the run time of the timing `name` against the number of parties, with one point for each
+/// of the `experiments` (which pairs the number of parties with the experiment's statistics). Each point is the mean
+/// duration of the slowest party, with error bars for its 95% confidence interval. The chart is written to
+/// `filename` as an SVG if it ends with `.svg`, and as a PNG otherwise.
+pub fn plot_runtime_vs_parties(
+    experiments: &[(usize, &AggregatedStats)],
+    name: &str,
+    filename: &str,
+) -> io::Result<()> {
+    let chart = LineChart::new(
+        format!("{} vs. number of parties", name),
+        "Number of parties",
+        experiments
+            .iter()
+            .map(|&(n_parties, stats)| (n_parties as f64, stats)),
+        name,
+    );
+    render(&chart, filename)
+}
+
+/// Renders a line chart of the run time of the timing `name` against the network latency, like
+/// `plot_runtime_vs_parties`, where the `experiments` pair the latency with the experiment's statistics.
+pub fn plot_runtime_vs_latency(
+    experiments: &[(Duration, &AggregatedStats)],
+    name: &str,
+    filename: &str,
+) -> io::Result<()> {
+    let chart = LineChart::new(
+        format!("{} vs. latency", name),
+        "Latency (ms)",
+        experiments
+            .iter()
+            .map(|&(latency, stats)| (latency.as_secs_f64() * 1000., stats)),
+        name,
+    );
+    render(&chart, filename)
+}
+
+/// Renders a bar chart with one bar for each party, which stacks the mean durations of the party's phases: its
+/// top-level timings other than "Total" (nested timings are part of their parent). The chart is written to
+/// `filename` as an SVG if it ends with `.svg`, and as a PNG otherwise.
+pub fn plot_phases(stats: &AggregatedStats, filename: &str) -> io::Result<()> {
+    let summary = stats.summarize_timings();
+    let means = summary.statistic(Statistic::Mean);
+    let phases = summary
+        .timing_names()
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| !name.contains('/') && *name != "Total")
+        .map(|(t, name)| {
+            let party_means = means.iter().map(|means| means[t].unwrap_or(0.)).collect();
+            (name.clone(), party_means)
+        })
+        .collect();
+
+    let chart = StackedBars {
+        title: format!("Phases of {}", stats.name()),
+        party_names: stats.party_names().to_vec(),
+        phases,
+    };
+    render(&chart, filename)
+}
+
+/// A chart that can be drawn on any plotters backend.
+trait Chart {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>>;
+}
+
+/// Draws the `chart` to an SVG or PNG file named `filename`, depending on its extension.
+fn render(chart: &impl Chart, filename: &str) -> io::Result<()> {
+    if filename.ends_with(".svg") {
+        let root = SVGBackend::new(filename, SIZE).into_drawing_area();
+        chart.draw(&root).map_err(io::Error::other)?;
+        root.present().map_err(io::Error::other)
+    } else {
+        let root = BitMapBackend::new(filename, SIZE).into_drawing_area();
+        chart.draw(&root).map_err(io::Error::other)?;
+        root.present().map_err(io::Error::other)
+    }
+}
+
+/// A line chart of a timing's mean duration (with its confidence interval) against a parameter of the experiments.
+struct LineChart {
+    title: String,
+    x_label: String,
+    /// The parameter, the mean and the confidence interval (if any) of each experiment, sorted by the parameter.
+    points: Vec<(f64, f64, Option<(f64, f64)>)>,
+}
+
+impl LineChart {
+    fn new<'a>(
+        title: String,
+        x_label: &str,
+        experiments: impl Iterator<Item = (f64, &'a AggregatedStats)>,
+        name: &str,
+    ) -> Self {
+        let mut points: Vec<_> = experiments
+            .filter_map(|(x, stats)| {
+                let durations: Vec<f64> = stats
+                    .slowest_durations(name)
+                    .iter()
+                    .map(Duration::as_secs_f64)
+                    .collect();
+                (!durations.is_empty()).then(|| {
+                    (
+                        x,
+                        mean(durations.iter().cloned()),
+                        confidence_interval(&durations),
+                    )
+                })
+            })
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        LineChart {
+            title,
+            x_label: x_label.to_string(),
+            points,
+        }
+    }
+}
+
+impl Chart for LineChart {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        root.fill(&WHITE)?;
+
+        let x_min = self.points.first().map_or(0., |point| point.0);
+        let x_max = self
+            .points
+            .last()
+            .map_or(1., |point| point.0)
+            .max(x_min + 1.);
+        let y_max = self
+            .points
+            .iter()
+            .map(|&(_, mean, interval)| interval.map_or(mean, |(_, upper)| upper))
+            .fold(0., f64::max);
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(x_min..x_max, 0f64..(y_max * 1.1).max(f64::EPSILON))?;
+        chart
+            .configure_mesh()
+            .x_desc(self.x_label.as_str())
+            .y_desc("Run time (s)")
+            .draw()?;
+
+        chart.draw_series(LineSeries::new(
+            self.points.iter().map(|&(x, mean, _)| (x, mean)),
+            &BLUE,
+        ))?;
+        chart.draw_series(self.points.iter().filter_map(|&(x, mean, interval)| {
+            interval.map(|(lower, upper)| {
+                ErrorBar::new_vertical(x, lower, mean, upper, BLUE.filled(), 8)
+            })
+        }))?;
+        chart.draw_series(
+            self.points
+                .iter()
+                .map(|&(x, mean, _)| Circle::new((x, mean), 3, BLUE.filled())),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// A bar for each party that stacks the mean durations of its phases.
+struct StackedBars {
+    title: String,
+    party_names: Vec<String>,
+    /// The name of each phase together with each party's mean duration of that phase.
+    phases: Vec<(String, Vec<f64>)>,
+}
+
+impl Chart for StackedBars {
+    fn draw<DB: DrawingBackend>(
+        &self,
+        root: &DrawingArea<DB, Shift>,
+    ) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+        root.fill(&WHITE)?;
+
+        let n_parties = self.party_names.len();
+        let totals: Vec<f64> = (0..n_parties)
+            .map(|id| self.phases.iter().map(|(_, means)| means[id]).sum())
+            .collect();
+        let y_max = totals.iter().cloned().fold(0., f64::max);
+
+        let mut chart = ChartBuilder::on(root)
+            .caption(&self.title, ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(
+                (0..n_parties).into_segmented(),
+                0f64..(y_max * 1.1).max(f64::EPSILON),
+            )?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_labels(n_parties)
+            .x_label_formatter(&|value| match value {
+                SegmentValue::CenterOf(id) => {
+                    self.party_names.get(*id).cloned().unwrap_or_default()
+                }
+                _ => String::new(),
+            })
+            .y_desc("Run time (s)")
+            .draw()?;
+
+        let mut bottoms = vec![0.; n_parties];
+        for (i, (name, means)) in self.phases.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            let bars: Vec<_> = (0..n_parties)
+                .map(|id| {
+                    let mut bar = Rectangle::new(
+                        [
+                            (SegmentValue::Exact(id), bottoms[id]),
+                            (SegmentValue::Exact(id + 1), bottoms[id] + means[id]),
+                        ],
+                        color.filled(),
+                    );
+                    bar.set_margin(0, 0, 10, 10);
+                    bar
+                })
+                .collect();
+            for (bottom, mean) in bottoms.iter_mut().zip(means) {
+                *bottom += mean;
+            }
+
+            chart
+                .draw_series(bars)?
+                .label(name.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled())
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::HashMap,
+    ffi::{CStr, CString},
+    fs::{create_dir_all, File},
+    path::PathBuf,
+};
+
+use pprof::{ProfilerGuard, ProfilerGuardBuilder, Report};
+
+use crate::error::EvaluationError;
+
+/// Configures the sampling profiler that can be attached to the parties during selected repetitions.
+/// For every profiled repetition, one flamegraph is written per party and per phase (the innermost running timer).
+#[derive(Debug, Clone)]
+pub struct ProfilerConfig {
+    output_dir: PathBuf,
+    frequency: i32,
+    repetitions: Option<Vec<usize>>,
+}
+
+impl ProfilerConfig {
+    /// Constructs a profiler configuration that writes its flamegraphs to `output_dir`. By default, every repetition
+    /// is profiled at 99 samples per second.
+    pub fn new(output_dir: &str) -> Self {
+        ProfilerConfig {
+            output_dir: PathBuf::from(output_dir),
+            frequency: 99,
+            repetitions: None,
+        }
+    }
+
+    /// Sets the sampling `frequency` in samples per second.
+    pub fn with_frequency(mut self, frequency: i32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Only profiles the repetitions with the given indices (starting from 0).
+    pub fn with_repetitions(mut self, repetitions: Vec<usize>) -> Self {
+        self.repetitions = Some(repetitions);
+        self
+    }
+
+    pub(crate) fn profiles(&self, repetition: usize) -> bool {
+        match &self.repetitions {
+            Some(repetitions) => repetitions.contains(&repetition),
+            None => true,
+        }
+    }
+
+    pub(crate) fn start(&self) -> Result<ProfilerGuard<'static>, EvaluationError> {
+        Ok(ProfilerGuardBuilder::default()
+            .frequency(self.frequency)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()?)
+    }
+
+    /// Writes one flamegraph for each party and phase that was sampled during this repetition.
+    pub(crate) fn write_flamegraphs(
+        &self,
+        guard: ProfilerGuard,
+        experiment_name: &str,
+        repetition: usize,
+        party_profilers: &[PartyProfiler],
+    ) -> Result<(), EvaluationError> {
+        let report = guard.report().build()?;
+
+        let mut per_thread: HashMap<String, Report> = HashMap::new();
+        for (frames, count) in report.data {
+            per_thread
+                .entry(frames.thread_name.clone())
+                .or_insert_with(|| Report {
+                    data: HashMap::new(),
+                    timing: report.timing.clone(),
+                })
+                .data
+                .insert(frames, count);
+        }
+
+        create_dir_all(&self.output_dir)?;
+
+        for party_profiler in party_profilers {
+            for (phase_index, phase) in party_profiler.phases.iter().enumerate() {
+                if let Some(phase_report) = per_thread.get(&party_profiler.thread_name(phase_index))
+                {
+                    let filename = format!(
+                        "{}_rep{}_party{}_{}.svg",
+                        experiment_name, repetition, party_profiler.id, phase
+                    )
+                    .replace(|c: char| !c.is_alphanumeric() && c != '.' && c != '_', "-");
+
+                    let file = File::create(self.output_dir.join(filename))?;
+                    phase_report.flamegraph(file)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Tags the samples taken from one party's thread with its id and current phase. The tag is stored in the name
+/// of the thread, since that is what the sampling signal handler records. The thread (e.g. of the user's thread pool)
+/// gets its original name back once no phase is running.
+#[derive(Debug)]
+pub(crate) struct PartyProfiler {
+    id: usize,
+    phases: Vec<String>,
+    running: Vec<usize>,
+    original_name: Option<CString>,
+}
+
+impl PartyProfiler {
+    /// Constructs the profiler of the party with `id`, which must be constructed on the thread that runs the party.
+    pub(crate) fn new(id: usize) -> Self {
+        PartyProfiler {
+            id,
+            phases: vec![],
+            running: vec![],
+            original_name: thread_name(),
+        }
+    }
+
+    fn thread_name(&self, phase_index: usize) -> String {
+        format!("mpc{}.{}", self.id, phase_index)
+    }
+
+    /// Tags the thread with the innermost running phase. Once no timers are running, the tag is cleared so that the
+    /// thread's later work is not attributed to this party.
+    fn tag_thread(&self) {
+        match self.running.last() {
+            Some(&phase_index) => set_thread_name(&self.thread_name(phase_index)),
+            None => self.restore_thread_name(),
+        }
+    }
+
+    /// Gives the thread its original name back, e.g. once the party finished (or panicked while a timer was running).
+    pub(crate) fn restore_thread_name(&self) {
+        match &self.original_name {
+            Some(original_name) => name_thread(original_name),
+            None => set_thread_name("mpc-idle"),
+        }
+    }
+
+    pub(crate) fn enter_phase(&mut self, name: &str) {
+        let phase_index = match self.phases.iter().position(|phase| phase == name) {
+            Some(phase_index) => phase_index,
+            None => {
+                self.phases.push(name.to_string());
+                self.phases.len() - 1
+            }
+        };
+
+        self.running.push(phase_index);
+        self.tag_thread();
+    }
+
+    pub(crate) fn exit_phase(&mut self, name: &str) {
+        if let Some(position) = self
+            .running
+            .iter()
+            .rposition(|&phase_index| self.phases[phase_index] == name)
+        {
+            self.running.remove(position);
+        }
+
+        self.tag_thread();
+    }
+}
+
+fn set_thread_name(name: &str) {
+    // Linux limits thread names to 15 bytes (excluding the terminating zero)
+    if let Ok(name) = CString::new(&name[..name.len().min(15)]) {
+        name_thread(&name);
+    }
+}
+
+fn name_thread(name: &CStr) {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::pthread_setname_np(libc::pthread_self(), name.as_ptr());
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe {
+        libc::pthread_setname_np(name.as_ptr());
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let _ = name;
+}
+
+/// Returns the name of the calling thread, or `None` if it cannot be read on this platform.
+fn thread_name() -> Option<CString> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        // macOS allows names of up to 63 bytes (excluding the terminating zero), Linux of up to 15
+        let mut buffer = [0 as libc::c_char; 64];
+        let result = unsafe {
+            libc::pthread_getname_np(libc::pthread_self(), buffer.as_mut_ptr(), buffer.len())
+        };
+        (result == 0).then(|| unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_owned())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    None
+}
@@ -0,0 +1,286 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        mpsc::{channel, Sender},
+        Arc,
+    },
+    time::Duration,
+};
+
+use quinn::{
+    rustls::{
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+        RootCertStore,
+    },
+    ClientConfig, Connecting, Connection, Endpoint, RecvStream, SendStream, ServerConfig,
+};
+use tokio::{
+    runtime::Runtime,
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+};
+
+use crate::{
+    comm::{Channels, Message, NetworkDescription, Transport},
+    tcp::{
+        checked_content_length, decode_message, encode_message, local_transports, Connected,
+        HEADER_LENGTH,
+    },
+};
+
+/// The name under which the parties' endpoints identify themselves.
+const SERVER_NAME: &str = "localhost";
+
+/// A network description that connects every pair of parties through a QUIC connection on localhost, instead of an
+/// in-memory channel. Messages are encrypted, pass through the kernel's UDP stack and are subject to QUIC's flow and
+/// congestion control, so the measured run times include these costs. Simulated latency and throughput are added on
+/// top of them, if configured.
+///
+/// By default, the parties only start once every connection completed its (1-RTT) handshake. With `with_zero_rtt`,
+/// the connections resume an earlier session instead, so the parties start right away and their first messages are
+/// sent as 0-RTT data while the handshakes complete.
+#[derive(Debug, Default)]
+pub struct QuicNetwork {
+    latency: Duration,
+    seconds_per_byte: Duration,
+    zero_rtt: bool,
+}
+
+impl QuicNetwork {
+    /// Constructs a QuicNetwork without simulated communication overhead.
+    pub fn new() -> Self {
+        QuicNetwork::default()
+    }
+
+    /// Constructs a QuicNetwork that additionally simulates the specified `latency` and throughput (maximum
+    /// `bytes_per_second`).
+    pub fn new_with_overhead(latency: Duration, bytes_per_second: f64) -> Self {
+        QuicNetwork {
+            latency,
+            seconds_per_byte: Duration::from_secs_f64(1. / bytes_per_second),
+            zero_rtt: false,
+        }
+    }
+
+    /// Sets up the connections with 0-RTT session resumption instead of a full 1-RTT handshake.
+    pub fn with_zero_rtt(mut self) -> Self {
+        self.zero_rtt = true;
+        self
+    }
+}
+
+impl NetworkDescription for QuicNetwork {
+    /// Instantiates the Channels for each party. Panics if the connections cannot be set up.
+    fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
+        connect_parties(n_parties, self.zero_rtt)
+            .expect("could not set up the QUIC connections between the parties")
+            .into_iter()
+            .enumerate()
+            .map(|(id, (transports, receiver))| {
+                Channels::with_transports(
+                    id,
+                    transports,
+                    receiver,
+                    self.latency,
+                    self.seconds_per_byte,
+                )
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// A transport to another party over a QUIC connection. The frames are written to a unidirectional stream by a task
+/// on the runtime, which is kept alive as long as any of the transports exist.
+struct QuicTransport {
+    frames: UnboundedSender<Vec<u8>>,
+    _runtime: Arc<Runtime>,
+}
+
+impl Transport for QuicTransport {
+    fn deliver(&self, message: Message) -> bool {
+        self.frames.send(encode_message(&message)).is_ok()
+    }
+}
+
+/// Connects every pair of `n_parties` parties through a QUIC connection on localhost, each of which carries one
+/// unidirectional stream per direction.
+fn connect_parties(n_parties: usize, zero_rtt: bool) -> io::Result<Vec<Connected>> {
+    let runtime = Arc::new(Runtime::new()?);
+    let queues: Vec<_> = (0..n_parties).map(|_| channel()).collect();
+    let mut links = local_transports(&queues);
+
+    runtime.block_on(async {
+        let endpoints = bind_endpoints(n_parties)?;
+
+        for a in 0..n_parties {
+            let address = endpoints[a].local_addr()?;
+
+            for b in (a + 1)..n_parties {
+                if zero_rtt {
+                    resume_later(&endpoints[b], &endpoints[a], address).await?;
+                }
+                let (connection_b, connection_a) = tokio::try_join!(
+                    connect(&endpoints[b], address, zero_rtt),
+                    accept(&endpoints[a], zero_rtt),
+                )?;
+
+                for (id, connection, other) in [(a, connection_a, b), (b, connection_b, a)] {
+                    let stream = connection.open_uni().await.map_err(io::Error::other)?;
+                    let (frames, pending) = unbounded_channel();
+                    tokio::spawn(write_frames(stream, pending, connection.clone()));
+                    tokio::spawn(forward_messages(connection, other, queues[id].0.clone()));
+
+                    links[id][other] = Some(Box::new(QuicTransport {
+                        frames,
+                        _runtime: runtime.clone(),
+                    }));
+                }
+            }
+        }
+
+        Ok::<_, io::Error>(())
+    })?;
+
+    Ok(links
+        .into_iter()
+        .zip(queues)
+        .map(|(links, (_, receiver))| (links.into_iter().map(Option::unwrap).collect(), receiver))
+        .collect())
+}
+
+/// Binds an endpoint for each of the `n_parties` parties on localhost, which accepts connections with a freshly
+/// generated self-signed certificate and trusts only that certificate when connecting.
+fn bind_endpoints(n_parties: usize) -> io::Result<Vec<Endpoint>> {
+    let certified = rcgen::generate_simple_self_signed(vec![SERVER_NAME.to_string()])
+        .map_err(io::Error::other)?;
+    let certificate = CertificateDer::from(certified.cert);
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der()));
+
+    // The parties share one configuration, so that a session with one party can be resumed with any other
+    let server_config =
+        ServerConfig::with_single_cert(vec![certificate.clone()], key).map_err(io::Error::other)?;
+    let mut roots = RootCertStore::empty();
+    roots.add(certificate).map_err(io::Error::other)?;
+    let client_config =
+        ClientConfig::with_root_certificates(Arc::new(roots)).map_err(io::Error::other)?;
+
+    (0..n_parties)
+        .map(|_| {
+            let mut endpoint = Endpoint::server(
+                server_config.clone(),
+                SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            )?;
+            endpoint.set_default_client_config(client_config.clone());
+            Ok(endpoint)
+        })
+        .collect()
+}
+
+/// Connects `client` to the `server` at `address` once with a full handshake, so that the next connection can
+/// resume the session with 0-RTT. The server opens and finishes a stream, so that the client has received the
+/// session tickets (which follow the handshake) by the time that it read the stream.
+async fn resume_later(client: &Endpoint, server: &Endpoint, address: SocketAddr) -> io::Result<()> {
+    let (client_connection, _) = tokio::try_join!(
+        async {
+            let connection = connect(client, address, false).await?;
+            let mut stream = connection.accept_uni().await.map_err(io::Error::other)?;
+            stream.read_to_end(0).await.map_err(io::Error::other)?;
+            Ok::<_, io::Error>(connection)
+        },
+        async {
+            let connection = accept(server, false).await?;
+            let mut stream = connection.open_uni().await.map_err(io::Error::other)?;
+            stream.finish().map_err(io::Error::other)?;
+            let _ = stream.stopped().await;
+            Ok(connection)
+        },
+    )?;
+
+    client_connection.close(0u32.into(), &[]);
+    Ok(())
+}
+
+/// Connects the `endpoint` to the party at `address`. With `zero_rtt`, the connection can be used before the
+/// handshake completed, unless there was no session to resume.
+async fn connect(
+    endpoint: &Endpoint,
+    address: SocketAddr,
+    zero_rtt: bool,
+) -> io::Result<Connection> {
+    let connecting = endpoint
+        .connect(address, SERVER_NAME)
+        .map_err(io::Error::other)?;
+    establish(connecting, zero_rtt).await
+}
+
+/// Accepts the next connection to the `endpoint`.
+async fn accept(endpoint: &Endpoint, zero_rtt: bool) -> io::Result<Connection> {
+    let incoming = endpoint
+        .accept()
+        .await
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "the endpoint was closed"))?;
+    establish(incoming.accept().map_err(io::Error::other)?, zero_rtt).await
+}
+
+async fn establish(connecting: Connecting, zero_rtt: bool) -> io::Result<Connection> {
+    if zero_rtt {
+        match connecting.into_0rtt() {
+            Ok((connection, _)) => return Ok(connection),
+            Err(connecting) => return connecting.await.map_err(io::Error::other),
+        }
+    }
+    connecting.await.map_err(io::Error::other)
+}
+
+/// Writes the frames that a party sends over its link to the `stream`. Once the party dropped its link, waits until
+/// the other party read all frames, because closing the `connection` earlier would discard them.
+async fn write_frames(
+    mut stream: SendStream,
+    mut frames: UnboundedReceiver<Vec<u8>>,
+    _connection: Connection,
+) {
+    while let Some(frame) = frames.recv().await {
+        if stream.write_all(&frame).await.is_err() {
+            return;
+        }
+    }
+
+    if stream.finish().is_ok() {
+        let _ = stream.stopped().await;
+    }
+}
+
+/// Reads the messages that the party with `from_id` sends over the `connection` and puts them in the receiving
+/// party's `queue`. Stops when the stream is finished or when the receiving party stopped receiving.
+async fn forward_messages(connection: Connection, from_id: usize, queue: Sender<Message>) {
+    let Ok(mut stream) = connection.accept_uni().await else {
+        return;
+    };
+
+    while let Ok(message) = read_message(&mut stream, from_id).await {
+        if queue.send(message).is_err() {
+            return;
+        }
+    }
+}
+
+/// Reads a frame that was encoded by `encode_message`.
+async fn read_message(stream: &mut RecvStream, from_id: usize) -> io::Result<Message> {
+    let mut header = [0; HEADER_LENGTH];
+    stream
+        .read_exact(&mut header)
+        .await
+        .map_err(io::Error::other)?;
+
+    let mut contents = vec![0; checked_content_length(&header)?];
+    stream
+        .read_exact(&mut contents)
+        .await
+        .map_err(io::Error::other)?;
+
+    Ok(decode_message(&header, contents, from_id))
+}
@@ -0,0 +1,158 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, ErrorKind},
+};
+
+use crate::{error::RegressionError, statistics::AggregatedStats};
+
+/// The metrics of an experiment that later runs are checked against, e.g. to fail a CI job when a change makes a
+/// protocol slower. The metrics are those of `AggregatedStats::headline_metrics`. All metrics are costs, so a run
+/// regresses when its metrics exceed those of the baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    metrics: Vec<(String, f64)>,
+}
+
+/// The value of one metric in the baseline and in a new run (see `Baseline::diff`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDiff {
+    /// The name of the metric.
+    pub metric: String,
+    /// The value of the metric in the baseline.
+    pub baseline: f64,
+    /// The value of the metric in the new run, or `None` if the new run did not measure it.
+    pub current: Option<f64>,
+}
+
+impl MetricDiff {
+    /// Returns the change of the metric relative to the baseline (e.g. 0.1 if the new run is 10% slower), or `None`
+    /// if the new run did not measure it or the baseline is zero.
+    pub fn relative_change(&self) -> Option<f64> {
+        let current = self.current?;
+        (self.baseline != 0.).then(|| (current - self.baseline) / self.baseline)
+    }
+
+    /// Returns whether the new run exceeds the baseline by more than the relative `tolerance`, or did not measure the
+    /// metric at all. A metric that was zero in the baseline regresses when it becomes positive.
+    pub fn regressed(&self, tolerance: f64) -> bool {
+        match self.current {
+            Some(current) => current > self.baseline * (1. + tolerance),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Display for MetricDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.current, self.relative_change()) {
+            (Some(current), Some(change)) => write!(
+                f,
+                "{}: {} -> {} ({:+.1}%)",
+                self.metric,
+                self.baseline,
+                current,
+                change * 100.
+            ),
+            (Some(current), None) => write!(f, "{}: {} -> {}", self.metric, self.baseline, current),
+            (None, _) => write!(f, "{}: {} -> not measured", self.metric, self.baseline),
+        }
+    }
+}
+
+impl Baseline {
+    /// Records the metrics of the experiment with the given `stats` as a baseline.
+    pub fn record(stats: &AggregatedStats) -> Self {
+        Baseline {
+            metrics: stats.headline_metrics(),
+        }
+    }
+
+    /// Returns the name and value of each metric in this baseline.
+    pub fn metrics(&self) -> &[(String, f64)] {
+        &self.metrics
+    }
+
+    /// Saves this baseline to a csv named `csv_filename`, with one row per metric, so that it can be committed next
+    /// to the protocol implementation.
+    pub fn save(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        csv_writer.write_record(["metric", "value"])?;
+        for (metric, value) in &self.metrics {
+            csv_writer.write_record([metric, &value.to_string()])?;
+        }
+
+        csv_writer.flush()
+    }
+
+    /// Loads a baseline that was saved with `save` from the csv named `csv_filename`.
+    pub fn load(csv_filename: &str) -> io::Result<Self> {
+        let mut csv_reader = csv::Reader::from_path(csv_filename)?;
+
+        let mut metrics = vec![];
+        for record in csv_reader.records() {
+            let record = record?;
+            let value = record[1]
+                .parse()
+                .map_err(|error| io::Error::new(ErrorKind::InvalidData, error))?;
+            metrics.push((record[0].to_string(), value));
+        }
+
+        Ok(Baseline { metrics })
+    }
+
+    /// Compares every metric of this baseline with the experiment with the given `stats`.
+    pub fn diff(&self, stats: &AggregatedStats) -> Vec<MetricDiff> {
+        let current = stats.headline_metrics();
+        self.metrics
+            .iter()
+            .map(|(metric, baseline)| MetricDiff {
+                metric: metric.clone(),
+                baseline: *baseline,
+                current: current
+                    .iter()
+                    .find(|(name, _)| name == metric)
+                    .map(|&(_, value)| value),
+            })
+            .collect()
+    }
+
+    /// Checks that none of the chosen `metrics` of the experiment with the given `stats` exceeds this baseline by
+    /// more than the relative `tolerance` (e.g. 0.1 to allow runs that are up to 10% slower). Returns the diff of the
+    /// chosen metrics, or a `RegressionError` if a metric is not in this baseline or regressed.
+    pub fn check(
+        &self,
+        stats: &AggregatedStats,
+        metrics: &[&str],
+        tolerance: f64,
+    ) -> Result<Vec<MetricDiff>, RegressionError> {
+        if let Some(metric) = metrics
+            .iter()
+            .find(|metric| self.metrics.iter().all(|(name, _)| name != *metric))
+        {
+            return Err(RegressionError::UnknownMetric(metric.to_string()));
+        }
+
+        let diff: Vec<MetricDiff> = self
+            .diff(stats)
+            .into_iter()
+            .filter(|diff| metrics.contains(&diff.metric.as_str()))
+            .collect();
+        let regressions: Vec<MetricDiff> = diff
+            .iter()
+            .filter(|diff| diff.regressed(tolerance))
+            .cloned()
+            .collect();
+
+        if regressions.is_empty() {
+            Ok(diff)
+        } else {
+            Err(RegressionError::Regressed {
+                tolerance,
+                regressions,
+            })
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::channel,
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    distributed::{decode, encode, receive_frame, run_worker, send_frame, Controller},
+    error::EvaluationError,
+    statistics::AggregatedStats,
+    Party, Protocol,
+};
+
+/// One parameter point of an experiment: a `protocol` that is evaluated with `n_parties` for a number of
+/// `repetitions`, on machines that have all the required labels (e.g. "gpu" or "eu-west").
+#[derive(Debug)]
+pub struct ParameterPoint<P: Protocol> {
+    name: String,
+    protocol: P,
+    n_parties: usize,
+    repetitions: usize,
+    requirements: Vec<String>,
+}
+
+impl<P: Protocol> ParameterPoint<P> {
+    /// Constructs a parameter point named `name` that can run on any machine.
+    pub fn new(name: &str, protocol: P, n_parties: usize, repetitions: usize) -> Self {
+        ParameterPoint {
+            name: name.to_string(),
+            protocol,
+            n_parties,
+            repetitions,
+            requirements: vec![],
+        }
+    }
+
+    /// Only runs the parties of this parameter point on machines with the given `label`; every party needs such a
+    /// machine.
+    pub fn with_requirement(mut self, label: &str) -> Self {
+        self.requirements.push(label.to_string());
+        self
+    }
+}
+
+/// A message from the scheduler to a machine.
+#[derive(Serialize, Deserialize)]
+enum MachineJob {
+    /// Runs one party of the serialized protocol, coordinated by the controller listening on `controller_port` of
+    /// the scheduler's host.
+    Run {
+        controller_port: u16,
+        protocol: Vec<u8>,
+    },
+    /// Ends the schedule.
+    Stop,
+}
+
+/// How much a machine in the pool was used during a schedule.
+#[derive(Debug, Clone)]
+pub struct MachineUtilization {
+    /// The address of the machine, as seen by the scheduler.
+    pub address: SocketAddr,
+    /// The labels of the machine.
+    pub labels: Vec<String>,
+    /// The number of parties (of any parameter point) that the machine ran.
+    pub parties_run: usize,
+    /// The total time that the machine was running a party.
+    pub busy: Duration,
+}
+
+/// The results of a schedule: the statistics of each parameter point, and the utilization of each machine.
+#[derive(Debug)]
+pub struct Schedule {
+    /// The result of each parameter point, in the order in which the points were queued.
+    pub results: Vec<(String, Result<AggregatedStats, EvaluationError>)>,
+    /// The utilization of each machine, in the order in which the machines connected.
+    pub utilization: Vec<MachineUtilization>,
+    /// The time that the whole schedule took.
+    pub elapsed: Duration,
+}
+
+impl Schedule {
+    /// Prints the fraction of the schedule that each machine was busy.
+    pub fn print_utilization(&self) {
+        for machine in &self.utilization {
+            println!(
+                "{} [{}]: {} parties, busy {:.1}%",
+                machine.address,
+                machine.labels.join(", "),
+                machine.parties_run,
+                100. * machine.busy.as_secs_f64() / self.elapsed.as_secs_f64()
+            );
+        }
+    }
+}
+
+/// The scheduler of a pool of machines, each of which runs `run_machine`. The scheduler queues parameter points and
+/// runs each of them as a distributed experiment as soon as enough idle machines meet its requirements. Points that
+/// cannot start yet do not hold back later points that can.
+pub struct Scheduler {
+    listener: TcpListener,
+    n_machines: usize,
+}
+
+/// A machine in the pool, as tracked by the scheduler.
+struct Machine {
+    stream: TcpStream,
+    labels: Vec<String>,
+    busy_since: Option<Instant>,
+    utilization: MachineUtilization,
+}
+
+impl Scheduler {
+    /// Binds the scheduler to `address`, on which it waits for `n_machines` machines.
+    pub fn bind<A: ToSocketAddrs>(address: A, n_machines: usize) -> io::Result<Self> {
+        Ok(Scheduler {
+            listener: TcpListener::bind(address)?,
+            n_machines,
+        })
+    }
+
+    /// Returns the address that the machines should connect to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Waits for all machines to connect, and then runs all parameter `points`, collecting their results centrally.
+    /// A point that needs more machines with its labels than there are in the pool fails without running.
+    pub fn run<P>(&self, points: Vec<ParameterPoint<P>>) -> Result<Schedule, EvaluationError>
+    where
+        P: Protocol + Serialize + Send + Sync,
+        <P::Party as Party>::Input: Serialize,
+        <P::Party as Party>::Output: DeserializeOwned,
+    {
+        let mut machines = vec![];
+        for _ in 0..self.n_machines {
+            let (mut stream, address) = self.listener.accept()?;
+            let labels: Vec<String> = receive_frame(&mut stream)?;
+            machines.push(Machine {
+                stream,
+                labels: labels.clone(),
+                busy_since: None,
+                utilization: MachineUtilization {
+                    address,
+                    labels,
+                    parties_run: 0,
+                    busy: Duration::ZERO,
+                },
+            });
+        }
+
+        let start = Instant::now();
+        let mut results: Vec<Option<Result<AggregatedStats, EvaluationError>>> =
+            points.iter().map(|_| None).collect();
+        let mut pending: Vec<usize> = (0..points.len()).collect();
+        let (done, finished) = channel();
+
+        // The controllers listen on the same interface as the scheduler
+        let ip = self.listener.local_addr()?.ip();
+
+        thread::scope(|scope| -> Result<(), EvaluationError> {
+            let mut running = 0;
+            loop {
+                let mut index = 0;
+                while index < pending.len() {
+                    let point = &points[pending[index]];
+                    let eligible = |machine: &Machine| {
+                        point
+                            .requirements
+                            .iter()
+                            .all(|label| machine.labels.contains(label))
+                    };
+
+                    if machines.iter().filter(|machine| eligible(machine)).count() < point.n_parties
+                    {
+                        results[pending.remove(index)] = Some(Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("too few machines meet the requirements of '{}'", point.name),
+                        )
+                        .into()));
+                        continue;
+                    }
+
+                    let idle: Vec<usize> = (0..machines.len())
+                        .filter(|&m| machines[m].busy_since.is_none() && eligible(&machines[m]))
+                        .take(point.n_parties)
+                        .collect();
+                    if idle.len() < point.n_parties {
+                        index += 1;
+                        continue;
+                    }
+
+                    let controller = Controller::bind((ip, 0), point.n_parties)?;
+                    let job = MachineJob::Run {
+                        controller_port: controller.local_addr()?.port(),
+                        protocol: encode(&point.protocol)?,
+                    };
+                    for &m in &idle {
+                        send_frame(&mut machines[m].stream, &job)?;
+                        machines[m].busy_since = Some(Instant::now());
+                        machines[m].utilization.parties_run += 1;
+                    }
+
+                    let point_index = pending.remove(index);
+                    let done = done.clone();
+                    scope.spawn(move || {
+                        let result = controller.evaluate(
+                            &point.protocol,
+                            point.name.clone(),
+                            point.repetitions,
+                        );
+                        let _ = done.send((point_index, result, idle));
+                    });
+                    running += 1;
+                }
+
+                if running == 0 {
+                    return Ok(());
+                }
+
+                let (point_index, result, idle) = finished.recv().unwrap();
+                results[point_index] = Some(result);
+                for m in idle {
+                    if let Some(busy_since) = machines[m].busy_since.take() {
+                        machines[m].utilization.busy += busy_since.elapsed();
+                    }
+                }
+                running -= 1;
+            }
+        })?;
+
+        for machine in machines.iter_mut() {
+            let _ = send_frame(&mut machine.stream, &MachineJob::Stop);
+        }
+
+        Ok(Schedule {
+            results: points
+                .iter()
+                .zip(results)
+                .map(|(point, result)| (point.name.clone(), result.unwrap()))
+                .collect(),
+            utilization: machines
+                .into_iter()
+                .map(|machine| machine.utilization)
+                .collect(),
+            elapsed: start.elapsed(),
+        })
+    }
+}
+
+/// Offers this machine, which has the given `labels`, to the pool of the scheduler at `scheduler`. The machine runs
+/// one party at a time of the parameter points that the scheduler assigns to it, until the scheduler ends the
+/// schedule.
+pub fn run_machine<P, A>(scheduler: A, labels: &[&str]) -> Result<(), EvaluationError>
+where
+    P: Protocol + DeserializeOwned,
+    <P::Party as Party>::Input: DeserializeOwned,
+    <P::Party as Party>::Output: Serialize,
+    A: ToSocketAddrs,
+{
+    let mut scheduler = TcpStream::connect(scheduler)?;
+    let labels: Vec<String> = labels.iter().map(|label| label.to_string()).collect();
+    send_frame(&mut scheduler, &labels)?;
+
+    let scheduler_ip = scheduler.peer_addr()?.ip();
+    loop {
+        match receive_frame(&mut scheduler)? {
+            MachineJob::Run {
+                controller_port,
+                protocol,
+            } => {
+                let protocol: P = decode(&protocol)?;
+                run_worker(
+                    &protocol,
+                    (scheduler_ip, controller_port),
+                    (Ipv4Addr::UNSPECIFIED, 0),
+                )?;
+            }
+            MachineJob::Stop => return Ok(()),
+        }
+    }
+}
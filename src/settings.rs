@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+#[cfg(feature = "dashboard")]
+use crate::dashboard::Dashboard;
+#[cfg(feature = "profiling")]
+use crate::profiling::ProfilerConfig;
+use crate::{evaluation::derive_seed, monitoring::MetricsEndpoint, statistics::AggregatedStats};
+
+/// Optional settings for evaluating a protocol, see `Protocol::evaluate_with_settings`. The default settings are
+/// the ones used by `Protocol::evaluate`.
+#[derive(Debug, Clone, Default)]
+pub struct EvaluationSettings {
+    pub(crate) snapshot_interval: Option<Duration>,
+    pub(crate) round_padding: bool,
+    pub(crate) warmup_repetitions: usize,
+    pub(crate) record_invalid: bool,
+    pub(crate) tracing: bool,
+    pub(crate) message_size_buckets: Option<Vec<usize>>,
+    pub(crate) round_statistics: bool,
+    pub(crate) metrics_endpoint: Option<MetricsEndpoint>,
+    #[cfg(feature = "dashboard")]
+    pub(crate) dashboard: Option<Dashboard>,
+    pub(crate) precision_target: Option<PrecisionTarget>,
+    pub(crate) core_budget: Option<usize>,
+    pub(crate) repetition_timeout: Option<Duration>,
+    pub(crate) seed: Option<u64>,
+    /// The seed of every measured repetition when replaying one (see `Protocol::replay`).
+    pub(crate) replayed_seed: Option<u64>,
+    #[cfg(feature = "persistence")]
+    pub(crate) checkpoint: Option<String>,
+    #[cfg(feature = "profiling")]
+    pub(crate) profiler: Option<ProfilerConfig>,
+    #[cfg(feature = "priority")]
+    pub(crate) niceness: Vec<(usize, i32)>,
+}
+
+impl EvaluationSettings {
+    /// Constructs the default evaluation settings.
+    pub fn new() -> Self {
+        EvaluationSettings::default()
+    }
+
+    /// Takes a snapshot of each party's progress (bytes sent, completed timers and current phase) every `interval`
+    /// during each repetition, so that long-running protocols can be analyzed as a time series.
+    pub fn with_snapshot_interval(mut self, interval: Duration) -> Self {
+        self.snapshot_interval = Some(interval);
+        self
+    }
+
+    /// Pads every message to the maximum size of all messages sent in the same round (see `Channels::round`), so
+    /// that constant-communication-pattern variants of a protocol can be evaluated. The harness first runs one
+    /// unpadded repetition that is not part of the results to determine the size of each round. Messages that are
+    /// larger than any message sent in the same round of that repetition are not padded. Padding only affects the
+    /// costs: the receiver gets the original message.
+    pub fn with_round_padding(mut self) -> Self {
+        self.round_padding = true;
+        self
+    }
+
+    /// Runs `warmup_repetitions` full repetitions before the measured ones, whose statistics are discarded. This
+    /// amortizes one-time costs such as page faults, lazy allocations and spinning up the thread pool, which would
+    /// otherwise inflate the first measured repetition. The outputs of the warmup repetitions are not validated.
+    pub fn with_warmup(mut self, warmup_repetitions: usize) -> Self {
+        self.warmup_repetitions = warmup_repetitions;
+        self
+    }
+
+    /// Records the repetitions whose outputs or transcript fail validation (see `Protocol::validate_outputs` and
+    /// `Protocol::observe`) as invalid in the statistics, instead of aborting the evaluation with a
+    /// `ValidationError`. The number of invalid repetitions is reported in the timing summary, and
+    /// `AggregatedStats::summarize_valid_timings` excludes them. Likewise, a repetition in which a party used its
+    /// channels incorrectly is skipped and its `ProtocolError` is recorded (see `AggregatedStats::protocol_errors`).
+    pub fn with_invalid_repetitions_recorded(mut self) -> Self {
+        self.record_invalid = true;
+        self
+    }
+
+    /// Records a timeline of each party's timers and sent and received messages in every repetition, which
+    /// `AggregatedStats::write_chrome_trace` exports. Every message adds an event, so this is off by default.
+    pub fn with_tracing(mut self) -> Self {
+        self.tracing = true;
+        self
+    }
+
+    /// Counts the messages that each party sends in a histogram of their sizes, with a bucket for the messages of at
+    /// most each of the `bucket_bounds` bytes (that are not in a smaller bucket) and one for larger messages. This
+    /// shows whether a protocol sends many tiny messages, whose cost is dominated by the per-message overhead, or a
+    /// few huge ones (see `AggregatedStats::summarize_message_sizes`). Panics if the bounds are not increasing.
+    pub fn with_message_size_histogram(mut self, bucket_bounds: Vec<usize>) -> Self {
+        assert!(
+            bucket_bounds.windows(2).all(|bounds| bounds[0] < bounds[1]),
+            "the bucket bounds must be increasing"
+        );
+        self.message_size_buckets = Some(bucket_bounds);
+        self
+    }
+
+    /// Records the duration of each round of each party and the bytes that it sent and received in that round (see
+    /// `Channels::round`), so that the rounds that dominate a protocol can be identified (see
+    /// `AggregatedStats::summarize_rounds`). A round lasts until the party's next round starts, and its last round
+    /// lasts until the party finishes.
+    pub fn with_round_statistics(mut self) -> Self {
+        self.round_statistics = true;
+        self
+    }
+
+    /// Reports the progress of every repetition to the Prometheus `endpoint`, e.g. to monitor a multi-hour sweep.
+    pub fn with_metrics_endpoint(mut self, endpoint: MetricsEndpoint) -> Self {
+        self.metrics_endpoint = Some(endpoint);
+        self
+    }
+
+    /// Shows the progress of every repetition live on the `dashboard`, e.g. to see why an experiment is slower than
+    /// expected.
+    #[cfg(feature = "dashboard")]
+    pub fn with_dashboard(mut self, dashboard: Dashboard) -> Self {
+        self.dashboard = Some(dashboard);
+        self
+    }
+
+    /// Keeps running repetitions until the 95% confidence interval of the mean duration of the timing `name` is at
+    /// most `relative_width` times that mean (e.g. 0.05 for ±2.5%), instead of running a fixed number of repetitions.
+    /// Each repetition contributes the duration of its slowest party. The `repetitions` passed to
+    /// `Protocol::evaluate_with_settings` are still run at least, and at most `max_repetitions` are run. Pipelines
+    /// always run their fixed number of repetitions.
+    pub fn with_precision_target(
+        mut self,
+        name: &str,
+        relative_width: f64,
+        max_repetitions: usize,
+    ) -> Self {
+        self.precision_target = Some(PrecisionTarget {
+            name: name.to_string(),
+            relative_width,
+            max_repetitions,
+        });
+        self
+    }
+
+    /// Runs independent repetitions concurrently, as many as fit in `core_budget` cores when each party of a
+    /// repetition takes a core, but at least one. This saves a lot of time for protocols that are bound by the
+    /// latency of the network and keep the CPU idle, but the repetitions compete for the CPU, so the timings of
+    /// CPU-heavy protocols become less accurate. Each concurrent repetition has parties of its own (set up once), and
+    /// the statistics are still incorporated in the order of the repetitions. Pipelines always run their repetitions
+    /// sequentially.
+    pub fn with_parallel_repetitions(mut self, core_budget: usize) -> Self {
+        self.core_budget = Some(core_budget);
+        self
+    }
+
+    /// Aborts a repetition when its parties did not complete it within the `timeout`, counts it as timed out in the
+    /// statistics (see `AggregatedStats::timed_out_repetitions`) and continues with the next repetition, so that e.g.
+    /// a livelocked parameter combination does not hang a whole sweep. The parties are aborted by failing their
+    /// channels, so a party stops once it waits for or sends a message, and simulated delays (e.g.
+    /// `Timings::simulate_compute`) end at the time limit. A party that computes without using its channels cannot be
+    /// interrupted, so it runs until it does. A timed out warmup repetition is ignored, and pipelines fail with the
+    /// timeout instead.
+    pub fn with_repetition_timeout(mut self, timeout: Duration) -> Self {
+        self.repetition_timeout = Some(timeout);
+        self
+    }
+
+    /// Generates the inputs of each repetition from a seed that is derived from the master `seed` and the index of
+    /// the repetition, so that an evaluation with the same master seed generates the same inputs. The seed of each
+    /// measured repetition is recorded in the statistics (see `AggregatedStats::seeds`). By default, the master seed
+    /// is random.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the master seed of an evaluation, which is random if it was not set (see `with_seed`).
+    pub(crate) fn master_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(rand::random)
+    }
+
+    /// Returns the seed of the inputs of the `repetition` of an evaluation with the `master_seed` (see
+    /// `derive_seed`), or the replayed seed when replaying a repetition.
+    pub(crate) fn repetition_seed(&self, master_seed: u64, repetition: usize) -> u64 {
+        self.replayed_seed
+            .unwrap_or_else(|| derive_seed(master_seed, repetition))
+    }
+
+    /// Returns how many repetitions with `n_parties` run concurrently.
+    pub(crate) fn concurrent_repetitions(&self, n_parties: usize) -> usize {
+        self.core_budget
+            .map_or(1, |core_budget| (core_budget / n_parties.max(1)).max(1))
+    }
+
+    /// Saves the statistics to the file named `checkpoint` after every measured repetition, and resumes from the
+    /// repetitions in this file if it already exists (see `AggregatedStats::save`), so that a crash or an interrupt
+    /// during a long experiment does not lose the completed repetitions. The file must have been saved by the same
+    /// experiment with the same parties. Pipelines do not save checkpoints.
+    #[cfg(feature = "persistence")]
+    pub fn with_checkpoint(mut self, checkpoint: &str) -> Self {
+        self.checkpoint = Some(checkpoint.to_string());
+        self
+    }
+
+    /// Returns whether another repetition should run after `completed` repetitions, given the minimum number of
+    /// `repetitions` and the `stats` gathered so far.
+    pub(crate) fn needs_repetition(
+        &self,
+        completed: usize,
+        repetitions: usize,
+        stats: &AggregatedStats,
+    ) -> bool {
+        if completed < repetitions {
+            return true;
+        }
+
+        match &self.precision_target {
+            Some(target) if completed < target.max_repetitions => stats
+                .relative_confidence_width(&target.name)
+                .is_none_or(|width| width > target.relative_width),
+            _ => false,
+        }
+    }
+
+    /// Samples the parties' threads as configured by `profiler`.
+    #[cfg(feature = "profiling")]
+    pub fn with_profiler(mut self, profiler: ProfilerConfig) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Runs the party with id `party` at the given OS scheduling `niceness` (from -20 to 19, where higher values
+    /// mean a lower priority). Deprioritizing one party shows the effect of an under-provisioned participant on the
+    /// protocol's latency on a single machine. Negative values typically require elevated privileges, and setting the
+    /// niceness is only supported on Linux. When any niceness is set, every party runs on a thread of its own, so the
+    /// niceness never carries over to other parties.
+    #[cfg(feature = "priority")]
+    pub fn with_niceness(mut self, party: usize, niceness: i32) -> Self {
+        self.niceness.retain(|&(id, _)| id != party);
+        self.niceness.push((party, niceness));
+        self
+    }
+
+    #[cfg(feature = "priority")]
+    pub(crate) fn niceness(&self, party: usize) -> Option<i32> {
+        self.niceness
+            .iter()
+            .find(|&&(id, _)| id == party)
+            .map(|&(_, niceness)| niceness)
+    }
+}
+
+/// The precision that the repetitions of an evaluation should achieve (see `EvaluationSettings::with_precision_target`).
+#[derive(Debug, Clone)]
+pub(crate) struct PrecisionTarget {
+    name: String,
+    relative_width: f64,
+    max_repetitions: usize,
+}
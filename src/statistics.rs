@@ -1,51 +1,421 @@
 use std::{
     collections::HashMap,
-    fs::File,
+    fmt::{self, Display},
+    fs::{create_dir_all, File},
+    io::{self, BufWriter, Write},
+    ops::{Deref, DerefMut},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{park_timeout, sleep},
     time::{Duration, Instant},
 };
 
+#[cfg(feature = "persistence")]
+use serde::{Deserialize, Serialize};
 use stats::{mean, stddev};
+#[cfg(feature = "persistence")]
+use std::io::BufReader;
 use tabled::{builder::Builder, Style};
 
+#[cfg(feature = "profiling")]
+use crate::profiling::PartyProfiler;
+use crate::{
+    comm::CommCheckpoint,
+    cost::{CostModel, EnergyModel, Usage},
+    error::{MergeError, ProtocolError},
+    watchdog::Watchdog,
+};
+
 #[derive(Debug)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
 /// Contains the aggregated statistics for multiple repetitions of the same experiment.
 pub struct AggregatedStats {
-    _name: String,
+    name: String,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    parameters: Option<String>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    params: Vec<(String, String)>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    network: Option<String>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    dataset: Option<String>,
     party_names: Vec<String>,
     timings: Vec<Vec<Timings>>,
+    makespans: Vec<Duration>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    seeds: Vec<u64>,
+    invalid_repetitions: Vec<usize>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    timed_out_repetitions: usize,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    protocol_errors: Vec<ProtocolError>,
+    snapshots: Vec<Vec<Snapshot>>,
+}
+
+/// A statistic of a timing's durations over the repetitions, which `TimingSummary::print_statistics` can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    /// The mean, which is reported together with its 95% confidence interval.
+    Mean,
+    /// The median (50th percentile).
+    Median,
+    /// The 95th percentile.
+    P95,
+    /// The shortest duration.
+    Min,
+    /// The longest duration.
+    Max,
+}
+
+impl Display for Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Statistic::Mean => "mean",
+            Statistic::Median => "median",
+            Statistic::P95 => "p95",
+            Statistic::Min => "min",
+            Statistic::Max => "max",
+        })
+    }
+}
+
+/// The raw statistics of one repetition of an experiment (see `AggregatedStats::repetitions`).
+#[derive(Debug, Clone, Copy)]
+pub struct RepetitionStats<'a> {
+    index: usize,
+    makespan: Option<Duration>,
+    seed: Option<u64>,
+    parties: &'a [Timings],
+}
+
+impl<'a> RepetitionStats<'a> {
+    /// Returns the index of this repetition, starting from 0.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the makespan of this repetition, if it was incorporated (see `AggregatedStats::makespans`).
+    pub fn makespan(&self) -> Option<Duration> {
+        self.makespan
+    }
+
+    /// Returns the seed that the inputs of this repetition were generated from, if it was incorporated (see
+    /// `AggregatedStats::seeds`).
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the statistics of each party in this repetition, indexed by party id.
+    pub fn parties(&self) -> &'a [Timings] {
+        self.parties
+    }
 }
 
-/// The names, means and standard deviations of all parties' measured run times.
+/// The names, means and standard deviations of all parties' measured run times, together with the means and
+/// standard deviations of the bytes that they sent and received and of the repetitions' makespans.
 pub struct TimingSummary {
     timing_names: Vec<String>,
+    makespan: Option<(f64, f64)>,
+    invalid_repetitions: usize,
+    timed_out_repetitions: usize,
+    protocol_errors: usize,
     party_names: Vec<String>,
     party_means: Vec<Vec<Option<f64>>>,
     party_stdevs: Vec<Vec<Option<f64>>>,
+    party_durations: Vec<Vec<Vec<f64>>>,
+    party_phase_bytes: Vec<Vec<Option<f64>>>,
+    party_cpu_means: Vec<Vec<Option<f64>>>,
+    sent_bytes: Vec<(f64, f64)>,
+    received_bytes: Vec<(f64, f64)>,
+    peer_sent_bytes: Vec<Vec<(f64, f64)>>,
 }
 
 impl TimingSummary {
-    /// Prints a pretty table of the summarized timings.
+    /// Returns the `statistic` of each party's durations for each timing (in the order of the timings), or `None` if
+    /// the party did not measure the timing. The percentiles are interpolated linearly between the closest durations.
+    pub fn statistic(&self, statistic: Statistic) -> Vec<Vec<Option<f64>>> {
+        self.party_durations
+            .iter()
+            .map(|durations| {
+                durations
+                    .iter()
+                    .map(|durations| {
+                        let mut sorted = durations.clone();
+                        sorted.sort_by(f64::total_cmp);
+                        (!sorted.is_empty()).then(|| match statistic {
+                            Statistic::Mean => mean(sorted.iter().cloned()),
+                            Statistic::Median => percentile(&sorted, 0.5),
+                            Statistic::P95 => percentile(&sorted, 0.95),
+                            Statistic::Min => sorted[0],
+                            Statistic::Max => sorted[sorted.len() - 1],
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the names of the summarized timings, in the order in which they were first measured.
+    pub fn timing_names(&self) -> &[String] {
+        &self.timing_names
+    }
+
+    /// Returns the number of repetitions whose outputs were invalid (see `AggregatedStats::invalid_repetitions`).
+    pub fn invalid_repetitions(&self) -> usize {
+        self.invalid_repetitions
+    }
+
+    /// Returns the number of repetitions that were aborted because they exceeded their time limit (see
+    /// `AggregatedStats::timed_out_repetitions`).
+    pub fn timed_out_repetitions(&self) -> usize {
+        self.timed_out_repetitions
+    }
+
+    /// Returns the number of repetitions that were skipped because a party used its channels incorrectly (see
+    /// `AggregatedStats::protocol_errors`).
+    pub fn protocol_errors(&self) -> usize {
+        self.protocol_errors
+    }
+
+    /// Returns the mean and standard deviation of the repetitions' makespans (see `AggregatedStats::makespans`), or
+    /// `None` if no makespans were incorporated.
+    pub fn makespan(&self) -> Option<(f64, f64)> {
+        self.makespan
+    }
+
+    /// Returns the mean number of bytes that each party sent while each of its timers was running (in the order of
+    /// the timings), or `None` if the party did not measure the timing or its bytes were not tracked.
+    pub fn phase_sent_bytes(&self) -> &[Vec<Option<f64>>] {
+        &self.party_phase_bytes
+    }
+
+    /// Returns the mean CPU time that each party's thread consumed while each of its timers was running (in the order
+    /// of the timings), or `None` if the party did not measure the timing or its CPU time was not tracked (see
+    /// `AggregatedStats::cpu_durations`).
+    pub fn cpu_means(&self) -> &[Vec<Option<f64>>] {
+        &self.party_cpu_means
+    }
+
+    /// Returns the mean and standard deviation of the total number of bytes that each party sent per repetition.
+    pub fn sent_bytes(&self) -> &[(f64, f64)] {
+        &self.sent_bytes
+    }
+
+    /// Returns the mean and standard deviation of the total number of bytes that each party received per
+    /// repetition.
+    pub fn received_bytes(&self) -> &[(f64, f64)] {
+        &self.received_bytes
+    }
+
+    /// Returns the mean and standard deviation of the number of bytes that each party sent to each party per
+    /// repetition, indexed by the sender's id and then by the recipient's id.
+    pub fn peer_sent_bytes(&self) -> &[Vec<(f64, f64)>] {
+        &self.peer_sent_bytes
+    }
+
+    /// Prints a pretty table of the summarized timings and bandwidth and the makespan, followed by a table of the
+    /// bytes that each party sent to each other party. The timings are printed as their mean and its 95% confidence
+    /// interval (see `confidence_intervals`), and the bytes as their mean and standard deviation. Each cell is printed
+    /// in the unit that suits its mean (µs, ms or s and B, KB, MB or GB), so that short phases remain readable.
     pub fn print(&self) {
+        let mut builder = Builder::default();
+        for record in self.records() {
+            builder.add_record(record);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+        if let Some((mean, stdev)) = self.makespan {
+            println!("Makespan: {}", format_duration_with_stdev(mean, stdev));
+        }
+        if self.invalid_repetitions > 0 {
+            println!("Invalid repetitions: {}", self.invalid_repetitions);
+        }
+        if self.timed_out_repetitions > 0 {
+            println!("Timed out repetitions: {}", self.timed_out_repetitions);
+        }
+        if self.protocol_errors > 0 {
+            println!("Protocol errors: {}", self.protocol_errors);
+        }
+
         let mut builder = Builder::default();
 
         // Add header
         builder.add_record(
-            ["Parties".to_string()]
+            ["Sent to".to_string()]
                 .into_iter()
-                .chain(self.timing_names.iter().cloned()),
+                .chain(self.party_names.iter().cloned()),
         );
 
-        // Add each party's data
-        for ((means, stdevs), party_name) in self
+        // Add each sender's data
+        for (party_name, peers) in self.party_names.iter().zip(&self.peer_sent_bytes) {
+            builder.add_record(
+                [party_name.clone()].into_iter().chain(
+                    peers
+                        .iter()
+                        .map(|&(mean, stdev)| format_bytes_with_stdev(mean, stdev)),
+                ),
+            );
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+
+    /// Renders the table of summarized timings and bandwidth of `print` as a Markdown table, e.g. for a README.
+    pub fn to_markdown(&self) -> String {
+        let records = self.records();
+        let mut lines: Vec<String> = records
+            .iter()
+            .map(|record| {
+                let cells: Vec<String> =
+                    record.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                format!("| {} |", cells.join(" | "))
+            })
+            .collect();
+        lines.insert(1, format!("|{}", "---|".repeat(records[0].len())));
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Renders the table of summarized timings and bandwidth of `print` as a LaTeX table in the style of the
+    /// `booktabs` package, e.g. for a paper.
+    pub fn to_latex(&self) -> String {
+        let records = self.records();
+        let rows: Vec<String> = records
+            .iter()
+            .map(|record| {
+                let cells: Vec<String> = record.iter().map(|cell| escape_latex(cell)).collect();
+                format!("{} \\\\", cells.join(" & "))
+            })
+            .collect();
+
+        let mut lines = vec![
+            format!(
+                "\\begin{{tabular}}{{l{}}}",
+                "r".repeat(records[0].len() - 1)
+            ),
+            "\\toprule".to_string(),
+            rows[0].clone(),
+            "\\midrule".to_string(),
+        ];
+        lines.extend(rows[1..].iter().cloned());
+        lines.push("\\bottomrule".to_string());
+        lines.push("\\end{tabular}".to_string());
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Returns the header and each party's row of the table of summarized timings and bandwidth (see `print`).
+    fn records(&self) -> Vec<Vec<String>> {
+        let intervals = self.confidence_intervals();
+
+        let header = ["Parties".to_string()]
+            .into_iter()
+            .chain(self.timing_names.iter().cloned())
+            .chain(["Sent".to_string(), "Received".to_string()])
+            .collect();
+        let mut records = vec![header];
+
+        for ((((((means, intervals), phase_bytes), cpu_means), party_name), sent), received) in self
             .party_means
             .iter()
-            .zip(&self.party_stdevs)
+            .zip(&intervals)
+            .zip(&self.party_phase_bytes)
+            .zip(&self.party_cpu_means)
             .zip(&self.party_names)
+            .zip(&self.sent_bytes)
+            .zip(&self.received_bytes)
         {
+            records.push(
+                [party_name.clone()]
+                    .into_iter()
+                    .chain(
+                        means
+                            .iter()
+                            .zip(intervals)
+                            .zip(phase_bytes)
+                            .zip(cpu_means)
+                            .map(|data| match data {
+                                (((&Some(mean), &interval), bytes), cpu_mean) => {
+                                    let mut cell = format_mean(mean, interval);
+                                    if let Some(cpu_mean) = cpu_mean {
+                                        cell += &format!(" ({} CPU)", format_duration(*cpu_mean));
+                                    }
+                                    if let Some(bytes) = bytes {
+                                        cell += &format!(", {}", format_bytes(*bytes));
+                                    }
+                                    cell
+                                }
+                                _ => "".to_string(),
+                            }),
+                    )
+                    .chain(
+                        [sent, received].map(|&(mean, stdev)| format_bytes_with_stdev(mean, stdev)),
+                    )
+                    .collect(),
+            );
+        }
+
+        records
+    }
+
+    /// Returns the 95% confidence interval of the mean of each party's durations for each timing (in the order of the
+    /// timings) as its lower and upper bound, or `None` if the party measured the timing in fewer than two
+    /// repetitions. The intervals follow Student's t-distribution, so they are also valid for few repetitions.
+    pub fn confidence_intervals(&self) -> Vec<Vec<Option<(f64, f64)>>> {
+        self.party_durations
+            .iter()
+            .map(|durations| {
+                durations
+                    .iter()
+                    .map(|durations| confidence_interval(durations))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Prints a pretty table of the summarized timings, in which each cell reports the given `statistics` of a party's
+    /// durations (e.g. the median and the 95th percentile, which reveal tail latency that the mean hides).
+    pub fn print_statistics(&self, statistics: &[Statistic]) {
+        let values: Vec<_> = statistics
+            .iter()
+            .map(|&statistic| self.statistic(statistic))
+            .collect();
+        let intervals = self.confidence_intervals();
+
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["Parties".to_string()]
+                .into_iter()
+                .chain(self.timing_names.iter().cloned()),
+        );
+
+        // Add each party's data
+        for (i, party_name) in self.party_names.iter().enumerate() {
             builder.add_record([party_name.clone()].into_iter().chain(
-                means.iter().zip(stdevs).map(|data| match data {
-                    (&Some(mean), &Some(stdev)) => format!("{:.3} ± {:.3} s", mean, stdev),
-                    _ => "".to_string(),
+                (0..self.timing_names.len()).map(|t| {
+                    statistics
+                        .iter()
+                        .zip(&values)
+                        .filter_map(|(statistic, values)| {
+                            let value = values[i][t]?;
+                            Some(match statistic {
+                                Statistic::Mean => {
+                                    format!("mean {}", format_mean(value, intervals[i][t]))
+                                }
+                                _ => format!("{} {}", statistic, format_duration(value)),
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
                 }),
             ));
         }
@@ -54,152 +424,3420 @@ impl TimingSummary {
 
         println!("{}", table);
     }
-}
 
-impl AggregatedStats {
-    /// Constructs `AggregatedStats` with the given name for tracking statistics.
-    pub fn new(name: String, party_names: Vec<String>) -> Self {
-        AggregatedStats {
-            _name: name,
-            party_names,
-            timings: vec![],
+    /// Returns the timings as a hierarchy, in which the timing `a/b` is nested in the timing `a` (see
+    /// `Timings::create_nested_timer`). Each timing is returned with its depth, after its parent and before the
+    /// next timing at the same depth. Parents that were not measured themselves are included, so that their nested
+    /// timings can be grouped. Siblings appear in the order in which they were first measured.
+    pub fn hierarchy(&self) -> Vec<(usize, String)> {
+        let mut paths: Vec<String> = vec![];
+        for name in &self.timing_names {
+            let mut path = String::new();
+            for (depth, component) in name.split('/').enumerate() {
+                if depth > 0 {
+                    path.push('/');
+                }
+                path.push_str(component);
+                if !paths.contains(&path) {
+                    paths.push(path.clone());
+                }
+            }
         }
-    }
 
-    /// Incorporates each party's resulting statistics into this aggregate.
-    pub fn incorporate_party_stats(&mut self, party_stats: Vec<Timings>) {
-        self.timings.push(party_stats);
+        // Sorting by the positions of a path's ancestors keeps every subtree together, in first-measured order
+        let position = |path: &str| paths.iter().position(|p| p == path).unwrap();
+        let mut keyed: Vec<(Vec<usize>, String)> = paths
+            .iter()
+            .map(|path| {
+                let key = path
+                    .match_indices('/')
+                    .map(|(i, _)| position(&path[..i]))
+                    .chain([position(path)])
+                    .collect();
+                (key, path.clone())
+            })
+            .collect();
+        keyed.sort();
+
+        keyed
+            .into_iter()
+            .map(|(key, path)| (key.len() - 1, path))
+            .collect()
     }
 
-    // TODO: These methods have many underlying assumptions and are not ergonomic.
-    /// Outputs one party's timings to a csv named `csv_filename`.
-    pub fn output_party_csv(&self, party_id: usize, csv_filename: &str) {
-        // Open CSV file
-        let writer = File::create(csv_filename).unwrap();
-        let mut csv_writer = csv::Writer::from_writer(writer);
+    /// Prints a pretty table of the summarized timings as a hierarchy (see `hierarchy`), with one row per timing and
+    /// one column per party. Nested timings are indented below their parent.
+    pub fn print_hierarchy(&self) {
+        let intervals = self.confidence_intervals();
+        let mut builder = Builder::default();
 
-        // Write header
-        let headers: Vec<String> = self.timings[0][party_id]
-            .measured_durations
-            .iter()
-            .map(|(name, _)| name.clone())
-            .collect();
-        csv_writer.write_record(&headers).unwrap();
+        // Add header
+        builder.add_record(
+            ["Timings".to_string()]
+                .into_iter()
+                .chain(self.party_names.iter().cloned()),
+        );
 
-        for party_timings in &self.timings {
-            let durations: Vec<String> = party_timings[party_id]
-                .measured_durations
-                .iter()
-                .map(|(_, dur)| dur.as_micros().to_string())
-                .collect();
-            csv_writer.write_record(&durations).unwrap();
+        // Add each timing's data
+        for (depth, path) in self.hierarchy() {
+            let label = path.rsplit('/').next().unwrap();
+            let t = self.timing_names.iter().position(|name| *name == path);
+            builder.add_record(
+                [format!("{}{}", "  ".repeat(depth), label)]
+                    .into_iter()
+                    .chain((0..self.party_names.len()).map(|i| {
+                        match t.map(|t| (self.party_means[i][t], intervals[i][t])) {
+                            Some((Some(mean), interval)) => format_mean(mean, interval),
+                            _ => "".to_string(),
+                        }
+                    })),
+            );
         }
 
-        csv_writer.flush().unwrap();
-    }
+        let table = builder.build().with(Style::modern());
 
-    /// Summarizes the timings of all parties.
-    pub fn summarize_timings(&self) -> TimingSummary {
-        let mut timing_names = vec![];
-        let mut party_timings_per_name: Vec<HashMap<String, Vec<f64>>> =
-            (0..self.party_names.len())
-                .map(|_| HashMap::new())
-                .collect();
+        println!("{}", table);
+    }
 
-        for (party_timings, map) in self.timings.iter().zip(&mut party_timings_per_name) {
-            for timing in party_timings {
-                for (t, d) in &timing.measured_durations {
-                    if !timing_names.contains(t) {
-                        timing_names.push(t.clone());
-                    }
+    /// Writes a `.dat` file and a pgfplots axis (in a `.tex` file that `\\input`s it) to `directory` for each timing
+    /// and for the sent and received bytes, so that they can be included in LaTeX figures directly. Each file plots
+    /// the parties' means as bars, with error bars for the 95% confidence interval of a timing and for the standard
+    /// deviation of the bytes. The files are named after the metric, with characters other than letters and digits
+    /// replaced by `-`.
+    pub fn write_pgfplots(&self, directory: &str) -> io::Result<()> {
+        create_dir_all(directory)?;
+        let intervals = self.confidence_intervals();
 
-                    map.entry(t.clone()).or_insert(vec![]).push(d.as_secs_f64());
-                }
-            }
+        for (t, name) in self.timing_names.iter().enumerate() {
+            let rows = self
+                .party_means
+                .iter()
+                .zip(&self.party_stdevs)
+                .zip(&intervals)
+                .map(|((means, stdevs), intervals)| {
+                    let error = match (means[t], intervals[t]) {
+                        (Some(mean), Some((lower, _))) => Some(mean - lower),
+                        _ => None,
+                    };
+                    means[t].map(|mean| (mean, stdevs[t].unwrap_or(0.), error.unwrap_or(0.)))
+                });
+            self.write_pgfplots_metric(directory, name, "Run time (s)", rows)?;
+        }
+        for (name, bytes) in [
+            ("Sent", &self.sent_bytes),
+            ("Received", &self.received_bytes),
+        ] {
+            let rows = bytes
+                .iter()
+                .map(|&(mean, stdev)| Some((mean, stdev, stdev)));
+            self.write_pgfplots_metric(directory, name, &format!("{} (B)", name), rows)?;
         }
 
-        println!("{:?}", party_timings_per_name);
+        Ok(())
+    }
 
-        let party_means = (0..self.party_names.len())
-            .map(|i| {
-                timing_names
-                    .iter()
-                    .map(|t| {
-                        party_timings_per_name[i]
-                            .get(t)
-                            .map(|durations| mean(durations.iter().cloned()))
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-        let party_stdevs = (0..self.party_names.len())
-            .map(|i| {
-                timing_names
-                    .iter()
-                    .map(|t| {
-                        party_timings_per_name[i]
-                            .get(t)
-                            .map(|durations| stddev(durations.iter().cloned()))
-                    })
-                    .collect::<Vec<_>>()
-            })
+    /// Writes the `.dat` and `.tex` files of one metric (see `write_pgfplots`), given each party's mean, standard
+    /// deviation and error bar, or `None` if the party did not measure the metric.
+    fn write_pgfplots_metric(
+        &self,
+        directory: &str,
+        name: &str,
+        y_label: &str,
+        rows: impl Iterator<Item = Option<(f64, f64, f64)>>,
+    ) -> io::Result<()> {
+        let filename: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
             .collect();
+        let path = Path::new(directory);
 
-        TimingSummary {
-            timing_names,
-            party_names: self.party_names.clone(),
-            party_means,
-            party_stdevs,
+        let mut dat = File::create(path.join(format!("{}.dat", filename)))?;
+        writeln!(dat, "party mean stdev error")?;
+        for (id, row) in rows.enumerate() {
+            if let Some((mean, stdev, error)) = row {
+                writeln!(dat, "{} {} {} {}", id, mean, stdev, error)?;
+            }
         }
+
+        let party_names: Vec<String> = self
+            .party_names
+            .iter()
+            .map(|party_name| format!("{{{}}}", escape_latex(party_name)))
+            .collect();
+        let mut tex = File::create(path.join(format!("{}.tex", filename)))?;
+        writeln!(tex, "\\begin{{tikzpicture}}")?;
+        writeln!(tex, "\\begin{{axis}}[")?;
+        writeln!(tex, "    ybar,")?;
+        writeln!(tex, "    title={{{}}},", escape_latex(name))?;
+        writeln!(tex, "    ylabel={{{}}},", escape_latex(y_label))?;
+        writeln!(tex, "    ymin=0,")?;
+        writeln!(tex, "    xtick=data,")?;
+        writeln!(tex, "    xticklabels={{{}}},", party_names.join(","))?;
+        writeln!(tex, "]")?;
+        writeln!(tex, "\\addplot+[error bars/.cd, y dir=both, y explicit]")?;
+        writeln!(
+            tex,
+            "    table[x=party, y=mean, y error=error] {{{}.dat}};",
+            filename
+        )?;
+        writeln!(tex, "\\end{{axis}}")?;
+        writeln!(tex, "\\end{{tikzpicture}}")
     }
-}
 
-/// Statistics pertaining to one party, such as the number of bytes sent and the durations measured.
-#[derive(Debug)]
-pub struct Timings {
-    measured_durations: Vec<(String, Duration)>,
+    /// Renders the summarized timings as plain text for snapshot tests. Unlike `print`, the rendering is stable:
+    /// the timings are sorted by name, the means and standard deviations are rounded to `decimals` decimals and
+    /// there is no table styling or unit. A timing that a party did not measure is rendered as `-`.
+    pub fn render_stable(&self, decimals: usize) -> String {
+        let mut order: Vec<usize> = (0..self.timing_names.len()).collect();
+        order.sort_by_key(|&t| &self.timing_names[t]);
+
+        // Adding zero turns a negative zero into a positive one, so rounding cannot change the sign
+        let round = |value: f64| format!("{:.*}", decimals, value + 0.);
+
+        let mut lines = vec![["Parties"]
+            .into_iter()
+            .chain(order.iter().map(|&t| self.timing_names[t].as_str()))
+            .collect::<Vec<_>>()
+            .join(" | ")];
+        for ((means, stdevs), party_name) in self
+            .party_means
+            .iter()
+            .zip(&self.party_stdevs)
+            .zip(&self.party_names)
+        {
+            lines.push(
+                [party_name.clone()]
+                    .into_iter()
+                    .chain(order.iter().map(|&t| match (means[t], stdevs[t]) {
+                        (Some(mean), Some(stdev)) => format!("{} ± {}", round(mean), round(stdev)),
+                        _ => "-".to_string(),
+                    }))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            );
+        }
+
+        lines.join("\n") + "\n"
+    }
 }
 
-impl Timings {
-    pub(crate) fn new() -> Self {
-        Timings {
-            measured_durations: vec![],
+/// Quotes `text` as a JSON string, which is also a valid DOT string.
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => escaped.push(c),
         }
     }
+    escaped.push('"');
+    escaped
+}
 
-    pub(crate) fn write_duration(&mut self, name: String, duration: Duration) {
-        self.measured_durations.push((name, duration));
+/// Escapes the characters of `text` that are special in LaTeX, and typesets `±` in math mode.
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '±' => escaped.push_str("$\\pm$"),
+            _ => escaped.push(c),
+        }
     }
+    escaped
 }
 
-/// A `Timer` that starts measuring a duration upon creation, until it is stopped.
-pub struct Timer {
-    name: String,
-    start_time: Instant,
+/// The number of fragments that each party's messages produced when an MTU was configured.
+pub struct FragmentSummary {
+    party_names: Vec<String>,
+    message_counts: Vec<usize>,
+    mean_fragments: Vec<Option<f64>>,
+    max_fragments: Vec<Option<usize>>,
 }
 
-impl Timer {
-    fn new(name: String) -> Self {
-        Timer {
-            name,
-            start_time: Instant::now(),
-        }
+impl FragmentSummary {
+    /// Returns the mean number of fragments per message for each party, or `None` if the party sent no messages.
+    pub fn mean_fragments(&self) -> &[Option<f64>] {
+        &self.mean_fragments
     }
 
-    fn stop(&self) -> (String, Duration) {
-        (self.name.clone(), self.start_time.elapsed())
+    /// Returns the maximum number of fragments of a single message for each party, or `None` if the party sent no
+    /// messages.
+    pub fn max_fragments(&self) -> &[Option<usize>] {
+        &self.max_fragments
+    }
+
+    /// Prints a pretty table of the summarized fragment counts.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record([
+            "Parties".to_string(),
+            "Messages".to_string(),
+            "Fragments per message".to_string(),
+            "Max fragments".to_string(),
+        ]);
+
+        // Add each party's data
+        for (((party_name, messages), mean), max) in self
+            .party_names
+            .iter()
+            .zip(&self.message_counts)
+            .zip(&self.mean_fragments)
+            .zip(&self.max_fragments)
+        {
+            builder.add_record([
+                party_name.clone(),
+                messages.to_string(),
+                mean.map(|mean| format!("{:.3}", mean)).unwrap_or_default(),
+                max.map(|max| max.to_string()).unwrap_or_default(),
+            ]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
     }
 }
 
-impl Timings {
-    /// Creates a timer with the given `name` that starts running immediately.
-    pub fn create_timer(&self, name: &str) -> Timer {
-        Timer::new(String::from(name))
+/// The number of bytes that each party's messages had before and after compression (see `FullMesh::with_compression`).
+#[cfg(feature = "compression")]
+pub struct CompressionSummary {
+    party_names: Vec<String>,
+    mean_uncompressed_bytes: Vec<f64>,
+    mean_compressed_bytes: Vec<f64>,
+}
+
+#[cfg(feature = "compression")]
+impl CompressionSummary {
+    /// Returns the mean number of bytes that each party's messages had before compression per repetition.
+    pub fn mean_uncompressed_bytes(&self) -> &[f64] {
+        &self.mean_uncompressed_bytes
     }
 
-    /// Stops the `timer` and writes it measured duration to this party's statistics.
-    pub fn stop_timer(&mut self, timer: Timer) {
-        let (name, duration) = timer.stop();
-        self.write_duration(name, duration);
+    /// Returns the mean number of bytes that each party's messages had after compression per repetition.
+    pub fn mean_compressed_bytes(&self) -> &[f64] {
+        &self.mean_compressed_bytes
     }
+
+    /// Returns the compression ratio (uncompressed divided by compressed bytes) of each party's messages, or `None`
+    /// if the party sent no messages.
+    pub fn ratios(&self) -> Vec<Option<f64>> {
+        self.mean_uncompressed_bytes
+            .iter()
+            .zip(&self.mean_compressed_bytes)
+            .map(|(&uncompressed, &compressed)| {
+                (compressed > 0.).then(|| uncompressed / compressed)
+            })
+            .collect()
+    }
+
+    /// Prints a pretty table of the summarized byte counts and compression ratios.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record([
+            "Parties".to_string(),
+            "Uncompressed bytes".to_string(),
+            "Compressed bytes".to_string(),
+            "Ratio".to_string(),
+        ]);
+
+        // Add each party's data
+        for (((party_name, uncompressed), compressed), ratio) in self
+            .party_names
+            .iter()
+            .zip(&self.mean_uncompressed_bytes)
+            .zip(&self.mean_compressed_bytes)
+            .zip(self.ratios())
+        {
+            builder.add_record([
+                party_name.clone(),
+                format!("{:.0}", uncompressed),
+                format!("{:.0}", compressed),
+                ratio
+                    .map(|ratio| format!("{:.2}", ratio))
+                    .unwrap_or_default(),
+            ]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The communication that each party attributed to its checkpoints with `Channels::checkpoint_comm` (or to its
+/// sessions with `Channels::subsession`).
+pub struct CheckpointSummary {
+    label_header: String,
+    party_names: Vec<String>,
+    checkpoints: Vec<(usize, String)>,
+    sent_bytes: Vec<f64>,
+    sent_messages: Vec<f64>,
+    received_bytes: Vec<f64>,
+    received_messages: Vec<f64>,
+}
+
+impl CheckpointSummary {
+    /// Returns the party id and label of each summarized checkpoint, ordered by party and then by the order in which
+    /// the party first recorded the label.
+    pub fn checkpoints(&self) -> &[(usize, String)] {
+        &self.checkpoints
+    }
+
+    /// Returns the mean number of bytes sent per repetition at each checkpoint.
+    pub fn sent_bytes(&self) -> &[f64] {
+        &self.sent_bytes
+    }
+
+    /// Returns the mean number of messages sent per repetition at each checkpoint.
+    pub fn sent_messages(&self) -> &[f64] {
+        &self.sent_messages
+    }
+
+    /// Returns the mean number of bytes received per repetition at each checkpoint.
+    pub fn received_bytes(&self) -> &[f64] {
+        &self.received_bytes
+    }
+
+    /// Returns the mean number of messages received per repetition at each checkpoint.
+    pub fn received_messages(&self) -> &[f64] {
+        &self.received_messages
+    }
+
+    /// Prints a pretty table of the summarized checkpoints.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record([
+            "Parties".to_string(),
+            self.label_header.clone(),
+            "Sent bytes".to_string(),
+            "Sent messages".to_string(),
+            "Received bytes".to_string(),
+            "Received messages".to_string(),
+        ]);
+
+        // Add each checkpoint's data
+        for (i, (party, label)) in self.checkpoints.iter().enumerate() {
+            builder.add_record([
+                self.party_names[*party].clone(),
+                label.clone(),
+                format!("{:.1}", self.sent_bytes[i]),
+                format!("{:.1}", self.sent_messages[i]),
+                format!("{:.1}", self.received_bytes[i]),
+                format!("{:.1}", self.received_messages[i]),
+            ]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The outcomes of the invariants that the parties checked with `Timings::assert_invariant`.
+pub struct InvariantSummary {
+    invariant_names: Vec<String>,
+    passed: Vec<usize>,
+    failed: Vec<usize>,
+    failed_repetitions: Vec<usize>,
+}
+
+impl InvariantSummary {
+    /// Returns the names of the checked invariants, in the order in which they were first checked.
+    pub fn invariant_names(&self) -> &[String] {
+        &self.invariant_names
+    }
+
+    /// Returns the number of checks of each invariant that passed, over all parties and repetitions.
+    pub fn passed(&self) -> &[usize] {
+        &self.passed
+    }
+
+    /// Returns the number of checks of each invariant that failed, over all parties and repetitions.
+    pub fn failed(&self) -> &[usize] {
+        &self.failed
+    }
+
+    /// Returns the number of repetitions in which at least one check of each invariant failed.
+    pub fn failed_repetitions(&self) -> &[usize] {
+        &self.failed_repetitions
+    }
+
+    /// Prints a pretty table of the summarized invariant checks.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record([
+            "Invariants".to_string(),
+            "Passed".to_string(),
+            "Failed".to_string(),
+            "Failure rate".to_string(),
+            "Failed repetitions".to_string(),
+        ]);
+
+        // Add each invariant's data
+        for (((name, passed), failed), failed_repetitions) in self
+            .invariant_names
+            .iter()
+            .zip(&self.passed)
+            .zip(&self.failed)
+            .zip(&self.failed_repetitions)
+        {
+            builder.add_record([
+                name.clone(),
+                passed.to_string(),
+                failed.to_string(),
+                format!("{:.1}%", 100. * *failed as f64 / (passed + failed) as f64),
+                failed_repetitions.to_string(),
+            ]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The means and standard deviations of the custom metrics that the parties recorded with
+/// `Timings::increment_counter` and `Timings::record_value`.
+pub struct MetricSummary {
+    metric_names: Vec<String>,
+    party_names: Vec<String>,
+    party_means: Vec<Vec<Option<f64>>>,
+    party_stdevs: Vec<Vec<Option<f64>>>,
+}
+
+impl MetricSummary {
+    /// Returns the names of the metrics, in the order in which they were first recorded.
+    pub fn metric_names(&self) -> &[String] {
+        &self.metric_names
+    }
+
+    /// Returns the mean of each metric (in the order of `metric_names`) for each party, or `None` if the party did
+    /// not record the metric.
+    pub fn means(&self) -> &[Vec<Option<f64>>] {
+        &self.party_means
+    }
+
+    /// Returns the standard deviation of each metric (in the order of `metric_names`) for each party, or `None` if
+    /// the party did not record the metric.
+    pub fn stdevs(&self) -> &[Vec<Option<f64>>] {
+        &self.party_stdevs
+    }
+
+    /// Prints a pretty table of the summarized metrics.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["Parties".to_string()]
+                .into_iter()
+                .chain(self.metric_names.iter().cloned()),
+        );
+
+        // Add each party's data
+        for ((means, stdevs), party_name) in self
+            .party_means
+            .iter()
+            .zip(&self.party_stdevs)
+            .zip(&self.party_names)
+        {
+            builder.add_record([party_name.clone()].into_iter().chain(
+                means.iter().zip(stdevs).map(|data| match data {
+                    (&Some(mean), &Some(stdev)) => format!("{:.3} ± {:.3}", mean, stdev),
+                    _ => "".to_string(),
+                }),
+            ));
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The mean number of bytes and messages that each party sent to each other party per repetition, which shows the
+/// asymmetries and hotspots in a protocol's communication pattern.
+pub struct CommunicationMatrix {
+    party_names: Vec<String>,
+    bytes: Vec<Vec<f64>>,
+    messages: Vec<Vec<f64>>,
+}
+
+impl CommunicationMatrix {
+    /// Returns the mean number of bytes that each party sent to each party, indexed by sender and then by recipient.
+    pub fn bytes(&self) -> &[Vec<f64>] {
+        &self.bytes
+    }
+
+    /// Returns the mean number of messages that each party sent to each party, indexed by sender and then by
+    /// recipient.
+    pub fn messages(&self) -> &[Vec<f64>] {
+        &self.messages
+    }
+
+    /// Prints a pretty table with a row for each sender and a column for each recipient.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["From \\ to".to_string()]
+                .into_iter()
+                .chain(self.party_names.iter().cloned()),
+        );
+
+        // Add each sender's data
+        for ((party_name, bytes), messages) in
+            self.party_names.iter().zip(&self.bytes).zip(&self.messages)
+        {
+            builder.add_record(
+                [party_name.clone()].into_iter().chain(
+                    bytes
+                        .iter()
+                        .zip(messages)
+                        .map(|(bytes, messages)| format!("{:.1} B ({:.1} msgs)", bytes, messages)),
+                ),
+            );
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+
+    /// Outputs the matrix to a csv named `csv_filename`, with one row per pair of sender and recipient.
+    pub fn write_csv(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        csv_writer.write_record(["from", "to", "bytes", "messages"])?;
+        for ((from, bytes), messages) in
+            self.party_names.iter().zip(&self.bytes).zip(&self.messages)
+        {
+            for ((to, bytes), messages) in self.party_names.iter().zip(bytes).zip(messages) {
+                csv_writer.write_record([from, to, &bytes.to_string(), &messages.to_string()])?;
+            }
+        }
+
+        csv_writer.flush()
+    }
+}
+
+/// How much of each timing each party spent waiting for the network instead of computing (see
+/// `AggregatedStats::summarize_idle_time`).
+pub struct IdleSummary {
+    party_names: Vec<String>,
+    timing_names: Vec<String>,
+    mean_waiting: Vec<Vec<Option<f64>>>,
+    idle_fractions: Vec<Vec<Option<f64>>>,
+}
+
+impl IdleSummary {
+    /// Returns the names of the timings.
+    pub fn timing_names(&self) -> &[String] {
+        &self.timing_names
+    }
+
+    /// Returns the mean time in seconds that each party spent waiting for the network while each of its timers was
+    /// running (in the order of the timings), or `None` if the party did not measure the timing or its waiting time
+    /// was not tracked.
+    pub fn mean_waiting(&self) -> &[Vec<Option<f64>>] {
+        &self.mean_waiting
+    }
+
+    /// Returns the fraction (between 0 and 1) of each party's duration of each timing that it spent waiting for the
+    /// network, or `None` if the party did not measure the timing or its waiting time was not tracked.
+    pub fn idle_fractions(&self) -> &[Vec<Option<f64>>] {
+        &self.idle_fractions
+    }
+
+    /// Returns the percentage of each party's run time (its "Total" timer) that it spent waiting for the network.
+    pub fn idle_percentages(&self) -> Vec<Option<f64>> {
+        let total = self.timing_names.iter().position(|name| name == "Total");
+        self.idle_fractions
+            .iter()
+            .map(|fractions| Some(fractions[total?]? * 100.))
+            .collect()
+    }
+
+    /// Prints a pretty table with a row for each party and a column for each timing, in which each cell reports the
+    /// time that the party waited and the percentage of the timing that this took.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["Parties".to_string()]
+                .into_iter()
+                .chain(self.timing_names.iter().cloned()),
+        );
+
+        // Add each party's data
+        for ((party_name, waiting), fractions) in self
+            .party_names
+            .iter()
+            .zip(&self.mean_waiting)
+            .zip(&self.idle_fractions)
+        {
+            builder.add_record([party_name.clone()].into_iter().chain(
+                waiting.iter().zip(fractions).map(|data| match data {
+                    (&Some(waiting), &Some(fraction)) => {
+                        format!(
+                            "{} ({:.1}% idle)",
+                            format_duration(waiting),
+                            fraction * 100.
+                        )
+                    }
+                    _ => "".to_string(),
+                }),
+            ));
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The mean duration and communication of each party in each round, together with how often each party was the
+/// straggler of a round: the party whose round took longest (see `EvaluationSettings::with_round_statistics`).
+pub struct RoundSummary {
+    party_names: Vec<String>,
+    mean_durations: Vec<Vec<Option<f64>>>,
+    mean_sent_bytes: Vec<Vec<Option<f64>>>,
+    straggler_counts: Vec<Vec<usize>>,
+}
+
+impl RoundSummary {
+    /// Returns the number of rounds of the party with the most rounds.
+    pub fn round_count(&self) -> usize {
+        self.straggler_counts.len()
+    }
+
+    /// Returns the mean duration in seconds of each party's rounds, indexed by party id and then by round, or `None`
+    /// if the party did not reach the round.
+    pub fn mean_durations(&self) -> &[Vec<Option<f64>>] {
+        &self.mean_durations
+    }
+
+    /// Returns the mean number of bytes that each party sent in each round, indexed by party id and then by round, or
+    /// `None` if the party did not reach the round.
+    pub fn mean_sent_bytes(&self) -> &[Vec<Option<f64>>] {
+        &self.mean_sent_bytes
+    }
+
+    /// Returns the number of repetitions in which each party was the straggler of each round, indexed by round and
+    /// then by party id.
+    pub fn straggler_counts(&self) -> &[Vec<usize>] {
+        &self.straggler_counts
+    }
+
+    /// Prints a pretty table with a row for each round and a column for each party, followed by a column with the
+    /// party that was most often the straggler of the round.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["Rounds".to_string()]
+                .into_iter()
+                .chain(self.party_names.iter().cloned())
+                .chain(["Straggler".to_string()]),
+        );
+
+        // Add each round's data
+        for (round, straggler_counts) in self.straggler_counts.iter().enumerate() {
+            let straggler = straggler_counts
+                .iter()
+                .enumerate()
+                .max_by_key(|&(_, count)| count)
+                .map(|(id, count)| {
+                    let repetitions: usize = straggler_counts.iter().sum();
+                    format!("{} ({}/{})", self.party_names[id], count, repetitions)
+                })
+                .unwrap_or_default();
+
+            builder.add_record(
+                [round.to_string()]
+                    .into_iter()
+                    .chain(self.mean_durations.iter().zip(&self.mean_sent_bytes).map(
+                        |(durations, bytes)| match (durations[round], bytes[round]) {
+                            (Some(duration), Some(bytes)) => {
+                                format!("{}, {}", format_duration(duration), format_bytes(bytes))
+                            }
+                            _ => "".to_string(),
+                        },
+                    ))
+                    .chain([straggler]),
+            );
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The mean number of messages per repetition that each party sent in each bucket of a histogram of their sizes (see
+/// `EvaluationSettings::with_message_size_histogram`).
+pub struct MessageSizeSummary {
+    party_names: Vec<String>,
+    labels: Vec<String>,
+    mean_counts: Vec<Vec<f64>>,
+}
+
+impl MessageSizeSummary {
+    /// Returns the label of each bucket (see `MessageSizeHistogram::labels`). It is empty if no histogram was
+    /// recorded.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Returns the mean number of messages that each party sent per repetition in each bucket, indexed by party id
+    /// and then by bucket.
+    pub fn mean_counts(&self) -> &[Vec<f64>] {
+        &self.mean_counts
+    }
+
+    /// Prints a pretty table with a row for each party and a column for each bucket of message sizes.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            ["Parties".to_string()]
+                .into_iter()
+                .chain(self.labels.iter().map(|label| format!("{} B", label))),
+        );
+
+        // Add each party's data
+        for (party_name, counts) in self.party_names.iter().zip(&self.mean_counts) {
+            builder.add_record(
+                [party_name.clone()]
+                    .into_iter()
+                    .chain(counts.iter().map(|count| format!("{:.1}", count))),
+            );
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// The estimated monetary cost of each party per repetition according to a `CostModel` (see
+/// `AggregatedStats::summarize_cost`).
+pub struct CostSummary {
+    party_names: Vec<String>,
+    party_costs: Vec<(f64, f64)>,
+    total_cost: (f64, f64),
+}
+
+impl CostSummary {
+    /// Returns the mean and standard deviation of each party's cost in dollars per repetition.
+    pub fn party_costs(&self) -> &[(f64, f64)] {
+        &self.party_costs
+    }
+
+    /// Returns the mean and standard deviation of the cost of all parties together in dollars per repetition, i.e.
+    /// of one execution of the protocol.
+    pub fn total_cost(&self) -> (f64, f64) {
+        self.total_cost
+    }
+
+    /// Prints a pretty table of each party's cost, followed by the cost of one execution.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(["Parties".to_string(), "Cost".to_string()]);
+
+        // Add each party's data
+        for (party_name, (mean, stdev)) in self.party_names.iter().zip(&self.party_costs) {
+            builder.add_record([party_name.clone(), format!("${:.6} ± {:.6}", mean, stdev)]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+        println!(
+            "Cost per execution: ${:.6} ± {:.6}",
+            self.total_cost.0, self.total_cost.1
+        );
+    }
+}
+
+/// The estimated energy that each party consumed per repetition according to an `EnergyModel` (see
+/// `AggregatedStats::summarize_energy`).
+pub struct EnergySummary {
+    party_names: Vec<String>,
+    party_energy: Vec<(f64, f64)>,
+}
+
+impl EnergySummary {
+    /// Returns the mean and standard deviation of the energy in joules that each party consumed per repetition.
+    pub fn party_energy(&self) -> &[(f64, f64)] {
+        &self.party_energy
+    }
+
+    /// Prints a pretty table of the energy that each party consumed.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(["Parties".to_string(), "Energy".to_string()]);
+
+        // Add each party's data
+        for (party_name, (mean, stdev)) in self.party_names.iter().zip(&self.party_energy) {
+            builder.add_record([party_name.clone(), format!("{:.3} ± {:.3} J", mean, stdev)]);
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
+
+/// A side-by-side comparison of multiple experiments (e.g. different protocols or variants of a protocol that were
+/// evaluated on the same network), with the factor by which each experiment is slower than the first one.
+pub struct ComparisonSummary {
+    experiment_names: Vec<String>,
+    timing_names: Vec<String>,
+    means: Vec<Vec<Option<f64>>>,
+    overheads: Vec<Vec<Option<f64>>>,
+    sent_bytes: Vec<f64>,
+    sent_overheads: Vec<Option<f64>>,
+    network: Option<String>,
+}
+
+impl ComparisonSummary {
+    /// Returns the names of the compared experiments, of which the first one is the baseline.
+    pub fn experiment_names(&self) -> &[String] {
+        &self.experiment_names
+    }
+
+    /// Returns the names of the timings that any of the experiments measured.
+    pub fn timing_names(&self) -> &[String] {
+        &self.timing_names
+    }
+
+    /// Returns the mean over the repetitions of the slowest party's duration of each experiment (in the order of
+    /// `experiment_names`) for each timing (in the order of `timing_names`), or `None` if the experiment did not
+    /// measure the timing.
+    pub fn means(&self) -> &[Vec<Option<f64>>] {
+        &self.means
+    }
+
+    /// Returns the factor by which each experiment's mean duration exceeds that of the baseline for each timing, so
+    /// that a factor below one is a speedup. It is `None` if either experiment did not measure the timing.
+    pub fn overheads(&self) -> &[Vec<Option<f64>>] {
+        &self.overheads
+    }
+
+    /// Returns the mean number of bytes that all parties of each experiment sent together per repetition.
+    pub fn sent_bytes(&self) -> &[f64] {
+        &self.sent_bytes
+    }
+
+    /// Returns the factor by which each experiment's communication exceeds that of the baseline, or `None` if the
+    /// baseline did not communicate.
+    pub fn sent_overheads(&self) -> &[Option<f64>] {
+        &self.sent_overheads
+    }
+
+    /// Prints a pretty table with a row for each experiment and a column for each timing and the communication,
+    /// each followed by a column with the factor relative to the baseline. The network is printed below the table
+    /// if all experiments were evaluated on the same one.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+        let baseline = self.experiment_names.first().cloned().unwrap_or_default();
+
+        // Add header
+        builder.add_record(
+            ["Experiments".to_string()].into_iter().chain(
+                self.timing_names
+                    .iter()
+                    .chain(["Sent".to_string()].iter())
+                    .flat_map(|name| [name.clone(), format!("{} vs. {}", name, baseline)]),
+            ),
+        );
+
+        // Add each experiment's data
+        let format_factor = |factor: &Option<f64>| {
+            factor
+                .map(|factor| format!("×{:.2}", factor))
+                .unwrap_or_default()
+        };
+        for ((((experiment_name, means), overheads), sent_bytes), sent_overhead) in self
+            .experiment_names
+            .iter()
+            .zip(&self.means)
+            .zip(&self.overheads)
+            .zip(&self.sent_bytes)
+            .zip(&self.sent_overheads)
+        {
+            builder.add_record(
+                [experiment_name.clone()]
+                    .into_iter()
+                    .chain(means.iter().zip(overheads).flat_map(|(mean, overhead)| {
+                        [
+                            mean.map(format_duration).unwrap_or_default(),
+                            format_factor(overhead),
+                        ]
+                    }))
+                    .chain([format_bytes(*sent_bytes), format_factor(sent_overhead)]),
+            );
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+        if let Some(network) = &self.network {
+            println!("Network: {}", network);
+        }
+    }
+}
+
+impl AggregatedStats {
+    /// Constructs `AggregatedStats` with the given name for tracking statistics.
+    pub fn new(name: String, party_names: Vec<String>) -> Self {
+        AggregatedStats {
+            name,
+            parameters: None,
+            params: vec![],
+            network: None,
+            dataset: None,
+            party_names,
+            timings: vec![],
+            makespans: vec![],
+            seeds: vec![],
+            invalid_repetitions: vec![],
+            timed_out_repetitions: 0,
+            protocol_errors: vec![],
+            snapshots: vec![],
+        }
+    }
+
+    /// Returns the name of the experiment that these statistics were gathered for.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names of the parties, indexed by party id.
+    pub fn party_names(&self) -> &[String] {
+        &self.party_names
+    }
+
+    /// Records the parameters of the protocol that these statistics were gathered for (e.g. its `Debug`
+    /// representation).
+    pub fn set_parameters(&mut self, parameters: String) {
+        self.parameters = Some(parameters);
+    }
+
+    /// Returns the parameters of the protocol, if they were recorded (see `set_parameters`).
+    pub fn parameters(&self) -> Option<&str> {
+        self.parameters.as_deref()
+    }
+
+    /// Records the name and value of each of the protocol's `Params` (see `Parameters::columns`), which the exports
+    /// include as columns.
+    pub fn set_params(&mut self, params: Vec<(String, String)>) {
+        self.params = params;
+    }
+
+    /// Returns the name and value of each of the protocol's `Params`, which are empty unless they were recorded
+    /// (see `set_params`).
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Records the description of the network that the experiment ran on (see `NetworkDescription::describe`).
+    pub fn set_network(&mut self, network: String) {
+        self.network = Some(network);
+    }
+
+    /// Returns the description of the network, if it was recorded (see `set_network`).
+    pub fn network(&self) -> Option<&str> {
+        self.network.as_deref()
+    }
+
+    /// Records the path of the dataset file that the inputs were read from (see `Protocol::dataset`).
+    pub fn set_dataset(&mut self, dataset: String) {
+        self.dataset = Some(dataset);
+    }
+
+    /// Returns the path of the dataset file, if the inputs were read from one (see `set_dataset`).
+    pub fn dataset(&self) -> Option<&str> {
+        self.dataset.as_deref()
+    }
+
+    /// Incorporates each party's resulting statistics into this aggregate.
+    pub fn incorporate_party_stats(&mut self, party_stats: Vec<Timings>) {
+        self.timings.push(party_stats);
+    }
+
+    /// Marks the most recently incorporated repetition as invalid, e.g. because its outputs failed validation. Its
+    /// statistics are still part of this aggregate, but they can be excluded from the summary (see
+    /// `summarize_valid_timings`).
+    pub fn mark_invalid(&mut self) {
+        if let Some(repetition) = self.timings.len().checked_sub(1) {
+            if !self.invalid_repetitions.contains(&repetition) {
+                self.invalid_repetitions.push(repetition);
+            }
+        }
+    }
+
+    /// Returns the indices of the repetitions that were marked as invalid (see `mark_invalid`), in ascending order.
+    pub fn invalid_repetitions(&self) -> &[usize] {
+        &self.invalid_repetitions
+    }
+
+    /// Counts a repetition that was aborted because it exceeded its time limit (see
+    /// `EvaluationSettings::with_repetition_timeout`). It has no statistics, so it is not part of the repetitions.
+    pub fn mark_timed_out(&mut self) {
+        self.timed_out_repetitions += 1;
+    }
+
+    /// Returns the number of repetitions that were aborted because they exceeded their time limit (see
+    /// `mark_timed_out`).
+    pub fn timed_out_repetitions(&self) -> usize {
+        self.timed_out_repetitions
+    }
+
+    /// Records the `ProtocolError` of a repetition that was skipped because a party used its channels incorrectly
+    /// (see `EvaluationSettings::with_invalid_repetitions_recorded`). It has no statistics, so it is not part of the
+    /// repetitions.
+    pub fn record_protocol_error(&mut self, error: ProtocolError) {
+        self.protocol_errors.push(error);
+    }
+
+    /// Returns the protocol errors of the repetitions that were skipped (see `record_protocol_error`), in the order
+    /// of the repetitions.
+    pub fn protocol_errors(&self) -> &[ProtocolError] {
+        &self.protocol_errors
+    }
+
+    /// Incorporates the makespan of one repetition into this aggregate: the wall-clock time from starting the parties
+    /// until the slowest party finished.
+    pub fn incorporate_makespan(&mut self, makespan: Duration) {
+        self.makespans.push(makespan);
+    }
+
+    /// Returns the makespan of each repetition, in the order of the repetitions. Unlike the slowest party's "Total"
+    /// timing, the makespan includes the time before a party started running.
+    pub fn makespans(&self) -> &[Duration] {
+        &self.makespans
+    }
+
+    /// Incorporates the seed that the inputs of one repetition were generated from into this aggregate.
+    pub fn incorporate_seed(&mut self, seed: u64) {
+        self.seeds.push(seed);
+    }
+
+    /// Returns the seed that the inputs of each repetition were generated from, in the order of the repetitions. A
+    /// repetition can be reproduced from its seed (see `Protocol::replay`).
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    /// Merges the repetitions of `other` into these statistics, e.g. to combine the results of runs on different
+    /// machines or of a resumed experiment into one report. Fails if the statistics are of different parties, or if
+    /// both contain repetitions in which different timings were measured.
+    pub fn merge(&mut self, other: AggregatedStats) -> Result<(), MergeError> {
+        if self.party_names != other.party_names {
+            return Err(MergeError::PartyNames {
+                expected: self.party_names.clone(),
+                found: other.party_names,
+            });
+        }
+
+        if !self.timings.is_empty() && !other.timings.is_empty() {
+            let mut expected = self.timing_names();
+            let mut found = other.timing_names();
+            expected.sort();
+            found.sort();
+            if expected != found {
+                return Err(MergeError::TimingNames { expected, found });
+            }
+        }
+
+        let offset = self.timings.len();
+        self.invalid_repetitions.extend(
+            other
+                .invalid_repetitions
+                .iter()
+                .map(|repetition| offset + repetition),
+        );
+        self.timed_out_repetitions += other.timed_out_repetitions;
+        self.protocol_errors.extend(other.protocol_errors);
+        self.timings.extend(other.timings);
+        self.makespans.extend(other.makespans);
+        self.seeds.extend(other.seeds);
+        self.snapshots.extend(other.snapshots);
+        Ok(())
+    }
+
+    /// Returns the durations that the party with id `party_id` measured with timers named `name`, in the order of the
+    /// repetitions.
+    pub fn durations(&self, party_id: usize, name: &str) -> Vec<Duration> {
+        self.timings
+            .iter()
+            .flat_map(|party_timings| &party_timings[party_id].measured_durations)
+            .filter(|(timing_name, _)| timing_name == name)
+            .map(|&(_, duration)| duration)
+            .collect()
+    }
+
+    /// Returns the CPU time that the thread of the party with id `party_id` consumed while its timers named `name` were
+    /// running, in the order of the repetitions. Unlike the durations, it excludes time spent sleeping or waiting
+    /// (e.g. for simulated network delays), and it is scaled by the party's slowdown factor in the same way. The CPU
+    /// time is only tracked with the `cpu-time` feature on Unix. It is not tracked for `AsyncParty`s, whose tasks can
+    /// move between threads, nor reported by the workers of a distributed experiment.
+    pub fn cpu_durations(&self, party_id: usize, name: &str) -> Vec<Duration> {
+        self.timings
+            .iter()
+            .flat_map(|party_timings| &party_timings[party_id].cpu_durations)
+            .filter(|(timing_name, _)| timing_name == name)
+            .map(|&(_, duration)| duration)
+            .collect()
+    }
+
+    /// Returns the names of all measured durations, in the order in which they were first measured.
+    pub(crate) fn timing_names(&self) -> Vec<String> {
+        let mut timing_names: Vec<String> = vec![];
+        for (name, _) in self
+            .timings
+            .iter()
+            .flatten()
+            .flat_map(|t| &t.measured_durations)
+        {
+            if !timing_names.contains(name) {
+                timing_names.push(name.clone());
+            }
+        }
+        timing_names
+    }
+
+    /// Returns the duration measured by the timer `name` of the slowest party in each repetition, where the durations
+    /// of a party's timers with the same name are added up. Repetitions in which no party measured `name` are
+    /// skipped.
+    pub(crate) fn slowest_durations(&self, name: &str) -> Vec<Duration> {
+        let party_duration = |timing: &Timings| {
+            timing
+                .measured_durations
+                .iter()
+                .filter(|(timing_name, _)| timing_name == name)
+                .map(|&(_, duration)| duration)
+                .sum::<Duration>()
+        };
+
+        self.timings
+            .iter()
+            .filter(|party_timings| {
+                party_timings
+                    .iter()
+                    .flat_map(|timing| &timing.measured_durations)
+                    .any(|(timing_name, _)| timing_name == name)
+            })
+            .map(|party_timings| party_timings.iter().map(party_duration).max().unwrap())
+            .collect()
+    }
+
+    /// Returns the width of the 95% confidence interval of the mean duration of the slowest party's timer `name`
+    /// (see `slowest_durations`) relative to that mean, or `None` if it was measured in fewer than two repetitions.
+    pub(crate) fn relative_confidence_width(&self, name: &str) -> Option<f64> {
+        let durations: Vec<f64> = self
+            .slowest_durations(name)
+            .iter()
+            .map(Duration::as_secs_f64)
+            .collect();
+        let (lower, upper) = confidence_interval(&durations)?;
+        Some((upper - lower) / mean(durations.iter().cloned()))
+    }
+
+    /// Returns the raw statistics of each repetition, in the order of the repetitions, so that they can be analyzed
+    /// beyond the summaries.
+    pub fn repetitions(&self) -> impl Iterator<Item = RepetitionStats<'_>> {
+        self.timings
+            .iter()
+            .enumerate()
+            .map(|(index, parties)| RepetitionStats {
+                index,
+                makespan: self.makespans.get(index).copied(),
+                seed: self.seeds.get(index).copied(),
+                parties,
+            })
+    }
+
+    /// Incorporates the progress snapshots taken during one repetition into this aggregate.
+    pub fn incorporate_snapshots(&mut self, snapshots: Vec<Snapshot>) {
+        self.snapshots.push(snapshots);
+    }
+
+    /// Returns the progress snapshots taken during each repetition, if a snapshot interval was configured.
+    pub fn snapshots(&self) -> &[Vec<Snapshot>] {
+        &self.snapshots
+    }
+
+    /// Saves these statistics as JSON to a file named `filename`, so that they can be summarized again later without
+    /// rerunning the experiment (see `load`).
+    #[cfg(feature = "persistence")]
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(filename)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    /// Converts these statistics into a structured JSON document for dashboards and other tools. Besides the
+    /// experiment's name, protocol parameters and network description, it contains the measurements of each
+    /// repetition and a summary of all repetitions (like `summarize_timings`), in which durations are in seconds.
+    #[cfg(feature = "persistence")]
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{json, Map, Value};
+
+        fn object<T: Into<Value> + Copy>(entries: &[(String, T)]) -> Value {
+            Value::Object(
+                entries
+                    .iter()
+                    .map(|(name, value)| (name.clone(), (*value).into()))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        fn seconds(entries: &[(String, Duration)]) -> Value {
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|(name, duration)| (name.clone(), duration.as_secs_f64()))
+                .collect();
+            object(&entries)
+        }
+
+        let repetitions: Vec<Value> = self
+            .repetitions()
+            .map(|repetition| {
+                let parties: Vec<Value> = self
+                    .party_names
+                    .iter()
+                    .zip(repetition.parties())
+                    .map(|(name, s)| {
+                        json!({
+                            "name": name,
+                            "durations": seconds(&s.measured_durations),
+                            "cpu_durations": seconds(&s.cpu_durations),
+                            "sent_bytes": s.sent_bytes,
+                            "received_bytes": s.received_bytes,
+                            "message_sizes": s.message_sizes.as_ref().map(|histogram| json!({
+                                "bounds": histogram.bounds,
+                                "counts": histogram.counts,
+                            })),
+                            "counters": object(&s.counters),
+                            "values": object(&s.values),
+                            "invariants": object(&s.invariants),
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "index": repetition.index(),
+                    "valid": !self.invalid_repetitions.contains(&repetition.index()),
+                    "makespan": repetition.makespan().map(|makespan| makespan.as_secs_f64()),
+                    "seed": repetition.seed(),
+                    "parties": parties,
+                })
+            })
+            .collect();
+
+        let summary = self.summarize_timings();
+        let means = summary.statistic(Statistic::Mean);
+        let timings: Vec<Value> = summary
+            .timing_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let parties: Vec<Value> = self
+                    .party_names
+                    .iter()
+                    .zip(&means)
+                    .zip(summary.confidence_intervals())
+                    .map(|((party_name, means), intervals)| {
+                        json!({
+                            "party": party_name,
+                            "mean": means[i],
+                            "confidence_interval": intervals[i],
+                        })
+                    })
+                    .collect();
+                json!({ "name": name, "parties": parties })
+            })
+            .collect();
+
+        json!({
+            "experiment": self.name,
+            "parameters": self.parameters,
+            "params": self
+                .params
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::from(value.as_str())))
+                .collect::<Map<_, _>>(),
+            "network": self.network,
+            "dataset": self.dataset,
+            "parties": self.party_names,
+            "repetitions": repetitions,
+            "summary": {
+                "timings": timings,
+                "makespan": summary.makespan.map(|(mean, stdev)| json!({ "mean": mean, "stdev": stdev })),
+                "invalid_repetitions": summary.invalid_repetitions,
+                "timed_out_repetitions": summary.timed_out_repetitions,
+                "protocol_errors": summary.protocol_errors,
+            },
+        })
+    }
+
+    /// Loads statistics that were saved by `save` from the file named `filename`.
+    #[cfg(feature = "persistence")]
+    pub fn load(filename: &str) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(filename)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Outputs the traces of all repetitions (see `EvaluationSettings::with_tracing`) in the Chrome trace event format
+    /// to a JSON file named `filename`, which can be opened in about://tracing or Perfetto. Every repetition is a
+    /// process with one track per party, on which the timers are spans and the sent and received messages are
+    /// instants.
+    pub fn write_chrome_trace(&self, filename: &str) -> io::Result<()> {
+        let micros = |duration: &Duration| duration.as_secs_f64() * 1e6;
+        let mut events = vec![];
+
+        for (pid, party_timings) in self.timings.iter().enumerate() {
+            events.push(format!(
+                r#"{{"name":"process_name","ph":"M","pid":{},"args":{{"name":"Repetition {}"}}}}"#,
+                pid, pid
+            ));
+            for (tid, (party_name, s)) in self.party_names.iter().zip(party_timings).enumerate() {
+                events.push(format!(
+                    r#"{{"name":"thread_name","ph":"M","pid":{},"tid":{},"args":{{"name":{}}}}}"#,
+                    pid,
+                    tid,
+                    escape_json(party_name)
+                ));
+
+                for event in &s.trace {
+                    events.push(match event {
+                        TraceEvent::Timer {
+                            name,
+                            start,
+                            duration,
+                        } => format!(
+                            r#"{{"name":{},"cat":"timer","ph":"X","ts":{},"dur":{},"pid":{},"tid":{}}}"#,
+                            escape_json(name),
+                            micros(start),
+                            micros(duration),
+                            pid,
+                            tid
+                        ),
+                        TraceEvent::Sent { to_id, bytes, time } => format!(
+                            r#"{{"name":{},"cat":"message","ph":"i","s":"t","ts":{},"pid":{},"tid":{},"args":{{"bytes":{}}}}}"#,
+                            escape_json(&format!("Send to {}", self.party_names[*to_id])),
+                            micros(time),
+                            pid,
+                            tid,
+                            bytes
+                        ),
+                        TraceEvent::Received {
+                            from_id,
+                            bytes,
+                            time,
+                        } => format!(
+                            r#"{{"name":{},"cat":"message","ph":"i","s":"t","ts":{},"pid":{},"tid":{},"args":{{"bytes":{}}}}}"#,
+                            escape_json(&format!("Receive from {}", self.party_names[*from_id])),
+                            micros(time),
+                            pid,
+                            tid,
+                            bytes
+                        ),
+                    });
+                }
+            }
+        }
+
+        let mut writer = BufWriter::new(File::create(filename)?);
+        writeln!(writer, "{{\"traceEvents\":[")?;
+        writeln!(writer, "{}", events.join(",\n"))?;
+        writeln!(writer, "]}}")?;
+        writer.flush()
+    }
+
+    /// Renders the message flow of the traced repetitions (see `EvaluationSettings::with_tracing`) as a Mermaid
+    /// sequence diagram, in which each message is annotated with its size and the time at which it was sent
+    /// (relative to the start of its repetition). Only the repetition with index `repetition` is included if given,
+    /// e.g. `Some(0)` for the first repetition.
+    pub fn to_mermaid(&self, repetition: Option<usize>) -> String {
+        let mut lines = vec!["sequenceDiagram".to_string()];
+        for (id, party_name) in self.party_names.iter().enumerate() {
+            lines.push(format!("    participant P{} as {}", id, party_name));
+        }
+
+        let mut current_repetition = None;
+        for (r, time, from_id, to_id, bytes) in self.message_flow(repetition) {
+            if repetition.is_none() && current_repetition != Some(r) {
+                let last = self.party_names.len().saturating_sub(1);
+                lines.push(format!("    Note over P0,P{}: Repetition {}", last, r));
+                current_repetition = Some(r);
+            }
+            lines.push(format!(
+                "    P{}->>P{}: {} B @ {:.3} ms",
+                from_id,
+                to_id,
+                bytes,
+                time.as_secs_f64() * 1000.
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Renders the message flow of the traced repetitions as a Graphviz (DOT) graph, like `to_mermaid`. Each message
+    /// is an edge that is labeled with its sequence number within its repetition, its size and its time.
+    pub fn to_dot(&self, repetition: Option<usize>) -> String {
+        let mut lines = vec![format!("digraph {} {{", escape_json(&self.name))];
+        for (id, party_name) in self.party_names.iter().enumerate() {
+            lines.push(format!("    p{} [label={}];", id, escape_json(party_name)));
+        }
+
+        let mut sequence_number = 0;
+        let mut current_repetition = None;
+        for (r, time, from_id, to_id, bytes) in self.message_flow(repetition) {
+            if current_repetition != Some(r) {
+                sequence_number = 0;
+                current_repetition = Some(r);
+            }
+            sequence_number += 1;
+
+            let prefix = match repetition {
+                Some(_) => String::new(),
+                None => format!("repetition {}, ", r),
+            };
+            lines.push(format!(
+                "    p{} -> p{} [label=\"{}#{}: {} B @ {:.3} ms\"];",
+                from_id,
+                to_id,
+                prefix,
+                sequence_number,
+                bytes,
+                time.as_secs_f64() * 1000.
+            ));
+        }
+        lines.push("}".to_string());
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Returns the repetition, time, sender id, recipient id and size of every traced message, in the order of the
+    /// repetitions and then of the times at which the messages were sent. Only the messages of the repetition with
+    /// index `repetition` are returned if given.
+    fn message_flow(
+        &self,
+        repetition: Option<usize>,
+    ) -> Vec<(usize, Duration, usize, usize, usize)> {
+        let mut messages = vec![];
+        for (r, party_timings) in self.timings.iter().enumerate() {
+            if repetition.is_some_and(|repetition| repetition != r) {
+                continue;
+            }
+
+            let start = messages.len();
+            for (from_id, s) in party_timings.iter().enumerate() {
+                for event in &s.trace {
+                    if let TraceEvent::Sent { to_id, bytes, time } = *event {
+                        messages.push((r, time, from_id, to_id, bytes));
+                    }
+                }
+            }
+            messages[start..].sort_by_key(|&(_, time, from_id, _, _)| (time, from_id));
+        }
+        messages
+    }
+
+    /// Writes the durations of the slowest party for each timing in Criterion's output format, so that existing
+    /// tooling (e.g. `critcmp`) can compare them with other benchmarks and across runs. Each timing becomes the
+    /// benchmark `<experiment>/<timing>` in `directory` (usually `target/criterion`), with a `new` directory that
+    /// contains its `benchmark.json`, `estimates.json` and `sample.json`. Unlike Criterion, the estimates are not
+    /// bootstrapped: the confidence interval of the mean follows Student's t-distribution and that of the median is
+    /// approximated from it, while the standard and median absolute deviations have no confidence interval.
+    pub fn write_criterion(&self, directory: &str) -> io::Result<()> {
+        // Criterion replaces the characters that are not allowed in file names on some platforms
+        let directory_name = |name: &str| -> String {
+            name.chars()
+                .map(|c| match c {
+                    '/' | '\\' | '?' | '*' | ':' | '|' | '"' | '<' | '>' => '_',
+                    c => c,
+                })
+                .collect()
+        };
+
+        for name in self.timing_names() {
+            let mut times: Vec<f64> = self
+                .slowest_durations(&name)
+                .iter()
+                .map(|duration| duration.as_secs_f64() * 1e9)
+                .collect();
+            let n = times.len() as f64;
+            let full_id = format!("{}/{}", self.name, name);
+            let new_directory = Path::new(directory)
+                .join(directory_name(&self.name))
+                .join(directory_name(&name))
+                .join("new");
+            create_dir_all(&new_directory)?;
+
+            let mut benchmark = File::create(new_directory.join("benchmark.json"))?;
+            write!(
+                benchmark,
+                r#"{{"group_id":{},"function_id":{},"value_str":null,"throughput":null,"full_id":{},"directory_name":{},"title":{}}}"#,
+                escape_json(&self.name),
+                escape_json(&name),
+                escape_json(&full_id),
+                escape_json(&format!(
+                    "{}/{}",
+                    directory_name(&self.name),
+                    directory_name(&name)
+                )),
+                escape_json(&full_id)
+            )?;
+
+            let mut sample = File::create(new_directory.join("sample.json"))?;
+            let join = |values: &mut dyn Iterator<Item = f64>| {
+                values
+                    .map(|value| format!("{:?}", value))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            write!(
+                sample,
+                r#"{{"sampling_mode":"Flat","iters":[{}],"times":[{}]}}"#,
+                join(&mut times.iter().map(|_| 1.)),
+                join(&mut times.iter().cloned())
+            )?;
+
+            times.sort_by(f64::total_cmp);
+            let sample_stdev = stddev(times.iter().cloned()) * (n / (n - 1.)).sqrt();
+            let mean_estimate = mean(times.iter().cloned());
+            let mean_error = sample_stdev / n.sqrt();
+            let median = percentile(&times, 0.5);
+            let mut deviations: Vec<f64> = times.iter().map(|time| (time - median).abs()).collect();
+            deviations.sort_by(f64::total_cmp);
+            let mean_interval =
+                confidence_interval(&times).unwrap_or((mean_estimate, mean_estimate));
+            // The median's standard error is approximately sqrt(pi / 2) times that of the mean for normal samples
+            let scale = (std::f64::consts::PI / 2.).sqrt();
+            let median_error = mean_error * scale;
+            let half_width = (mean_interval.1 - mean_interval.0) / 2. * scale;
+            let median_interval = (median - half_width, median + half_width);
+
+            let estimate = |point: f64, (lower, upper): (f64, f64), error: f64| {
+                let finite = |value: f64| if value.is_finite() { value } else { 0. };
+                format!(
+                    r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{:?},"upper_bound":{:?}}},"point_estimate":{:?},"standard_error":{:?}}}"#,
+                    finite(lower),
+                    finite(upper),
+                    finite(point),
+                    finite(error)
+                )
+            };
+            // Like Criterion, the median absolute deviation is scaled to estimate the standard deviation
+            let median_abs_dev = percentile(&deviations, 0.5) * 1.4826;
+
+            let mut estimates = File::create(new_directory.join("estimates.json"))?;
+            write!(
+                estimates,
+                r#"{{"mean":{},"median":{},"median_abs_dev":{},"slope":null,"std_dev":{}}}"#,
+                estimate(mean_estimate, mean_interval, mean_error),
+                estimate(median, median_interval, median_error),
+                estimate(median_abs_dev, (median_abs_dev, median_abs_dev), 0.),
+                estimate(sample_stdev, (sample_stdev, sample_stdev), 0.)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Outputs the progress snapshots of all repetitions as a time series to a csv named `csv_filename`.
+    pub fn output_snapshots_csv(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        csv_writer.write_record([
+            "repetition",
+            "elapsed_us",
+            "party",
+            "sent_bytes",
+            "sent_bytes_per_second",
+            "completed_timers",
+            "phase",
+        ])?;
+
+        for (repetition, snapshots) in self.snapshots.iter().enumerate() {
+            for snapshot in snapshots {
+                csv_writer.write_record([
+                    repetition.to_string(),
+                    snapshot.elapsed.as_micros().to_string(),
+                    self.party_names[snapshot.party].clone(),
+                    snapshot.sent_bytes.to_string(),
+                    snapshot.sent_bytes_per_second.to_string(),
+                    snapshot.completed_timers.to_string(),
+                    snapshot.phase.clone().unwrap_or_default(),
+                ])?;
+            }
+        }
+
+        csv_writer.flush()
+    }
+
+    /// Outputs all statistics of all repetitions to a csv named `csv_filename`, in long format with the columns
+    /// `repetition`, `party`, `metric`, `value` and `unit`, so that it can be loaded directly into e.g. pandas or R.
+    /// The metric's kind prefixes its name (e.g. `timer:Total`, `sent:Party 1` or `counter:triples`). The makespan
+    /// and validity of a repetition have an empty party. The protocol's `Params` are included as leading columns, if
+    /// they were recorded (see `set_params`).
+    pub fn write_csv(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        let (param_names, param_values): (Vec<&str>, Vec<&str>) = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .unzip();
+        csv_writer.write_record(param_names.iter().copied().chain([
+            "repetition",
+            "party",
+            "metric",
+            "value",
+            "unit",
+        ]))?;
+
+        for (repetition, party_timings) in self.timings.iter().enumerate() {
+            let mut write = |party: &str, metric: String, value: String, unit: &str| {
+                csv_writer.write_record(param_values.iter().copied().chain([
+                    repetition.to_string().as_str(),
+                    party,
+                    &metric,
+                    &value,
+                    unit,
+                ]))
+            };
+
+            if let Some(makespan) = self.makespans.get(repetition) {
+                write(
+                    "",
+                    "makespan".to_string(),
+                    makespan.as_secs_f64().to_string(),
+                    "s",
+                )?;
+            }
+            let valid = !self.invalid_repetitions.contains(&repetition);
+            write("", "valid".to_string(), (valid as u8).to_string(), "bool")?;
+
+            for (party_name, s) in self.party_names.iter().zip(party_timings) {
+                for (name, duration) in &s.measured_durations {
+                    let value = duration.as_secs_f64().to_string();
+                    write(party_name, format!("timer:{}", name), value, "s")?;
+                }
+                for (name, duration) in &s.cpu_durations {
+                    let value = duration.as_secs_f64().to_string();
+                    write(party_name, format!("cpu:{}", name), value, "s")?;
+                }
+                for (name, bytes) in &s.phase_bytes {
+                    write(
+                        party_name,
+                        format!("phase_sent:{}", name),
+                        bytes.to_string(),
+                        "B",
+                    )?;
+                }
+                for (peer_name, bytes) in self.party_names.iter().zip(&s.sent_bytes) {
+                    write(
+                        party_name,
+                        format!("sent:{}", peer_name),
+                        bytes.to_string(),
+                        "B",
+                    )?;
+                }
+                for (peer_name, bytes) in self.party_names.iter().zip(&s.received_bytes) {
+                    let metric = format!("received:{}", peer_name);
+                    write(party_name, metric, bytes.to_string(), "B")?;
+                }
+                for (peer_name, count) in self.party_names.iter().zip(&s.sent_message_counts) {
+                    let metric = format!("messages:{}", peer_name);
+                    write(party_name, metric, count.to_string(), "messages")?;
+                }
+                if let Some(message_sizes) = &s.message_sizes {
+                    for (label, count) in message_sizes.labels().iter().zip(&message_sizes.counts) {
+                        let metric = format!("message_size:{}", label);
+                        write(party_name, metric, count.to_string(), "messages")?;
+                    }
+                }
+                write(
+                    party_name,
+                    "relayed".to_string(),
+                    s.relayed_bytes.to_string(),
+                    "B",
+                )?;
+                #[cfg(feature = "compression")]
+                {
+                    let (uncompressed, compressed) = (s.uncompressed_bytes, s.compressed_bytes);
+                    write(
+                        party_name,
+                        "uncompressed".to_string(),
+                        uncompressed.to_string(),
+                        "B",
+                    )?;
+                    write(
+                        party_name,
+                        "compressed".to_string(),
+                        compressed.to_string(),
+                        "B",
+                    )?;
+                }
+                for (kind, checkpoints) in [
+                    ("checkpoint", &s.comm_checkpoints),
+                    ("session", &s.session_comm),
+                ] {
+                    for checkpoint in checkpoints {
+                        for (direction, bytes, messages) in [
+                            ("sent", checkpoint.sent_bytes, checkpoint.sent_messages),
+                            (
+                                "received",
+                                checkpoint.received_bytes,
+                                checkpoint.received_messages,
+                            ),
+                        ] {
+                            let metric = format!("{}_{}:{}", kind, direction, checkpoint.label);
+                            write(party_name, metric.clone(), bytes.to_string(), "B")?;
+                            write(party_name, metric, messages.to_string(), "messages")?;
+                        }
+                    }
+                }
+                for (name, holds) in &s.invariants {
+                    let metric = format!("invariant:{}", name);
+                    write(party_name, metric, (*holds as u8).to_string(), "bool")?;
+                }
+                for (name, count) in &s.counters {
+                    write(
+                        party_name,
+                        format!("counter:{}", name),
+                        count.to_string(),
+                        "",
+                    )?;
+                }
+                for (name, value) in &s.values {
+                    write(party_name, format!("value:{}", name), value.to_string(), "")?;
+                }
+            }
+        }
+
+        csv_writer.flush()
+    }
+
+    /// Summarizes the timings of all parties.
+    pub fn summarize_timings(&self) -> TimingSummary {
+        self.summarize_repetitions(false)
+    }
+
+    /// Summarizes the timings of all parties like `summarize_timings`, but without the repetitions whose outputs were
+    /// invalid (see `invalid_repetitions`).
+    pub fn summarize_valid_timings(&self) -> TimingSummary {
+        self.summarize_repetitions(true)
+    }
+
+    fn summarize_repetitions(&self, exclude_invalid: bool) -> TimingSummary {
+        let included =
+            |repetition: &usize| !exclude_invalid || !self.invalid_repetitions.contains(repetition);
+        let repetitions: Vec<&Vec<Timings>> = self
+            .timings
+            .iter()
+            .enumerate()
+            .filter(|(repetition, _)| included(repetition))
+            .map(|(_, party_timings)| party_timings)
+            .collect();
+
+        let mut timing_names = vec![];
+        let mut party_timings_per_name: Vec<HashMap<String, Vec<f64>>> =
+            (0..self.party_names.len())
+                .map(|_| HashMap::new())
+                .collect();
+
+        // The timings are stored per repetition, with one entry per party
+        for party_timings in &repetitions {
+            for (timing, map) in party_timings.iter().zip(&mut party_timings_per_name) {
+                for (t, d) in &timing.measured_durations {
+                    if !timing_names.contains(t) {
+                        timing_names.push(t.clone());
+                    }
+
+                    map.entry(t.clone()).or_insert(vec![]).push(d.as_secs_f64());
+                }
+            }
+        }
+
+        // The bytes sent during a phase are only tracked when the party ran on `Channels`
+        let mut party_bytes_per_name: Vec<HashMap<String, Vec<f64>>> = (0..self.party_names.len())
+            .map(|_| HashMap::new())
+            .collect();
+        for party_timings in &repetitions {
+            for (timing, map) in party_timings.iter().zip(&mut party_bytes_per_name) {
+                for (t, bytes) in &timing.phase_bytes {
+                    map.entry(t.clone()).or_insert(vec![]).push(*bytes as f64);
+                }
+            }
+        }
+        let party_phase_bytes = (0..self.party_names.len())
+            .map(|i| {
+                timing_names
+                    .iter()
+                    .map(|t| {
+                        party_bytes_per_name[i]
+                            .get(t)
+                            .map(|bytes| mean(bytes.iter().cloned()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // The CPU time is only tracked with the `cpu-time` feature, for parties that run on a thread of their own
+        let mut party_cpu_per_name: Vec<HashMap<String, Vec<f64>>> = (0..self.party_names.len())
+            .map(|_| HashMap::new())
+            .collect();
+        for party_timings in &repetitions {
+            for (timing, map) in party_timings.iter().zip(&mut party_cpu_per_name) {
+                for (t, d) in &timing.cpu_durations {
+                    map.entry(t.clone()).or_insert(vec![]).push(d.as_secs_f64());
+                }
+            }
+        }
+        let party_cpu_means = (0..self.party_names.len())
+            .map(|i| {
+                timing_names
+                    .iter()
+                    .map(|t| {
+                        party_cpu_per_name[i]
+                            .get(t)
+                            .map(|durations| mean(durations.iter().cloned()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let party_means = (0..self.party_names.len())
+            .map(|i| {
+                timing_names
+                    .iter()
+                    .map(|t| {
+                        party_timings_per_name[i]
+                            .get(t)
+                            .map(|durations| mean(durations.iter().cloned()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let party_stdevs = (0..self.party_names.len())
+            .map(|i| {
+                timing_names
+                    .iter()
+                    .map(|t| {
+                        party_timings_per_name[i]
+                            .get(t)
+                            .map(|durations| stddev(durations.iter().cloned()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let party_durations = (0..self.party_names.len())
+            .map(|i| {
+                timing_names
+                    .iter()
+                    .map(|t| party_timings_per_name[i].remove(t).unwrap_or_default())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // Parties that were not run on `Channels` (e.g. on another machine) did not record their bytes
+        let n_parties = self.party_names.len();
+        let summarize = |bytes: &dyn Fn(&Timings) -> usize, i: usize| -> (f64, f64) {
+            let bytes: Vec<f64> = repetitions
+                .iter()
+                .map(|party_timings| bytes(&party_timings[i]) as f64)
+                .collect();
+            (mean(bytes.iter().cloned()), stddev(bytes.iter().cloned()))
+        };
+        let sent_bytes = (0..n_parties)
+            .map(|i| summarize(&|s| s.sent_bytes.iter().sum(), i))
+            .collect();
+        let received_bytes = (0..n_parties)
+            .map(|i| summarize(&|s| s.received_bytes.iter().sum(), i))
+            .collect();
+        let peer_sent_bytes = (0..n_parties)
+            .map(|i| {
+                (0..n_parties)
+                    .map(|j| summarize(&|s| s.sent_bytes.get(j).cloned().unwrap_or(0), i))
+                    .collect()
+            })
+            .collect();
+
+        let makespans: Vec<f64> = self
+            .makespans
+            .iter()
+            .enumerate()
+            .filter(|(repetition, _)| included(repetition))
+            .map(|(_, makespan)| makespan.as_secs_f64())
+            .collect();
+        let makespan = (!makespans.is_empty()).then(|| {
+            (
+                mean(makespans.iter().cloned()),
+                stddev(makespans.iter().cloned()),
+            )
+        });
+
+        // The invalid repetitions are reported even when they are excluded
+        let invalid_repetitions = self.invalid_repetitions.len();
+
+        TimingSummary {
+            timing_names,
+            makespan,
+            invalid_repetitions,
+            timed_out_repetitions: self.timed_out_repetitions,
+            protocol_errors: self.protocol_errors.len(),
+            party_names: self.party_names.clone(),
+            party_means,
+            party_stdevs,
+            party_durations,
+            party_phase_bytes,
+            party_cpu_means,
+            sent_bytes,
+            received_bytes,
+            peer_sent_bytes,
+        }
+    }
+
+    /// Returns the number of bytes that the party with `party_id` sent to each party in every repetition, indexed by
+    /// repetition and then by party id.
+    pub fn sent_bytes(&self, party_id: usize) -> Vec<Vec<usize>> {
+        self.timings
+            .iter()
+            .map(|party_timings| party_timings[party_id].sent_bytes.clone())
+            .collect()
+    }
+
+    /// Returns the number of bytes that the party with `party_id` received from each party in every repetition,
+    /// indexed by repetition and then by party id.
+    pub fn received_bytes(&self, party_id: usize) -> Vec<Vec<usize>> {
+        self.timings
+            .iter()
+            .map(|party_timings| party_timings[party_id].received_bytes.clone())
+            .collect()
+    }
+
+    /// Summarizes how many bytes and messages each party sent to each other party, as the mean over the
+    /// repetitions. Parties that were not run on `Channels` (e.g. on another machine) count as sending nothing.
+    pub fn summarize_communication(&self) -> CommunicationMatrix {
+        let n_parties = self.party_names.len();
+        let matrix = |counts: &dyn Fn(&Timings) -> &[usize]| -> Vec<Vec<f64>> {
+            (0..n_parties)
+                .map(|from| {
+                    (0..n_parties)
+                        .map(|to| {
+                            mean(self.timings.iter().map(|party_timings| {
+                                counts(&party_timings[from]).get(to).copied().unwrap_or(0) as f64
+                            }))
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+
+        CommunicationMatrix {
+            party_names: self.party_names.clone(),
+            bytes: matrix(&|timings| &timings.sent_bytes),
+            messages: matrix(&|timings| &timings.sent_message_counts),
+        }
+    }
+
+    /// Returns the name and value of the metrics that summarize this experiment, named like the metrics of `write_csv`:
+    /// - `timer:<name>`: the mean over the repetitions of the slowest party's duration of the timing in seconds,
+    /// - `makespan`: the mean makespan in seconds, if the makespans were incorporated,
+    /// - `sent`: the mean number of bytes that all parties sent together per repetition.
+    pub fn headline_metrics(&self) -> Vec<(String, f64)> {
+        let mut metrics: Vec<(String, f64)> = self
+            .timing_names()
+            .into_iter()
+            .map(|name| {
+                let durations = self.slowest_durations(&name);
+                let value = mean(durations.iter().map(Duration::as_secs_f64));
+                (format!("timer:{}", name), value)
+            })
+            .collect();
+
+        if !self.makespans.is_empty() {
+            let value = mean(self.makespans.iter().map(Duration::as_secs_f64));
+            metrics.push(("makespan".to_string(), value));
+        }
+
+        let sent_bytes = self.timings.iter().map(|party_timings| {
+            party_timings
+                .iter()
+                .map(|party| party.sent_bytes.iter().sum::<usize>())
+                .sum::<usize>() as f64
+        });
+        metrics.push(("sent".to_string(), mean(sent_bytes)));
+
+        metrics
+    }
+
+    /// Compares the `experiments` side by side, relative to the first one (see `ComparisonSummary`). Each timing is
+    /// the mean over the repetitions of the slowest party's duration, and the communication is the mean number of
+    /// bytes that all parties sent together per repetition.
+    pub fn compare(experiments: &[&AggregatedStats]) -> ComparisonSummary {
+        let mut timing_names: Vec<String> = vec![];
+        for stats in experiments {
+            for name in stats.timing_names() {
+                if !timing_names.contains(&name) {
+                    timing_names.push(name);
+                }
+            }
+        }
+
+        let means: Vec<Vec<Option<f64>>> = experiments
+            .iter()
+            .map(|stats| {
+                timing_names
+                    .iter()
+                    .map(|name| {
+                        let durations = stats.slowest_durations(name);
+                        (!durations.is_empty())
+                            .then(|| mean(durations.iter().map(Duration::as_secs_f64)))
+                    })
+                    .collect()
+            })
+            .collect();
+        let sent_bytes: Vec<f64> = experiments
+            .iter()
+            .map(|stats| {
+                mean(stats.timings.iter().map(|party_timings| {
+                    party_timings
+                        .iter()
+                        .map(|timings| timings.sent_bytes.iter().sum::<usize>())
+                        .sum::<usize>() as f64
+                }))
+            })
+            .collect();
+
+        let factor = |value: f64, baseline: f64| (baseline > 0.).then(|| value / baseline);
+        let overheads = means
+            .iter()
+            .map(|experiment_means| {
+                experiment_means
+                    .iter()
+                    .zip(&means[0])
+                    .map(|(mean, baseline)| factor((*mean)?, (*baseline)?))
+                    .collect()
+            })
+            .collect();
+        let sent_overheads = sent_bytes
+            .iter()
+            .map(|&bytes| factor(bytes, sent_bytes[0]))
+            .collect();
+
+        let network = experiments
+            .first()
+            .and_then(|stats| stats.network.clone())
+            .filter(|network| {
+                experiments
+                    .iter()
+                    .all(|stats| stats.network.as_ref() == Some(network))
+            });
+
+        ComparisonSummary {
+            experiment_names: experiments.iter().map(|stats| stats.name.clone()).collect(),
+            timing_names,
+            means,
+            overheads,
+            sent_bytes,
+            sent_overheads,
+            network,
+        }
+    }
+
+    /// Analyzes how much of each party's timings was spent waiting for the network (blocked in receiving a message or
+    /// waiting for its transfer) rather than computing. The idle fraction of a timing is the party's total waiting
+    /// time while the timing ran divided by the timing's total duration over all repetitions. The waiting time is
+    /// not scaled by the party's slowdown factor, and it is only tracked for parties that run on `Channels`.
+    pub fn summarize_idle_time(&self) -> IdleSummary {
+        let timing_names = self.timing_names();
+
+        let mut mean_waiting = vec![];
+        let mut idle_fractions = vec![];
+        for i in 0..self.party_names.len() {
+            let (waiting, fractions) = timing_names
+                .iter()
+                .map(|name| {
+                    let total = |durations: &dyn Fn(&Timings) -> &[(String, Duration)]| {
+                        let matching: Vec<Duration> = self
+                            .timings
+                            .iter()
+                            .flat_map(|party_timings| durations(&party_timings[i]))
+                            .filter(|(timing_name, _)| timing_name == name)
+                            .map(|&(_, duration)| duration)
+                            .collect();
+                        (!matching.is_empty()).then(|| matching.iter().sum::<Duration>())
+                    };
+                    let (Some(duration), Some(waiting)) = (
+                        total(&|s| &s.measured_durations),
+                        total(&|s| &s.waiting_durations),
+                    ) else {
+                        return (None, None);
+                    };
+
+                    let repetitions = self
+                        .timings
+                        .iter()
+                        .filter(|party_timings| {
+                            party_timings[i]
+                                .measured_durations
+                                .iter()
+                                .any(|(timing_name, _)| timing_name == name)
+                        })
+                        .count();
+                    let waiting = waiting.as_secs_f64();
+                    let fraction = (!duration.is_zero()).then(|| waiting / duration.as_secs_f64());
+                    (Some(waiting / repetitions as f64), fraction)
+                })
+                .unzip();
+            mean_waiting.push(waiting);
+            idle_fractions.push(fractions);
+        }
+
+        IdleSummary {
+            party_names: self.party_names.clone(),
+            timing_names,
+            mean_waiting,
+            idle_fractions,
+        }
+    }
+
+    /// Summarizes the duration and communication of each party's rounds (see
+    /// `EvaluationSettings::with_round_statistics`), as the mean over the repetitions in which the party reached the
+    /// round. In each repetition, the party whose round took longest is counted as the straggler of that round.
+    pub fn summarize_rounds(&self) -> RoundSummary {
+        let n_parties = self.party_names.len();
+        let round_count = self
+            .timings
+            .iter()
+            .flatten()
+            .map(|s| s.rounds.len())
+            .max()
+            .unwrap_or(0);
+
+        let summarize = |statistic: &dyn Fn(&RoundStats) -> f64| -> Vec<Vec<Option<f64>>> {
+            (0..n_parties)
+                .map(|i| {
+                    (0..round_count)
+                        .map(|round| {
+                            let values: Vec<f64> = self
+                                .timings
+                                .iter()
+                                .filter_map(|party_timings| party_timings[i].rounds.get(round))
+                                .map(statistic)
+                                .collect();
+                            (!values.is_empty()).then(|| mean(values.into_iter()))
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+
+        let mut straggler_counts = vec![vec![0; n_parties]; round_count];
+        for party_timings in &self.timings {
+            for (round, counts) in straggler_counts.iter_mut().enumerate() {
+                let straggler = party_timings
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(id, s)| Some((id, s.rounds.get(round)?.duration)))
+                    .max_by_key(|&(_, duration)| duration);
+                if let Some((id, _)) = straggler {
+                    counts[id] += 1;
+                }
+            }
+        }
+
+        RoundSummary {
+            party_names: self.party_names.clone(),
+            mean_durations: summarize(&|round| round.duration.as_secs_f64()),
+            mean_sent_bytes: summarize(&|round| round.sent_bytes as f64),
+            straggler_counts,
+        }
+    }
+
+    /// Summarizes the histograms of the sizes of the messages that each party sent (see
+    /// `EvaluationSettings::with_message_size_histogram`), as the mean number of messages per repetition in each
+    /// bucket. Parties without a histogram (e.g. because they were not run on `Channels`) count as sending nothing.
+    pub fn summarize_message_sizes(&self) -> MessageSizeSummary {
+        let labels = self
+            .timings
+            .iter()
+            .flatten()
+            .find_map(|s| s.message_sizes.as_ref())
+            .map(MessageSizeHistogram::labels)
+            .unwrap_or_default();
+
+        let mean_counts = (0..self.party_names.len())
+            .map(|i| {
+                (0..labels.len())
+                    .map(|bucket| {
+                        mean(self.timings.iter().map(|party_timings| {
+                            party_timings[i]
+                                .message_sizes
+                                .as_ref()
+                                .map_or(0, |histogram| histogram.counts[bucket])
+                                as f64
+                        }))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        MessageSizeSummary {
+            party_names: self.party_names.clone(),
+            labels,
+            mean_counts,
+        }
+    }
+
+    /// Estimates the monetary cost of each party in each repetition with the cost `model`, from the bytes that the
+    /// party sent and the duration (and CPU time, if tracked) of its "Total" timer.
+    pub fn summarize_cost(&self, model: &impl CostModel) -> CostSummary {
+        let costs = self.estimate_usage(|usage| model.cost(usage));
+
+        let party_costs = (0..self.party_names.len())
+            .map(|i| {
+                let party_costs = costs.iter().map(|costs| costs[i]);
+                (mean(party_costs.clone()), stddev(party_costs))
+            })
+            .collect();
+        let total_costs = costs.iter().map(|costs| costs.iter().sum::<f64>());
+
+        CostSummary {
+            party_names: self.party_names.clone(),
+            party_costs,
+            total_cost: (mean(total_costs.clone()), stddev(total_costs)),
+        }
+    }
+
+    /// Estimates the energy that each party consumed in each repetition with the energy `model`, from the bytes that
+    /// the party sent and received and the duration (and CPU time, if tracked) of its "Total" timer.
+    pub fn summarize_energy(&self, model: &EnergyModel) -> EnergySummary {
+        let energy = self.estimate_usage(|usage| model.energy(usage));
+
+        EnergySummary {
+            party_names: self.party_names.clone(),
+            party_energy: (0..self.party_names.len())
+                .map(|i| {
+                    let party_energy = energy.iter().map(|energy| energy[i]);
+                    (mean(party_energy.clone()), stddev(party_energy))
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies the `estimate` to the `Usage` of each party in each repetition, indexed by repetition and then by party
+    /// id.
+    fn estimate_usage(&self, estimate: impl Fn(&Usage) -> f64) -> Vec<Vec<f64>> {
+        let total = |durations: &[(String, Duration)]| {
+            durations
+                .iter()
+                .filter(|(name, _)| name == "Total")
+                .map(|&(_, duration)| duration)
+                .reduce(|a, b| a + b)
+        };
+
+        self.timings
+            .iter()
+            .map(|party_timings| {
+                party_timings
+                    .iter()
+                    .enumerate()
+                    .map(|(party_id, timings)| {
+                        estimate(&Usage {
+                            party_id,
+                            sent_bytes: &timings.sent_bytes,
+                            received_bytes: &timings.received_bytes,
+                            run_time: total(&timings.measured_durations).unwrap_or_default(),
+                            cpu_time: total(&timings.cpu_durations),
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the mean number of bytes that each party sent via the NAT relay per repetition (see
+    /// `FullMesh::with_nat_relay`).
+    pub fn mean_relayed_bytes(&self) -> Vec<f64> {
+        (0..self.party_names.len())
+            .map(|i| {
+                mean(
+                    self.timings
+                        .iter()
+                        .map(|party_timings| party_timings[i].relayed_bytes as f64),
+                )
+            })
+            .collect()
+    }
+
+    /// Summarizes the number of fragments that each party's messages produced over all repetitions. This is only
+    /// meaningful when the network has an MTU (see `FullMesh::with_mtu`).
+    pub fn summarize_fragments(&self) -> FragmentSummary {
+        let party_fragments: Vec<Vec<usize>> = (0..self.party_names.len())
+            .map(|i| {
+                self.timings
+                    .iter()
+                    .flat_map(|party_timings| party_timings[i].fragment_counts.iter().cloned())
+                    .collect()
+            })
+            .collect();
+
+        FragmentSummary {
+            party_names: self.party_names.clone(),
+            message_counts: party_fragments.iter().map(Vec::len).collect(),
+            mean_fragments: party_fragments
+                .iter()
+                .map(|fragments| {
+                    (!fragments.is_empty()).then(|| mean(fragments.iter().map(|&f| f as f64)))
+                })
+                .collect(),
+            max_fragments: party_fragments
+                .iter()
+                .map(|fragments| fragments.iter().max().cloned())
+                .collect(),
+        }
+    }
+
+    /// Summarizes the number of bytes that each party's messages had before and after compression, as means per
+    /// repetition. This is only meaningful when the network compresses messages (see `FullMesh::with_compression`).
+    #[cfg(feature = "compression")]
+    pub fn summarize_compression(&self) -> CompressionSummary {
+        let mean_bytes = |bytes: fn(&Timings) -> usize| -> Vec<f64> {
+            (0..self.party_names.len())
+                .map(|i| {
+                    mean(
+                        self.timings
+                            .iter()
+                            .map(|party_timings| bytes(&party_timings[i]) as f64),
+                    )
+                })
+                .collect()
+        };
+
+        CompressionSummary {
+            party_names: self.party_names.clone(),
+            mean_uncompressed_bytes: mean_bytes(|timings| timings.uncompressed_bytes),
+            mean_compressed_bytes: mean_bytes(|timings| timings.compressed_bytes),
+        }
+    }
+
+    /// Summarizes the communication that each party attributed to its checkpoints, as means per repetition. The
+    /// checkpoints that a party recorded under the same label in one repetition are added up.
+    pub fn summarize_checkpoints(&self) -> CheckpointSummary {
+        self.summarize_comm(|timings| &timings.comm_checkpoints, "Checkpoints")
+    }
+
+    /// Summarizes the communication of each party in each of its sessions (see `Channels::subsession`) like
+    /// `summarize_checkpoints`, labeled by the session ids.
+    pub fn summarize_sessions(&self) -> CheckpointSummary {
+        self.summarize_comm(|timings| &timings.session_comm, "Sessions")
+    }
+
+    /// Summarizes the communication `records` of each party, which are labeled under `label_header`.
+    fn summarize_comm(
+        &self,
+        records: fn(&Timings) -> &[CommCheckpoint],
+        label_header: &str,
+    ) -> CheckpointSummary {
+        let repetitions = self.timings.len() as f64;
+        let mut summary = CheckpointSummary {
+            label_header: label_header.to_string(),
+            party_names: self.party_names.clone(),
+            checkpoints: vec![],
+            sent_bytes: vec![],
+            sent_messages: vec![],
+            received_bytes: vec![],
+            received_messages: vec![],
+        };
+
+        for party in 0..self.party_names.len() {
+            let party_checkpoints = self
+                .timings
+                .iter()
+                .flat_map(|party_timings| records(&party_timings[party]));
+            for checkpoint in party_checkpoints {
+                let key = (party, checkpoint.label.clone());
+                let i = match summary.checkpoints.iter().position(|k| *k == key) {
+                    Some(i) => i,
+                    None => {
+                        summary.checkpoints.push(key);
+                        summary.sent_bytes.push(0.);
+                        summary.sent_messages.push(0.);
+                        summary.received_bytes.push(0.);
+                        summary.received_messages.push(0.);
+                        summary.checkpoints.len() - 1
+                    }
+                };
+
+                summary.sent_bytes[i] += checkpoint.sent_bytes as f64 / repetitions;
+                summary.sent_messages[i] += checkpoint.sent_messages as f64 / repetitions;
+                summary.received_bytes[i] += checkpoint.received_bytes as f64 / repetitions;
+                summary.received_messages[i] += checkpoint.received_messages as f64 / repetitions;
+            }
+        }
+
+        summary
+    }
+
+    /// Summarizes the invariant checks of all parties over all repetitions (see `Timings::assert_invariant`).
+    pub fn summarize_invariants(&self) -> InvariantSummary {
+        let mut summary = InvariantSummary {
+            invariant_names: vec![],
+            passed: vec![],
+            failed: vec![],
+            failed_repetitions: vec![],
+        };
+
+        for party_timings in &self.timings {
+            let mut failed_in_repetition = vec![];
+            for (name, holds) in party_timings.iter().flat_map(|timing| &timing.invariants) {
+                let i = match summary.invariant_names.iter().position(|n| n == name) {
+                    Some(i) => i,
+                    None => {
+                        summary.invariant_names.push(name.clone());
+                        summary.passed.push(0);
+                        summary.failed.push(0);
+                        summary.failed_repetitions.push(0);
+                        summary.invariant_names.len() - 1
+                    }
+                };
+
+                if *holds {
+                    summary.passed[i] += 1;
+                } else {
+                    summary.failed[i] += 1;
+                    if !failed_in_repetition.contains(&i) {
+                        failed_in_repetition.push(i);
+                        summary.failed_repetitions[i] += 1;
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Summarizes the custom metrics of all parties. A counter contributes its total of each repetition, and a
+    /// recorded value contributes every value that was recorded.
+    pub fn summarize_metrics(&self) -> MetricSummary {
+        let mut metric_names: Vec<String> = vec![];
+        let mut party_samples_per_name: Vec<HashMap<String, Vec<f64>>> =
+            (0..self.party_names.len())
+                .map(|_| HashMap::new())
+                .collect();
+
+        // The metrics are stored per repetition, with one entry per party
+        for party_timings in &self.timings {
+            for (timing, map) in party_timings.iter().zip(&mut party_samples_per_name) {
+                let counters = timing
+                    .counters
+                    .iter()
+                    .map(|(name, count)| (name, *count as f64));
+                let values = timing.values.iter().map(|(name, value)| (name, *value));
+                for (name, sample) in counters.chain(values) {
+                    if !metric_names.contains(name) {
+                        metric_names.push(name.clone());
+                    }
+
+                    map.entry(name.clone()).or_insert(vec![]).push(sample);
+                }
+            }
+        }
+
+        let summarize = |statistic: fn(&[f64]) -> f64| -> Vec<Vec<Option<f64>>> {
+            party_samples_per_name
+                .iter()
+                .map(|map| {
+                    metric_names
+                        .iter()
+                        .map(|name| map.get(name).map(|samples| statistic(samples)))
+                        .collect()
+                })
+                .collect()
+        };
+
+        MetricSummary {
+            party_names: self.party_names.clone(),
+            party_means: summarize(|samples| mean(samples.iter().cloned())),
+            party_stdevs: summarize(|samples| stddev(samples.iter().cloned())),
+            metric_names,
+        }
+    }
+}
+
+/// Contains the statistics of each stage of a pipeline, as well as its end-to-end durations.
+#[derive(Debug)]
+pub struct PipelineStats {
+    name: String,
+    stages: Vec<AggregatedStats>,
+    end_to_end: Vec<Duration>,
+}
+
+impl PipelineStats {
+    pub(crate) fn new(
+        name: String,
+        stages: Vec<AggregatedStats>,
+        end_to_end: Vec<Duration>,
+    ) -> Self {
+        PipelineStats {
+            name,
+            stages,
+            end_to_end,
+        }
+    }
+
+    /// Returns the statistics of each stage, in the order of the stages.
+    pub fn stages(&self) -> &[AggregatedStats] {
+        &self.stages
+    }
+
+    /// Returns the end-to-end duration of each repetition of the whole pipeline.
+    pub fn end_to_end(&self) -> &[Duration] {
+        &self.end_to_end
+    }
+
+    /// Prints a pretty table of the summarized timings of each stage, followed by the end-to-end duration.
+    pub fn print(&self) {
+        for stage in &self.stages {
+            println!("{}", stage.name());
+            stage.summarize_timings().print();
+        }
+
+        let durations = self.end_to_end.iter().map(Duration::as_secs_f64);
+        println!(
+            "{} (end-to-end): {:.3} ± {:.3} s",
+            self.name,
+            mean(durations.clone()),
+            stddev(durations)
+        );
+    }
+}
+
+/// An event on a party's timeline, recorded when tracing is enabled (see `EvaluationSettings::with_tracing`). The
+/// times are relative to the start of the repetition.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub(crate) enum TraceEvent {
+    /// A timer that ran from `start` for `duration` (which is not scaled by the slowdown factor).
+    Timer {
+        name: String,
+        start: Duration,
+        duration: Duration,
+    },
+    /// A message of `bytes` bytes that was sent to the party with `to_id`.
+    Sent {
+        to_id: usize,
+        bytes: usize,
+        time: Duration,
+    },
+    /// A message of `bytes` bytes from the party with `from_id` that became available to the party.
+    Received {
+        from_id: usize,
+        bytes: usize,
+        time: Duration,
+    },
+}
+
+/// A snapshot of one party's progress, taken periodically while a repetition runs.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    /// The time since the start of the repetition.
+    pub elapsed: Duration,
+    /// The id of the party.
+    pub party: usize,
+    /// The total number of bytes that the party sent so far.
+    pub sent_bytes: usize,
+    /// The average number of bytes per second that the party sent since the previous snapshot.
+    pub sent_bytes_per_second: f64,
+    /// The number of timers that the party stopped so far.
+    pub completed_timers: usize,
+    /// The name of the innermost timer that is running, if any.
+    pub phase: Option<String>,
+}
+
+/// A party's progress that is shared with the thread taking snapshots.
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    sent_bytes: AtomicUsize,
+    completed_timers: AtomicUsize,
+    running_timers: Mutex<Vec<String>>,
+    /// The time that the party spent waiting for the network, in nanoseconds.
+    waiting_nanos: AtomicU64,
+    round: AtomicUsize,
+}
+
+impl Progress {
+    pub(crate) fn add_sent_bytes(&self, byte_count: usize) {
+        self.sent_bytes.fetch_add(byte_count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_waiting_time(&self, duration: Duration) {
+        self.waiting_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn waiting_time(&self) -> Duration {
+        Duration::from_nanos(self.waiting_nanos.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn set_round(&self, round: usize) {
+        self.round.store(round, Ordering::Relaxed);
+    }
+
+    /// Returns the round that the party is in (see `Channels::round`).
+    #[cfg(feature = "dashboard")]
+    pub(crate) fn round(&self) -> usize {
+        self.round.load(Ordering::Relaxed)
+    }
+
+    /// Returns the name of the innermost timer that is running, if any.
+    #[cfg(feature = "dashboard")]
+    pub(crate) fn running_timer(&self) -> Option<String> {
+        self.running_timers.lock().unwrap().last().cloned()
+    }
+
+    fn start_timer(&self, name: &str) {
+        self.running_timers.lock().unwrap().push(name.to_string());
+    }
+
+    pub(crate) fn sent_bytes(&self) -> usize {
+        self.sent_bytes.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn completed_timers(&self) -> usize {
+        self.completed_timers.load(Ordering::Relaxed)
+    }
+
+    fn stop_timer(&self, name: &str) {
+        let mut running_timers = self.running_timers.lock().unwrap();
+        if let Some(position) = running_timers.iter().rposition(|running| running == name) {
+            running_timers.remove(position);
+        }
+
+        self.completed_timers.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Takes a snapshot of each party's `progress` every `interval` until `done` is set (and once more afterwards).
+/// The thread that sets `done` should unpark this thread so that it does not wait for the remaining interval.
+pub(crate) fn take_snapshots(
+    progress: &[Arc<Progress>],
+    interval: Duration,
+    done: &AtomicBool,
+) -> Vec<Snapshot> {
+    let start_time = Instant::now();
+    let mut previous_time = start_time;
+    let mut previous_sent_bytes = vec![0; progress.len()];
+    let mut snapshots = vec![];
+
+    loop {
+        park_timeout(interval);
+        let finished = done.load(Ordering::Acquire);
+
+        let now = Instant::now();
+        for (party, party_progress) in progress.iter().enumerate() {
+            let sent_bytes = party_progress.sent_bytes.load(Ordering::Relaxed);
+            let window = (now - previous_time).as_secs_f64();
+
+            snapshots.push(Snapshot {
+                elapsed: now - start_time,
+                party,
+                sent_bytes,
+                sent_bytes_per_second: if window > 0. {
+                    (sent_bytes - previous_sent_bytes[party]) as f64 / window
+                } else {
+                    0.
+                },
+                completed_timers: party_progress.completed_timers.load(Ordering::Relaxed),
+                phase: party_progress
+                    .running_timers
+                    .lock()
+                    .unwrap()
+                    .last()
+                    .cloned(),
+            });
+
+            previous_sent_bytes[party] = sent_bytes;
+        }
+        previous_time = now;
+
+        if finished {
+            return snapshots;
+        }
+    }
+}
+
+/// A histogram of the sizes of the messages that a party sent (see `EvaluationSettings::with_message_size_histogram`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct MessageSizeHistogram {
+    bounds: Vec<usize>,
+    counts: Vec<usize>,
+}
+
+impl MessageSizeHistogram {
+    pub(crate) fn new(bounds: Vec<usize>) -> Self {
+        MessageSizeHistogram {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+        }
+    }
+
+    /// Counts a message of `byte_count` bytes in the smallest bucket that fits it.
+    pub(crate) fn record(&mut self, byte_count: usize) {
+        self.counts[self.bounds.partition_point(|&bound| bound < byte_count)] += 1;
+    }
+
+    /// Returns the upper bound in bytes of each bucket but the last one, which holds the larger messages.
+    pub fn bounds(&self) -> &[usize] {
+        &self.bounds
+    }
+
+    /// Returns the number of messages in each bucket.
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// Returns a label for each bucket, e.g. `<=64` and `>1024`.
+    pub fn labels(&self) -> Vec<String> {
+        self.bounds
+            .iter()
+            .map(|bound| format!("<={}", bound))
+            .chain(self.bounds.last().map(|bound| format!(">{}", bound)))
+            .chain(self.bounds.is_empty().then(|| "all".to_string()))
+            .collect()
+    }
+}
+
+/// The start of a round (relative to the start of the repetition), and the bytes that the party sent and received in
+/// it, as recorded by the party's `Channels`.
+pub(crate) type RoundRecord = (Duration, usize, usize);
+
+/// The duration of one round of a party and the bytes that it sent and received in that round (see
+/// `EvaluationSettings::with_round_statistics`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct RoundStats {
+    duration: Duration,
+    sent_bytes: usize,
+    received_bytes: usize,
+}
+
+impl RoundStats {
+    /// Returns the duration of the round.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns the number of bytes that the party sent in the round.
+    pub fn sent_bytes(&self) -> usize {
+        self.sent_bytes
+    }
+
+    /// Returns the number of bytes that the party received in the round.
+    pub fn received_bytes(&self) -> usize {
+        self.received_bytes
+    }
+}
+
+/// Statistics pertaining to one party, such as the number of bytes sent and the durations measured.
+#[derive(Debug)]
+#[cfg_attr(feature = "persistence", derive(Serialize, Deserialize))]
+pub struct Timings {
+    measured_durations: Vec<(String, Duration)>,
+    cpu_durations: Vec<(String, Duration)>,
+    tracks_cpu_time: bool,
+    phase_bytes: Vec<(String, usize)>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    waiting_durations: Vec<(String, Duration)>,
+    sent_bytes: Vec<usize>,
+    received_bytes: Vec<usize>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    sent_message_counts: Vec<usize>,
+    fragment_counts: Vec<usize>,
+    relayed_bytes: usize,
+    #[cfg(feature = "compression")]
+    #[cfg_attr(feature = "persistence", serde(default))]
+    uncompressed_bytes: usize,
+    #[cfg(feature = "compression")]
+    #[cfg_attr(feature = "persistence", serde(default))]
+    compressed_bytes: usize,
+    invariants: Vec<(String, bool)>,
+    counters: Vec<(String, u64)>,
+    values: Vec<(String, f64)>,
+    comm_checkpoints: Vec<CommCheckpoint>,
+    session_comm: Vec<CommCheckpoint>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    trace: Vec<TraceEvent>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    message_sizes: Option<MessageSizeHistogram>,
+    #[cfg_attr(feature = "persistence", serde(default))]
+    rounds: Vec<RoundStats>,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    trace_start: Option<Instant>,
+    slowdown_factor: f64,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    progress: Option<Arc<Progress>>,
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    watchdog: Option<Arc<Watchdog>>,
+    #[cfg(feature = "profiling")]
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    profiler: Mutex<Option<PartyProfiler>>,
+}
+
+impl Timings {
+    pub(crate) fn new() -> Self {
+        Timings {
+            measured_durations: vec![],
+            cpu_durations: vec![],
+            tracks_cpu_time: false,
+            phase_bytes: vec![],
+            waiting_durations: vec![],
+            sent_bytes: vec![],
+            received_bytes: vec![],
+            sent_message_counts: vec![],
+            fragment_counts: vec![],
+            relayed_bytes: 0,
+            #[cfg(feature = "compression")]
+            uncompressed_bytes: 0,
+            #[cfg(feature = "compression")]
+            compressed_bytes: 0,
+            invariants: vec![],
+            counters: vec![],
+            values: vec![],
+            comm_checkpoints: vec![],
+            session_comm: vec![],
+            trace: vec![],
+            trace_start: None,
+            message_sizes: None,
+            rounds: vec![],
+            slowdown_factor: 1.,
+            progress: None,
+            watchdog: None,
+            #[cfg(feature = "profiling")]
+            profiler: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn set_slowdown_factor(&mut self, slowdown_factor: f64) {
+        self.slowdown_factor = slowdown_factor;
+    }
+
+    /// Measures the CPU time of this party's timers from now on, which requires that the party runs on one thread.
+    pub(crate) fn track_cpu_time(&mut self) {
+        self.tracks_cpu_time = true;
+    }
+
+    pub(crate) fn attach_progress(&mut self, progress: Arc<Progress>) {
+        self.progress = Some(progress);
+    }
+
+    pub(crate) fn attach_watchdog(&mut self, watchdog: Arc<Watchdog>) {
+        self.watchdog = Some(watchdog);
+    }
+
+    #[cfg(feature = "profiling")]
+    pub(crate) fn attach_profiler(&mut self, profiler: PartyProfiler) {
+        *self.profiler.get_mut().unwrap() = Some(profiler);
+    }
+
+    #[cfg(feature = "profiling")]
+    pub(crate) fn restore_thread_name(&mut self) {
+        if let Some(profiler) = self.profiler.get_mut().unwrap().as_ref() {
+            profiler.restore_thread_name();
+        }
+    }
+
+    #[cfg(feature = "profiling")]
+    pub(crate) fn detach_profiler(&mut self) -> Option<PartyProfiler> {
+        self.profiler.get_mut().unwrap().take()
+    }
+
+    pub(crate) fn write_bytes(&mut self, sent_bytes: &[usize], received_bytes: &[usize]) {
+        self.sent_bytes = sent_bytes.to_vec();
+        self.received_bytes = received_bytes.to_vec();
+    }
+
+    pub(crate) fn write_message_counts(&mut self, sent_message_counts: &[usize]) {
+        self.sent_message_counts = sent_message_counts.to_vec();
+    }
+
+    pub(crate) fn write_message_sizes(&mut self, message_sizes: Option<&MessageSizeHistogram>) {
+        self.message_sizes = message_sizes.cloned();
+    }
+
+    /// Writes the statistics of the rounds from their start (relative to the start of the repetition), sent bytes and
+    /// received bytes. The last round ends when the party's "Total" timer stopped.
+    pub(crate) fn write_rounds(&mut self, rounds: &[RoundRecord]) {
+        let end = self
+            .measured_durations
+            .iter()
+            .find(|(name, _)| name == "Total")
+            .map_or(Duration::ZERO, |&(_, duration)| duration);
+        let ends = rounds
+            .iter()
+            .skip(1)
+            .map(|&(start, _, _)| start)
+            .chain([end]);
+
+        self.rounds = rounds
+            .iter()
+            .zip(ends)
+            .map(|(&(start, sent_bytes, received_bytes), end)| RoundStats {
+                duration: end.saturating_sub(start),
+                sent_bytes,
+                received_bytes,
+            })
+            .collect();
+    }
+
+    pub(crate) fn write_fragment_counts(&mut self, fragment_counts: &[usize]) {
+        self.fragment_counts.extend_from_slice(fragment_counts);
+    }
+
+    pub(crate) fn write_relayed_bytes(&mut self, relayed_bytes: usize) {
+        self.relayed_bytes += relayed_bytes;
+    }
+
+    #[cfg(feature = "compression")]
+    pub(crate) fn write_compression(&mut self, uncompressed_bytes: usize, compressed_bytes: usize) {
+        self.uncompressed_bytes += uncompressed_bytes;
+        self.compressed_bytes += compressed_bytes;
+    }
+
+    pub(crate) fn write_comm_checkpoints(&mut self, comm_checkpoints: &[CommCheckpoint]) {
+        self.comm_checkpoints.extend_from_slice(comm_checkpoints);
+    }
+
+    /// Starts recording the timers as `TraceEvent`s, relative to `start`.
+    pub(crate) fn attach_trace(&mut self, start: Instant) {
+        self.trace_start = Some(start);
+    }
+
+    /// Adds the `TraceEvent`s of the party's messages to its trace.
+    pub(crate) fn write_trace(&mut self, events: &[TraceEvent]) {
+        self.trace.extend_from_slice(events);
+    }
+
+    pub(crate) fn write_session_comm(&mut self, session_comm: &[CommCheckpoint]) {
+        self.session_comm.extend_from_slice(session_comm);
+    }
+
+    /// Returns the name and duration of every timer that this party stopped, in the order in which they stopped.
+    pub fn measured_durations(&self) -> &[(String, Duration)] {
+        &self.measured_durations
+    }
+
+    /// Returns the statistics of each round of this party, in the order of the rounds. It is empty if they were not
+    /// recorded (see `EvaluationSettings::with_round_statistics`).
+    pub fn rounds(&self) -> &[RoundStats] {
+        &self.rounds
+    }
+
+    /// Returns the histogram of the sizes of the messages that this party sent, if it was recorded (see
+    /// `EvaluationSettings::with_message_size_histogram`).
+    pub fn message_sizes(&self) -> Option<&MessageSizeHistogram> {
+        self.message_sizes.as_ref()
+    }
+
+    /// Returns the number of bytes that this party sent to each party, indexed by party id. It is empty if the
+    /// party's bytes were not tracked.
+    pub fn sent_bytes(&self) -> &[usize] {
+        &self.sent_bytes
+    }
+
+    /// Returns the number of bytes that this party received from each party, indexed by party id. It is empty if the
+    /// party's bytes were not tracked.
+    pub fn received_bytes(&self) -> &[usize] {
+        &self.received_bytes
+    }
+
+    /// Returns the total of every counter of this party (see `increment_counter`).
+    pub fn counters(&self) -> &[(String, u64)] {
+        &self.counters
+    }
+
+    /// Returns every value that this party recorded (see `record_value`), in the order in which it was recorded.
+    pub fn values(&self) -> &[(String, f64)] {
+        &self.values
+    }
+
+    pub(crate) fn write_duration(&mut self, name: String, duration: Duration) {
+        self.measured_durations.push((name, duration));
+    }
+
+    /// Returns the name and outcome of every invariant that this party checked (see `assert_invariant`).
+    pub fn invariants(&self) -> &[(String, bool)] {
+        &self.invariants
+    }
+}
+
+/// A `Timer` that starts measuring a duration upon creation, until it is stopped. It also counts the bytes that the
+/// party sends in the meantime, if they are tracked.
+pub struct Timer {
+    name: String,
+    start_time: Instant,
+    start_cpu_time: Option<Duration>,
+    start_sent_bytes: Option<usize>,
+    start_waiting_time: Option<Duration>,
+}
+
+impl Timer {
+    fn new(
+        name: String,
+        tracks_cpu_time: bool,
+        start_sent_bytes: Option<usize>,
+        start_waiting_time: Option<Duration>,
+    ) -> Self {
+        // The CPU time is read after the wall-clock time, so that the CPU time is measured within the wall-clock time
+        let start_time = Instant::now();
+        let start_cpu_time = tracks_cpu_time.then(thread_cpu_time).flatten();
+        Timer {
+            name,
+            start_time,
+            start_cpu_time,
+            start_sent_bytes,
+            start_waiting_time,
+        }
+    }
+
+    fn stop(&self) -> (String, Duration) {
+        (self.name.clone(), self.start_time.elapsed())
+    }
+}
+
+/// A `Timer` that is stopped when it is dropped (see `Timings::scoped_timer`).
+pub struct ScopedTimer<'a> {
+    timings: &'a mut Timings,
+    timer: Option<Timer>,
+}
+
+impl Deref for ScopedTimer<'_> {
+    type Target = Timings;
+
+    fn deref(&self) -> &Timings {
+        self.timings
+    }
+}
+
+impl DerefMut for ScopedTimer<'_> {
+    fn deref_mut(&mut self) -> &mut Timings {
+        self.timings
+    }
+}
+
+impl Drop for ScopedTimer<'_> {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            self.timings.stop_timer(timer);
+        }
+    }
+}
+
+impl Timings {
+    /// Creates a timer with the given `name` that starts running immediately.
+    pub fn create_timer(&self, name: &str) -> Timer {
+        #[cfg(feature = "profiling")]
+        if let Some(profiler) = self.profiler.lock().unwrap().as_mut() {
+            profiler.enter_phase(name);
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.start_timer(name);
+        }
+
+        let start_sent_bytes = self.progress.as_ref().map(|progress| progress.sent_bytes());
+        let start_waiting_time = self
+            .progress
+            .as_ref()
+            .map(|progress| progress.waiting_time());
+        Timer::new(
+            String::from(name),
+            self.tracks_cpu_time,
+            start_sent_bytes,
+            start_waiting_time,
+        )
+    }
+
+    /// Creates a timer named `name` that is nested in the `parent` timer and starts running immediately. It is named
+    /// `{parent}/{name}`, so `create_timer("online/round 1")` creates the same timer. Nested timers are summarized as
+    /// a hierarchy by `TimingSummary::print_hierarchy`.
+    pub fn create_nested_timer(&self, parent: &Timer, name: &str) -> Timer {
+        self.create_timer(&format!("{}/{}", parent.name, name))
+    }
+
+    /// Stops the `timer` and writes it measured duration to this party's statistics. The duration is scaled by the
+    /// party's slowdown factor (see `Party::slowdown_factor`). Note that this includes any time spent waiting for the
+    /// network, e.g. in a blocking `Channels::receive`, so timers that should model computation only must not span
+    /// communication. How much of a timer was spent waiting is reported by `AggregatedStats::summarize_idle_time`.
+    pub fn stop_timer(&mut self, timer: Timer) {
+        self.finish_timer(timer, self.slowdown_factor);
+    }
+
+    /// Measures the duration of running `f` as the timer `name`, like `create_timer` followed by `stop_timer`, and
+    /// returns its result.
+    pub fn time<T>(&mut self, name: &str, f: impl FnOnce() -> T) -> T {
+        let _timer = self.scoped_timer(name);
+        f()
+    }
+
+    /// Creates a timer with the given `name` that is stopped when the returned guard is dropped, so that an early
+    /// return (e.g. with `?`) does not lose the measurement. The guard dereferences to these `Timings`, so that they
+    /// remain usable (e.g. for nested scoped timers) while the timer runs.
+    pub fn scoped_timer(&mut self, name: &str) -> ScopedTimer<'_> {
+        let timer = self.create_timer(name);
+        ScopedTimer {
+            timings: self,
+            timer: Some(timer),
+        }
+    }
+
+    /// Stops the `timer` without scaling its duration, e.g. for the harness's "Total" timer, which measures the
+    /// party's actual run time.
+    pub(crate) fn stop_unscaled_timer(&mut self, timer: Timer) {
+        self.finish_timer(timer, 1.);
+    }
+
+    fn finish_timer(&mut self, timer: Timer, slowdown_factor: f64) {
+        // The CPU time is read before the wall-clock time, in the reverse order of `Timer::new`
+        let cpu_time = timer.start_cpu_time.and_then(|_| thread_cpu_time());
+        let (name, duration) = timer.stop();
+        if let Some(trace_start) = self.trace_start {
+            self.trace.push(TraceEvent::Timer {
+                name: name.clone(),
+                start: timer.start_time.saturating_duration_since(trace_start),
+                duration,
+            });
+        }
+        // The thread's CPU clock may be coarser than the wall clock, so the CPU time is capped at the wall-clock time
+        if let (Some(start_cpu_time), Some(cpu_time)) = (timer.start_cpu_time, cpu_time) {
+            let cpu_duration = cpu_time.saturating_sub(start_cpu_time).min(duration);
+            self.cpu_durations
+                .push((name.clone(), cpu_duration.mul_f64(slowdown_factor)));
+        }
+        let duration = duration.mul_f64(slowdown_factor);
+
+        #[cfg(feature = "profiling")]
+        if let Some(profiler) = self.profiler.get_mut().unwrap() {
+            profiler.exit_phase(&name);
+        }
+
+        if let Some(progress) = &self.progress {
+            progress.stop_timer(&name);
+            if let Some(start_sent_bytes) = timer.start_sent_bytes {
+                let sent_bytes = progress.sent_bytes() - start_sent_bytes;
+                self.phase_bytes.push((name.clone(), sent_bytes));
+            }
+            if let Some(start_waiting_time) = timer.start_waiting_time {
+                let waiting_time = progress.waiting_time().saturating_sub(start_waiting_time);
+                self.waiting_durations.push((name.clone(), waiting_time));
+            }
+        }
+
+        self.write_duration(name, duration);
+    }
+
+    /// Records whether the invariant `name` holds (`condition`) in this repetition. Unlike `assert!`, a violated
+    /// invariant does not abort the protocol: the number of passed and failed checks becomes part of the report (see
+    /// `AggregatedStats::summarize_invariants`).
+    pub fn assert_invariant(&mut self, name: &str, condition: bool) {
+        self.invariants.push((name.to_string(), condition));
+    }
+
+    /// Adds `amount` to the counter `name` of this repetition, e.g. to count the Beaver triples that the party
+    /// consumed or the OTs that it executed. The totals are aggregated over the repetitions (see
+    /// `AggregatedStats::summarize_metrics`).
+    pub fn increment_counter(&mut self, name: &str, amount: u64) {
+        match self
+            .counters
+            .iter_mut()
+            .find(|(counter, _)| counter == name)
+        {
+            Some((_, count)) => *count += amount,
+            None => self.counters.push((name.to_string(), amount)),
+        }
+    }
+
+    /// Records a `value` of the metric `name`, e.g. the number of garbled gates of a circuit. Every recorded value is
+    /// aggregated as a sample of the metric (see `AggregatedStats::summarize_metrics`).
+    pub fn record_value(&mut self, name: &str, value: f64) {
+        self.values.push((name.to_string(), value));
+    }
+
+    /// Simulates a computation that would take `duration` on an unimpeded machine, by blocking for that duration
+    /// scaled by the party's slowdown factor. Unlike the scaling of timers, this also delays the party's messages.
+    pub fn simulate_compute(&self, duration: Duration) {
+        let duration = duration.mul_f64(self.slowdown_factor);
+        match &self.watchdog {
+            Some(watchdog) => watchdog.sleep_until(Instant::now() + duration),
+            None => sleep(duration),
+        }
+    }
+}
+
+/// The 97.5th percentile of Student's t-distribution with 1 to 30 degrees of freedom.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Returns the 95% confidence interval of the mean of the `samples`, or `None` if there are fewer than two samples.
+pub(crate) fn confidence_interval(samples: &[f64]) -> Option<(f64, f64)> {
+    if samples.len() < 2 {
+        return None;
+    }
+
+    let n = samples.len() as f64;
+    let mean = mean(samples.iter().cloned());
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.);
+
+    // Beyond the table, the percentile is approximated by expanding it around the normal distribution's
+    let df = samples.len() - 1;
+    let t = match T_CRITICAL_95.get(df - 1) {
+        Some(&t) => t,
+        None => {
+            let (z, df) = (1.959964f64, df as f64);
+            z + (z.powi(3) + z) / (4. * df)
+                + (5. * z.powi(5) + 16. * z.powi(3) + 3. * z) / (96. * df.powi(2))
+        }
+    };
+
+    let half_width = t * (variance / n).sqrt();
+    Some((mean - half_width, mean + half_width))
+}
+
+/// Formats a mean duration together with its confidence `interval`, if any.
+fn format_mean(mean: f64, interval: Option<(f64, f64)>) -> String {
+    let (scale, unit) = duration_unit(mean);
+    match interval {
+        Some((lower, upper)) => format!(
+            "{:.3} {} [{:.3}, {:.3}]",
+            mean * scale,
+            unit,
+            lower * scale,
+            upper * scale
+        ),
+        None => format!("{:.3} {}", mean * scale, unit),
+    }
+}
+
+/// Formats a duration of `seconds` in the unit that suits its magnitude.
+pub(crate) fn format_duration(seconds: f64) -> String {
+    let (scale, unit) = duration_unit(seconds);
+    format!("{:.3} {}", seconds * scale, unit)
+}
+
+/// Formats the mean and standard deviation of a duration in seconds, both in the unit that suits the mean.
+fn format_duration_with_stdev(mean: f64, stdev: f64) -> String {
+    let (scale, unit) = duration_unit(mean);
+    format!("{:.3} ± {:.3} {}", mean * scale, stdev * scale, unit)
+}
+
+/// Returns the factor that converts a duration of `seconds` to the largest unit in which it is at least one, and the
+/// unit's symbol. Durations below a millisecond (including zero) are expressed in microseconds.
+fn duration_unit(seconds: f64) -> (f64, &'static str) {
+    match seconds.abs() {
+        s if s >= 1. => (1., "s"),
+        s if s >= 1e-3 => (1e3, "ms"),
+        _ => (1e6, "µs"),
+    }
+}
+
+/// Formats a number of `bytes` in the unit that suits its magnitude.
+pub(crate) fn format_bytes(bytes: f64) -> String {
+    let (scale, unit, decimals) = byte_unit(bytes);
+    format!("{:.*} {}", decimals, bytes * scale, unit)
+}
+
+/// Formats the mean and standard deviation of a number of bytes, both in the unit that suits the mean.
+fn format_bytes_with_stdev(mean: f64, stdev: f64) -> String {
+    let (scale, unit, decimals) = byte_unit(mean);
+    format!(
+        "{:.*} ± {:.*} {}",
+        decimals,
+        mean * scale,
+        decimals,
+        stdev * scale,
+        unit
+    )
+}
+
+/// Returns the factor that converts a number of `bytes` to the largest (decimal) unit in which it is at least one,
+/// the unit's symbol and the number of decimals to print it with.
+fn byte_unit(bytes: f64) -> (f64, &'static str, usize) {
+    match bytes.abs() {
+        b if b >= 1e9 => (1e-9, "GB", 2),
+        b if b >= 1e6 => (1e-6, "MB", 2),
+        b if b >= 1e3 => (1e-3, "KB", 2),
+        _ => (1., "B", 1),
+    }
+}
+
+/// Returns the `p`-th quantile (between 0 and 1) of the non-empty `sorted` samples, interpolating linearly between the
+/// closest samples.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Returns the CPU time that the calling thread consumed so far.
+#[cfg(all(feature = "cpu-time", unix))]
+fn thread_cpu_time() -> Option<Duration> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } == -1 {
+        return None;
+    }
+
+    Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+/// Returns the CPU time that the calling thread consumed so far, which cannot be measured without the `cpu-time`
+/// feature on Unix.
+#[cfg(not(all(feature = "cpu-time", unix)))]
+fn thread_cpu_time() -> Option<Duration> {
+    None
 }
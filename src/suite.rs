@@ -0,0 +1,202 @@
+use std::{
+    fs::{create_dir_all, File},
+    io,
+    path::Path,
+    thread,
+};
+
+use rayon::ThreadPoolBuilder;
+
+use crate::{
+    comm::NetworkDescription,
+    error::EvaluationError,
+    experiment::{csv_header, file_name, Experiment, ExperimentResults},
+    Protocol,
+};
+
+/// Runs one registered experiment of a suite.
+type RunExperiment<'a> =
+    Box<dyn Fn() -> Result<ExperimentResults, EvaluationError> + Send + Sync + 'a>;
+
+/// A collection of named experiments (e.g. all protocols, networks and sizes of a paper's evaluation) that are run
+/// together and reported in one place, instead of from a hand-written main function. The experiments may sweep
+/// different `Protocol` types over different networks.
+pub struct ExperimentSuite<'a> {
+    name: String,
+    experiments: Vec<(String, RunExperiment<'a>)>,
+    parallel: bool,
+    output_directory: Option<String>,
+}
+
+impl<'a> ExperimentSuite<'a> {
+    /// Constructs a suite named `name` without experiments, which runs its experiments sequentially and does not
+    /// persist their results.
+    pub fn new(name: &str) -> Self {
+        ExperimentSuite {
+            name: name.to_string(),
+            experiments: vec![],
+            parallel: false,
+            output_directory: None,
+        }
+    }
+
+    /// Registers the `experiment`, which runs after the experiments that were registered before it. Panics if an
+    /// experiment with the same name was already registered.
+    pub fn with_experiment<P, N>(mut self, experiment: Experiment<P, N>) -> Self
+    where
+        P: Protocol + Send + Sync + 'a,
+        N: NetworkDescription + Send + Sync + 'a,
+    {
+        let name = experiment.name().to_string();
+        assert!(
+            self.experiments
+                .iter()
+                .all(|(existing, _)| *existing != name),
+            "the experiment '{}' was already registered in '{}'",
+            name,
+            self.name
+        );
+
+        self.experiments
+            .push((name, Box::new(move || experiment.run())));
+        self
+    }
+
+    /// Runs the experiments concurrently, each on a thread with a thread pool of its own for its parties. This is
+    /// faster, but the experiments compete for the CPU, so their timings are less accurate than when they run
+    /// sequentially.
+    pub fn in_parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
+    /// Persists the results of each experiment as soon as it completes, to a csv named after the experiment in the
+    /// `directory` (see `ExperimentResults::write_csv`), so that the completed experiments are not lost when a later
+    /// one fails. The directory is created if it does not exist.
+    pub fn with_output_directory(mut self, directory: &str) -> Self {
+        self.output_directory = Some(directory.to_string());
+        self
+    }
+
+    /// Returns the names of the registered experiments, in the order in which they were registered.
+    pub fn experiment_names(&self) -> Vec<&str> {
+        self.experiments
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Runs every experiment and returns their results in the order in which they were registered. Sequentially,
+    /// it stops at the first experiment that fails. In parallel, every experiment runs and the failure of the first
+    /// one that failed is returned.
+    pub fn run(&self) -> Result<SuiteResults, EvaluationError> {
+        if let Some(directory) = &self.output_directory {
+            create_dir_all(directory)?;
+        }
+
+        let results = if self.parallel {
+            thread::scope(|scope| {
+                let handles: Vec<_> = self
+                    .experiments
+                    .iter()
+                    .map(|experiment| {
+                        scope.spawn(move || {
+                            // The parties of an experiment may wait for each other, so they must not be starved by
+                            // the parties of the other experiments in a shared pool
+                            let pool = ThreadPoolBuilder::new()
+                                .build()
+                                .expect("could not build a thread pool for the experiment");
+                            pool.install(|| self.run_experiment(experiment))
+                        })
+                    })
+                    .collect();
+
+                // Every experiment runs to completion before the first failure is returned
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .collect::<Result<_, _>>()
+            })?
+        } else {
+            self.experiments
+                .iter()
+                .map(|experiment| self.run_experiment(experiment))
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(SuiteResults {
+            name: self.name.clone(),
+            results,
+        })
+    }
+
+    /// Runs one registered `experiment` and persists its results, if an output directory was set.
+    fn run_experiment(
+        &self,
+        (name, run): &(String, RunExperiment<'a>),
+    ) -> Result<ExperimentResults, EvaluationError> {
+        let results = run()?;
+        if let Some(directory) = &self.output_directory {
+            let filename = Path::new(directory).join(format!("{}.csv", file_name(name)));
+            results.write_csv(&filename.to_string_lossy())?;
+        }
+        Ok(results)
+    }
+}
+
+/// The results of every experiment of an `ExperimentSuite`, in the order in which they were registered.
+#[derive(Debug)]
+pub struct SuiteResults {
+    name: String,
+    results: Vec<ExperimentResults>,
+}
+
+impl SuiteResults {
+    /// Returns the name of the suite.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the results of every experiment, in the order in which they were registered.
+    pub fn results(&self) -> &[ExperimentResults] {
+        &self.results
+    }
+
+    /// Returns the results of the experiment named `name`, if it was part of the suite.
+    pub fn experiment(&self, name: &str) -> Option<&ExperimentResults> {
+        self.results.iter().find(|results| results.name() == name)
+    }
+
+    /// Prints the metrics of every experiment, one table per experiment (see `ExperimentResults::print_metrics`).
+    pub fn print(&self) {
+        println!("{}", self.name);
+        for results in &self.results {
+            results.print_metrics();
+        }
+    }
+
+    /// Outputs the metrics of every experiment to one csv named `csv_filename`, with the columns of
+    /// `ExperimentResults::write_csv` and a column for each parameter that any experiment swept.
+    pub fn write_csv(&self, csv_filename: &str) -> io::Result<()> {
+        let writer = File::create(csv_filename)?;
+        let mut csv_writer = csv::Writer::from_writer(writer);
+
+        let mut param_names: Vec<String> = vec![];
+        for name in self.results.iter().flat_map(ExperimentResults::param_names) {
+            if !param_names.contains(&name) {
+                param_names.push(name);
+            }
+        }
+
+        csv_writer.write_record(csv_header(&param_names))?;
+        for results in &self.results {
+            for record in results.csv_records(&param_names) {
+                csv_writer.write_record(&record)?;
+            }
+        }
+
+        csv_writer.flush()
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    io::{self, BufReader, Read, Write},
+    net::{Ipv4Addr, Shutdown, TcpListener, TcpStream},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::comm::{Channels, Message, NetworkDescription, Transport};
+
+/// A network description that connects every pair of parties through a real TCP socket on localhost, instead of an
+/// in-memory channel. Messages are serialized and pass through the kernel's networking stack, so the measured run
+/// times include these costs. Simulated latency and throughput are added on top of them, if configured.
+#[derive(Debug, Default)]
+pub struct TcpNetwork {
+    latency: Duration,
+    seconds_per_byte: Duration,
+}
+
+impl TcpNetwork {
+    /// Constructs a TcpNetwork without simulated communication overhead.
+    pub fn new() -> Self {
+        TcpNetwork::default()
+    }
+
+    /// Constructs a TcpNetwork that additionally simulates the specified `latency` and throughput (maximum
+    /// `bytes_per_second`).
+    pub fn new_with_overhead(latency: Duration, bytes_per_second: f64) -> Self {
+        TcpNetwork {
+            latency,
+            seconds_per_byte: Duration::from_secs_f64(1. / bytes_per_second),
+        }
+    }
+}
+
+impl NetworkDescription for TcpNetwork {
+    /// Instantiates the Channels for each party. Panics if the sockets cannot be set up.
+    fn instantiate(&self, n_parties: usize) -> Vec<Channels> {
+        connect_parties(n_parties)
+            .expect("could not set up the TCP sockets between the parties")
+            .into_iter()
+            .enumerate()
+            .map(|(id, (transports, receiver))| {
+                Channels::with_transports(
+                    id,
+                    transports,
+                    receiver,
+                    self.latency,
+                    self.seconds_per_byte,
+                )
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// The transports to every party and the queue of incoming messages of one party.
+pub(crate) type Connected = (Vec<Box<dyn Transport>>, Receiver<Message>);
+
+/// A transport over a TCP socket to the other party, possibly on another machine.
+pub(crate) struct TcpTransport(pub(crate) TcpStream);
+
+impl Transport for TcpTransport {
+    fn deliver(&self, message: Message) -> bool {
+        write_message(&self.0, &message).is_ok()
+    }
+}
+
+impl Drop for TcpTransport {
+    fn drop(&mut self) {
+        // The other party's reader thread holds a clone of the socket, so it only stops once the socket is shut down
+        let _ = self.0.shutdown(Shutdown::Write);
+    }
+}
+
+/// Returns the transports of `n_parties` parties, in which each party's transport to itself is its own queue and
+/// the other transports are still missing.
+pub(crate) fn local_transports(
+    queues: &[(Sender<Message>, Receiver<Message>)],
+) -> Vec<Vec<Option<Box<dyn Transport>>>> {
+    (0..queues.len())
+        .map(|id| {
+            (0..queues.len())
+                .map(|to_id| {
+                    (to_id == id).then(|| Box::new(queues[id].0.clone()) as Box<dyn Transport>)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Connects every pair of `n_parties` parties through a TCP socket on localhost. A reader thread per socket forwards
+/// the incoming messages to the receiving party's queue, until the sending party closes the socket.
+pub(crate) fn connect_parties(n_parties: usize) -> io::Result<Vec<Connected>> {
+    let queues: Vec<_> = (0..n_parties).map(|_| channel()).collect();
+    let mut links = local_transports(&queues);
+
+    for a in 0..n_parties {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let address = listener.local_addr()?;
+
+        for b in (a + 1)..n_parties {
+            let stream_b = TcpStream::connect(address)?;
+            let (stream_a, _) = listener.accept()?;
+
+            for (id, stream, other) in [(a, stream_a, b), (b, stream_b, a)] {
+                stream.set_nodelay(true)?;
+
+                let reader = stream.try_clone()?;
+                let queue = queues[id].0.clone();
+                thread::spawn(move || forward_messages(reader, other, queue));
+
+                links[id][other] = Some(Box::new(TcpTransport(stream)));
+            }
+        }
+    }
+
+    Ok(links
+        .into_iter()
+        .zip(queues)
+        .map(|(links, (_, receiver))| (links.into_iter().map(Option::unwrap).collect(), receiver))
+        .collect())
+}
+
+/// Reads the messages that the party with `from_id` sends over the `stream` and puts them in the receiving party's
+/// `queue`. Stops when the socket is closed or when the receiving party stopped receiving.
+pub(crate) fn forward_messages(stream: TcpStream, from_id: usize, queue: impl Transport) {
+    let mut reader = BufReader::new(stream);
+    while let Ok(message) = read_message(&mut reader, from_id) {
+        if !queue.deliver(message) {
+            return;
+        }
+    }
+}
+
+/// The length of the header of a frame that was encoded by `encode_message`.
+pub(crate) const HEADER_LENGTH: usize = 29;
+
+/// The largest length of a frame's contents that is read, so that a corrupt length field cannot exhaust the memory.
+pub(crate) const MAX_FRAME_LENGTH: usize = 1 << 30;
+
+/// The flag in a frame's header that marks an abort message.
+const ABORT_FLAG: u8 = 1;
+
+/// The flag in a frame's header that marks compressed contents (see `Channels::with_compression`).
+const COMPRESSED_FLAG: u8 = 2;
+
+/// Writes the `message` to the `stream` as a frame that was encoded by `encode_message`.
+pub(crate) fn write_message(mut stream: &TcpStream, message: &Message) -> io::Result<()> {
+    stream.write_all(&encode_message(message))
+}
+
+/// Reads a frame that was written by `write_message`.
+fn read_message(reader: &mut impl Read, from_id: usize) -> io::Result<Message> {
+    let mut header = [0; HEADER_LENGTH];
+    reader.read_exact(&mut header)?;
+
+    let mut contents = vec![0; checked_content_length(&header)?];
+    reader.read_exact(&mut contents)?;
+
+    Ok(decode_message(&header, contents, from_id))
+}
+
+/// Encodes the `message` as a frame consisting of a header (content length, padding, remaining simulated delay in
+/// nanoseconds, the abort and compression flags and the tag) followed by the contents. The delay is relative, because
+/// the parties' clocks are not comparable across machines.
+pub(crate) fn encode_message(message: &Message) -> Vec<u8> {
+    let delay = message
+        .arrival_time
+        .saturating_duration_since(Instant::now());
+
+    let mut frame = Vec::with_capacity(HEADER_LENGTH + message.contents.len());
+    frame.extend_from_slice(&(message.contents.len() as u64).to_le_bytes());
+    frame.extend_from_slice(&(message.padding as u64).to_le_bytes());
+    frame.extend_from_slice(&(delay.as_nanos() as u64).to_le_bytes());
+    let mut flags = 0;
+    if message.abort {
+        flags |= ABORT_FLAG;
+    }
+    if message.compressed {
+        flags |= COMPRESSED_FLAG;
+    }
+    frame.push(flags);
+    frame.extend_from_slice(&message.tag.to_le_bytes());
+    frame.extend_from_slice(&message.contents);
+    frame
+}
+
+/// Returns the length of the contents that follow the `header` of a frame.
+pub(crate) fn content_length(header: &[u8; HEADER_LENGTH]) -> usize {
+    header_field(header, 0) as usize
+}
+
+/// Returns the length of the contents that follow the `header` of a frame that was read from a stream, or an
+/// `InvalidData` error if it exceeds `MAX_FRAME_LENGTH`.
+pub(crate) fn checked_content_length(header: &[u8; HEADER_LENGTH]) -> io::Result<usize> {
+    frame_length(header_field(header, 0))
+}
+
+/// Checks that a `length` field read from a stream does not exceed `MAX_FRAME_LENGTH`.
+pub(crate) fn frame_length(length: u64) -> io::Result<usize> {
+    usize::try_from(length)
+        .ok()
+        .filter(|&length| length <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the frame length {} exceeds the maximum of {} bytes",
+                    length, MAX_FRAME_LENGTH
+                ),
+            )
+        })
+}
+
+/// Decodes a frame from its `header` and `contents`. The remaining delay starts when the frame was read.
+pub(crate) fn decode_message(
+    header: &[u8; HEADER_LENGTH],
+    contents: Vec<u8>,
+    from_id: usize,
+) -> Message {
+    Message {
+        arrival_time: Instant::now() + Duration::from_nanos(header_field(header, 2)),
+        from_id,
+        tag: u32::from_le_bytes(header[25..29].try_into().unwrap()),
+        contents: contents.into(),
+        padding: header_field(header, 1) as usize,
+        abort: header[24] & ABORT_FLAG != 0,
+        compressed: header[24] & COMPRESSED_FLAG != 0,
+        payload: None,
+    }
+}
+
+fn header_field(header: &[u8; HEADER_LENGTH], i: usize) -> u64 {
+    u64::from_le_bytes(header[8 * i..8 * (i + 1)].try_into().unwrap())
+}
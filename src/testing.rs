@@ -0,0 +1,98 @@
+use std::fmt::{self, Debug};
+
+use rand::{Rng, RngCore};
+
+use crate::{Party, PartyContext, Protocol};
+
+/// The code that every party of a `TestProtocol` runs, given its input and context.
+type Run<I, O> = fn(&I, &mut PartyContext) -> O;
+
+/// Generates the inputs of a `TestProtocol` for the given number of parties.
+type GenerateInputs<I> = Box<dyn Fn(usize, &mut dyn RngCore) -> Vec<I> + Send + Sync>;
+
+/// A party of a `TestProtocol`, which runs the protocol's code.
+pub(crate) struct TestParty<I, O> {
+    run: Run<I, O>,
+}
+
+impl<I: Send, O: Debug + Send> Party for TestParty<I, O> {
+    type Input = I;
+    type Output = O;
+
+    fn run(&mut self, input: &Self::Input, context: &mut PartyContext) -> Self::Output {
+        (self.run)(input, context)
+    }
+}
+
+/// A protocol for the tests whose parties all run the same code, so that a test only describes what the parties do.
+/// By default, every party gets the default input and all outputs are valid.
+pub(crate) struct TestProtocol<I, O> {
+    run: Run<I, O>,
+    generate_inputs: GenerateInputs<I>,
+    validate_outputs: fn(&[I], &[O]) -> bool,
+    dataset: Option<String>,
+}
+
+impl<I: Clone + Default + 'static, O> TestProtocol<I, O> {
+    /// Constructs a protocol whose parties `run` the given code.
+    pub(crate) fn new(run: Run<I, O>) -> Self {
+        TestProtocol {
+            run,
+            generate_inputs: Box::new(|n_parties, _| vec![I::default(); n_parties]),
+            validate_outputs: |_, _| true,
+            dataset: None,
+        }
+    }
+}
+
+impl<I, O> TestProtocol<I, O> {
+    /// Generates the inputs with `generate_inputs`, which is given the number of parties and the seeded rng.
+    pub(crate) fn with_inputs(
+        mut self,
+        generate_inputs: impl Fn(usize, &mut dyn RngCore) -> Vec<I> + Send + Sync + 'static,
+    ) -> Self {
+        self.generate_inputs = Box::new(generate_inputs);
+        self
+    }
+
+    /// Validates the outputs with `validate_outputs`, which is given the inputs and the outputs.
+    pub(crate) fn with_validation(mut self, validate_outputs: fn(&[I], &[O]) -> bool) -> Self {
+        self.validate_outputs = validate_outputs;
+        self
+    }
+
+    /// Reports that the inputs are read from the dataset file at `path` (see `Protocol::dataset`).
+    #[cfg(feature = "datasets")]
+    pub(crate) fn with_dataset(mut self, path: &str) -> Self {
+        self.dataset = Some(path.to_string());
+        self
+    }
+}
+
+impl<I, O> Debug for TestProtocol<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TestProtocol")
+    }
+}
+
+impl<I: Send, O: Debug + Send> Protocol for TestProtocol<I, O> {
+    type Party = TestParty<I, O>;
+
+    fn setup_parties(&self, n_parties: usize) -> Vec<Self::Party> {
+        (0..n_parties)
+            .map(|_| TestParty { run: self.run })
+            .collect()
+    }
+
+    fn generate_inputs(&self, n_parties: usize, rng: &mut impl Rng) -> Vec<I> {
+        (self.generate_inputs)(n_parties, rng)
+    }
+
+    fn validate_outputs(&self, inputs: &[I], outputs: &[O]) -> bool {
+        (self.validate_outputs)(inputs, outputs)
+    }
+
+    fn dataset(&self) -> Option<&str> {
+        self.dataset.as_deref()
+    }
+}
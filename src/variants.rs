@@ -0,0 +1,218 @@
+use std::fmt::{self, Display};
+
+use stats::mean;
+use tabled::{builder::Builder, Style};
+
+use crate::{
+    comm::NetworkDescription, error::EvaluationError, statistics::AggregatedStats, Protocol,
+};
+
+/// The security model that a protocol variant achieves, ordered from weakest to strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SecurityModel {
+    /// Secure against adversaries that follow the protocol but try to learn more from their view.
+    SemiHonest,
+    /// Secure against adversaries that deviate from the protocol, who are caught with some probability.
+    Covert,
+    /// Secure against adversaries that deviate from the protocol arbitrarily.
+    Malicious,
+}
+
+impl Display for SecurityModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SecurityModel::SemiHonest => "semi-honest",
+            SecurityModel::Covert => "covert",
+            SecurityModel::Malicious => "malicious",
+        })
+    }
+}
+
+/// Evaluates one variant of a protocol with an experiment name, a number of parties, a network and a number of
+/// repetitions.
+type EvaluateVariant<'a, N> =
+    Box<dyn Fn(String, usize, &N, usize) -> Result<AggregatedStats, EvaluationError> + 'a>;
+
+/// A protocol that is registered under one name in multiple security variants (e.g. a semi-honest and a malicious
+/// version), which are evaluated under the same conditions and compared in one table. The variants may be different
+/// `Protocol` types.
+pub struct Variants<'a, N: NetworkDescription> {
+    name: String,
+    variants: Vec<(SecurityModel, EvaluateVariant<'a, N>)>,
+}
+
+impl<'a, N: NetworkDescription> Variants<'a, N> {
+    /// Constructs a protocol named `name` without variants.
+    pub fn new(name: &str) -> Self {
+        Variants {
+            name: name.to_string(),
+            variants: vec![],
+        }
+    }
+
+    /// Registers the variant of the protocol that achieves the security `model`. Panics if a variant with this model
+    /// was already registered.
+    pub fn with_variant<P: Protocol + 'a>(mut self, model: SecurityModel, protocol: P) -> Self {
+        assert!(
+            self.variants.iter().all(|(existing, _)| *existing != model),
+            "the {} variant of '{}' was already registered",
+            model,
+            self.name
+        );
+
+        self.variants.push((
+            model,
+            Box::new(move |experiment_name, n_parties, network, repetitions| {
+                protocol.evaluate(experiment_name, n_parties, network, repetitions)
+            }),
+        ));
+        self.variants.sort_by_key(|(model, _)| *model);
+        self
+    }
+
+    /// Evaluates multiple `repetitions` of every variant with `n_parties` on the same network, from the weakest to
+    /// the strongest security model.
+    pub fn evaluate(
+        &self,
+        n_parties: usize,
+        network_description: &N,
+        repetitions: usize,
+    ) -> Result<VariantStats, EvaluationError> {
+        let variants = self
+            .variants
+            .iter()
+            .map(|(model, evaluate)| {
+                let experiment_name = format!("{} ({})", self.name, model);
+                evaluate(experiment_name, n_parties, network_description, repetitions)
+                    .map(|stats| (*model, stats))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(VariantStats {
+            name: self.name.clone(),
+            variants,
+        })
+    }
+}
+
+/// The statistics of each variant of a protocol (see `Variants`).
+#[derive(Debug)]
+pub struct VariantStats {
+    name: String,
+    variants: Vec<(SecurityModel, AggregatedStats)>,
+}
+
+impl VariantStats {
+    /// Returns the statistics of the variant with the security `model`, if it was registered.
+    pub fn stats(&self, model: SecurityModel) -> Option<&AggregatedStats> {
+        self.variants
+            .iter()
+            .find(|(variant, _)| *variant == model)
+            .map(|(_, stats)| stats)
+    }
+
+    /// Compares the run times of the variants. Each timing is the mean over the repetitions of the slowest party's
+    /// duration, and its overhead factor is relative to the weakest variant.
+    pub fn summarize_overhead(&self) -> OverheadSummary {
+        let mut timing_names: Vec<String> = vec![];
+        for (_, stats) in &self.variants {
+            for name in stats.timing_names() {
+                if !timing_names.contains(&name) {
+                    timing_names.push(name);
+                }
+            }
+        }
+
+        let means: Vec<Vec<Option<f64>>> = self
+            .variants
+            .iter()
+            .map(|(_, stats)| {
+                timing_names
+                    .iter()
+                    .map(|name| {
+                        let durations = stats.slowest_durations(name);
+                        (!durations.is_empty())
+                            .then(|| mean(durations.iter().map(|d| d.as_secs_f64())))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let overheads = means
+            .iter()
+            .map(|variant_means| {
+                variant_means
+                    .iter()
+                    .zip(&means[0])
+                    .map(|(mean, baseline)| match (mean, baseline) {
+                        (Some(mean), Some(baseline)) if *baseline > 0. => Some(mean / baseline),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        OverheadSummary {
+            name: self.name.clone(),
+            models: self.variants.iter().map(|(model, _)| *model).collect(),
+            timing_names,
+            means,
+            overheads,
+        }
+    }
+}
+
+/// The run times of each variant of a protocol and their overhead factors relative to the weakest variant.
+pub struct OverheadSummary {
+    name: String,
+    models: Vec<SecurityModel>,
+    timing_names: Vec<String>,
+    means: Vec<Vec<Option<f64>>>,
+    overheads: Vec<Vec<Option<f64>>>,
+}
+
+impl OverheadSummary {
+    /// Returns the security models of the compared variants, from weakest to strongest.
+    pub fn models(&self) -> &[SecurityModel] {
+        &self.models
+    }
+
+    /// Returns the names of the compared timings.
+    pub fn timing_names(&self) -> &[String] {
+        &self.timing_names
+    }
+
+    /// Returns the overhead factor of each variant (in the order of `models`) for each timing (in the order of
+    /// `timing_names`), or `None` if either this variant or the weakest variant did not measure the timing.
+    pub fn overheads(&self) -> &[Vec<Option<f64>>] {
+        &self.overheads
+    }
+
+    /// Prints a pretty table of the run times and overhead factors of the variants.
+    pub fn print(&self) {
+        let mut builder = Builder::default();
+
+        // Add header
+        builder.add_record(
+            [self.name.clone()]
+                .into_iter()
+                .chain(self.timing_names.iter().cloned()),
+        );
+
+        // Add each variant's data
+        for ((model, means), overheads) in self.models.iter().zip(&self.means).zip(&self.overheads)
+        {
+            builder.add_record([model.to_string()].into_iter().chain(
+                means.iter().zip(overheads).map(|data| match data {
+                    (&Some(mean), &Some(overhead)) => format!("{:.3} s (×{:.2})", mean, overhead),
+                    (&Some(mean), None) => format!("{:.3} s", mean),
+                    _ => "".to_string(),
+                }),
+            ));
+        }
+
+        let table = builder.build().with(Style::modern());
+
+        println!("{}", table);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::error::{EvaluationError, TimeoutError};
+
+/// How often a party that waits for a message checks whether the parties deadlocked.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Detects when the parties of one repetition deadlocked: every party that did not finish yet waits for a message,
+/// while no message is on its way. The parties' channels report when they wait and when messages are sent and
+/// received, and a waiting party checks for a deadlock every `POLL_INTERVAL`. A waiting party also checks whether the
+/// repetition exceeded its time limit, if it has one. Each party's state is kept in atomics of its own, so that
+/// sending and receiving does not contend for a lock shared by all parties.
+#[derive(Debug)]
+pub(crate) struct Watchdog {
+    parties: Vec<PartyState>,
+    deadlocked: AtomicBool,
+    /// The parties' waits and the finished parties at the moment that the deadlock was detected.
+    deadlock: Mutex<Option<(Waits, Vec<usize>)>>,
+    /// The time limit of the repetition together with the moment at which it expires.
+    deadline: Option<(Duration, Instant)>,
+    expired: AtomicBool,
+}
+
+/// The id of each waiting party together with the id of the party that it waits for (or `None` for any party).
+type Waits = Vec<(usize, Option<usize>)>;
+
+/// The value of `PartyState::waiting` while the party does not wait.
+const NOT_WAITING: usize = usize::MAX;
+/// The value of `PartyState::waiting` while the party waits for a message from any party.
+const WAITING_FOR_ANY: usize = usize::MAX - 1;
+
+/// The state of one party, which only the party itself changes, except for the messages that are sent to it.
+#[derive(Debug)]
+struct PartyState {
+    /// The id of the party that this party waits for, `WAITING_FOR_ANY` or `NOT_WAITING`.
+    waiting: AtomicUsize,
+    finished: AtomicBool,
+    /// The number of messages that were sent to this party but not yet taken from its inbox.
+    in_flight: AtomicUsize,
+    /// Odd while the party changes `waiting` or `finished`, and incremented again afterwards, so that a deadlock is
+    /// only detected when no party changed its state while the states were read.
+    epoch: AtomicUsize,
+}
+
+impl PartyState {
+    /// Changes the state of the party with `change`, marking it as changing in the meantime.
+    fn update(&self, change: impl FnOnce(&Self)) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        change(self);
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl Watchdog {
+    /// Constructs a watchdog for `n_parties`, whose repetition expires after the `timeout` (if any) from now.
+    pub(crate) fn new(n_parties: usize, timeout: Option<Duration>) -> Self {
+        Watchdog {
+            parties: (0..n_parties)
+                .map(|_| PartyState {
+                    waiting: AtomicUsize::new(NOT_WAITING),
+                    finished: AtomicBool::new(false),
+                    in_flight: AtomicUsize::new(0),
+                    epoch: AtomicUsize::new(0),
+                })
+                .collect(),
+            deadlocked: AtomicBool::new(false),
+            deadlock: Mutex::new(None),
+            deadline: timeout.map(|timeout| (timeout, Instant::now() + timeout)),
+            expired: AtomicBool::new(false),
+        }
+    }
+
+    /// Records that a message is about to be put in the inbox of the party with `to_id`.
+    pub(crate) fn sent(&self, to_id: usize) {
+        self.parties[to_id].in_flight.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Records that the party with `id` waits for a message from the party with `from_id` (or any party if `None`).
+    pub(crate) fn wait(&self, id: usize, from_id: Option<usize>) {
+        let waiting = from_id.unwrap_or(WAITING_FOR_ANY);
+        let party = &self.parties[id];
+        // A waiting party reports that it waits whenever it polls, which only changes its state the first time
+        if party.waiting.load(Ordering::SeqCst) != waiting {
+            party.update(|party| party.waiting.store(waiting, Ordering::SeqCst));
+        }
+    }
+
+    /// Records that the party with `id` took a message from its inbox, so it no longer waits. Only the `counted`
+    /// messages were recorded when they were sent (see `sent`).
+    pub(crate) fn received(&self, id: usize, counted: bool) {
+        self.parties[id].update(|party| {
+            party.waiting.store(NOT_WAITING, Ordering::SeqCst);
+            if counted {
+                party.in_flight.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+    }
+
+    /// Records that the party with `id` finished (or failed), so it will not send any more messages.
+    pub(crate) fn finish(&self, id: usize) {
+        self.parties[id].update(|party| {
+            party.waiting.store(NOT_WAITING, Ordering::SeqCst);
+            party.finished.store(true, Ordering::SeqCst);
+        });
+    }
+
+    /// Returns whether the parties deadlocked, detecting it if they just did.
+    pub(crate) fn is_deadlocked(&self) -> bool {
+        if self.deadlocked.load(Ordering::Acquire) {
+            return true;
+        }
+
+        // A waiting party does not send, so while no party changes its state, no message can be sent either
+        let epochs: Vec<usize> = self
+            .parties
+            .iter()
+            .map(|party| party.epoch.load(Ordering::SeqCst))
+            .collect();
+        if epochs.iter().any(|epoch| epoch % 2 == 1) {
+            return false;
+        }
+
+        let mut waits = vec![];
+        let mut finished = vec![];
+        for (id, party) in self.parties.iter().enumerate() {
+            match party.waiting.load(Ordering::SeqCst) {
+                _ if party.finished.load(Ordering::SeqCst) => finished.push(id),
+                NOT_WAITING => return false,
+                WAITING_FOR_ANY => waits.push((id, None)),
+                from_id => waits.push((id, Some(from_id))),
+            }
+            if party.in_flight.load(Ordering::SeqCst) > 0 {
+                return false;
+            }
+        }
+
+        let unchanged = self
+            .parties
+            .iter()
+            .zip(epochs)
+            .all(|(party, epoch)| party.epoch.load(Ordering::SeqCst) == epoch);
+        if !unchanged {
+            return false;
+        }
+
+        let mut deadlock = self.deadlock.lock().unwrap();
+        if deadlock.is_none() {
+            *deadlock = Some((waits, finished));
+        }
+        self.deadlocked.store(true, Ordering::Release);
+        true
+    }
+
+    /// Returns whether the repetition exceeded its time limit, detecting it if it just did.
+    pub(crate) fn is_expired(&self) -> bool {
+        match self.deadline {
+            Some((_, deadline)) if Instant::now() >= deadline => {
+                self.expired.store(true, Ordering::Release);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Sleeps until `time`, but wakes up once the repetition exceeds its time limit (if any), so that a simulated delay
+    /// does not outlast the time limit.
+    pub(crate) fn sleep_until(&self, time: Instant) {
+        let end = match self.deadline {
+            Some((_, deadline)) => time.min(deadline),
+            None => time,
+        };
+        sleep(end.saturating_duration_since(Instant::now()));
+    }
+
+    /// Returns the `TimeoutError` that describes the timeout of the given repetition, if a party noticed that it
+    /// exceeded its time limit.
+    pub(crate) fn timeout(&self, repetition: Option<usize>) -> Option<TimeoutError> {
+        let (limit, _) = self.deadline?;
+
+        self.expired
+            .load(Ordering::Acquire)
+            .then_some(TimeoutError { repetition, limit })
+    }
+
+    /// Returns the `EvaluationError` that describes the deadlock, if the parties deadlocked.
+    pub(crate) fn deadlock(&self) -> Option<EvaluationError> {
+        let (waits, finished) = self.deadlock.lock().unwrap().clone()?;
+
+        Some(EvaluationError::Deadlock { waits, finished })
+    }
+}